@@ -0,0 +1,45 @@
+//! Generate the source code of a Goose load test from a HAR file.
+//!
+//! Record a session in your browser, export it as a HAR file, then run:
+//!
+//! ```text
+//! cargo run --example har -- session.har > src/main.rs
+//! ```
+//!
+//! Only requests made to the host of the first recorded request are included.
+//!
+//! ## License
+//!
+//! Copyright 2020-2022 Jeremy Andrews
+//!
+//! Licensed under the Apache License, Version 2.0 (the "License");
+//! you may not use this file except in compliance with the License.
+//! You may obtain a copy of the License at
+//!
+//! <http://www.apache.org/licenses/LICENSE-2.0>
+//!
+//! Unless required by applicable law or agreed to in writing, software
+//! distributed under the License is distributed on an "AS IS" BASIS,
+//! WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//! See the License for the specific language governing permissions and
+//! limitations under the License.
+
+use goose::har::HarImporter;
+use goose::prelude::*;
+
+fn main() -> Result<(), GooseError> {
+    let path = match std::env::args().nth(1) {
+        Some(p) => p,
+        None => {
+            eprintln!("usage: har <path/to/session.har>");
+            std::process::exit(1);
+        }
+    };
+
+    let source = HarImporter::from_file(&path)?
+        .keep_think_time(true)
+        .to_rust("Recorded session")?;
+    print!("{}", source);
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+//! Run load tests defined in YAML or JSON scenario files, without creating a Cargo
+//! project.
+//!
+//! ```text
+//! goose run plan.yaml --host https://example.com --users 100 --run-time 5m
+//! ```
+//!
+//! All run-time options supported by Goose load tests are available, including the
+//! controllers and reports. See the `goose::scenario_file` module for the file format.
+
+use gumdrop::Options;
+
+use goose::config::GooseConfiguration;
+use goose::prelude::*;
+
+/// Display usage information, including all Goose run-time options.
+fn print_usage() {
+    println!("Usage: goose run FILE [OPTIONS]");
+    println!();
+    println!("Runs the load test defined in FILE, a YAML or JSON scenario file.");
+    println!();
+    println!("{}", GooseConfiguration::usage());
+}
+
+/// Display an error and usage information, then exit.
+fn exit_with_usage(error: &str) -> ! {
+    eprintln!("goose: {}", error);
+    eprintln!();
+    print_usage();
+    std::process::exit(2);
+}
+
+#[tokio::main]
+async fn main() -> Result<(), GooseError> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.first().map(|a| a.as_str()) {
+        Some("run") => (),
+        Some("-V") | Some("--version") => {
+            println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+            return Ok(());
+        }
+        Some("-h") | Some("--help") | Some("help") | None => {
+            print_usage();
+            return Ok(());
+        }
+        Some(command) => exit_with_usage(&format!("unrecognized command '{}'", command)),
+    }
+
+    let file = match args.get(1) {
+        Some(arg) if arg == "-h" || arg == "--help" => {
+            print_usage();
+            return Ok(());
+        }
+        Some(file) if !file.starts_with('-') => file,
+        _ => exit_with_usage("missing scenario FILE"),
+    };
+
+    let configuration = match GooseConfiguration::parse_args_default(&args[2..]) {
+        Ok(c) => c,
+        Err(e) => exit_with_usage(&e.to_string()),
+    };
+    if configuration.help {
+        print_usage();
+        return Ok(());
+    }
+
+    GooseAttack::initialize_with_config(configuration)?
+        .register_scenarios_from_file(file)?
+        .execute()
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,546 @@
+//! Automatically search for the maximum number of users a load test can sustain.
+//!
+//! A capacity search is enabled with `--capacity-search`, and replaces a fixed number of
+//! users and run time. Goose starts with `step` users (or `--users` if set), holds each
+//! level for `hold`, then evaluates the requests made during that window against the
+//! configured criteria. The search ends when no more levels need to be tried, reporting
+//! the highest number of users that passed.
+//!
+//! ```text
+//! --capacity-search "p95=500ms,errors=1%,step=10,max=1000,hold=30s,mode=binary"
+//! ```
+//!
+//! The following criteria can be combined, at least one is required:
+//!  - `pNN={time}`: the NNth percentile response time must not exceed this time, for
+//!    example `p95=500ms` or `p99=2s`.
+//!  - `errors={percent}`: the percentage of failed requests must not exceed this value,
+//!    for example `errors=1%`.
+//!
+//! The following settings are optional:
+//!  - `step={users}`: how many users to add at each level (defaults to 10), also the
+//!    precision of a binary search.
+//!  - `max={users}`: the most users to try (defaults to 1000).
+//!  - `hold={timespan}`: how long to hold each level (defaults to 30s).
+//!  - `mode=incremental`: add `step` users until a level fails (the default).
+//!  - `mode=binary`: double the users until a level fails, then bisect between the
+//!    highest level that passed and the lowest level that failed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::goose_trait::Goose;
+use crate::metrics::GooseRequestMetrics;
+use crate::util;
+use crate::{AttackPhase, GooseAttack, GooseAttackRunState, GooseError};
+
+/// How a capacity search chooses the next number of users to try.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CapacitySearchMode {
+    /// Add `step` users at a time until a level fails.
+    Incremental,
+    /// Double the users until a level fails, then bisect.
+    Binary,
+}
+
+/// The criteria and settings of a capacity search, configured with `--capacity-search`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CapacitySearch {
+    /// An optional percentile (between 0 and 1) and the maximum response time in
+    /// milliseconds allowed at that percentile.
+    pub percentile: Option<(f32, usize)>,
+    /// An optional maximum percentage of requests that can fail.
+    pub errors: Option<f32>,
+    /// How many users to add at each level.
+    pub step: usize,
+    /// The most users to try.
+    pub max: usize,
+    /// How many seconds to hold each level.
+    pub hold: usize,
+    /// How to choose the next number of users to try.
+    pub mode: CapacitySearchMode,
+}
+
+impl CapacitySearch {
+    /// Determine whether the requests made during a window meet the criteria.
+    fn passes(&self, percentile_time: Option<usize>, error_percent: f32) -> bool {
+        if let Some((_, maximum)) = self.percentile {
+            match percentile_time {
+                Some(time) if time <= maximum => (),
+                _ => return false,
+            }
+        }
+        if let Some(maximum) = self.errors {
+            if error_percent > maximum {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Parse a response time such as "500ms", "2s" or "500" (milliseconds).
+fn parse_response_time(value: &str) -> Option<usize> {
+    if let Some(ms) = value.strip_suffix("ms") {
+        ms.parse().ok()
+    } else if let Some(seconds) = value.strip_suffix('s') {
+        seconds.parse::<usize>().ok().map(|s| s * 1_000)
+    } else {
+        value.parse().ok()
+    }
+}
+
+impl FromStr for CapacitySearch {
+    type Err = GooseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |detail: &str| GooseError::InvalidOption {
+            option: "`configuration.capacity_search`".to_string(),
+            value: s.to_string(),
+            detail: detail.to_string(),
+        };
+
+        let mut search = CapacitySearch {
+            percentile: None,
+            errors: None,
+            step: 10,
+            max: 1_000,
+            hold: 30,
+            mode: CapacitySearchMode::Incremental,
+        };
+
+        // Settings are a comma separated list of "{key}={value}", white space is ignored.
+        for setting in s.split(',') {
+            let (key, value) = match setting.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => {
+                    return Err(invalid(&format!(
+                        "expected \"{{key}}={{value}}\", found \"{}\".",
+                        setting.trim()
+                    )))
+                }
+            };
+            match key {
+                "errors" => match value.trim_end_matches('%').parse::<f32>() {
+                    Ok(errors) if errors >= 0.0 => search.errors = Some(errors),
+                    _ => return Err(invalid("`errors` must be a percentage, ie \"1%\".")),
+                },
+                "step" => match value.parse::<usize>() {
+                    Ok(step) if step > 0 => search.step = step,
+                    _ => return Err(invalid("`step` must be an integer greater than 0.")),
+                },
+                "max" => match value.parse::<usize>() {
+                    Ok(max) if max > 0 => search.max = max,
+                    _ => return Err(invalid("`max` must be an integer greater than 0.")),
+                },
+                "hold" => match util::parse_timespan(value) {
+                    0 => return Err(invalid("`hold` must be a timespan, ie \"30s\".")),
+                    hold => search.hold = hold,
+                },
+                "mode" => {
+                    search.mode = match value {
+                        "incremental" => CapacitySearchMode::Incremental,
+                        "binary" => CapacitySearchMode::Binary,
+                        _ => return Err(invalid("`mode` must be \"incremental\" or \"binary\".")),
+                    }
+                }
+                _ => {
+                    // Percentiles are in the format "pNN", ie "p95".
+                    let percent = key
+                        .strip_prefix('p')
+                        .and_then(|p| p.parse::<f32>().ok())
+                        .filter(|p| *p > 0.0 && *p <= 100.0);
+                    match (percent, parse_response_time(value)) {
+                        (Some(percent), Some(time)) => {
+                            search.percentile = Some((percent / 100.0, time))
+                        }
+                        (Some(_), None) => {
+                            return Err(invalid(
+                                "percentiles must be a response time, ie \"p95=500ms\".",
+                            ))
+                        }
+                        _ => return Err(invalid(&format!("unknown setting \"{}\".", key))),
+                    }
+                }
+            }
+        }
+
+        if search.percentile.is_none() && search.errors.is_none() {
+            return Err(invalid(
+                "at least one criteria is required, ie \"p95=500ms\" or \"errors=1%\".",
+            ));
+        }
+        if search.step > search.max {
+            return Err(invalid("`step` can not be larger than `max`."));
+        }
+
+        Ok(search)
+    }
+}
+
+/// The result of holding one level of a capacity search.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CapacitySearchLevel {
+    /// How many users were running.
+    pub users: usize,
+    /// How many requests were made while holding this level.
+    pub requests: usize,
+    /// The configured percentile response time in milliseconds, if any requests completed.
+    pub percentile_time: Option<usize>,
+    /// The percentage of requests that failed.
+    pub error_percent: f32,
+    /// Whether or not this level met the criteria.
+    pub passed: bool,
+}
+
+/// A snapshot of all requests made so far, used to measure a single window.
+#[derive(Debug, Default)]
+struct RequestTotals {
+    /// Response times rounded into buckets, with the number of requests in each.
+    times: BTreeMap<usize, usize>,
+    /// How many requests succeeded.
+    success: usize,
+    /// How many requests failed.
+    fail: usize,
+}
+
+impl RequestTotals {
+    /// Sum all requests made so far.
+    fn new(requests: &GooseRequestMetrics) -> Self {
+        let mut totals = RequestTotals::default();
+        for request in requests.values() {
+            for (time, count) in &request.raw_data.times {
+                *totals.times.entry(*time).or_insert(0) += count;
+            }
+            totals.success += request.success_count;
+            totals.fail += request.fail_count;
+        }
+        totals
+    }
+
+    /// The requests made since an earlier snapshot.
+    fn since(mut self, earlier: &RequestTotals) -> Self {
+        for (time, count) in &earlier.times {
+            if let Some(current) = self.times.get_mut(time) {
+                *current = current.saturating_sub(*count);
+            }
+        }
+        self.success = self.success.saturating_sub(earlier.success);
+        self.fail = self.fail.saturating_sub(earlier.fail);
+        self
+    }
+
+    /// The response time that `percent` of the requests finished within.
+    fn percentile(&self, percent: f32) -> Option<usize> {
+        let total: usize = self.times.values().sum();
+        if total == 0 {
+            return None;
+        }
+        let target = ((total as f32 * percent).round() as usize).max(1);
+        let mut seen = 0;
+        for (time, count) in &self.times {
+            seen += count;
+            if seen >= target {
+                return Some(*time);
+            }
+        }
+        self.times.keys().last().copied()
+    }
+}
+
+/// Tracks the progress of a running capacity search.
+#[derive(Debug)]
+pub(crate) struct CapacitySearchState {
+    /// The number of users currently being tried.
+    level: usize,
+    /// The highest level that passed.
+    passed: Option<usize>,
+    /// The lowest level that failed.
+    failed: Option<usize>,
+    /// When the current window started, and the requests made before it.
+    window: Option<(Instant, RequestTotals)>,
+}
+
+impl CapacitySearchState {
+    pub(crate) fn new(level: usize) -> Self {
+        CapacitySearchState {
+            level,
+            passed: None,
+            failed: None,
+            window: None,
+        }
+    }
+
+    /// Start a new window, as the requests the current window was measured from were reset.
+    pub(crate) fn restart_window(&mut self) {
+        self.window = None;
+    }
+
+    /// Extend the current window by the time the load test was paused.
+    pub(crate) fn resume(&mut self, paused: Duration) {
+        if let Some((started, _)) = self.window.as_mut() {
+            *started += paused;
+        }
+    }
+
+    /// Record the result of the current level, returning the next level to try or
+    /// `None` if the search is finished.
+    fn next_level(&mut self, search: &CapacitySearch, passed: bool) -> Option<usize> {
+        if passed {
+            self.passed = Some(self.passed.map_or(self.level, |p| p.max(self.level)));
+        } else {
+            self.failed = Some(self.failed.map_or(self.level, |f| f.min(self.level)));
+        }
+
+        let next = match search.mode {
+            CapacitySearchMode::Incremental => {
+                if passed && self.level < search.max {
+                    Some((self.level + search.step).min(search.max))
+                } else {
+                    None
+                }
+            }
+            CapacitySearchMode::Binary => match self.failed {
+                // Keep doubling until a level fails.
+                None => {
+                    if self.level < search.max {
+                        Some(
+                            (self.level * 2)
+                                .max(self.level + search.step)
+                                .min(search.max),
+                        )
+                    } else {
+                        None
+                    }
+                }
+                // Then bisect until within `step` users of the failure.
+                Some(failed) => {
+                    let passed = self.passed.unwrap_or(0);
+                    if failed.saturating_sub(passed) <= search.step {
+                        None
+                    } else {
+                        Some(passed + (failed - passed) / 2)
+                    }
+                }
+            },
+        };
+
+        if let Some(level) = next {
+            self.level = level;
+        }
+        next
+    }
+}
+
+impl<G: Goose> GooseAttack<G> {
+    // Evaluate the current level of an active capacity search, moving to the next level
+    // or ending the load test when the search is finished.
+    pub(crate) async fn update_capacity_search(
+        &mut self,
+        goose_attack_run_state: &mut GooseAttackRunState,
+    ) -> Result<(), GooseError> {
+        let search = match self.configuration.capacity_search.as_ref() {
+            Some(search) => search.clone(),
+            None => return Ok(()),
+        };
+        // Only hold a level once all of its users are running.
+        if self.attack_phase != AttackPhase::Maintain {
+            return Ok(());
+        }
+
+        let next = {
+            let state = match goose_attack_run_state.capacity_search.as_mut() {
+                Some(state) => state,
+                None => return Ok(()),
+            };
+
+            // Start a new window the first time a level is maintained.
+            let earlier = match state.window.take() {
+                None => {
+                    state.window =
+                        Some((Instant::now(), RequestTotals::new(&self.metrics.requests)));
+                    return Ok(());
+                }
+                Some((started, earlier)) => {
+                    if started.elapsed().as_secs() < search.hold as u64 {
+                        state.window = Some((started, earlier));
+                        return Ok(());
+                    }
+                    earlier
+                }
+            };
+
+            // Measure the requests made while holding this level.
+            let window = RequestTotals::new(&self.metrics.requests).since(&earlier);
+            let requests = window.success + window.fail;
+            let percentile_time = search
+                .percentile
+                .and_then(|(percent, _)| window.percentile(percent));
+            let error_percent = match requests {
+                0 => 0.0,
+                _ => window.fail as f32 / requests as f32 * 100.0,
+            };
+            // A level that made no requests can't be evaluated, so it fails.
+            let passed = requests > 0 && search.passes(percentile_time, error_percent);
+
+            info!(
+                "capacity search: {} users made {} requests, {}",
+                state.level,
+                requests,
+                if passed { "passed" } else { "failed" }
+            );
+            self.metrics.capacity_search.push(CapacitySearchLevel {
+                users: state.level,
+                requests,
+                percentile_time,
+                error_percent,
+                passed,
+            });
+
+            state.next_level(&search, passed)
+        };
+
+        match next {
+            Some(users) => {
+                info!("capacity search: changing users to {}", users);
+                self.change_running_users(goose_attack_run_state, users)?;
+            }
+            None => {
+                match self.metrics.capacity_search_result() {
+                    Some(users) => info!("capacity search: maximum sustainable users: {}", users),
+                    None => info!("capacity search: no level met the criteria"),
+                }
+                self.cancel_attack(goose_attack_run_state).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_capacity_search() {
+        let search: CapacitySearch = "p95=500ms,errors=1%".parse().unwrap();
+        assert_eq!(search.percentile, Some((0.95, 500)));
+        assert_eq!(search.errors, Some(1.0));
+        assert_eq!(search.step, 10);
+        assert_eq!(search.max, 1_000);
+        assert_eq!(search.hold, 30);
+        assert_eq!(search.mode, CapacitySearchMode::Incremental);
+
+        let search: CapacitySearch = "p99 = 2s, step=5, max=50, hold=1m, mode=binary"
+            .parse()
+            .unwrap();
+        assert_eq!(search.percentile, Some((0.99, 2_000)));
+        assert_eq!(search.errors, None);
+        assert_eq!(search.step, 5);
+        assert_eq!(search.max, 50);
+        assert_eq!(search.hold, 60);
+        assert_eq!(search.mode, CapacitySearchMode::Binary);
+
+        // At least one criteria is required.
+        assert!("step=10".parse::<CapacitySearch>().is_err());
+        // Invalid settings.
+        assert!("p95=fast".parse::<CapacitySearch>().is_err());
+        assert!("errors=1%,step=0".parse::<CapacitySearch>().is_err());
+        assert!("errors=1%,hold=soon".parse::<CapacitySearch>().is_err());
+        assert!("errors=1%,mode=random".parse::<CapacitySearch>().is_err());
+        assert!("errors=1%,foo=bar".parse::<CapacitySearch>().is_err());
+        assert!("errors=1%,step=20,max=10"
+            .parse::<CapacitySearch>()
+            .is_err());
+        assert!("errors".parse::<CapacitySearch>().is_err());
+    }
+
+    #[test]
+    fn capacity_search_passes() {
+        let search: CapacitySearch = "p95=500ms,errors=1%".parse().unwrap();
+        assert!(search.passes(Some(500), 1.0));
+        assert!(!search.passes(Some(510), 0.0));
+        assert!(!search.passes(Some(100), 1.5));
+        assert!(!search.passes(None, 0.0));
+
+        let search: CapacitySearch = "errors=0%".parse().unwrap();
+        assert!(search.passes(None, 0.0));
+        assert!(!search.passes(Some(1), 0.1));
+    }
+
+    #[test]
+    fn request_totals_percentile() {
+        let mut earlier = RequestTotals::default();
+        earlier.times.insert(10, 5);
+        earlier.success = 5;
+
+        let mut totals = RequestTotals::default();
+        totals.times.insert(10, 15);
+        totals.times.insert(20, 8);
+        totals.times.insert(600, 2);
+        totals.success = 23;
+        totals.fail = 2;
+
+        let window = totals.since(&earlier);
+        assert_eq!(window.success, 18);
+        assert_eq!(window.fail, 2);
+        assert_eq!(window.percentile(0.5), Some(10));
+        assert_eq!(window.percentile(0.9), Some(20));
+        assert_eq!(window.percentile(1.0), Some(600));
+        assert_eq!(RequestTotals::default().percentile(0.95), None);
+
+        // Windows restart when the requests they were measured from are reset.
+        let mut state = CapacitySearchState::new(10);
+        state.window = Some((Instant::now(), RequestTotals::default()));
+        state.restart_window();
+        assert!(state.window.is_none());
+    }
+
+    #[test]
+    fn incremental_next_level() {
+        let search: CapacitySearch = "errors=1%,step=10,max=35".parse().unwrap();
+        let mut state = CapacitySearchState::new(10);
+        assert_eq!(state.next_level(&search, true), Some(20));
+        assert_eq!(state.next_level(&search, true), Some(30));
+        assert_eq!(state.next_level(&search, true), Some(35));
+        assert_eq!(state.next_level(&search, true), None);
+        assert_eq!(state.passed, Some(35));
+
+        let mut state = CapacitySearchState::new(10);
+        assert_eq!(state.next_level(&search, true), Some(20));
+        assert_eq!(state.next_level(&search, false), None);
+        assert_eq!(state.passed, Some(10));
+        assert_eq!(state.failed, Some(20));
+    }
+
+    #[test]
+    fn binary_next_level() {
+        let search: CapacitySearch = "errors=1%,step=10,max=1000,mode=binary".parse().unwrap();
+        let mut state = CapacitySearchState::new(10);
+        assert_eq!(state.next_level(&search, true), Some(20));
+        assert_eq!(state.next_level(&search, true), Some(40));
+        assert_eq!(state.next_level(&search, true), Some(80));
+        assert_eq!(state.next_level(&search, true), Some(160));
+        // 160 fails, bisect between 80 and 160.
+        assert_eq!(state.next_level(&search, false), Some(120));
+        assert_eq!(state.next_level(&search, true), Some(140));
+        assert_eq!(state.next_level(&search, false), Some(130));
+        // Within `step` users of the failure.
+        assert_eq!(state.next_level(&search, true), None);
+        assert_eq!(state.passed, Some(130));
+
+        // Stops at `max`.
+        let search: CapacitySearch = "errors=1%,step=10,max=30,mode=binary".parse().unwrap();
+        let mut state = CapacitySearchState::new(10);
+        assert_eq!(state.next_level(&search, true), Some(20));
+        assert_eq!(state.next_level(&search, true), Some(30));
+        assert_eq!(state.next_level(&search, true), None);
+
+        // The first level fails.
+        let mut state = CapacitySearchState::new(10);
+        assert_eq!(state.next_level(&search, false), None);
+        assert_eq!(state.passed, None);
+    }
+}
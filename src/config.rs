@@ -11,6 +11,9 @@ use simplelog::*;
 use std::path::PathBuf;
 use std::str::FromStr;
 
+use crate::capacity::CapacitySearch;
+use crate::controller::ControllerTokens;
+use crate::controller_script::ControllerScript;
 use crate::logger::GooseLogFormat;
 use crate::metrics::GooseCoordinatedOmissionMitigation;
 use crate::test_plan::TestPlan;
@@ -153,6 +156,12 @@ pub struct GooseConfiguration {
     /// Defines a more complex test plan ("10,60s;0,30s")
     #[options(no_short, meta = "\"TESTPLAN\"")]
     pub(crate) test_plan: Option<TestPlan>,
+    /// Loads a test plan with named stages from a file
+    #[options(no_short, meta = "FILE", parse(try_from_str = "TestPlan::from_file"))]
+    pub(crate) test_plan_file: Option<TestPlan>,
+    /// Searches for the most users meeting criteria ("p95=500ms,errors=1%")
+    #[options(no_short, meta = "\"CRITERIA\"")]
+    pub(crate) capacity_search: Option<CapacitySearch>,
     /// Sets how many times to run scenarios then exit
     #[options(no_short)]
     pub iterations: usize,
@@ -180,9 +189,42 @@ pub struct GooseConfiguration {
     /// Sets WebSocket Controller TCP port (default: 5117)
     #[options(no_short, meta = "PORT")]
     pub websocket_port: u16,
+    /// Doesn't enable HTTP Controller
+    #[options(no_short)]
+    pub no_http: bool,
+    /// Sets HTTP Controller host (default: 0.0.0.0)
+    #[options(no_short, meta = "HOST")]
+    pub http_host: String,
+    /// Sets HTTP Controller TCP port (default: 5118)
+    #[options(no_short, meta = "PORT")]
+    pub http_port: u16,
+    /// Requires Controller clients to provide a token ("TOKEN,read:TOKEN")
+    #[options(no_short, meta = "\"TOKENS\"")]
+    #[serde(skip)]
+    pub(crate) controller_token: Option<ControllerTokens>,
+    /// Encrypts WebSocket and HTTP Controllers with PEM certificate
+    #[options(no_short, meta = "FILE")]
+    pub controller_tls_cert: String,
+    /// Sets PEM private key for Controller certificate
+    #[options(no_short, meta = "FILE")]
+    pub controller_tls_key: String,
+    /// Runs a script of timed Controller commands
+    #[options(
+        no_short,
+        meta = "FILE",
+        parse(try_from_str = "ControllerScript::from_file")
+    )]
+    #[serde(skip)]
+    pub(crate) controller_script: Option<ControllerScript>,
+    /// Sets directory Controllers write reports to
+    #[options(no_short, meta = "DIR")]
+    pub controller_report_dir: String,
     /// Doesn't automatically start load test
     #[options(no_short)]
     pub no_autostart: bool,
+    /// Displays a live full-screen dashboard
+    #[options(no_short)]
+    pub tui: bool,
     /// Doesn't set the gzip Accept-Encoding header
     #[options(no_short)]
     pub no_gzip: bool,
@@ -195,6 +237,9 @@ pub struct GooseConfiguration {
     /// Sets maximum requests per second
     #[options(no_short, meta = "VALUE")]
     pub throttle_requests: usize,
+    /// Sets maximum requests allowed in a burst (default: 1)
+    #[options(no_short, meta = "VALUE")]
+    pub throttle_burst: usize,
     /// Follows base_url redirect with subsequent requests
     #[options(
     no_short,
@@ -284,6 +329,8 @@ pub(crate) struct GooseDefaults {
     pub run_time: Option<usize>,
     /// An optional default test plan.
     pub test_plan: Option<TestPlan>,
+    /// An optional default capacity search.
+    pub capacity_search: Option<CapacitySearch>,
     /// An optional default test plan.
     pub iterations: Option<usize>,
     /// Optional default scenarios.
@@ -342,8 +389,12 @@ pub(crate) struct GooseDefaults {
     pub no_telnet: Option<bool>,
     /// An optional default for not enabling WebSocket Controller thread.
     pub no_websocket: Option<bool>,
+    /// An optional default for not enabling HTTP Controller thread.
+    pub no_http: Option<bool>,
     /// An optional default for not auto-starting the load test.
     pub no_autostart: Option<bool>,
+    /// An optional default for displaying a live full-screen dashboard.
+    pub tui: Option<bool>,
     /// An optional default for not setting the gzip Accept-Encoding header.
     pub no_gzip: Option<bool>,
     /// An optional default number of seconds to timeout requests.
@@ -354,6 +405,8 @@ pub(crate) struct GooseDefaults {
     pub no_status_codes: Option<bool>,
     /// An optional default maximum requests per second.
     pub throttle_requests: Option<usize>,
+    /// An optional default maximum requests allowed in a burst.
+    pub throttle_burst: Option<usize>,
     /// An optional default to follows base_url redirect with subsequent request.
     pub sticky_follow: Option<bool>,
     /// An optional default to enable Manager mode.
@@ -370,6 +423,20 @@ pub(crate) struct GooseDefaults {
     pub websocket_host: Option<String>,
     /// An optional default for port WebSocket Controller listens on.
     pub websocket_port: Option<u16>,
+    /// An optional default for host HTTP Controller listens on.
+    pub http_host: Option<String>,
+    /// An optional default for port HTTP Controller listens on.
+    pub http_port: Option<u16>,
+    /// An optional default for tokens Controller clients must provide.
+    pub controller_token: Option<ControllerTokens>,
+    /// An optional default for the certificate encrypting Controller connections.
+    pub controller_tls_cert: Option<String>,
+    /// An optional default for the private key of the Controller certificate.
+    pub controller_tls_key: Option<String>,
+    /// An optional default for the script of timed Controller commands to run.
+    pub controller_script: Option<ControllerScript>,
+    /// An optional default for the directory Controllers write reports to.
+    pub controller_report_dir: Option<String>,
     /// An optional default for host Manager listens on.
     pub manager_bind_host: Option<String>,
     /// An optional default for port Manager listens on.
@@ -400,6 +467,10 @@ pub enum GooseDefault {
     RunTime,
     /// An optional default test plan.
     TestPlan,
+    /// An optional default test plan file.
+    TestPlanFile,
+    /// An optional default capacity search.
+    CapacitySearch,
     /// An optional default number of iterations to run scenarios then exit.
     Iterations,
     /// Optional default list of scenarios to run.
@@ -458,10 +529,14 @@ pub enum GooseDefault {
     NoTelnet,
     /// An optional default for not enabling WebSocket Controller thread.
     NoWebSocket,
+    /// An optional default for not enabling HTTP Controller thread.
+    NoHttp,
     /// An optional default for coordinated omission mitigation.
     CoordinatedOmissionMitigation,
     /// An optional default for not automatically starting load test.
     NoAutoStart,
+    /// An optional default for displaying a live full-screen dashboard.
+    Tui,
     /// An optional default timeout for all requests, in seconds.
     Timeout,
     /// An optional default for not setting the gzip Accept-Encoding header.
@@ -470,6 +545,8 @@ pub enum GooseDefault {
     NoStatusCodes,
     /// An optional default maximum requests per second.
     ThrottleRequests,
+    /// An optional default maximum requests allowed in a burst.
+    ThrottleBurst,
     /// An optional default to follows base_url redirect with subsequent request.
     StickyFollow,
     /// An optional default to enable Manager mode.
@@ -486,6 +563,20 @@ pub enum GooseDefault {
     WebSocketHost,
     /// An optional default for port WebSocket Controller listens on.
     WebSocketPort,
+    /// An optional default for host HTTP Controller listens on.
+    HttpHost,
+    /// An optional default for port HTTP Controller listens on.
+    HttpPort,
+    /// An optional default for tokens Controller clients must provide.
+    ControllerToken,
+    /// An optional default for the certificate encrypting Controller connections.
+    ControllerTlsCert,
+    /// An optional default for the private key of the Controller certificate.
+    ControllerTlsKey,
+    /// An optional default for the script of timed Controller commands to run.
+    ControllerScript,
+    /// An optional default for the directory Controllers write reports to.
+    ControllerReportDir,
     /// An optional default for host Manager listens on.
     ManagerBindHost,
     /// An optional default for port Manager listens on.
@@ -533,6 +624,7 @@ pub enum GooseDefault {
 ///  - [`GooseDefault::ErrorLog`]
 ///  - [`GooseDefault::GooseLog`]
 ///  - [`GooseDefault::HatchRate`]
+///  - [`GooseDefault::HttpHost`]
 ///  - [`GooseDefault::Host`]
 ///  - [`GooseDefault::ManagerBindHost`]
 ///  - [`GooseDefault::ManagerHost`]
@@ -542,6 +634,13 @@ pub enum GooseDefault {
 ///  - [`GooseDefault::Scenarios`]
 ///  - [`GooseDefault::TelnetHost`]
 ///  - [`GooseDefault::TestPlan`]
+///  - [`GooseDefault::TestPlanFile`]
+///  - [`GooseDefault::CapacitySearch`]
+///  - [`GooseDefault::ControllerToken`]
+///  - [`GooseDefault::ControllerTlsCert`]
+///  - [`GooseDefault::ControllerTlsKey`]
+///  - [`GooseDefault::ControllerScript`]
+///  - [`GooseDefault::ControllerReportDir`]
 ///  - [`GooseDefault::Timeout`]
 ///  - [`GooseDefault::TransactionLog`]
 ///  - [`GooseDefault::WebSocketHost`]
@@ -557,9 +656,11 @@ pub enum GooseDefault {
 ///  - [`GooseDefault::Quiet`]
 ///  - [`GooseDefault::Verbose`]
 ///  - [`GooseDefault::ThrottleRequests`]
+///  - [`GooseDefault::ThrottleBurst`]
 ///  - [`GooseDefault::ExpectWorkers`]
 ///  - [`GooseDefault::TelnetPort`]
 ///  - [`GooseDefault::WebSocketPort`]
+///  - [`GooseDefault::HttpPort`]
 ///  - [`GooseDefault::ManagerBindPort`]
 ///  - [`GooseDefault::ManagerPort`]
 ///
@@ -575,7 +676,9 @@ pub enum GooseDefault {
 ///  - [`GooseDefault::NoDebugBody`]
 ///  - [`GooseDefault::NoTelnet`]
 ///  - [`GooseDefault::NoWebSocket`]
+///  - [`GooseDefault::NoHttp`]
 ///  - [`GooseDefault::NoAutoStart`]
+///  - [`GooseDefault::Tui`]
 ///  - [`GooseDefault::NoGzip`]
 ///  - [`GooseDefault::NoStatusCodes`]
 ///  - [`GooseDefault::StickyFollow`]
@@ -646,9 +749,31 @@ impl<G: Goose> GooseDefaultType<&str> for GooseAttack<G> {
                 self.defaults.scenarios = Some(value.parse::<Scenarios>().unwrap())
             }
             GooseDefault::TelnetHost => self.defaults.telnet_host = Some(value.to_string()),
+            GooseDefault::HttpHost => self.defaults.http_host = Some(value.to_string()),
             GooseDefault::TestPlan => {
                 self.defaults.test_plan = Some(value.parse::<TestPlan>().unwrap())
             }
+            GooseDefault::TestPlanFile => {
+                self.defaults.test_plan = Some(TestPlan::from_file(value)?)
+            }
+            GooseDefault::CapacitySearch => {
+                self.defaults.capacity_search = Some(value.parse::<CapacitySearch>()?)
+            }
+            GooseDefault::ControllerToken => {
+                self.defaults.controller_token = Some(value.parse::<ControllerTokens>()?)
+            }
+            GooseDefault::ControllerTlsCert => {
+                self.defaults.controller_tls_cert = Some(value.to_string())
+            }
+            GooseDefault::ControllerTlsKey => {
+                self.defaults.controller_tls_key = Some(value.to_string())
+            }
+            GooseDefault::ControllerScript => {
+                self.defaults.controller_script = Some(ControllerScript::from_file(value)?)
+            }
+            GooseDefault::ControllerReportDir => {
+                self.defaults.controller_report_dir = Some(value.to_string())
+            }
             GooseDefault::Timeout => self.defaults.timeout = Some(value.to_string()),
             GooseDefault::TransactionLog => self.defaults.transaction_log = Some(value.to_string()),
             GooseDefault::WebSocketHost => self.defaults.websocket_host = Some(value.to_string()),
@@ -661,8 +786,10 @@ impl<G: Goose> GooseDefaultType<&str> for GooseAttack<G> {
             | GooseDefault::Quiet
             | GooseDefault::Verbose
             | GooseDefault::ThrottleRequests
+            | GooseDefault::ThrottleBurst
             | GooseDefault::ExpectWorkers
             | GooseDefault::TelnetPort
+            | GooseDefault::HttpPort
             | GooseDefault::WebSocketPort
             | GooseDefault::ManagerBindPort
             | GooseDefault::ManagerPort => {
@@ -685,8 +812,10 @@ impl<G: Goose> GooseDefaultType<&str> for GooseAttack<G> {
             | GooseDefault::NoErrorSummary
             | GooseDefault::NoDebugBody
             | GooseDefault::NoTelnet
+            | GooseDefault::NoHttp
             | GooseDefault::NoWebSocket
             | GooseDefault::NoAutoStart
+            | GooseDefault::Tui
             | GooseDefault::NoGzip
             | GooseDefault::NoStatusCodes
             | GooseDefault::StickyFollow
@@ -745,8 +874,10 @@ impl<G: Goose> GooseDefaultType<usize> for GooseAttack<G> {
             GooseDefault::Quiet => self.defaults.quiet = Some(value as u8),
             GooseDefault::Verbose => self.defaults.verbose = Some(value as u8),
             GooseDefault::ThrottleRequests => self.defaults.throttle_requests = Some(value),
+            GooseDefault::ThrottleBurst => self.defaults.throttle_burst = Some(value),
             GooseDefault::ExpectWorkers => self.defaults.expect_workers = Some(value),
             GooseDefault::TelnetPort => self.defaults.telnet_port = Some(value as u16),
+            GooseDefault::HttpPort => self.defaults.http_port = Some(value as u16),
             GooseDefault::WebSocketPort => self.defaults.websocket_port = Some(value as u16),
             GooseDefault::ManagerBindPort => self.defaults.manager_bind_port = Some(value as u16),
             GooseDefault::ManagerPort => self.defaults.manager_port = Some(value as u16),
@@ -763,7 +894,15 @@ impl<G: Goose> GooseDefaultType<usize> for GooseAttack<G> {
             | GooseDefault::ScenarioLog
             | GooseDefault::Scenarios
             | GooseDefault::TelnetHost
+            | GooseDefault::HttpHost
             | GooseDefault::TestPlan
+            | GooseDefault::TestPlanFile
+            | GooseDefault::CapacitySearch
+            | GooseDefault::ControllerToken
+            | GooseDefault::ControllerTlsCert
+            | GooseDefault::ControllerTlsKey
+            | GooseDefault::ControllerScript
+            | GooseDefault::ControllerReportDir
             | GooseDefault::Timeout
             | GooseDefault::TransactionLog
             | GooseDefault::WebSocketHost => {
@@ -785,8 +924,10 @@ impl<G: Goose> GooseDefaultType<usize> for GooseAttack<G> {
             | GooseDefault::NoErrorSummary
             | GooseDefault::NoDebugBody
             | GooseDefault::NoTelnet
+            | GooseDefault::NoHttp
             | GooseDefault::NoWebSocket
             | GooseDefault::NoAutoStart
+            | GooseDefault::Tui
             | GooseDefault::NoGzip
             | GooseDefault::NoStatusCodes
             | GooseDefault::StickyFollow
@@ -847,8 +988,10 @@ impl<G: Goose> GooseDefaultType<bool> for GooseAttack<G> {
             GooseDefault::NoErrorSummary => self.defaults.no_error_summary = Some(value),
             GooseDefault::NoDebugBody => self.defaults.no_debug_body = Some(value),
             GooseDefault::NoTelnet => self.defaults.no_telnet = Some(value),
+            GooseDefault::NoHttp => self.defaults.no_http = Some(value),
             GooseDefault::NoWebSocket => self.defaults.no_websocket = Some(value),
             GooseDefault::NoAutoStart => self.defaults.no_autostart = Some(value),
+            GooseDefault::Tui => self.defaults.tui = Some(value),
             GooseDefault::NoGzip => self.defaults.no_gzip = Some(value),
             GooseDefault::NoStatusCodes => self.defaults.no_status_codes = Some(value),
             GooseDefault::StickyFollow => self.defaults.sticky_follow = Some(value),
@@ -869,7 +1012,15 @@ impl<G: Goose> GooseDefaultType<bool> for GooseAttack<G> {
             | GooseDefault::ScenarioLog
             | GooseDefault::Scenarios
             | GooseDefault::TelnetHost
+            | GooseDefault::HttpHost
             | GooseDefault::TestPlan
+            | GooseDefault::TestPlanFile
+            | GooseDefault::CapacitySearch
+            | GooseDefault::ControllerToken
+            | GooseDefault::ControllerTlsCert
+            | GooseDefault::ControllerTlsKey
+            | GooseDefault::ControllerScript
+            | GooseDefault::ControllerReportDir
             | GooseDefault::Timeout
             | GooseDefault::TransactionLog
             | GooseDefault::WebSocketHost => {
@@ -891,8 +1042,10 @@ impl<G: Goose> GooseDefaultType<bool> for GooseAttack<G> {
             | GooseDefault::Quiet
             | GooseDefault::Verbose
             | GooseDefault::ThrottleRequests
+            | GooseDefault::ThrottleBurst
             | GooseDefault::ExpectWorkers
             | GooseDefault::TelnetPort
+            | GooseDefault::HttpPort
             | GooseDefault::WebSocketPort
             | GooseDefault::ManagerBindPort
             | GooseDefault::ManagerPort => {
@@ -953,8 +1106,10 @@ impl<G: Goose> GooseDefaultType<GooseCoordinatedOmissionMitigation> for GooseAtt
             | GooseDefault::NoErrorSummary
             | GooseDefault::NoDebugBody
             | GooseDefault::NoTelnet
+            | GooseDefault::NoHttp
             | GooseDefault::NoWebSocket
             | GooseDefault::NoAutoStart
+            | GooseDefault::Tui
             | GooseDefault::NoGzip
             | GooseDefault::NoStatusCodes
             | GooseDefault::StickyFollow
@@ -984,7 +1139,15 @@ impl<G: Goose> GooseDefaultType<GooseCoordinatedOmissionMitigation> for GooseAtt
             | GooseDefault::ScenarioLog
             | GooseDefault::Scenarios
             | GooseDefault::TelnetHost
+            | GooseDefault::HttpHost
             | GooseDefault::TestPlan
+            | GooseDefault::TestPlanFile
+            | GooseDefault::CapacitySearch
+            | GooseDefault::ControllerToken
+            | GooseDefault::ControllerTlsCert
+            | GooseDefault::ControllerTlsKey
+            | GooseDefault::ControllerScript
+            | GooseDefault::ControllerReportDir
             | GooseDefault::Timeout
             | GooseDefault::TransactionLog
             | GooseDefault::WebSocketHost => {
@@ -1006,8 +1169,10 @@ impl<G: Goose> GooseDefaultType<GooseCoordinatedOmissionMitigation> for GooseAtt
             | GooseDefault::Quiet
             | GooseDefault::Verbose
             | GooseDefault::ThrottleRequests
+            | GooseDefault::ThrottleBurst
             | GooseDefault::ExpectWorkers
             | GooseDefault::TelnetPort
+            | GooseDefault::HttpPort
             | GooseDefault::WebSocketPort
             | GooseDefault::ManagerBindPort
             | GooseDefault::ManagerPort => {
@@ -1062,8 +1227,10 @@ impl<G: Goose> GooseDefaultType<GooseLogFormat> for GooseAttack<G> {
             | GooseDefault::NoErrorSummary
             | GooseDefault::NoDebugBody
             | GooseDefault::NoTelnet
+            | GooseDefault::NoHttp
             | GooseDefault::NoWebSocket
             | GooseDefault::NoAutoStart
+            | GooseDefault::Tui
             | GooseDefault::NoGzip
             | GooseDefault::NoStatusCodes
             | GooseDefault::StickyFollow
@@ -1093,7 +1260,15 @@ impl<G: Goose> GooseDefaultType<GooseLogFormat> for GooseAttack<G> {
             | GooseDefault::ScenarioLog
             | GooseDefault::Scenarios
             | GooseDefault::TelnetHost
+            | GooseDefault::HttpHost
             | GooseDefault::TestPlan
+            | GooseDefault::TestPlanFile
+            | GooseDefault::CapacitySearch
+            | GooseDefault::ControllerToken
+            | GooseDefault::ControllerTlsCert
+            | GooseDefault::ControllerTlsKey
+            | GooseDefault::ControllerScript
+            | GooseDefault::ControllerReportDir
             | GooseDefault::Timeout
             | GooseDefault::TransactionLog
             | GooseDefault::WebSocketHost => {
@@ -1115,8 +1290,10 @@ impl<G: Goose> GooseDefaultType<GooseLogFormat> for GooseAttack<G> {
             | GooseDefault::Quiet
             | GooseDefault::Verbose
             | GooseDefault::ThrottleRequests
+            | GooseDefault::ThrottleBurst
             | GooseDefault::ExpectWorkers
             | GooseDefault::TelnetPort
+            | GooseDefault::HttpPort
             | GooseDefault::WebSocketPort
             | GooseDefault::ManagerBindPort
             | GooseDefault::ManagerPort => {
@@ -1255,6 +1432,63 @@ impl GooseConfigure<TestPlan> for GooseConfiguration {
     }
 }
 
+impl GooseConfigure<CapacitySearch> for GooseConfiguration {
+    /// Use [`GooseValue`] to set a [`CapacitySearch`] value.
+    fn get_value(&self, values: Vec<GooseValue<CapacitySearch>>) -> Option<CapacitySearch> {
+        for value in values {
+            if let Some(v) = value.value {
+                if value.filter {
+                    continue;
+                } else {
+                    if !value.message.is_empty() {
+                        info!("{} = {:?}", value.message, v)
+                    }
+                    return Some(v);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl GooseConfigure<ControllerTokens> for GooseConfiguration {
+    /// Use [`GooseValue`] to set a [`ControllerTokens`] value.
+    fn get_value(&self, values: Vec<GooseValue<ControllerTokens>>) -> Option<ControllerTokens> {
+        for value in values {
+            if let Some(v) = value.value {
+                if value.filter {
+                    continue;
+                } else {
+                    if !value.message.is_empty() {
+                        info!("{} = {:?}", value.message, v)
+                    }
+                    return Some(v);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl GooseConfigure<ControllerScript> for GooseConfiguration {
+    /// Use [`GooseValue`] to set a [`ControllerScript`] value.
+    fn get_value(&self, values: Vec<GooseValue<ControllerScript>>) -> Option<ControllerScript> {
+        for value in values {
+            if let Some(v) = value.value {
+                if value.filter {
+                    continue;
+                } else {
+                    if !value.message.is_empty() {
+                        info!("{} = {:?}", value.message, v)
+                    }
+                    return Some(v);
+                }
+            }
+        }
+        None
+    }
+}
+
 impl GooseConfigure<String> for GooseConfiguration {
     /// Use [`GooseValue`] to set a [`String`] value.
     fn get_value(&self, values: Vec<GooseValue<String>>) -> Option<String> {
@@ -1428,6 +1662,24 @@ impl GooseConfiguration {
             ])
             .unwrap_or_else(|| "".to_string());
 
+        // Configure `tui` before initializing the logger, as the dashboard draws the terminal.
+        self.tui = self
+            .get_value(vec![
+                // Use --tui if set.
+                GooseValue {
+                    value: Some(self.tui),
+                    filter: !self.tui,
+                    message: "",
+                },
+                // Use GooseDefault if not already set and not Worker.
+                GooseValue {
+                    value: defaults.tui,
+                    filter: defaults.tui.is_none() || self.worker,
+                    message: "",
+                },
+            ])
+            .unwrap_or(false);
+
         // Initialize the Goose logger.
         self.initialize_goose_logger();
 
@@ -1480,6 +1732,12 @@ impl GooseConfiguration {
                 filter: self.test_plan.is_none(),
                 message: "test_plan",
             },
+            // Otherwise use --test-plan-file if set.
+            GooseValue {
+                value: self.test_plan_file.clone(),
+                filter: self.test_plan_file.is_none(),
+                message: "test_plan_file",
+            },
             // Otherwise use GooseDefault if set and not on Worker.
             GooseValue {
                 value: defaults.test_plan.clone(),
@@ -1488,6 +1746,22 @@ impl GooseConfiguration {
             },
         ]);
 
+        // Configure `capacity_search` before `users` so users starts at the first level.
+        self.capacity_search = self.get_value(vec![
+            // Use --capacity-search if set.
+            GooseValue {
+                value: self.capacity_search.clone(),
+                filter: self.capacity_search.is_none(),
+                message: "capacity_search",
+            },
+            // Otherwise use GooseDefault if set and not on Worker.
+            GooseValue {
+                value: defaults.capacity_search.clone(),
+                filter: defaults.capacity_search.is_none() || self.worker,
+                message: "capacity_search",
+            },
+        ]);
+
         // Configure `users`.
         self.users = self.get_value(vec![
             // Use --users if set.
@@ -1502,6 +1776,12 @@ impl GooseConfiguration {
                 filter: defaults.users.is_none() || self.worker,
                 message: "users",
             },
+            // Otherwise start a capacity search with `step` users.
+            GooseValue {
+                value: self.capacity_search.as_ref().map(|search| search.step),
+                filter: self.worker,
+                message: "users defaulted to capacity search step",
+            },
             // Otherwise use detected number of CPUs if not on Worker.
             GooseValue {
                 value: Some(num_cpus::get()),
@@ -1863,6 +2143,122 @@ impl GooseConfiguration {
             ])
             .unwrap_or(false);
 
+        // Configure `no_http`.
+        self.no_http = self
+            .get_value(vec![
+                // Use --no-http if set.
+                GooseValue {
+                    value: Some(self.no_http),
+                    filter: !self.no_http,
+                    message: "no_http",
+                },
+                // Disable HTTP Controller if not compiled with the `http-controller` feature.
+                GooseValue {
+                    value: Some(true),
+                    filter: cfg!(feature = "http-controller"),
+                    message: "",
+                },
+                // Force-disable HTTP Controller if in Gaggle mode.
+                GooseValue {
+                    value: Some(true),
+                    filter: !self.manager && !self.worker,
+                    message: "",
+                },
+                // Use default if --no-http hasn't been set.
+                GooseValue {
+                    value: defaults.no_http,
+                    filter: defaults.no_http.is_none(),
+                    message: "",
+                },
+            ])
+            .unwrap_or(false);
+
+        // Configure `controller_token`.
+        self.controller_token = self.get_value(vec![
+            // Use --controller-token if set.
+            GooseValue {
+                value: self.controller_token.clone(),
+                filter: self.controller_token.is_none(),
+                message: "controller_token",
+            },
+            // Otherwise use GooseDefault if set.
+            GooseValue {
+                value: defaults.controller_token.clone(),
+                filter: defaults.controller_token.is_none(),
+                message: "controller_token",
+            },
+        ]);
+
+        // Configure `controller_tls_cert`.
+        self.controller_tls_cert = self
+            .get_value(vec![
+                // Use --controller-tls-cert if set.
+                GooseValue {
+                    value: Some(self.controller_tls_cert.to_string()),
+                    filter: self.controller_tls_cert.is_empty(),
+                    message: "controller_tls_cert",
+                },
+                // Otherwise use GooseDefault if set.
+                GooseValue {
+                    value: defaults.controller_tls_cert.clone(),
+                    filter: defaults.controller_tls_cert.is_none(),
+                    message: "controller_tls_cert",
+                },
+            ])
+            .unwrap_or_default();
+
+        // Configure `controller_tls_key`.
+        self.controller_tls_key = self
+            .get_value(vec![
+                // Use --controller-tls-key if set.
+                GooseValue {
+                    value: Some(self.controller_tls_key.to_string()),
+                    filter: self.controller_tls_key.is_empty(),
+                    message: "controller_tls_key",
+                },
+                // Otherwise use GooseDefault if set.
+                GooseValue {
+                    value: defaults.controller_tls_key.clone(),
+                    filter: defaults.controller_tls_key.is_none(),
+                    message: "controller_tls_key",
+                },
+            ])
+            .unwrap_or_default();
+
+        // Configure `controller_script`.
+        self.controller_script = self.get_value(vec![
+            // Use --controller-script if set.
+            GooseValue {
+                value: self.controller_script.clone(),
+                filter: self.controller_script.is_none(),
+                message: "controller_script",
+            },
+            // Otherwise use GooseDefault if set.
+            GooseValue {
+                value: defaults.controller_script.clone(),
+                filter: defaults.controller_script.is_none(),
+                message: "controller_script",
+            },
+        ]);
+
+        // Configure `controller_report_dir`.
+        self.controller_report_dir = self
+            .get_value(vec![
+                // Use --controller-report-dir if set.
+                GooseValue {
+                    value: Some(self.controller_report_dir.to_string()),
+                    filter: self.controller_report_dir.is_empty(),
+                    message: "controller_report_dir",
+                },
+                // Otherwise use GooseDefault if set.
+                GooseValue {
+                    value: defaults.controller_report_dir.clone(),
+                    filter: defaults.controller_report_dir.is_none(),
+                    message: "controller_report_dir",
+                },
+            ])
+            .unwrap_or_default();
+
         // Configure `no_autostart`.
         self.no_autostart = self
             .get_value(vec![
@@ -1938,6 +2334,24 @@ impl GooseConfiguration {
             ])
             .unwrap_or(0);
 
+        // Configure `throttle_burst`.
+        self.throttle_burst = self
+            .get_value(vec![
+                // Use --throttle-burst if set.
+                GooseValue {
+                    value: Some(self.throttle_burst),
+                    filter: self.throttle_burst == 0,
+                    message: "throttle_burst",
+                },
+                // Otherwise use GooseDefault if set and not on Manager.
+                GooseValue {
+                    value: defaults.throttle_burst,
+                    filter: defaults.throttle_burst.is_none() || self.manager,
+                    message: "throttle_burst",
+                },
+            ])
+            .unwrap_or(1);
+
         // Configure `sticky_follow`.
         self.sticky_follow = self
             .get_value(vec![
@@ -2138,6 +2552,12 @@ impl GooseConfiguration {
                     detail: "`configuration.no_autostart` can not be set on the Manager."
                         .to_string(),
                 });
+            } else if self.tui {
+                return Err(GooseError::InvalidOption {
+                    option: "`configuration.tui`".to_string(),
+                    value: true.to_string(),
+                    detail: "`configuration.tui` can not be set on the Manager.".to_string(),
+                });
             } else if !self.report_file.is_empty() {
                 return Err(GooseError::InvalidOption {
                     option: "`configuration.report_file`".to_string(),
@@ -2323,6 +2743,13 @@ impl GooseConfiguration {
                     detail: "`configuration.no_autostart` can not be set in Worker mode."
                         .to_string(),
                 });
+                // Can't set `tui` on Worker.
+            } else if self.tui {
+                return Err(GooseError::InvalidOption {
+                    option: "`configuration.tui`".to_string(),
+                    value: true.to_string(),
+                    detail: "`configuration.tui` can not be set in Worker mode.".to_string(),
+                });
                 // Can't set `no_gzip` on Worker.
             } else if self.no_gzip {
                 return Err(GooseError::InvalidOption {
@@ -2462,6 +2889,17 @@ impl GooseConfiguration {
             }
         }
 
+        // Validate `test_plan_file`.
+        if self.test_plan_file.is_some() && self.test_plan != self.test_plan_file {
+            return Err(GooseError::InvalidOption {
+                option: "`configuration.test_plan_file`".to_string(),
+                value: format!("{:?}", self.test_plan_file),
+                detail:
+                    "`configuration.test_plan_file` can not be set with `configuration.test_plan`."
+                        .to_string(),
+            });
+        }
+
         // Validate `test_plan`.
         if self.test_plan.is_some() {
             // The --users option isn't compatible with --test-plan.
@@ -2526,6 +2964,61 @@ impl GooseConfiguration {
             }
         }
 
+        // Validate `capacity_search`.
+        if let Some(capacity_search) = self.capacity_search.as_ref() {
+            let incompatible = if self.test_plan.is_some() {
+                Some("test_plan")
+            } else if self.run_time != "0" {
+                Some("run_time")
+            } else if self.iterations > 0 {
+                Some("iterations")
+            } else if self.no_metrics {
+                // Each level is evaluated with the metrics it collected.
+                Some("no_metrics")
+            } else if self.manager {
+                Some("manager")
+            } else if self.worker {
+                Some("worker")
+            } else {
+                None
+            };
+            if let Some(option) = incompatible {
+                return Err(GooseError::InvalidOption {
+                    option: "`configuration.capacity_search`".to_string(),
+                    value: format!("{:?}", capacity_search),
+                    detail: format!(
+                        "`configuration.capacity_search` can not be set with `configuration.{}`.",
+                        option
+                    ),
+                });
+            }
+        }
+
+        // Validate `controller_tls_cert` and `controller_tls_key`, which must be set together.
+        if self.controller_tls_cert.is_empty() != self.controller_tls_key.is_empty() {
+            let (option, value, missing) = if self.controller_tls_cert.is_empty() {
+                (
+                    "controller_tls_key",
+                    &self.controller_tls_key,
+                    "controller_tls_cert",
+                )
+            } else {
+                (
+                    "controller_tls_cert",
+                    &self.controller_tls_cert,
+                    "controller_tls_key",
+                )
+            };
+            return Err(GooseError::InvalidOption {
+                option: format!("`configuration.{}`", option),
+                value: value.to_string(),
+                detail: format!(
+                    "`configuration.{}` can not be set without `configuration.{}`.",
+                    option, missing
+                ),
+            });
+        }
+
         // Validate `iterations`.
         if self.iterations > 0 {
             // The --run-time option isn't compatible with --iterations.
@@ -2635,8 +3128,35 @@ impl GooseConfiguration {
             });
         }
 
+        // Can't configure the HTTP Controller if it's not compiled in.
+        if !cfg!(feature = "http-controller") {
+            if !self.http_host.is_empty() {
+                return Err(GooseError::InvalidOption {
+                    option: "`configuration.http_host`".to_string(),
+                    value: self.http_host.to_string(),
+                    detail: "Goose must be compiled with the `http-controller` feature to enable the HTTP Controller.".to_string(),
+                });
+            } else if self.http_port != 0 {
+                return Err(GooseError::InvalidOption {
+                    option: "`configuration.http_port`".to_string(),
+                    value: self.http_port.to_string(),
+                    detail: "Goose must be compiled with the `http-controller` feature to enable the HTTP Controller.".to_string(),
+                });
+            }
+        }
+
+        // Can't display the dashboard if it's not compiled in.
+        if self.tui && !cfg!(feature = "tui") {
+            return Err(GooseError::InvalidOption {
+                option: "`configuration.tui`".to_string(),
+                value: true.to_string(),
+                detail: "Goose must be compiled with the `tui` feature to display the dashboard."
+                    .to_string(),
+            });
+        }
+
         // Can't disable autostart if there's no Controller enabled.
-        if self.no_autostart && self.no_telnet && self.no_websocket {
+        if self.no_autostart && self.no_telnet && self.no_websocket && self.no_http {
             return Err(GooseError::InvalidOption {
                 option: "`configuration.no_autostart`".to_string(),
                 value: true.to_string(),
@@ -2713,28 +3233,26 @@ impl GooseConfiguration {
             None
         };
 
-        if let Some(log_to_file) = goose_log {
-            match CombinedLogger::init(vec![
-                SimpleLogger::new(debug_level, Config::default()),
-                WriteLogger::new(
-                    log_level,
-                    Config::default(),
-                    std::fs::File::create(&log_to_file).unwrap(),
-                ),
-            ]) {
-                Ok(_) => (),
-                Err(e) => {
-                    info!("failed to initialize CombinedLogger: {}", e);
-                }
+        // The dashboard draws the whole terminal, so only log to the log file if enabled.
+        let mut loggers: Vec<Box<dyn SharedLogger>> = Vec::new();
+        if !self.tui {
+            loggers.push(SimpleLogger::new(debug_level, Config::default()));
+        }
+        if let Some(log_to_file) = goose_log.as_ref() {
+            loggers.push(WriteLogger::new(
+                log_level,
+                Config::default(),
+                std::fs::File::create(log_to_file).unwrap(),
+            ));
+        }
+        match CombinedLogger::init(loggers) {
+            Ok(_) => (),
+            Err(e) => {
+                info!("failed to initialize CombinedLogger: {}", e);
             }
+        }
+        if let Some(log_to_file) = goose_log {
             info!("Writing to log file: {}", log_to_file.display());
-        } else {
-            match CombinedLogger::init(vec![SimpleLogger::new(debug_level, Config::default())]) {
-                Ok(_) => (),
-                Err(e) => {
-                    info!("failed to initialize CombinedLogger: {}", e);
-                }
-            }
         }
 
         info!("Output verbosity level: {}", debug_level);
@@ -2765,6 +3283,7 @@ mod test {
         let debug_log = "custom-goose-debug.log".to_string();
         let error_log = "custom-goose-error.log".to_string();
         let throttle_requests: usize = 25;
+        let throttle_burst: usize = 5;
         let expect_workers: usize = 5;
         let manager_bind_host = "127.0.0.1".to_string();
         let manager_bind_port: usize = 1221;
@@ -2809,8 +3328,12 @@ mod test {
             .unwrap()
             .set_default(GooseDefault::NoWebSocket, true)
             .unwrap()
+            .set_default(GooseDefault::NoHttp, true)
+            .unwrap()
             .set_default(GooseDefault::NoAutoStart, true)
             .unwrap()
+            .set_default(GooseDefault::Tui, true)
+            .unwrap()
             .set_default(GooseDefault::NoGzip, true)
             .unwrap()
             .set_default(GooseDefault::ReportFile, report_file.as_str())
@@ -2848,6 +3371,8 @@ mod test {
             .unwrap()
             .set_default(GooseDefault::ThrottleRequests, throttle_requests)
             .unwrap()
+            .set_default(GooseDefault::ThrottleBurst, throttle_burst)
+            .unwrap()
             .set_default(GooseDefault::StickyFollow, true)
             .unwrap()
             .set_default(GooseDefault::Manager, true)
@@ -2886,7 +3411,9 @@ mod test {
         assert!(goose_attack.defaults.no_error_summary == Some(true));
         assert!(goose_attack.defaults.no_telnet == Some(true));
         assert!(goose_attack.defaults.no_websocket == Some(true));
+        assert!(goose_attack.defaults.no_http == Some(true));
         assert!(goose_attack.defaults.no_autostart == Some(true));
+        assert!(goose_attack.defaults.tui == Some(true));
         assert!(goose_attack.defaults.timeout == Some(timeout));
         assert!(goose_attack.defaults.no_gzip == Some(true));
         assert!(goose_attack.defaults.report_file == Some(report_file));
@@ -2902,6 +3429,7 @@ mod test {
                 == Some(GooseCoordinatedOmissionMitigation::Disabled)
         );
         assert!(goose_attack.defaults.throttle_requests == Some(throttle_requests));
+        assert!(goose_attack.defaults.throttle_burst == Some(throttle_burst));
         assert!(goose_attack.defaults.sticky_follow == Some(true));
         assert!(goose_attack.defaults.manager == Some(true));
         assert!(goose_attack.defaults.expect_workers == Some(expect_workers));
@@ -1,9 +1,11 @@
-//! Optional telnet and WebSocket Controller threads.
+//! Optional telnet, WebSocket and HTTP Controller threads.
 //!
-//! By default, Goose launches both a telnet Controller and a WebSocket Controller, allowing
-//! real-time control of the running load test.
+//! By default, Goose launches a telnet Controller and a WebSocket Controller, allowing
+//! real-time control of the running load test. When compiled with the `http-controller`
+//! feature, Goose also launches an HTTP Controller.
 
 use crate::config::GooseConfiguration;
+use crate::controller_script::ControllerScript;
 use crate::metrics::GooseMetrics;
 use crate::test_plan::{TestPlan, TestPlanHistory, TestPlanStepAction};
 use crate::{Goose, util};
@@ -11,21 +13,30 @@ use crate::{AttackPhase, GooseAttack, GooseAttackRunState, GooseError};
 
 use async_trait::async_trait;
 use futures::{SinkExt, StreamExt};
+use http::StatusCode;
+#[cfg(feature = "http-controller")]
+use hyper::{Body, Method};
 use regex::{Regex, RegexSet};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::io::{self, Write};
 use std::str::{self, FromStr};
+#[cfg(any(feature = "http-controller", feature = "rustls-tls"))]
+use std::sync::Arc;
 use std::time::Duration;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::net::TcpListener;
+use tokio::sync::{broadcast, Mutex};
 use tokio_tungstenite::tungstenite::Message;
 
 /// All commands recognized by the Goose Controllers.
 ///
 /// Commands are not case sensitive. When sending commands to the WebSocket Controller,
 /// they must be formatted as json as defined by
+/// [ControllerWebSocketTypedRequest](./struct.ControllerWebSocketTypedRequest.html), or
+/// by the original
 /// [ControllerWebSocketRequest](./struct.ControllerWebSocketRequest.html).
 ///
 /// GOOSE DEVELOPER NOTE: The following steps are required to add a new command:
@@ -83,6 +94,27 @@ pub enum ControllerCommand {
     ///
     /// Goose must be running (or starting) to process this command.
     Stop,
+    /// Pause a running test, holding all [`GooseUser`](../goose/struct.GooseUser.html)s at
+    /// their next transaction boundary.
+    ///
+    /// # Example
+    /// Pauses a running (or starting) load test.
+    /// ```notest
+    /// pause
+    /// ```
+    ///
+    /// Goose must be running (or starting) to process this command.
+    Pause,
+    /// Resume a paused test.
+    ///
+    /// # Example
+    /// Resumes a paused load test from where it was paused.
+    /// ```notest
+    /// resume
+    /// ```
+    ///
+    /// Goose must be paused to process this command.
+    Resume,
     /// Tell the load test to shut down (which will disconnect the controller).
     ///
     /// # Example
@@ -143,6 +175,22 @@ pub enum ControllerCommand {
     ///
     /// This can be configured when Goose is idle as well as when a Goose load test is running.
     RunTime,
+    /// Configure the maximum number of requests per second, or disable the throttle.
+    ///
+    /// # Example
+    /// Tells Goose to make at most 100 requests per second.
+    /// ```notest
+    /// throttle 100
+    /// ```
+    ///
+    /// # Example
+    /// Tells Goose to disable the throttle.
+    /// ```notest
+    /// throttle 0
+    /// ```
+    ///
+    /// Can be configured on an idle or running load test.
+    ThrottleRequests,
     /// Define a load test plan. This will replace the previously configured test plan, if any.
     ///
     /// # Example
@@ -154,6 +202,117 @@ pub enum ControllerCommand {
     ///
     /// Can be configured on an idle or running load test.
     TestPlan,
+    /// Append steps to the running test plan. Shapes start from the number of users
+    /// configured by the last step.
+    ///
+    /// # Example
+    /// Tells Goose to run 10 users for another hour after the current test plan completes,
+    /// then to stop all users over 30 seconds.
+    /// ```notest
+    /// append-plan "10,1h;0,30s"
+    /// ```
+    ///
+    /// Can only be configured on a running load test.
+    AppendTestPlan,
+    /// Extend how long the current test plan step runs.
+    ///
+    /// # Example
+    /// Tells Goose to run the current test plan step for 30 more minutes.
+    /// ```notest
+    /// extend-step 30m
+    /// ```
+    ///
+    /// Can only be configured on a running load test.
+    ExtendTestPlanStep,
+    /// Jump to a test plan step, identified by its number in the test plan preview or by
+    /// the name of the stage it starts. Users are launched or stopped from however many
+    /// are running.
+    ///
+    /// # Example
+    /// Tells Goose to skip ahead to the `cooldown` stage of the test plan.
+    /// ```notest
+    /// jump-step cooldown
+    /// ```
+    ///
+    /// Can only be configured on a running load test.
+    JumpTestPlanStep,
+    /// Run a script of Controller commands, each run a set time after the script starts
+    /// or when a condition on the metrics of the load test becomes true. Lines of the
+    /// script are separated by new lines or semicolons.
+    ///
+    /// # Example
+    /// Tells Goose to launch 200 users after 5 minutes, to remove 50 users if the 95th
+    /// percentile response time grows over 800 milliseconds, and to shut down after 30
+    /// minutes.
+    /// ```notest
+    /// script at 5m users 200; when p95 > 800ms users -50; at 30m shutdown
+    /// ```
+    ///
+    /// This command can be run at any time.
+    Script,
+    /// Enable a [`Scenario`](../goose/struct.Scenario.html), even if it wasn't included with
+    /// `--scenarios`.
+    ///
+    /// # Example
+    /// Tells Goose to re-allocate running users to include the `checkout` scenario.
+    /// ```notest
+    /// enable checkout
+    /// ```
+    ///
+    /// Can be configured on an idle or running load test.
+    EnableScenario,
+    /// Disable a [`Scenario`](../goose/struct.Scenario.html).
+    ///
+    /// # Example
+    /// Tells Goose to re-allocate users running the `checkout` scenario to other scenarios.
+    /// ```notest
+    /// disable checkout
+    /// ```
+    ///
+    /// Can be configured on an idle or running load test.
+    DisableScenario,
+    /// Change the weight of a [`Scenario`](../goose/struct.Scenario.html).
+    ///
+    /// # Example
+    /// Tells Goose to assign the `checkout` scenario a weight of 3, re-allocating running
+    /// users.
+    /// ```notest
+    /// weight checkout 3
+    /// ```
+    ///
+    /// Can be configured on an idle or running load test.
+    ScenarioWeight,
+    /// Skip all [`Transaction`](../goose/struct.Transaction.html)s with the given name.
+    ///
+    /// # Example
+    /// Tells Goose to stop running transactions named `add to cart`.
+    /// ```notest
+    /// skip add to cart
+    /// ```
+    ///
+    /// This command can be run at any time.
+    SkipTransaction,
+    /// Stop skipping all [`Transaction`](../goose/struct.Transaction.html)s with the given
+    /// name.
+    ///
+    /// # Example
+    /// Tells Goose to run transactions named `add to cart` again.
+    /// ```notest
+    /// unskip add to cart
+    /// ```
+    ///
+    /// This command can be run at any time.
+    UnskipTransaction,
+    /// Display the current status of the load test.
+    ///
+    /// # Example
+    /// Returns the current phase of the load test and how many users are running.
+    /// ```notest
+    /// status
+    /// ```
+    ///
+    /// This command can be run at any time.
+    Status,
     /// Display the current [`GooseConfiguration`](../struct.GooseConfiguration.html)s.
     ///
     /// # Example
@@ -192,6 +351,51 @@ pub enum ControllerCommand {
     ///
     /// This command can be run at any time.
     MetricsJson,
+    /// Reset all metrics, so they only describe the load test from this point on.
+    ///
+    /// # Example
+    /// Resets metrics, for example after changing the configuration of the server being
+    /// load tested.
+    /// ```notest
+    /// reset-metrics
+    /// ```
+    ///
+    /// This command can only be run while the load test is running or paused.
+    ResetMetrics,
+    /// Write a report of the load test so far to the given path, formatted as json if
+    /// the path ends with `.json` and otherwise as HTML.
+    ///
+    /// # Example
+    /// Writes an HTML report to `window.html` without stopping the load test.
+    /// ```notest
+    /// report window.html
+    /// ```
+    ///
+    /// This command can be run at any time after the load test has started.
+    Report,
+    /// Push live metrics to the WebSocket Controller client every second.
+    ///
+    /// # Example
+    /// Once subscribed, the client receives
+    /// [`ControllerEvent`](./enum.ControllerEvent.html)s without requesting them.
+    /// ```notest
+    /// subscribe
+    /// ```
+    ///
+    /// This command can be run at any time, but is only supported by the WebSocket
+    /// Controller.
+    Subscribe,
+    /// Stop pushing live metrics to the WebSocket Controller client.
+    ///
+    /// # Example
+    /// Stops sending [`ControllerEvent`](./enum.ControllerEvent.html)s to the client.
+    /// ```notest
+    /// unsubscribe
+    /// ```
+    ///
+    /// This command can be run at any time, but is only supported by the WebSocket
+    /// Controller.
+    Unsubscribe,
 }
 
 /// Defines details around identifying and processing ControllerCommands.
@@ -206,6 +410,23 @@ impl ControllerCommand {
     ///    response to the command and responds to the controller appropriately.
     fn details(&self) -> ControllerCommandDetails {
         match self {
+            ControllerCommand::AppendTestPlan => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "append-plan PLAN",
+                    description: "append steps to running test-plan, (ie 10,1h;0,30s)\n",
+                },
+                regex: r"(?i)^(append-plan|append_plan|appendplan|append) ((\s*((\d+)\s*,\s*(\d+|((\d+?)h)?((\d+?)m)?((\d+?)s)?)|[a-z]+\s*\([^)]*\))\s*;?)+)$",
+                process_response: Box::new(|response| {
+                    if let ControllerResponseMessage::Bool(true) = response {
+                        Ok("test-plan steps appended".to_string())
+                    } else {
+                        Err(
+                            "failed to append test-plan steps, be sure load test is running"
+                                .to_string(),
+                        )
+                    }
+                }),
+            },
             ControllerCommand::Config => ControllerCommandDetails {
                 help: ControllerHelp {
                     name: "config",
@@ -234,6 +455,34 @@ impl ControllerCommand {
                     }
                 }),
             },
+            ControllerCommand::DisableScenario => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "disable NAME",
+                    description: "disable scenario, re-allocating its users\n",
+                },
+                regex: r"(?i)^(disable|disable-scenario|disable_scenario) ([a-z0-9]+)$",
+                process_response: Box::new(|response| {
+                    if let ControllerResponseMessage::Bool(true) = response {
+                        Ok("scenario disabled".to_string())
+                    } else {
+                        Err("failed to disable scenario, be sure it exists and another scenario is enabled".to_string())
+                    }
+                }),
+            },
+            ControllerCommand::EnableScenario => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "enable NAME",
+                    description: "enable scenario, re-allocating users\n",
+                },
+                regex: r"(?i)^(enable|enable-scenario|enable_scenario) ([a-z0-9]+)$",
+                process_response: Box::new(|response| {
+                    if let ControllerResponseMessage::Bool(true) = response {
+                        Ok("scenario enabled".to_string())
+                    } else {
+                        Err("failed to enable scenario, be sure it exists".to_string())
+                    }
+                }),
+            },
             ControllerCommand::Exit => ControllerCommandDetails {
                 help: ControllerHelp {
                     name: "exit",
@@ -246,6 +495,23 @@ impl ControllerCommand {
                     Err(e.to_string())
                 }),
             },
+            ControllerCommand::ExtendTestPlanStep => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "extend-step TIME",
+                    description: "extend current test-plan step, (ie 30m)\n",
+                },
+                regex: r"(?i)^(extend-step|extend_step|extendstep|extend) (\d+|((\d+?)h)?((\d+?)m)?((\d+?)s)?)$",
+                process_response: Box::new(|response| {
+                    if let ControllerResponseMessage::Bool(true) = response {
+                        Ok("test-plan step extended".to_string())
+                    } else {
+                        Err(
+                            "failed to extend test-plan step, be sure load test is running"
+                                .to_string(),
+                        )
+                    }
+                }),
+            },
             ControllerCommand::HatchRate => ControllerCommandDetails {
                 help: ControllerHelp {
                     name: "hatchrate FLOAT",
@@ -286,6 +552,20 @@ impl ControllerCommand {
                     }
                 }),
             },
+            ControllerCommand::JumpTestPlanStep => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "jump-step STEP",
+                    description: "jump to test-plan step number or stage name\n",
+                },
+                regex: r"(?i)^(jump-step|jump_step|jumpstep|jump) (.+)$",
+                process_response: Box::new(|response| {
+                    if let ControllerResponseMessage::Bool(true) = response {
+                        Ok("jumped to test-plan step".to_string())
+                    } else {
+                        Err("failed to jump to test-plan step, be sure load test is running and step exists".to_string())
+                    }
+                }),
+            },
             ControllerCommand::Metrics => ControllerCommandDetails {
                 help: ControllerHelp {
                     name: "metrics",
@@ -300,23 +580,76 @@ impl ControllerCommand {
                     }
                 }),
             },
-            ControllerCommand::MetricsJson => {
-                ControllerCommandDetails {
-                    help: ControllerHelp {
-                        name: "metrics-json",
-                        // No new-line as this is the last line of the help screen.
-                        description: "display metrics for current load test in json format",
-                    },
-                    regex: r"(?i)^(metricsjson|metrics-json|statsjson|stats-json)$",
-                    process_response: Box::new(|response| {
-                        if let ControllerResponseMessage::Metrics(metrics) = response {
-                            Ok(serde_json::to_string(&metrics).expect("unexpected serde failure"))
-                        } else {
-                            Err("error loading metrics".to_string())
-                        }
-                    }),
-                }
-            }
+            ControllerCommand::MetricsJson => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "metrics-json",
+                    description: "display metrics for current load test in json format\n\n",
+                },
+                regex: r"(?i)^(metricsjson|metrics-json|statsjson|stats-json)$",
+                process_response: Box::new(|response| {
+                    if let ControllerResponseMessage::Metrics(metrics) = response {
+                        Ok(serde_json::to_string(&metrics).expect("unexpected serde failure"))
+                    } else {
+                        Err("error loading metrics".to_string())
+                    }
+                }),
+            },
+            ControllerCommand::Pause => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "pause",
+                    description: "pause a running load test, holding all users\n",
+                },
+                regex: r"(?i)^pause$",
+                process_response: Box::new(|response| {
+                    if let ControllerResponseMessage::Bool(true) = response {
+                        Ok("load test paused".to_string())
+                    } else {
+                        Err("load test not running, failed to pause".to_string())
+                    }
+                }),
+            },
+            ControllerCommand::Report => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "report PATH",
+                    description: "write html report, or json if PATH ends with .json\n\n",
+                },
+                regex: r"(?i)^(report|write-report|write_report) (.+)$",
+                process_response: Box::new(|response| {
+                    if let ControllerResponseMessage::Bool(true) = response {
+                        Ok("report written".to_string())
+                    } else {
+                        Err("failed to write report, be sure load test has started and path is writable".to_string())
+                    }
+                }),
+            },
+            ControllerCommand::ResetMetrics => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "reset-metrics",
+                    description: "reset metrics, measuring from now on\n",
+                },
+                regex: r"(?i)^(resetmetrics|reset-metrics|reset_metrics|resetstats|reset-stats)$",
+                process_response: Box::new(|response| {
+                    if let ControllerResponseMessage::Bool(true) = response {
+                        Ok("metrics reset".to_string())
+                    } else {
+                        Err("load test not running, failed to reset metrics".to_string())
+                    }
+                }),
+            },
+            ControllerCommand::Resume => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "resume",
+                    description: "resume a paused load test\n",
+                },
+                regex: r"(?i)^resume$",
+                process_response: Box::new(|response| {
+                    if let ControllerResponseMessage::Bool(true) = response {
+                        Ok("load test resumed".to_string())
+                    } else {
+                        Err("load test not paused, failed to resume".to_string())
+                    }
+                }),
+            },
             ControllerCommand::RunTime => ControllerCommandDetails {
                 help: ControllerHelp {
                     name: "runtime TIME",
@@ -331,6 +664,34 @@ impl ControllerCommand {
                     }
                 }),
             },
+            ControllerCommand::ScenarioWeight => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "weight NAME INT",
+                    description: "set scenario weight, re-allocating users\n",
+                },
+                regex: r"(?i)^(weight|scenario-weight|scenario_weight) ([a-z0-9]+ \d+)$",
+                process_response: Box::new(|response| {
+                    if let ControllerResponseMessage::Bool(true) = response {
+                        Ok("scenario weight configured".to_string())
+                    } else {
+                        Err("failed to configure scenario weight, be sure it exists and doesn't have its own test plan".to_string())
+                    }
+                }),
+            },
+            ControllerCommand::Script => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "script SCRIPT",
+                    description: "run timed commands, (ie at 5m users 200; at 30m shutdown)\n\n",
+                },
+                regex: r"(?is)^(script|run-script|run_script) (.+)$",
+                process_response: Box::new(|response| {
+                    if let ControllerResponseMessage::Bool(true) = response {
+                        Ok("script started".to_string())
+                    } else {
+                        Err("failed to start script, be sure script is valid".to_string())
+                    }
+                }),
+            },
             ControllerCommand::Shutdown => ControllerCommandDetails {
                 help: ControllerHelp {
                     name: "shutdown",
@@ -345,6 +706,20 @@ impl ControllerCommand {
                     }
                 }),
             },
+            ControllerCommand::SkipTransaction => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "skip TRANSACTION",
+                    description: "skip all transactions with this name\n",
+                },
+                regex: r"(?i)^(skip|skip-transaction|skip_transaction) (.+)$",
+                process_response: Box::new(|response| {
+                    if let ControllerResponseMessage::Bool(true) = response {
+                        Ok("transaction skipped".to_string())
+                    } else {
+                        Err("failed to skip transaction, be sure it exists".to_string())
+                    }
+                }),
+            },
             ControllerCommand::Start => {
                 ControllerCommandDetails {
                     help: ControllerHelp {
@@ -378,6 +753,20 @@ impl ControllerCommand {
                     }
                 }),
             },
+            ControllerCommand::Status => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "status",
+                    description: "display load test status\n",
+                },
+                regex: r"(?i)^status$",
+                process_response: Box::new(|response| {
+                    if let ControllerResponseMessage::Status(status) = response {
+                        Ok(format!("{:#?}", status))
+                    } else {
+                        Err("error loading status".to_string())
+                    }
+                }),
+            },
             ControllerCommand::Stop => ControllerCommandDetails {
                 help: ControllerHelp {
                     name: "stop",
@@ -392,12 +781,26 @@ impl ControllerCommand {
                     }
                 }),
             },
+            ControllerCommand::Subscribe => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "subscribe",
+                    description: "push live metrics every second (WebSocket only)\n",
+                },
+                regex: r"(?i)^subscribe$",
+                process_response: Box::new(|response| {
+                    if let ControllerResponseMessage::Subscribe(_) = response {
+                        Ok("subscribed to live metrics".to_string())
+                    } else {
+                        Err("failed to subscribe to live metrics".to_string())
+                    }
+                }),
+            },
             ControllerCommand::TestPlan => ControllerCommandDetails {
                 help: ControllerHelp {
                     name: "test-plan PLAN",
-                    description: "define or replace test-plan, (ie 10,5m;10,1h;0,30s)\n\n",
+                    description: "define or replace test-plan, (ie 10,5m;10,1h;0,30s)\n",
                 },
-                regex: r"(?i)^(testplan|test_plan|test-plan|plan) (((\d+)\s*,\s*(\d+|((\d+?)h)?((\d+?)m)?((\d+?)s)?)*;*)+)$",
+                regex: r"(?i)^(testplan|test_plan|test-plan|plan) ((\s*((\d+)\s*,\s*(\d+|((\d+?)h)?((\d+?)m)?((\d+?)s)?)|[a-z]+\s*\([^)]*\))\s*;?)+)$",
                 process_response: Box::new(|response| {
                     if let ControllerResponseMessage::Bool(true) = response {
                         Ok("test-plan configured".to_string())
@@ -406,6 +809,47 @@ impl ControllerCommand {
                     }
                 }),
             },
+            ControllerCommand::ThrottleRequests => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "throttle INT",
+                    description: "set maximum requests per second, 0 disables\n",
+                },
+                regex: r"(?i)^(throttle|throttle_requests|throttle-requests) (\d+)$",
+                process_response: Box::new(|response| {
+                    if let ControllerResponseMessage::Bool(true) = response {
+                        Ok("throttle configured".to_string())
+                    } else {
+                        Err("failed to configure throttle".to_string())
+                    }
+                }),
+            },
+            ControllerCommand::UnskipTransaction => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "unskip TRANSACTION",
+                    description: "stop skipping transactions with this name\n\n",
+                },
+                regex: r"(?i)^(unskip|unskip-transaction|unskip_transaction) (.+)$",
+                process_response: Box::new(|response| {
+                    if let ControllerResponseMessage::Bool(true) = response {
+                        Ok("transaction no longer skipped".to_string())
+                    } else {
+                        Err("failed to unskip transaction, be sure it exists".to_string())
+                    }
+                }),
+            },
+            ControllerCommand::Unsubscribe => ControllerCommandDetails {
+                help: ControllerHelp {
+                    name: "unsubscribe",
+                    // No new-line as this is the last line of the help screen.
+                    description: "stop pushing live metrics (WebSocket only)",
+                },
+                regex: r"(?i)^unsubscribe$",
+                process_response: Box::new(|_| {
+                    let e = "received an impossible UNSUBSCRIBE command";
+                    error!("{}", e);
+                    Err(e.to_string())
+                }),
+            },
             ControllerCommand::Users => ControllerCommandDetails {
                 help: ControllerHelp {
                     name: "users INT",
@@ -436,6 +880,25 @@ impl ControllerCommand {
             } else {
                 None
             }
+        } else if self == &ControllerCommand::ThrottleRequests {
+            // The throttle can not be set to more than 1,000,000 requests per second.
+            match value.parse::<usize>() {
+                Ok(throttle_requests) if throttle_requests <= 1_000_000 => Some(value.to_string()),
+                _ => None,
+            }
+        } else if self == &ControllerCommand::ScenarioWeight {
+            // Scenarios must have a weight of at least 1.
+            match value.rsplit(' ').next().map(str::parse::<usize>) {
+                Some(Ok(weight)) if weight > 0 => Some(value.to_string()),
+                _ => None,
+            }
+        } else if self == &ControllerCommand::Script {
+            // Each line of the script must be a valid trigger followed by a valid command.
+            if ControllerScript::from_str(value).is_ok() {
+                Some(value.to_string())
+            } else {
+                None
+            }
         } else if value.is_empty() {
             None
         } else {
@@ -443,6 +906,34 @@ impl ControllerCommand {
         }
     }
 
+    // Parse a command the same way as when it's typed into the telnet Controller.
+    //
+    // Returns an error if the command isn't recognized, or requires a value that's missing
+    // or invalid.
+    pub(crate) fn parse_request(command_string: &str) -> Result<ControllerRequestMessage, String> {
+        let command = ControllerCommand::from_str(command_string)
+            .map_err(|_| format!("unrecognized controller command: '{}'", command_string))?;
+        let value = command.get_value(command_string);
+        // Commands that set a value display the value in their help.
+        if value.is_none() && command.details().help.name.contains(' ') {
+            return Err(format!(
+                "invalid value for {}: '{}'",
+                command.name(),
+                command_string
+            ));
+        }
+        Ok(ControllerRequestMessage { command, value })
+    }
+
+    // Describe the parent's response to this command the same way it's described to
+    // Controller clients.
+    pub(crate) fn describe_response(
+        &self,
+        response: ControllerResponseMessage,
+    ) -> Result<String, String> {
+        (self.details().process_response)(response)
+    }
+
     // If the regular expression that matches this command also matches a value, get and validate
     // the value.
     //
@@ -455,6 +946,63 @@ impl ControllerCommand {
         self.validate_value(value)
     }
 
+    // The name of this command, as displayed by the help screen.
+    fn name(&self) -> String {
+        self.details()
+            .help
+            .name
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    // The HTTP Controller endpoint that runs this command, if any.
+    #[cfg(feature = "http-controller")]
+    fn http_endpoint(&self) -> Option<ControllerHttpEndpoint> {
+        let (method, path, value) = match self {
+            ControllerCommand::Status => (Method::GET, "/status", None),
+            ControllerCommand::ConfigJson => (Method::GET, "/config", None),
+            ControllerCommand::MetricsJson => (Method::GET, "/metrics", None),
+            ControllerCommand::Start => (Method::POST, "/start", None),
+            ControllerCommand::Stop => (Method::POST, "/stop", None),
+            ControllerCommand::Pause => (Method::POST, "/pause", None),
+            ControllerCommand::Resume => (Method::POST, "/resume", None),
+            ControllerCommand::Shutdown => (Method::POST, "/shutdown", None),
+            ControllerCommand::Host => (Method::POST, "/host", Some("host")),
+            ControllerCommand::HatchRate => (Method::POST, "/hatch-rate", Some("hatch_rate")),
+            ControllerCommand::StartupTime => (Method::POST, "/startup-time", Some("startup_time")),
+            ControllerCommand::Users => (Method::POST, "/users", Some("users")),
+            ControllerCommand::RunTime => (Method::POST, "/run-time", Some("run_time")),
+            ControllerCommand::ThrottleRequests => (Method::POST, "/throttle", Some("throttle")),
+            ControllerCommand::TestPlan => (Method::POST, "/test-plan", Some("test_plan")),
+            ControllerCommand::AppendTestPlan => (Method::POST, "/append-plan", Some("test_plan")),
+            ControllerCommand::ExtendTestPlanStep => (Method::POST, "/extend-step", Some("time")),
+            ControllerCommand::JumpTestPlanStep => (Method::POST, "/jump-step", Some("step")),
+            ControllerCommand::Script => (Method::POST, "/script", Some("script")),
+            ControllerCommand::EnableScenario => (Method::POST, "/enable", Some("scenario")),
+            ControllerCommand::DisableScenario => (Method::POST, "/disable", Some("scenario")),
+            ControllerCommand::ScenarioWeight => (Method::POST, "/weight", Some("weight")),
+            ControllerCommand::SkipTransaction => (Method::POST, "/skip", Some("transaction")),
+            ControllerCommand::UnskipTransaction => (Method::POST, "/unskip", Some("transaction")),
+            ControllerCommand::ResetMetrics => (Method::POST, "/reset-metrics", None),
+            ControllerCommand::Report => (Method::POST, "/report", Some("path")),
+            // Help and exit only make sense for interactive Controllers, and the HTTP
+            // Controller always returns json.
+            ControllerCommand::Help
+            | ControllerCommand::Exit
+            | ControllerCommand::Config
+            | ControllerCommand::Metrics => return None,
+            // HTTP requests can't be pushed data.
+            ControllerCommand::Subscribe | ControllerCommand::Unsubscribe => return None,
+        };
+        Some(ControllerHttpEndpoint {
+            method,
+            path,
+            value,
+        })
+    }
+
     // Builds a help screen displayed when a controller receives the `help` command.
     fn display_help() -> String {
         let mut help_text = Vec::new();
@@ -513,6 +1061,64 @@ impl<G: Goose> GooseAttack<G> {
                                 ControllerResponseMessage::Metrics(Box::new(self.metrics.clone())),
                             );
                         }
+                        // Reset metrics while the load test is running, and acknowledge
+                        // command.
+                        ControllerCommand::ResetMetrics => {
+                            let running = [
+                                AttackPhase::Increase,
+                                AttackPhase::Maintain,
+                                AttackPhase::Decrease,
+                                AttackPhase::Pause,
+                            ]
+                            .contains(&self.attack_phase);
+                            if running {
+                                self.reset_controller_metrics(goose_attack_run_state)
+                                    .await?;
+                            }
+                            self.reply_to_controller(
+                                message,
+                                ControllerResponseMessage::Bool(running),
+                            );
+                        }
+                        // Write a report of the load test so far, and acknowledge command.
+                        ControllerCommand::Report => {
+                            let written = match message.request.value.clone() {
+                                // There's nothing to report until the load test has started.
+                                Some(path) if !self.metrics.history.is_empty() => {
+                                    match self
+                                        .write_controller_report(goose_attack_run_state, &path)
+                                        .await
+                                    {
+                                        Ok(_) => true,
+                                        Err(e) => {
+                                            warn!("failed to write report to {}: {}", path, e);
+                                            false
+                                        }
+                                    }
+                                }
+                                _ => false,
+                            };
+                            self.reply_to_controller(
+                                message,
+                                ControllerResponseMessage::Bool(written),
+                            );
+                        }
+                        // Send back the current status of the load test.
+                        ControllerCommand::Status => {
+                            self.update_duration();
+                            let status = ControllerStatus {
+                                phase: self.attack_phase.clone(),
+                                users: goose_attack_run_state.active_users,
+                                target_users: self.target_users(),
+                                host: self.configuration.host.clone(),
+                                duration: self.metrics.duration,
+                                throttle_requests: self.metrics.throttle_requests,
+                            };
+                            self.reply_to_controller(
+                                message,
+                                ControllerResponseMessage::Status(Box::new(status)),
+                            );
+                        }
                         // Start the load test, and acknowledge command.
                         ControllerCommand::Start => {
                             // We can only start an idle load test.
@@ -533,6 +1139,7 @@ impl<G: Goose> GooseAttack<G> {
                                     self.metrics.history.push(TestPlanHistory::step(
                                         TestPlanStepAction::Increasing,
                                         0,
+                                        self.test_plan.stage(0),
                                     ));
                                 } else {
                                     // Do not move to Starting phase if unable to prepare load test.
@@ -550,9 +1157,13 @@ impl<G: Goose> GooseAttack<G> {
                         }
                         // Stop the load test, and acknowledge command.
                         ControllerCommand::Stop => {
-                            // We can only stop a starting or running load test.
-                            if [AttackPhase::Increase, AttackPhase::Maintain]
-                                .contains(&self.attack_phase)
+                            // We can only stop a starting, running or paused load test.
+                            if [
+                                AttackPhase::Increase,
+                                AttackPhase::Maintain,
+                                AttackPhase::Pause,
+                            ]
+                            .contains(&self.attack_phase)
                             {
                                 // Don't shutdown when load test is stopped by controller, remain idle instead.
                                 goose_attack_run_state.shutdown_after_stop = false;
@@ -570,6 +1181,22 @@ impl<G: Goose> GooseAttack<G> {
                                 );
                             }
                         }
+                        // Pause the load test, and acknowledge command.
+                        ControllerCommand::Pause => {
+                            let paused = self.pause_attack(goose_attack_run_state);
+                            self.reply_to_controller(
+                                message,
+                                ControllerResponseMessage::Bool(paused),
+                            );
+                        }
+                        // Resume the load test, and acknowledge command.
+                        ControllerCommand::Resume => {
+                            let resumed = self.resume_attack(goose_attack_run_state);
+                            self.reply_to_controller(
+                                message,
+                                ControllerResponseMessage::Bool(resumed),
+                            );
+                        }
                         // Stop the load test, and acknowledge request.
                         ControllerCommand::Shutdown => {
                             // If load test is Idle, there are no metrics to display.
@@ -665,51 +1292,11 @@ impl<G: Goose> GooseAttack<G> {
                                             "changing users from {} to {}",
                                             goose_attack_run_state.active_users, new_users
                                         );
-                                        // Determine how long has elapsed since this step started.
-                                        let elapsed = self.step_elapsed() as usize;
-
-                                        // Determine how quickly to adjust user account.
-                                        let hatch_rate = if let Some(hatch_rate) =
-                                        self.configuration.hatch_rate.as_ref()
-                                        {
-                                            util::get_hatch_rate(Some(hatch_rate.to_string()))
-                                        } else {
-                                            util::get_hatch_rate(None)
-                                        };
-                                        // Convert hatch_rate to milliseconds.
-                                        let ms_hatch_rate = 1.0 / hatch_rate * 1_000.0;
-                                        // Determine how many users to increase or decrease by.
-                                        let user_difference = (goose_attack_run_state.active_users
-                                            as isize
-                                            - new_users as isize)
-                                            .abs();
-                                        // Multiply the user difference by the hatch rate to get the total_time required.
-                                        let total_time =
-                                            (ms_hatch_rate * user_difference as f32) as usize;
-
-                                        // Reset the test_plan to adjust to the newly specified users.
-                                        self.test_plan.steps = vec![
-                                            // Record how many active users there are currently.
-                                            (goose_attack_run_state.active_users, elapsed),
-                                            // Configure the new user count.
-                                            (new_users, total_time),
-                                        ];
-
-                                        // Reset the current step to what was happening when reconfiguration happened.
-                                        self.test_plan.current = 0;
-
-                                        // Allocate more users if increasing users.
-                                        if new_users > goose_attack_run_state.active_users {
-                                            self.weighted_users = self
-                                                .weight_scenario_users(user_difference as usize)?;
-                                        }
-
-                                        // Also update the running configurtion (this impacts if the test is stopped and then
-                                        // restarted through the controller).
-                                        self.configuration.users = Some(new_users);
-
-                                        // Finally, advance to the next step to adjust user count.
-                                        self.advance_test_plan(goose_attack_run_state);
+                                        // Rebuild the active test plan to adjust to the new user count.
+                                        self.change_running_users(
+                                            goose_attack_run_state,
+                                            new_users,
+                                        )?;
 
                                         self.reply_to_controller(
                                             message,
@@ -824,6 +1411,40 @@ impl<G: Goose> GooseAttack<G> {
                                 );
                             }
                         }
+                        ControllerCommand::ThrottleRequests => {
+                            // The controller uses a regular expression and validate_value to
+                            // confirm this is a valid number of requests per second.
+                            if let Some(throttle_requests) = message
+                                .request
+                                .value
+                                .as_ref()
+                                .and_then(|v| v.parse::<usize>().ok())
+                            {
+                                info!(
+                                    "changing throttle_requests from {} to {}",
+                                    self.configuration.throttle_requests, throttle_requests
+                                );
+                                self.configuration.throttle_requests = throttle_requests;
+                                // Update the throttle shared by all running GooseUsers.
+                                if let Some(throttle) = goose_attack_run_state.throttle.as_ref() {
+                                    throttle.set_rate(throttle_requests);
+                                }
+                                self.metrics.throttle_requests = throttle_requests;
+                                self.reply_to_controller(
+                                    message,
+                                    ControllerResponseMessage::Bool(true),
+                                );
+                            } else {
+                                warn!(
+                                    "Controller didn't provide throttle_requests: {:#?}",
+                                    &message.request
+                                );
+                                self.reply_to_controller(
+                                    message,
+                                    ControllerResponseMessage::Bool(false),
+                                );
+                            }
+                        }
                         ControllerCommand::TestPlan => {
                             if let Some(value) = &message.request.value {
                                 match value.parse::<TestPlan>() {
@@ -872,6 +1493,13 @@ impl<G: Goose> GooseAttack<G> {
                                                     ControllerResponseMessage::Bool(true),
                                                 );
                                             }
+                                            // The test plan can't be changed while paused.
+                                            AttackPhase::Pause => {
+                                                self.reply_to_controller(
+                                                    message,
+                                                    ControllerResponseMessage::Bool(false),
+                                                );
+                                            }
                                             _ => {
                                                 unreachable!("Controller used in impossible phase.")
                                             }
@@ -896,8 +1524,155 @@ impl<G: Goose> GooseAttack<G> {
                                 );
                             }
                         }
+                        ControllerCommand::AppendTestPlan => {
+                            // The controller uses a regular expression to validate the format
+                            // of the steps, but shapes can still have invalid arguments.
+                            let appended = match &message.request.value {
+                                Some(plan) if self.test_plan_changeable() => {
+                                    match self.append_test_plan(goose_attack_run_state, plan) {
+                                        Ok(()) => true,
+                                        Err(e) => {
+                                            warn!("Controller provided invalid test_plan: {}", e);
+                                            false
+                                        }
+                                    }
+                                }
+                                _ => false,
+                            };
+                            self.reply_to_controller(
+                                message,
+                                ControllerResponseMessage::Bool(appended),
+                            );
+                        }
+                        ControllerCommand::ExtendTestPlanStep => {
+                            // The controller uses a regular expression to validate that this
+                            // is a valid timespan.
+                            let extended = match &message.request.value {
+                                Some(time) if self.test_plan_changeable() => {
+                                    self.extend_test_plan_step(util::parse_timespan(time) * 1_000)
+                                }
+                                _ => false,
+                            };
+                            self.reply_to_controller(
+                                message,
+                                ControllerResponseMessage::Bool(extended),
+                            );
+                        }
+                        ControllerCommand::JumpTestPlanStep => {
+                            let step = match &message.request.value {
+                                Some(step) if self.test_plan_changeable() => {
+                                    self.test_plan.find_step(step.trim())
+                                }
+                                _ => None,
+                            };
+                            let jumped = match step {
+                                Some(step) => {
+                                    match self.jump_test_plan(goose_attack_run_state, step) {
+                                        Ok(()) => true,
+                                        Err(e) => {
+                                            warn!("failed to jump to test_plan step: {}", e);
+                                            false
+                                        }
+                                    }
+                                }
+                                None => false,
+                            };
+                            self.reply_to_controller(
+                                message,
+                                ControllerResponseMessage::Bool(jumped),
+                            );
+                        }
+                        ControllerCommand::EnableScenario | ControllerCommand::DisableScenario => {
+                            let active =
+                                message.request.command == ControllerCommand::EnableScenario;
+                            // The controller uses a regular expression to validate that this
+                            // is a valid scenario machine name.
+                            let changed = match &message.request.value {
+                                Some(machine_name) => self.set_scenario_active(
+                                    goose_attack_run_state,
+                                    machine_name,
+                                    active,
+                                )?,
+                                None => false,
+                            };
+                            self.reply_to_controller(
+                                message,
+                                ControllerResponseMessage::Bool(changed),
+                            );
+                        }
+                        ControllerCommand::ScenarioWeight => {
+                            // The controller uses a regular expression and validate_value to
+                            // confirm this is a scenario machine name followed by a weight.
+                            let changed = match message
+                                .request
+                                .value
+                                .as_ref()
+                                .and_then(|v| v.split_once(' '))
+                                .and_then(|(name, weight)| Some((name, weight.parse().ok()?)))
+                            {
+                                Some((machine_name, weight)) => self.set_scenario_weight(
+                                    goose_attack_run_state,
+                                    machine_name,
+                                    weight,
+                                )?,
+                                None => false,
+                            };
+                            self.reply_to_controller(
+                                message,
+                                ControllerResponseMessage::Bool(changed),
+                            );
+                        }
+                        // Start running a script of Controller commands, and acknowledge
+                        // command.
+                        ControllerCommand::Script => {
+                            // The controller already validated the script, but the parent
+                            // process parses it again to run it.
+                            let started = match message
+                                .request
+                                .value
+                                .as_deref()
+                                .map(ControllerScript::from_str)
+                            {
+                                Some(Ok(script)) => {
+                                    self.run_controller_script(goose_attack_run_state, script)
+                                }
+                                Some(Err(e)) => {
+                                    warn!("Controller provided invalid script: {}", e);
+                                    false
+                                }
+                                None => false,
+                            };
+                            self.reply_to_controller(
+                                message,
+                                ControllerResponseMessage::Bool(started),
+                            );
+                        }
+                        ControllerCommand::SkipTransaction
+                        | ControllerCommand::UnskipTransaction => {
+                            let skipped =
+                                message.request.command == ControllerCommand::SkipTransaction;
+                            let changed = match &message.request.value {
+                                Some(name) => self.skip_transactions(name, skipped),
+                                None => false,
+                            };
+                            self.reply_to_controller(
+                                message,
+                                ControllerResponseMessage::Bool(changed),
+                            );
+                        }
+                        // Send back a channel that receives live metrics and phase changes.
+                        ControllerCommand::Subscribe => {
+                            let events_rx =
+                                goose_attack_run_state.controller_subscriptions.subscribe();
+                            self.reply_to_controller(
+                                message,
+                                ControllerResponseMessage::Subscribe(events_rx),
+                            );
+                        }
                         // These messages shouldn't be received here.
-                        ControllerCommand::Help | ControllerCommand::Exit => {
+                        ControllerCommand::Help
+                        | ControllerCommand::Exit
+                        | ControllerCommand::Unsubscribe => {
                             warn!("Unexpected command: {:?}", &message.request);
                         }
                     }
@@ -911,6 +1686,40 @@ impl<G: Goose> GooseAttack<G> {
         Ok(())
     }
 
+    /// Push the metrics of each completed second to WebSocket Controller clients
+    /// subscribed to live metrics.
+    pub(crate) fn publish_controller_events(
+        &mut self,
+        goose_attack_run_state: &mut GooseAttackRunState,
+    ) {
+        let subscriptions = &mut goose_attack_run_state.controller_subscriptions;
+        if !subscriptions.is_active() {
+            return;
+        }
+        let current_second = match subscriptions.started {
+            Some(started) => started.elapsed().as_secs() as usize,
+            None => return,
+        };
+        // Wait an extra second before pushing metrics, giving GooseUser threads time to
+        // send all metrics for the second.
+        while subscriptions.next_second + 1 < current_second {
+            let second = subscriptions.next_second;
+            let response_times = self.graph_data.take_response_times(second);
+            subscriptions.send(ControllerEvent::Metrics(ControllerMetricsDelta {
+                second,
+                users: goose_attack_run_state.active_users,
+                requests: self.graph_data.requests_in_second(second),
+                errors: self.graph_data.errors_in_second(second),
+                average_response_time: self.graph_data.average_response_time_in_second(second),
+                median_response_time: response_time_percentile(&response_times, 0.5),
+                p95_response_time: response_time_percentile(&response_times, 0.95),
+                p99_response_time: response_time_percentile(&response_times, 0.99),
+                response_times,
+            }));
+            subscriptions.next_second += 1;
+        }
+    }
+
     /// Use the provided oneshot channel to reply to a controller client request.
     pub(crate) fn reply_to_controller(
         &mut self,
@@ -929,14 +1738,52 @@ impl<G: Goose> GooseAttack<G> {
             }
         }
     }
+
+    /// Run a script of Controller commands in the background until the load test shuts
+    /// down. Returns false if the Controller channel isn't enabled.
+    pub(crate) fn run_controller_script(
+        &mut self,
+        goose_attack_run_state: &mut GooseAttackRunState,
+        script: ControllerScript,
+    ) -> bool {
+        if let Some(channel_tx) = goose_attack_run_state.controller_channel_tx.clone() {
+            goose_attack_run_state
+                .controller_scripts
+                .push(tokio::spawn(script.run(channel_tx)));
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Send a request to the parent process from a Controller client running in the same
+/// process, such as a script or the dashboard, and wait for a reply.
+///
+/// Returns None if the parent process has closed the Controller channel.
+pub(crate) async fn send_controller_request(
+    channel_tx: &flume::Sender<ControllerRequest>,
+    client_id: u32,
+    request: ControllerRequestMessage,
+) -> Option<ControllerResponseMessage> {
+    let (response_tx, response_rx): (
+        tokio::sync::oneshot::Sender<ControllerResponse>,
+        tokio::sync::oneshot::Receiver<ControllerResponse>,
+    ) = tokio::sync::oneshot::channel();
+    channel_tx
+        .try_send(ControllerRequest {
+            response_channel: Some(response_tx),
+            client_id,
+            request,
+        })
+        .ok()?;
+    response_rx.await.ok().map(|response| response.response)
 }
 
 /// The control loop listens for connections on the configured TCP port. Each connection
 /// spawns a new thread so multiple clients can connect. Handles incoming connections for
-/// both telnet and WebSocket clients.
+/// telnet, WebSocket and HTTP clients.
 ///  -  @TODO: optionally limit how many controller connections are allowed
-///  -  @TODO: optionally require client authentication
-///  -  @TODO: optionally ssl-encrypt client communication
 pub(crate) async fn controller_main(
     // Expose load test configuration to controller thread.
     configuration: GooseConfiguration,
@@ -944,6 +1791,8 @@ pub(crate) async fn controller_main(
     channel_tx: flume::Sender<ControllerRequest>,
     // Which type of controller to launch.
     protocol: ControllerProtocol,
+    // Optionally encrypt connections to the controller.
+    tls: Option<ControllerTls>,
 ) -> io::Result<()> {
     // Build protocol-appropriate address.
     let address = match &protocol {
@@ -955,6 +1804,10 @@ pub(crate) async fn controller_main(
             "{}:{}",
             configuration.websocket_host, configuration.websocket_port
         ),
+        #[cfg(feature = "http-controller")]
+        ControllerProtocol::Http => {
+            format!("{}:{}", configuration.http_host, configuration.http_port)
+        }
     };
 
     // All controllers use a TcpListener port.
@@ -983,6 +1836,11 @@ pub(crate) async fn controller_main(
             peer_address,
             channel_tx: channel_tx.clone(),
             protocol: protocol.clone(),
+            subscription: Mutex::new(None),
+            tokens: configuration.controller_token.clone(),
+            // Clients are only granted a role once they authenticate.
+            role: ControllerRole::ReadOnly,
+            tls: tls.clone(),
         };
 
         // Spawn a new thread to communicate with a client. The returned JoinHandle is
@@ -1046,13 +1904,263 @@ async fn write_to_socket_raw(socket: &mut tokio::net::TcpStream, message: &str)
     }
 }
 
-/// Goose supports two different Controller protocols: telnet and WebSocket.
+/// Goose supports three different Controller protocols: telnet, WebSocket and HTTP.
 #[derive(Clone, Debug)]
 pub(crate) enum ControllerProtocol {
     /// Allows control of Goose via telnet.
     Telnet,
     /// Allows control of Goose via a WebSocket.
     WebSocket,
+    /// Allows control of Goose via HTTP requests.
+    #[cfg(feature = "http-controller")]
+    Http,
+}
+
+/// What a Controller client is allowed to do, determined by the token it connects with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControllerRole {
+    /// Can only run commands that don't change the load test, such as `status` and
+    /// `metrics`.
+    ReadOnly,
+    /// Can run all commands.
+    Control,
+}
+
+impl ControllerRole {
+    /// Whether or not a client with this role can run the command.
+    pub fn allows(&self, command: &ControllerCommand) -> bool {
+        match self {
+            ControllerRole::Control => true,
+            ControllerRole::ReadOnly => matches!(
+                command,
+                ControllerCommand::Help
+                    | ControllerCommand::Exit
+                    | ControllerCommand::Status
+                    | ControllerCommand::Config
+                    | ControllerCommand::ConfigJson
+                    | ControllerCommand::Metrics
+                    | ControllerCommand::MetricsJson
+                    | ControllerCommand::Subscribe
+                    | ControllerCommand::Unsubscribe
+            ),
+        }
+    }
+
+    /// Returns an error if a client with this role can't run the command.
+    fn authorize(&self, command: &ControllerCommand) -> Result<(), String> {
+        if self.allows(command) {
+            Ok(())
+        } else {
+            Err(format!(
+                "permission denied, {} requires a control token",
+                command.name()
+            ))
+        }
+    }
+}
+
+/// The tokens Controller clients must provide to connect, configured with
+/// `--controller-token`.
+///
+/// Tokens are defined as a comma separated list. Tokens prefixed with `read:` only allow
+/// read-only access, all other tokens allow full control of the load test (the `control:`
+/// prefix is optional), for example: `--controller-token "s3cr3t,read:dashboard"`.
+#[derive(Clone, Default)]
+pub struct ControllerTokens {
+    tokens: Vec<(String, ControllerRole)>,
+}
+
+impl ControllerTokens {
+    /// Return the role granted by a token, or None if the token isn't valid.
+    pub fn role(&self, token: &str) -> Option<ControllerRole> {
+        self.tokens
+            .iter()
+            .find(|(valid_token, _)| tokens_match(valid_token, token))
+            .map(|(_, role)| *role)
+    }
+}
+
+/// Compare every byte of the tokens, so the time taken doesn't reveal how much of a
+/// token was guessed correctly.
+fn tokens_match(valid_token: &str, token: &str) -> bool {
+    valid_token.len() == token.len()
+        && valid_token
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+/// Never display the tokens, only how many of each role are configured.
+impl std::fmt::Debug for ControllerTokens {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let read_only = self
+            .tokens
+            .iter()
+            .filter(|(_, role)| *role == ControllerRole::ReadOnly)
+            .count();
+        f.debug_struct("ControllerTokens")
+            .field("control", &(self.tokens.len() - read_only))
+            .field("read_only", &read_only)
+            .finish()
+    }
+}
+
+impl FromStr for ControllerTokens {
+    type Err = GooseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = Vec::new();
+        // Tokens are a comma separated list of "[read:|control:]{token}", white space is
+        // ignored.
+        for token in s.split(',').map(str::trim) {
+            let (token, role) = if let Some(token) = token.strip_prefix("read:") {
+                (token, ControllerRole::ReadOnly)
+            } else if let Some(token) = token.strip_prefix("control:") {
+                (token, ControllerRole::Control)
+            } else {
+                (token, ControllerRole::Control)
+            };
+            if token.is_empty() {
+                return Err(GooseError::InvalidOption {
+                    option: "`configuration.controller_token`".to_string(),
+                    // Don't expose the other tokens.
+                    value: "[redacted]".to_string(),
+                    detail: "Controller tokens can not be empty.".to_string(),
+                });
+            }
+            tokens.push((token.to_string(), role));
+        }
+        Ok(ControllerTokens { tokens })
+    }
+}
+
+/// A connection to a WebSocket or HTTP Controller client, optionally encrypted with TLS.
+trait ControllerStream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> ControllerStream for T {}
+
+/// Encrypts connections to the WebSocket and HTTP Controllers, configured with
+/// `--controller-tls-cert` and `--controller-tls-key`.
+#[derive(Clone)]
+#[cfg_attr(not(feature = "rustls-tls"), allow(dead_code))]
+pub(crate) struct ControllerTls {
+    #[cfg(feature = "rustls-tls")]
+    acceptor: tokio_rustls::TlsAcceptor,
+}
+
+impl ControllerTls {
+    /// Load the configured certificate chain and private key, returns None if TLS isn't
+    /// enabled.
+    pub(crate) fn load(configuration: &GooseConfiguration) -> Result<Option<Self>, GooseError> {
+        if configuration.controller_tls_cert.is_empty() {
+            return Ok(None);
+        }
+
+        #[cfg(feature = "rustls-tls")]
+        {
+            let invalid = |option: &str, value: &str, detail: String| GooseError::InvalidOption {
+                option: format!("`configuration.{}`", option),
+                value: value.to_string(),
+                detail,
+            };
+
+            let cert_file = &configuration.controller_tls_cert;
+            let certs = std::fs::File::open(cert_file)
+                .and_then(|file| rustls_pemfile::certs(&mut io::BufReader::new(file)))
+                .map_err(|e| {
+                    invalid(
+                        "controller_tls_cert",
+                        cert_file,
+                        format!("failed to read certificates: {}", e),
+                    )
+                })?;
+            if certs.is_empty() {
+                return Err(invalid(
+                    "controller_tls_cert",
+                    cert_file,
+                    "no PEM encoded certificates found.".to_string(),
+                ));
+            }
+
+            // The private key can be PKCS #8 or RSA encoded.
+            let key_file = &configuration.controller_tls_key;
+            let key = std::fs::File::open(key_file)
+                .and_then(|file| {
+                    rustls_pemfile::read_all(&mut io::BufReader::new(file)).map(|items| {
+                        items.into_iter().find_map(|item| match item {
+                            rustls_pemfile::Item::PKCS8Key(key)
+                            | rustls_pemfile::Item::RSAKey(key)
+                            | rustls_pemfile::Item::ECKey(key) => Some(key),
+                            _ => None,
+                        })
+                    })
+                })
+                .map_err(|e| {
+                    invalid(
+                        "controller_tls_key",
+                        key_file,
+                        format!("failed to read private key: {}", e),
+                    )
+                })?
+                .ok_or_else(|| {
+                    invalid(
+                        "controller_tls_key",
+                        key_file,
+                        "no PEM encoded private key found.".to_string(),
+                    )
+                })?;
+
+            let config = tokio_rustls::rustls::ServerConfig::builder()
+                .with_safe_defaults()
+                .with_no_client_auth()
+                .with_single_cert(
+                    certs
+                        .into_iter()
+                        .map(tokio_rustls::rustls::Certificate)
+                        .collect(),
+                    tokio_rustls::rustls::PrivateKey(key),
+                )
+                .map_err(|e| {
+                    invalid(
+                        "controller_tls_cert",
+                        cert_file,
+                        format!("invalid certificate or private key: {}", e),
+                    )
+                })?;
+
+            Ok(Some(ControllerTls {
+                acceptor: tokio_rustls::TlsAcceptor::from(Arc::new(config)),
+            }))
+        }
+
+        #[cfg(not(feature = "rustls-tls"))]
+        Err(GooseError::InvalidOption {
+            option: "`configuration.controller_tls_cert`".to_string(),
+            value: configuration.controller_tls_cert.to_string(),
+            detail: "Goose must be compiled with the `rustls-tls` feature to encrypt Controller connections.".to_string(),
+        })
+    }
+
+    /// Perform the TLS handshake with a newly connected client.
+    async fn accept(&self, stream: tokio::net::TcpStream) -> io::Result<Box<dyn ControllerStream>> {
+        #[cfg(feature = "rustls-tls")]
+        return Ok(Box::new(self.acceptor.accept(stream).await?));
+
+        #[cfg(not(feature = "rustls-tls"))]
+        Ok(Box::new(stream))
+    }
+}
+
+/// Where the HTTP Controller serves a command.
+#[cfg(feature = "http-controller")]
+#[derive(Clone, Debug)]
+pub(crate) struct ControllerHttpEndpoint {
+    // The HTTP method that runs the command.
+    method: Method,
+    // The path that runs the command.
+    path: &'static str,
+    // If the command sets a value, the json key the value is read from.
+    value: Option<&'static str>,
 }
 
 /// All commands define their own ControllerHelp to create a help screen.
@@ -1079,7 +2187,7 @@ pub(crate) struct ControllerCommandDetails<'a> {
 }
 
 /// This structure is used to send commands and values to the parent process.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct ControllerRequestMessage {
     /// The command that is being sent to the parent.
     pub command: ControllerCommand,
@@ -1096,6 +2204,40 @@ pub(crate) enum ControllerResponseMessage {
     Config(Box<GooseConfiguration>),
     /// A response containing current load test metrics.
     Metrics(Box<GooseMetrics>),
+    /// A response containing the current load test status.
+    Status(Box<ControllerStatus>),
+    /// A response containing a channel that receives live metrics.
+    Subscribe(broadcast::Receiver<ControllerEvent>),
+}
+
+/// The current status of the load test, returned by the `status` command.
+///
+/// The HTTP Controller returns the status from `GET /status` in the following format:
+/// ```json
+/// {
+///     "phase": "Maintain",
+///     "users": 10,
+///     "target_users": 10,
+///     "host": "http://local.dev/",
+///     "duration": 35,
+///     "throttle_requests": 0
+/// }
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ControllerStatus {
+    /// Which phase the load test is in.
+    pub phase: AttackPhase,
+    /// How many [`GooseUser`](../goose/struct.GooseUser.html)s are running.
+    pub users: usize,
+    /// How many [`GooseUser`](../goose/struct.GooseUser.html)s the current test plan step
+    /// is ramping to, or that will be launched when an idle load test starts.
+    pub target_users: usize,
+    /// The host being load tested.
+    pub host: String,
+    /// How many seconds the load test has been running.
+    pub duration: usize,
+    /// The current maximum requests per second, or 0 if the throttle is disabled.
+    pub throttle_requests: usize,
 }
 
 /// The request that's passed from the controller to the parent thread.
@@ -1142,43 +2284,470 @@ pub(crate) struct ControllerResponse {
 ///
 /// Responses will be formatted as defined in
 /// [ControllerWebSocketResponse](./struct.ControllerWebSocketResponse.html).
+///
+/// This is version 1 of the WebSocket Controller protocol, new clients should instead
+/// use the typed requests defined in
+/// [ControllerWebSocketTypedRequest](./struct.ControllerWebSocketTypedRequest.html).
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ControllerWebSocketRequest {
     /// A valid command string.
     pub request: String,
 }
 
-/// This structure defines the json format of any response returned from the WebSocket
-/// Controller.
+/// This structure defines the json format of any response returned from the WebSocket
+/// Controller.
+///
+/// Responses are in the following format:
+/// ```json
+/// {
+///     "response": String,
+///     "success": bool,
+/// }
+/// ```
+///
+/// # Example
+/// The following response will be returned when a request is made to shut down the
+/// load test:
+/// ```json
+/// {
+///     "response": "load test shut down",
+///     "success": true
+/// }
+/// ```
+///
+/// Requests must be formatted as defined in
+/// [ControllerWebSocketRequest](./struct.ControllerWebSocketRequest.html).
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ControllerWebSocketResponse {
+    /// The response from the controller.
+    pub response: String,
+    /// Whether the request was successful or not.
+    pub success: bool,
+}
+
+/// The current version of the typed WebSocket Controller protocol.
+///
+/// Version 1 is the original protocol defined by
+/// [ControllerWebSocketRequest](./struct.ControllerWebSocketRequest.html), which is still
+/// supported.
+pub const WEBSOCKET_PROTOCOL_VERSION: u32 = 2;
+
+/// This structure defines the json format of a typed request sent to the WebSocket
+/// Controller.
+///
+/// Requests are made in the following format:
+/// ```json
+/// {
+///     "version": 2,
+///     "id": String or Number,
+///     "command": String,
+///     "value": String or Number
+/// }
+/// ```
+///
+/// The `command` is the name of a [`ControllerCommand`](./enum.ControllerCommand.html)
+/// as listed by the `help` command, such as `users` or `test-plan`. The `value` is only
+/// required by commands that set a value, and is validated the same way as it is by the
+/// telnet Controller. The optional `id` is returned unchanged in the response, allowing
+/// clients to match responses to requests. If the `version` is not set, the current
+/// [`WEBSOCKET_PROTOCOL_VERSION`] is assumed.
+///
+/// # Example
+/// The following request will configure the load test to run 50 users:
+/// ```json
+/// {
+///     "version": 2,
+///     "id": 7,
+///     "command": "users",
+///     "value": 50
+/// }
+/// ```
+///
+/// Responses will be formatted as defined in
+/// [ControllerWebSocketTypedResponse](./struct.ControllerWebSocketTypedResponse.html).
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ControllerWebSocketTypedRequest {
+    /// The version of the protocol the request is made with.
+    #[serde(default)]
+    pub version: Option<u32>,
+    /// An optional id that is returned in the response.
+    #[serde(default)]
+    pub id: Option<serde_json::Value>,
+    /// The name of the command to run.
+    pub command: String,
+    /// The value to set, if the command sets a value.
+    #[serde(default)]
+    pub value: Option<serde_json::Value>,
+}
+
+/// This structure defines the json format of any response returned from the WebSocket
+/// Controller to a
+/// [ControllerWebSocketTypedRequest](./struct.ControllerWebSocketTypedRequest.html).
+///
+/// Responses are in the following format:
+/// ```json
+/// {
+///     "version": 2,
+///     "id": String or Number or null,
+///     "command": String or null,
+///     "success": bool,
+///     "message": String or null,
+///     "data": Object or null,
+///     "error": Object or null
+/// }
+/// ```
+///
+/// Commands that return data (`config`, `config-json`, `metrics`, `metrics-json` and
+/// `status`) set `data`, all other successful commands set `message`. Failed requests set
+/// `error` as defined in
+/// [ControllerWebSocketError](./struct.ControllerWebSocketError.html).
+///
+/// # Example
+/// The following response will be returned when a request with the id `7` is made to
+/// configure the load test to run 50 users:
+/// ```json
+/// {
+///     "version": 2,
+///     "id": 7,
+///     "command": "users",
+///     "success": true,
+///     "message": "users configured",
+///     "data": null,
+///     "error": null
+/// }
+/// ```
+#[derive(Debug, Serialize)]
+pub struct ControllerWebSocketTypedResponse {
+    /// The version of the protocol the response is made with.
+    pub version: u32,
+    /// The id of the request, if it had one.
+    pub id: Option<serde_json::Value>,
+    /// The name of the command that was run, if it was recognized.
+    pub command: Option<String>,
+    /// Whether the request was successful or not.
+    pub success: bool,
+    /// A description of what happened, for commands that don't return data.
+    pub message: Option<String>,
+    /// The data returned by the command.
+    pub data: Option<ControllerWebSocketData>,
+    /// Why the request failed.
+    pub error: Option<ControllerWebSocketError>,
+}
+
+/// The data returned in a
+/// [ControllerWebSocketTypedResponse](./struct.ControllerWebSocketTypedResponse.html).
+///
+/// The data is identified by its key, for example the `status` command returns:
+/// ```json
+/// {
+///     "status": {
+///         "phase": "Maintain",
+///         "users": 10,
+///         "host": "http://local.dev/",
+///         "duration": 35,
+///         "throttle_requests": 0
+///     }
+/// }
+/// ```
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControllerWebSocketData {
+    /// The load test configuration.
+    Config(Box<GooseConfiguration>),
+    /// The load test metrics.
+    Metrics(Box<GooseMetrics>),
+    /// The load test status.
+    Status(Box<ControllerStatus>),
+}
+
+/// Why a [ControllerWebSocketTypedRequest](./struct.ControllerWebSocketTypedRequest.html)
+/// failed.
+///
+/// # Example
+/// ```json
+/// {
+///     "code": "invalid_value",
+///     "message": "invalid value for users: many"
+/// }
+/// ```
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ControllerWebSocketError {
+    /// A machine-readable error code.
+    pub code: ControllerErrorCode,
+    /// A description of the error.
+    pub message: String,
+}
+
+/// Machine-readable error codes returned by the WebSocket Controller.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControllerErrorCode {
+    /// The request was made with an unsupported protocol version.
+    UnsupportedVersion,
+    /// The command is not a known [`ControllerCommand`](./enum.ControllerCommand.html).
+    UnrecognizedCommand,
+    /// The value is missing, or is not valid for the command.
+    InvalidValue,
+    /// The command can't be run in the current phase of the load test, for example
+    /// stopping an idle load test.
+    CommandFailed,
+    /// The load test is shutting down and can no longer be controlled.
+    Unavailable,
+    /// The client connected with a read-only token, and the command changes the load
+    /// test.
+    PermissionDenied,
+}
+
+/// Events pushed to WebSocket Controller clients after sending the `subscribe` command.
+///
+/// Events are pushed in the following format, and can be told apart from responses by
+/// their `event` key:
+/// ```json
+/// {
+///     "version": 2,
+///     "event": Object
+/// }
+/// ```
+///
+/// # Example
+/// The event pushed when the load test enters the maintain phase:
+/// ```json
+/// {
+///     "version": 2,
+///     "event": {
+///         "phase": "Maintain"
+///     }
+/// }
+/// ```
+#[derive(Debug, Serialize)]
+pub struct ControllerWebSocketEvent {
+    /// The version of the protocol the event is sent with.
+    pub version: u32,
+    /// The event.
+    pub event: ControllerEvent,
+}
+
+/// Live updates pushed to subscribed WebSocket Controller clients.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControllerEvent {
+    /// The metrics collected during one second of the load test.
+    Metrics(ControllerMetricsDelta),
+    /// The load test entered a new phase.
+    Phase(AttackPhase),
+}
+
+/// The metrics collected during one second of the load test, pushed to subscribed
+/// WebSocket Controller clients.
 ///
-/// Responses are in the following format:
+/// Metrics are pushed one second after the second completes, allowing time for all
+/// metrics to be collected. All response times are in milliseconds.
+///
+/// # Example
 /// ```json
 /// {
-///     "response": String,
-///     "success": bool,
+///     "metrics": {
+///         "second": 12,
+///         "users": 10,
+///         "requests": 231,
+///         "errors": 2,
+///         "average_response_time": 41.3,
+///         "median_response_time": 38,
+///         "p95_response_time": 72,
+///         "p99_response_time": 105
+///     }
 /// }
 /// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ControllerMetricsDelta {
+    /// Which second of the load test the metrics were collected in.
+    pub second: usize,
+    /// How many [`GooseUser`](../goose/struct.GooseUser.html)s are running when the
+    /// metrics are pushed.
+    pub users: usize,
+    /// How many requests were made.
+    pub requests: u32,
+    /// How many requests failed.
+    pub errors: u32,
+    /// The average response time.
+    pub average_response_time: f32,
+    /// The response time that 50% of requests finished within.
+    pub median_response_time: usize,
+    /// The response time that 95% of requests finished within.
+    pub p95_response_time: usize,
+    /// The response time that 99% of requests finished within.
+    pub p99_response_time: usize,
+    /// How many requests took each response time, used by Controller scripts. Not sent to
+    /// WebSocket Controller clients.
+    #[serde(skip)]
+    pub(crate) response_times: BTreeMap<usize, usize>,
+}
+
+/// Tracks the WebSocket Controller clients subscribed to live metrics.
+#[derive(Debug, Default)]
+pub(crate) struct ControllerSubscriptions {
+    /// Sends events to all subscribed clients, created by the first subscription.
+    events_tx: Option<broadcast::Sender<ControllerEvent>>,
+    /// When the load test started, used to find which seconds have completed.
+    started: Option<std::time::Instant>,
+    /// The next second of metrics to push to subscribed clients.
+    next_second: usize,
+}
+
+impl ControllerSubscriptions {
+    /// How many events can be queued for a slow client before it starts skipping them.
+    const CAPACITY: usize = 64;
+
+    /// Returns true if any clients are subscribed.
+    pub(crate) fn is_active(&self) -> bool {
+        match self.events_tx.as_ref() {
+            Some(events_tx) => events_tx.receiver_count() > 0,
+            None => false,
+        }
+    }
+
+    /// Subscribe a client, returning the channel it receives events on. When no other
+    /// clients are subscribed, metrics are pushed from the current second instead of
+    /// every second that completed while nobody was subscribed.
+    fn subscribe(&mut self) -> broadcast::Receiver<ControllerEvent> {
+        if !self.is_active() {
+            if let Some(started) = self.started {
+                self.next_second = started.elapsed().as_secs() as usize;
+            }
+        }
+        self.events_tx
+            .get_or_insert_with(|| broadcast::channel(Self::CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Start pushing metrics from the first second of a newly started load test.
+    pub(crate) fn restart(&mut self) {
+        self.started = Some(std::time::Instant::now());
+        self.next_second = 0;
+    }
+
+    /// Continue pushing metrics from where they were when the load test was paused.
+    pub(crate) fn resume(&mut self, paused: Duration) {
+        if let Some(started) = self.started.as_mut() {
+            *started += paused;
+        }
+    }
+
+    /// Push an event to all subscribed clients.
+    pub(crate) fn send(&self, event: ControllerEvent) {
+        if let Some(events_tx) = self.events_tx.as_ref() {
+            // An error only means no clients are subscribed.
+            let _ = events_tx.send(event);
+        }
+    }
+}
+
+/// The response time that `percent` of the requests finished within.
+pub(crate) fn response_time_percentile(
+    response_times: &BTreeMap<usize, usize>,
+    percent: f32,
+) -> usize {
+    let total: usize = response_times.values().sum();
+    let target = ((total as f32 * percent).round() as usize).max(1);
+    let mut seen = 0;
+    for (response_time, count) in response_times {
+        seen += count;
+        if seen >= target {
+            return *response_time;
+        }
+    }
+    0
+}
+
+/// Requests accepted by the WebSocket Controller, in either protocol version.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ControllerWebSocketRequestVersion {
+    /// The original protocol, `{"request": String}`.
+    Original(ControllerWebSocketRequest),
+    /// The typed protocol.
+    Typed(ControllerWebSocketTypedRequest),
+}
+
+/// This structure defines the json format of responses returned from the HTTP Controller
+/// when running a command that doesn't return data.
+///
+/// `GET /status`, `GET /config` and `GET /metrics` instead return
+/// [`ControllerStatus`](./struct.ControllerStatus.html), the
+/// [`GooseConfiguration`](../config/struct.GooseConfiguration.html) and the
+/// [`GooseMetrics`](../metrics/struct.GooseMetrics.html) in json format.
 ///
 /// # Example
-/// The following response will be returned when a request is made to shut down the
-/// load test:
+/// The following response will be returned when `POST /users` is sent the body
+/// `{"users": 50}`:
 /// ```json
 /// {
-///     "response": "load test shut down",
-///     "success": true
+///     "success": true,
+///     "message": "users configured"
 /// }
 /// ```
 ///
-/// Requests must be formatted as defined in
-/// [ControllerWebSocketRequest](./struct.ControllerWebSocketRequest.html).
+/// Failed requests return an HTTP error status code, and `success` is `false`.
 #[derive(Debug, Deserialize, Serialize)]
-pub struct ControllerWebSocketResponse {
-    /// The response from the controller.
-    pub response: String,
+pub struct ControllerHttpResponse {
     /// Whether the request was successful or not.
     pub success: bool,
+    /// A description of what happened.
+    pub message: String,
+}
+
+/// Build an HTTP Controller response containing json.
+#[cfg(feature = "http-controller")]
+fn http_json_response<T: Serialize>(status: StatusCode, body: &T) -> hyper::Response<Body> {
+    match serde_json::to_string(body) {
+        Ok(json) => hyper::Response::builder()
+            .status(status)
+            .header(hyper::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(json))
+            .expect("failed to build HTTP response"),
+        Err(e) => {
+            warn!("failed to json encode response: {}", e);
+            hyper::Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::empty())
+                .expect("failed to build HTTP response")
+        }
+    }
+}
+
+/// Build an HTTP Controller response for a command that doesn't return data.
+#[cfg(feature = "http-controller")]
+fn http_message_response(status: StatusCode, message: String) -> hyper::Response<Body> {
+    http_json_response(
+        status,
+        &ControllerHttpResponse {
+            success: status.is_success(),
+            message,
+        },
+    )
+}
+
+/// Extract the token a client authenticates with from an `Authorization: Bearer` header,
+/// or from a `token` query parameter as browsers can't set WebSocket request headers.
+fn request_token(headers: &http::HeaderMap, uri: &http::Uri) -> Option<String> {
+    if let Some(token) = headers
+        .get(http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    {
+        return Some(token.trim().to_string());
+    }
+    url::form_urlencoded::parse(uri.query()?.as_bytes())
+        .find(|(key, _)| key == "token")
+        .map(|(_, token)| token.into_owned())
 }
 
+/// The WebSocket Controller handshake request, used to authenticate the client.
+type WebSocketHandshakeRequest = tokio_tungstenite::tungstenite::handshake::server::Request;
+
+/// The response refusing a WebSocket Controller handshake.
+type WebSocketHandshakeError = tokio_tungstenite::tungstenite::handshake::server::ErrorResponse;
+
 /// Return type to indicate whether or not to exit the Controller thread.
 type ControllerExit = bool;
 
@@ -1193,7 +2762,7 @@ type ControllerWebSocketMessage = std::result::Result<
 
 /// Simplify the ControllerExecuteCommand trait definition for WebSockets.
 type ControllerWebSocketSender = futures::stream::SplitSink<
-    tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>,
+    tokio_tungstenite::WebSocketStream<Box<dyn ControllerStream>>,
     tokio_tungstenite::tungstenite::Message,
 >;
 
@@ -1208,11 +2777,19 @@ pub(crate) struct ControllerState {
     channel_tx: flume::Sender<ControllerRequest>,
     /// Which protocol this Controller understands.
     protocol: ControllerProtocol,
+    /// Receives live metrics if the WebSocket Controller client subscribed.
+    subscription: Mutex<Option<broadcast::Receiver<ControllerEvent>>>,
+    /// If set, clients must provide one of these tokens to connect.
+    tokens: Option<ControllerTokens>,
+    /// What the connected client is allowed to do.
+    role: ControllerRole,
+    /// If set, connections to the WebSocket and HTTP Controllers are encrypted.
+    tls: Option<ControllerTls>,
 }
 
 // Defines functions shared by all Controllers.
 impl ControllerState {
-    async fn accept_connections(self, mut socket: tokio::net::TcpStream) {
+    async fn accept_connections(mut self, mut socket: tokio::net::TcpStream) {
         info!(
             "{:?} client [{}] connected from {}",
             self.protocol, self.thread_id, self.peer_address
@@ -1221,6 +2798,26 @@ impl ControllerState {
             ControllerProtocol::Telnet => {
                 let mut buf: ControllerTelnetMessage = [0; 1024];
 
+                // Prompt for a token if clients must authenticate.
+                let mut token = None;
+                if self.tokens.is_some() {
+                    write_to_socket_raw(&mut socket, "token: ").await;
+                    let mut token_buf: ControllerTelnetMessage = [0; 1024];
+                    if let Ok(n) = socket.read(&mut token_buf).await {
+                        if n > 0 {
+                            token = self.get_command_string(token_buf).await.ok();
+                        }
+                    }
+                }
+                if !self.authenticate(token.as_deref().map(str::trim)) {
+                    write_to_socket_raw(&mut socket, "invalid token\n").await;
+                    info!(
+                        "Telnet client [{}] failed to authenticate from {}",
+                        self.thread_id, self.peer_address
+                    );
+                    return;
+                }
+
                 // Display initial goose> prompt.
                 write_to_socket_raw(&mut socket, "goose> ").await;
 
@@ -1276,19 +2873,82 @@ impl ControllerState {
                     }
                 }
             }
+            #[cfg(feature = "http-controller")]
+            ControllerProtocol::Http => {
+                let stream = match self.encrypt(socket).await {
+                    Some(stream) => stream,
+                    None => return,
+                };
+                let thread_id = self.thread_id;
+                let peer_address = self.peer_address.clone();
+                let controller_state = Arc::new(self);
+                // Each request on the connection is handled by process_http_request().
+                let service = hyper::service::service_fn(move |request| {
+                    let controller_state = controller_state.clone();
+                    async move {
+                        Ok::<_, hyper::Error>(controller_state.process_http_request(request).await)
+                    }
+                });
+                if let Err(e) = hyper::server::conn::Http::new()
+                    .http1_only(true)
+                    .serve_connection(stream, service)
+                    .await
+                {
+                    info!("HTTP client [{}] error: {}", thread_id, e);
+                }
+                info!(
+                    "HTTP client [{}] disconnected from {}",
+                    thread_id, peer_address
+                );
+            }
             ControllerProtocol::WebSocket => {
-                let stream = match tokio_tungstenite::accept_async(socket).await {
+                let stream = match self.encrypt(socket).await {
+                    Some(stream) => stream,
+                    None => return,
+                };
+                // Clients authenticate during the handshake, refuse the connection if the
+                // token isn't valid.
+                let mut role = None;
+                // The error response type is defined by tungstenite.
+                #[allow(clippy::result_large_err)]
+                let authenticate = |request: &WebSocketHandshakeRequest, response| {
+                    role =
+                        self.role_for(request_token(request.headers(), request.uri()).as_deref());
+                    if role.is_some() {
+                        Ok(response)
+                    } else {
+                        let mut response = WebSocketHandshakeError::new(Some(
+                            "a valid token is required".to_string(),
+                        ));
+                        *response.status_mut() = StatusCode::UNAUTHORIZED;
+                        Err(response)
+                    }
+                };
+                let stream = match tokio_tungstenite::accept_hdr_async(stream, authenticate).await {
                     Ok(s) => s,
                     Err(e) => {
                         info!("invalid WebSocket handshake: {}", e);
                         return;
                     }
                 };
+                if let Some(role) = role {
+                    self.role = role;
+                }
                 let (mut ws_sender, mut ws_receiver) = stream.split();
 
                 loop {
-                    // Wait until the client sends a command.
-                    let data = match ws_receiver.next().await {
+                    // Wait until the client sends a command, pushing live metrics to the
+                    // client in the meantime if it subscribed.
+                    let data = tokio::select! {
+                        data = ws_receiver.next() => data,
+                        event = self.next_event() => {
+                            if let Some(event) = event {
+                                self.write_event(&mut ws_sender, event).await;
+                            }
+                            continue;
+                        }
+                    };
+                    let data = match data {
                         Some(d) => (d),
                         None => {
                             // Returning with no data means the client disconnected.
@@ -1301,26 +2961,39 @@ impl ControllerState {
                     };
 
                     // Extract the command string in a protocol-specific way.
-                    if let Ok(command_string) = self.get_command_string(data).await {
-                        // Extract the command and value in a generic way.
-                        if let Ok(request_message) = self.get_match(command_string.trim()).await {
-                            if self.execute_command(&mut ws_sender, request_message).await {
-                                // If execute_command() returns true, it's time to exit.
-                                info!(
-                                    "Telnet client [{}] disconnected from {}",
-                                    self.thread_id, self.peer_address
-                                );
-                                break;
+                    if let Ok(request) = self.get_command_string(data).await {
+                        let exit_controller = match request {
+                            // Requests made with the original protocol.
+                            ControllerWebSocketRequestVersion::Original(request) => {
+                                // Extract the command and value in a generic way.
+                                if let Ok(request_message) =
+                                    self.get_match(request.request.trim()).await
+                                {
+                                    self.execute_command(&mut ws_sender, request_message).await
+                                } else {
+                                    self.write_to_socket(
+                                        &mut ws_sender,
+                                        Err(
+                                            "unrecognized command, see Goose book https://book.goose.rs/controller/websocket.html"
+                                                .to_string(),
+                                        ),
+                                    )
+                                        .await;
+                                    false
+                                }
                             }
-                        } else {
-                            self.write_to_socket(
-                                &mut ws_sender,
-                                Err(
-                                    "unrecognized command, see Goose book https://book.goose.rs/controller/websocket.html"
-                                        .to_string(),
-                                ),
-                            )
-                                .await;
+                            // Requests made with the typed protocol.
+                            ControllerWebSocketRequestVersion::Typed(request) => {
+                                self.execute_typed_command(&mut ws_sender, request).await
+                            }
+                        };
+                        if exit_controller {
+                            // If the command returns true, it's time to exit.
+                            info!(
+                                "Telnet client [{}] disconnected from {}",
+                                self.thread_id, self.peer_address
+                            );
+                            break;
                         }
                     } else {
                         self.write_to_socket(
@@ -1337,6 +3010,44 @@ impl ControllerState {
         }
     }
 
+    /// Return the role granted by the token a client provided, or None if the token isn't
+    /// valid. Clients have full control if no tokens are configured.
+    fn role_for(&self, token: Option<&str>) -> Option<ControllerRole> {
+        match &self.tokens {
+            Some(tokens) => token.and_then(|token| tokens.role(token)),
+            None => Some(ControllerRole::Control),
+        }
+    }
+
+    /// Grant the client the role of the token it provided, returns false if the token
+    /// isn't valid.
+    fn authenticate(&mut self, token: Option<&str>) -> bool {
+        match self.role_for(token) {
+            Some(role) => {
+                self.role = role;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Perform the TLS handshake if TLS is enabled, returns None if the handshake fails.
+    async fn encrypt(&self, socket: tokio::net::TcpStream) -> Option<Box<dyn ControllerStream>> {
+        match &self.tls {
+            Some(tls) => match tls.accept(socket).await {
+                Ok(stream) => Some(stream),
+                Err(e) => {
+                    info!(
+                        "{:?} client [{}] TLS handshake failed: {}",
+                        self.protocol, self.thread_id, e
+                    );
+                    None
+                }
+            },
+            None => Some(Box::new(socket)),
+        }
+    }
+
     // Both Controllers use a common function to identify commands.
     async fn get_match(
         &self,
@@ -1352,15 +3063,198 @@ impl ControllerState {
 
     /// Process a request entirely within the Controller thread, without sending a message
     /// to the parent thread.
-    fn process_local_command(&self, request_message: &ControllerRequestMessage) -> Option<String> {
+    async fn process_local_command(
+        &self,
+        request_message: &ControllerRequestMessage,
+    ) -> Option<Result<String, String>> {
+        let websocket = matches!(self.protocol, ControllerProtocol::WebSocket);
         match request_message.command {
-            ControllerCommand::Help => Some(ControllerCommand::display_help()),
-            ControllerCommand::Exit => Some("goodbye!".to_string()),
+            ControllerCommand::Help => Some(Ok(ControllerCommand::display_help())),
+            ControllerCommand::Exit => Some(Ok("goodbye!".to_string())),
+            // Only WebSocket clients can be pushed live metrics.
+            ControllerCommand::Subscribe | ControllerCommand::Unsubscribe if !websocket => Some(
+                Err("live metrics are only supported by the WebSocket Controller".to_string()),
+            ),
+            ControllerCommand::Unsubscribe => {
+                if self.subscription.lock().await.take().is_some() {
+                    Some(Ok("unsubscribed from live metrics".to_string()))
+                } else {
+                    Some(Err("not subscribed to live metrics".to_string()))
+                }
+            }
             // All other commands require sending the request to the parent thread.
             _ => None,
         }
     }
 
+    /// Start pushing live metrics received from the parent process to the client.
+    async fn subscribe(
+        &self,
+        events_rx: broadcast::Receiver<ControllerEvent>,
+    ) -> Result<String, String> {
+        *self.subscription.lock().await = Some(events_rx);
+        Ok("subscribed to live metrics".to_string())
+    }
+
+    /// Wait for the next event pushed to a subscribed client. Never returns if the client
+    /// isn't subscribed, and returns None if the parent process stopped sending events.
+    async fn next_event(&self) -> Option<ControllerEvent> {
+        let mut subscription = self.subscription.lock().await;
+        let events_rx = match subscription.as_mut() {
+            Some(events_rx) => events_rx,
+            None => return futures::future::pending().await,
+        };
+        loop {
+            match events_rx.recv().await {
+                Ok(event) => return Some(event),
+                // A slow client skips events rather than delaying the load test.
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    info!(
+                        "WebSocket client [{}] skipped {} live metrics events",
+                        self.thread_id, skipped
+                    );
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    *subscription = None;
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Push an event to a subscribed WebSocket Controller client.
+    async fn write_event(&self, socket: &mut ControllerWebSocketSender, event: ControllerEvent) {
+        match serde_json::to_string(&ControllerWebSocketEvent {
+            version: WEBSOCKET_PROTOCOL_VERSION,
+            event,
+        }) {
+            Ok(json) => {
+                if let Err(e) = socket.send(Message::Text(json)).await {
+                    info!("failed to write data to websocket: {}", e);
+                }
+            }
+            Err(e) => warn!("failed to json encode event: {}", e),
+        }
+    }
+
+    /// Run the command served at the requested path of the HTTP Controller, and respond
+    /// with json.
+    #[cfg(feature = "http-controller")]
+    async fn process_http_request(&self, request: hyper::Request<Body>) -> hyper::Response<Body> {
+        debug!("http request: {} {}", request.method(), request.uri());
+        let path = request.uri().path().to_string();
+
+        // Every request must be authenticated if tokens are configured.
+        let token = request_token(request.headers(), request.uri());
+        let role = match self.role_for(token.as_deref()) {
+            Some(role) => role,
+            None => {
+                let mut response = http_message_response(
+                    StatusCode::UNAUTHORIZED,
+                    "a valid token is required".to_string(),
+                );
+                response.headers_mut().insert(
+                    hyper::header::WWW_AUTHENTICATE,
+                    hyper::header::HeaderValue::from_static("Bearer"),
+                );
+                return response;
+            }
+        };
+
+        // Find the command served at this path.
+        let (command, endpoint) = match ControllerCommand::iter().find_map(|command| {
+            command
+                .http_endpoint()
+                .filter(|endpoint| endpoint.path == path)
+                .map(|endpoint| (command, endpoint))
+        }) {
+            Some(matched) => matched,
+            None => {
+                return http_message_response(
+                    StatusCode::NOT_FOUND,
+                    format!("unrecognized path: {}", path),
+                )
+            }
+        };
+        if request.method() != endpoint.method {
+            return http_message_response(
+                StatusCode::METHOD_NOT_ALLOWED,
+                format!("{} must be requested with {}", path, endpoint.method),
+            );
+        }
+        if let Err(message) = role.authorize(&command) {
+            return http_message_response(StatusCode::FORBIDDEN, message);
+        }
+
+        let request_message = if let Some(key) = endpoint.value {
+            // Commands that set a value expect it in a json body, ie `{"users": 10}`.
+            let body = match hyper::body::to_bytes(request.into_body()).await {
+                Ok(body) => body,
+                Err(e) => {
+                    return http_message_response(
+                        StatusCode::BAD_REQUEST,
+                        format!("failed to read request body: {}", e),
+                    )
+                }
+            };
+            let value = match serde_json::from_slice::<serde_json::Value>(&body)
+                .ok()
+                .as_ref()
+                .and_then(|json| json.get(key))
+            {
+                Some(serde_json::Value::String(value)) => value.to_string(),
+                Some(serde_json::Value::Number(value)) => value.to_string(),
+                _ => {
+                    return http_message_response(
+                        StatusCode::BAD_REQUEST,
+                        format!("expected a json body in the format {{\"{}\": VALUE}}", key),
+                    )
+                }
+            };
+            // Validate the value the same way as the telnet and WebSocket Controllers.
+            match self
+                .get_match(&format!("{} {}", path.trim_start_matches('/'), value))
+                .await
+            {
+                Ok(request_message)
+                    if request_message.command == command && request_message.value.is_some() =>
+                {
+                    request_message
+                }
+                _ => {
+                    return http_message_response(
+                        StatusCode::BAD_REQUEST,
+                        format!("invalid {}: {}", key, value),
+                    )
+                }
+            }
+        } else {
+            ControllerRequestMessage {
+                command: command.clone(),
+                value: None,
+            }
+        };
+
+        match self.process_command(request_message).await {
+            Ok(ControllerResponseMessage::Config(config)) => {
+                http_json_response(StatusCode::OK, &config)
+            }
+            Ok(ControllerResponseMessage::Metrics(metrics)) => {
+                http_json_response(StatusCode::OK, &metrics)
+            }
+            Ok(ControllerResponseMessage::Status(status)) => {
+                http_json_response(StatusCode::OK, &status)
+            }
+            // Otherwise the parent process replied whether or not the command succeeded, a
+            // command is refused if the load test is in the wrong phase.
+            Ok(response) => match (command.details().process_response)(response) {
+                Ok(message) => http_message_response(StatusCode::OK, message),
+                Err(message) => http_message_response(StatusCode::CONFLICT, message),
+            },
+            Err(e) => http_message_response(StatusCode::SERVICE_UNAVAILABLE, e),
+        }
+    }
+
     /// Send a message to parent thread, with or without an optional value, and wait for
     /// a reply.
     async fn process_command(
@@ -1397,13 +3291,13 @@ impl ControllerState {
 /// Controller-protocol-specific functions, necessary to manage the different way each
 /// Controller protocol communicates with a client.
 #[async_trait]
-trait Controller<T> {
+trait Controller<T, R> {
     // Extract the command string from a Controller client request.
-    async fn get_command_string(&self, raw_value: T) -> Result<String, String>;
+    async fn get_command_string(&self, raw_value: T) -> Result<R, String>;
 }
 
 #[async_trait]
-impl Controller<ControllerTelnetMessage> for ControllerState {
+impl Controller<ControllerTelnetMessage, String> for ControllerState {
     // Extract the command string from a telnet Controller client request.
     async fn get_command_string(
         &self,
@@ -1429,25 +3323,27 @@ impl Controller<ControllerTelnetMessage> for ControllerState {
 }
 
 #[async_trait]
-impl Controller<ControllerWebSocketMessage> for ControllerState {
-    // Extract the command string from a WebSocket Controller client request.
+impl Controller<ControllerWebSocketMessage, ControllerWebSocketRequestVersion> for ControllerState {
+    // Extract the request from a WebSocket Controller client request, made with either
+    // the original or the typed protocol.
     async fn get_command_string(
         &self,
         raw_value: ControllerWebSocketMessage,
-    ) -> Result<String, String> {
+    ) -> Result<ControllerWebSocketRequestVersion, String> {
         if let Ok(request) = raw_value {
             if request.is_text() {
                 if let Ok(request) = request.into_text() {
                     debug!("websocket request: {:?}", request.trim());
-                    let command_string: ControllerWebSocketRequest =
-                        match serde_json::from_str(&request) {
-                            Ok(c) => c,
-                            Err(_) => {
-                                return Err("invalid json, see Goose book https://book.goose.rs/controller/websocket.html"
+                    let request: ControllerWebSocketRequestVersion = match serde_json::from_str(
+                        &request,
+                    ) {
+                        Ok(c) => c,
+                        Err(_) => {
+                            return Err("invalid json, see Goose book https://book.goose.rs/controller/websocket.html"
                                     .to_string());
                             }
                         };
-                    return Ok(command_string.request);
+                    return Ok(request);
                 } else {
                     // Failed to consume the WebSocket message and convert it to a String.
                     return Err("unsupported string format".to_string());
@@ -1484,9 +3380,15 @@ impl ControllerExecuteCommand<tokio::net::TcpStream> for ControllerState {
         socket: &mut tokio::net::TcpStream,
         request_message: ControllerRequestMessage,
     ) -> ControllerExit {
+        // Refuse commands the client isn't allowed to run.
+        if let Err(message) = self.role.authorize(&request_message.command) {
+            self.write_to_socket(socket, Err(message)).await;
+            return false;
+        }
+
         // First handle commands that don't require interaction with the parent process.
-        if let Some(message) = self.process_local_command(&request_message) {
-            self.write_to_socket(socket, Ok(message)).await;
+        if let Some(message) = self.process_local_command(&request_message).await {
+            self.write_to_socket(socket, message).await;
             // If Exit was received return true to exit, otherwise return false.
             return request_message.command == ControllerCommand::Exit;
         }
@@ -1547,23 +3449,21 @@ impl ControllerExecuteCommand<ControllerWebSocketSender> for ControllerState {
         socket: &mut ControllerWebSocketSender,
         request_message: ControllerRequestMessage,
     ) -> ControllerExit {
+        // Refuse commands the client isn't allowed to run.
+        if let Err(message) = self.role.authorize(&request_message.command) {
+            self.write_to_socket(socket, Err(message)).await;
+            return false;
+        }
+
         // First handle commands that don't require interaction with the parent process.
-        if let Some(message) = self.process_local_command(&request_message) {
-            self.write_to_socket(socket, Ok(message)).await;
+        if let Some(message) = self.process_local_command(&request_message).await {
+            self.write_to_socket(socket, message).await;
 
             // If Exit was received return true to exit, otherwise return false.
             let exit_controller = request_message.command == ControllerCommand::Exit;
             // If exiting, notify the WebSocket client that this connection is closing.
-            if exit_controller
-                && socket
-                .send(Message::Close(Some(tokio_tungstenite::tungstenite::protocol::CloseFrame {
-                    code: tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode::Normal,
-                    reason: std::borrow::Cow::Borrowed("exit"),
-                })))
-                .await
-                .is_err()
-            {
-                warn!("failed to write data to stream");
+            if exit_controller {
+                close_websocket(socket, "exit").await;
             }
 
             return exit_controller;
@@ -1592,20 +3492,15 @@ impl ControllerExecuteCommand<ControllerWebSocketSender> for ControllerState {
         let exit_controller = command == ControllerCommand::Shutdown;
 
         // Write the response to the Controller client socket.
-        let processed_response = (command.details().process_response)(response);
+        let processed_response = match response {
+            ControllerResponseMessage::Subscribe(events_rx) => self.subscribe(events_rx).await,
+            response => (command.details().process_response)(response),
+        };
         self.write_to_socket(socket, processed_response).await;
 
         // If exiting, notify the WebSocket client that this connection is closing.
-        if exit_controller
-            && socket
-            .send(Message::Close(Some(tokio_tungstenite::tungstenite::protocol::CloseFrame {
-                code: tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode::Normal,
-                reason: std::borrow::Cow::Borrowed("shutdown"),
-            })))
-            .await
-            .is_err()
-        {
-            warn!("failed to write data to stream");
+        if exit_controller {
+            close_websocket(socket, "shutdown").await;
         }
 
         // Return true if it's time to exit the Controller.
@@ -1649,3 +3544,262 @@ impl ControllerExecuteCommand<ControllerWebSocketSender> for ControllerState {
         }
     }
 }
+
+// Defines functions used by the typed WebSocket Controller protocol.
+impl ControllerState {
+    /// Run the command received from a typed WebSocket Controller request, and send a
+    /// typed response. Returns true if it's time to exit.
+    async fn execute_typed_command(
+        &self,
+        socket: &mut ControllerWebSocketSender,
+        request: ControllerWebSocketTypedRequest,
+    ) -> ControllerExit {
+        let (response, exit_controller) = self.process_typed_request(request).await;
+
+        match serde_json::to_string(&response) {
+            Ok(json) => {
+                if let Err(e) = socket.send(Message::Text(json)).await {
+                    info!("failed to write data to websocket: {}", e);
+                }
+            }
+            Err(e) => warn!("failed to json encode response: {}", e),
+        }
+
+        // If exiting, notify the WebSocket client that this connection is closing.
+        if exit_controller {
+            let reason = if response.command.as_deref() == Some("exit") {
+                "exit"
+            } else {
+                "shutdown"
+            };
+            close_websocket(socket, reason).await;
+        }
+
+        exit_controller
+    }
+
+    /// Validate and run a typed request, returning the response and whether or not it's
+    /// time to exit.
+    async fn process_typed_request(
+        &self,
+        request: ControllerWebSocketTypedRequest,
+    ) -> (ControllerWebSocketTypedResponse, ControllerExit) {
+        let mut response = ControllerWebSocketTypedResponse {
+            version: WEBSOCKET_PROTOCOL_VERSION,
+            id: request.id.clone(),
+            command: None,
+            success: false,
+            message: None,
+            data: None,
+            error: None,
+        };
+
+        if let Some(version) = request.version {
+            if version != WEBSOCKET_PROTOCOL_VERSION {
+                return (
+                    response.failed(
+                        ControllerErrorCode::UnsupportedVersion,
+                        format!(
+                            "unsupported protocol version {}, expected {}",
+                            version, WEBSOCKET_PROTOCOL_VERSION
+                        ),
+                    ),
+                    false,
+                );
+            }
+        }
+
+        // Commands are identified by the name displayed in the help screen.
+        let command = match ControllerCommand::iter()
+            .find(|command| command.name().eq_ignore_ascii_case(request.command.trim()))
+        {
+            Some(command) => command,
+            None => {
+                return (
+                    response.failed(
+                        ControllerErrorCode::UnrecognizedCommand,
+                        format!("unrecognized command: {}", request.command),
+                    ),
+                    false,
+                )
+            }
+        };
+        let name = command.name();
+        response.command = Some(name.clone());
+
+        // Refuse commands the client isn't allowed to run.
+        if let Err(message) = self.role.authorize(&command) {
+            return (
+                response.failed(ControllerErrorCode::PermissionDenied, message),
+                false,
+            );
+        }
+
+        // Validate the value the same way as the telnet Controller.
+        let command_string = match &request.value {
+            None => name.clone(),
+            Some(serde_json::Value::String(value)) => format!("{} {}", name, value),
+            Some(serde_json::Value::Number(value)) => format!("{} {}", name, value),
+            Some(value) => {
+                return (
+                    response.failed(
+                        ControllerErrorCode::InvalidValue,
+                        format!("invalid value for {}: {}", name, value),
+                    ),
+                    false,
+                )
+            }
+        };
+        let request_message = match self.get_match(&command_string).await {
+            Ok(request_message)
+                if request_message.command == command
+                    && (request.value.is_none() || request_message.value.is_some()) =>
+            {
+                request_message
+            }
+            _ => {
+                let message = match &request.value {
+                    Some(value) => format!("invalid value for {}: {}", name, value),
+                    None => format!("{} requires a value", name),
+                };
+                return (
+                    response.failed(ControllerErrorCode::InvalidValue, message),
+                    false,
+                );
+            }
+        };
+
+        // First handle commands that don't require interaction with the parent process.
+        if let Some(message) = self.process_local_command(&request_message).await {
+            match message {
+                Ok(message) => {
+                    response.success = true;
+                    response.message = Some(message);
+                }
+                Err(message) => {
+                    response = response.failed(ControllerErrorCode::CommandFailed, message);
+                }
+            }
+            return (response, command == ControllerCommand::Exit);
+        }
+
+        match self.process_command(request_message).await {
+            Ok(ControllerResponseMessage::Config(config)) => {
+                response.success = true;
+                response.data = Some(ControllerWebSocketData::Config(config));
+            }
+            Ok(ControllerResponseMessage::Metrics(metrics)) => {
+                response.success = true;
+                response.data = Some(ControllerWebSocketData::Metrics(metrics));
+            }
+            Ok(ControllerResponseMessage::Status(status)) => {
+                response.success = true;
+                response.data = Some(ControllerWebSocketData::Status(status));
+            }
+            Ok(ControllerResponseMessage::Subscribe(events_rx)) => {
+                response.success = true;
+                response.message = self.subscribe(events_rx).await.ok();
+            }
+            // Otherwise the parent process replied whether or not the command succeeded.
+            Ok(parent_response) => match (command.details().process_response)(parent_response) {
+                Ok(message) => {
+                    response.success = true;
+                    response.message = Some(message);
+                }
+                Err(message) => {
+                    response = response.failed(ControllerErrorCode::CommandFailed, message);
+                }
+            },
+            // The parent closed the communication channel, so exit.
+            Err(e) => {
+                return (response.failed(ControllerErrorCode::Unavailable, e), true);
+            }
+        }
+
+        (response, command == ControllerCommand::Shutdown)
+    }
+}
+
+impl ControllerWebSocketTypedResponse {
+    /// Mark the response as failed.
+    fn failed(mut self, code: ControllerErrorCode, message: String) -> Self {
+        self.success = false;
+        self.error = Some(ControllerWebSocketError { code, message });
+        self
+    }
+}
+
+/// Notify a WebSocket Controller client that the connection is closing.
+async fn close_websocket(socket: &mut ControllerWebSocketSender, reason: &'static str) {
+    if socket
+        .send(Message::Close(Some(
+            tokio_tungstenite::tungstenite::protocol::CloseFrame {
+                code: tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode::Normal,
+                reason: std::borrow::Cow::Borrowed(reason),
+            },
+        )))
+        .await
+        .is_err()
+    {
+        warn!("failed to write data to stream");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_controller_tokens() {
+        let tokens: ControllerTokens = "s3cr3t, control:admin, read:viewer".parse().unwrap();
+        assert_eq!(tokens.role("s3cr3t"), Some(ControllerRole::Control));
+        assert_eq!(tokens.role("admin"), Some(ControllerRole::Control));
+        assert_eq!(tokens.role("viewer"), Some(ControllerRole::ReadOnly));
+        assert_eq!(tokens.role("read:viewer"), None);
+        assert_eq!(tokens.role("s3cr3"), None);
+        assert_eq!(tokens.role(""), None);
+
+        // Tokens are never displayed.
+        assert_eq!(
+            format!("{:?}", tokens),
+            "ControllerTokens { control: 2, read_only: 1 }"
+        );
+
+        // Tokens can't be empty.
+        assert!("".parse::<ControllerTokens>().is_err());
+        assert!("s3cr3t,".parse::<ControllerTokens>().is_err());
+        assert!("read:".parse::<ControllerTokens>().is_err());
+    }
+
+    #[test]
+    fn read_only_role() {
+        let role = ControllerRole::ReadOnly;
+        assert!(role.allows(&ControllerCommand::Status));
+        assert!(role.allows(&ControllerCommand::MetricsJson));
+        assert!(!role.allows(&ControllerCommand::Users));
+        assert!(!role.allows(&ControllerCommand::Shutdown));
+        assert!(ControllerRole::Control.allows(&ControllerCommand::Shutdown));
+    }
+
+    #[test]
+    fn subscribe_from_current_second() {
+        let mut subscriptions = ControllerSubscriptions::default();
+        subscriptions.restart();
+        subscriptions.started = Some(std::time::Instant::now() - Duration::from_secs(10));
+
+        // The first client starts receiving metrics from the current second.
+        let first = subscriptions.subscribe();
+        assert_eq!(subscriptions.next_second, 10);
+
+        // Clients subscribing later don't change which second is pushed next.
+        subscriptions.next_second = 12;
+        let second = subscriptions.subscribe();
+        assert_eq!(subscriptions.next_second, 12);
+
+        // Once all clients unsubscribed, the next client starts from the current second.
+        drop(first);
+        drop(second);
+        subscriptions.subscribe();
+        assert_eq!(subscriptions.next_second, 10);
+    }
+}
@@ -0,0 +1,640 @@
+//! Scripts of Controller commands, run with `--controller-script` or the `script`
+//! Controller command.
+//!
+//! Each line of a script runs a Controller command either a set time after the script
+//! starts, or each time a condition on the metrics of the running load test becomes true.
+//! Commands are written the same way as they're typed into the telnet Controller, and
+//! `users` also accepts a number of users to add or remove:
+//! ```text
+//! # Launch more users after 5 minutes, and back off if response times degrade.
+//! at 5m users 200
+//! when p95 > 800ms users -50
+//! at 30m shutdown
+//! ```
+//!
+//! Lines are separated by new lines or semicolons, so a script can be written on a single
+//! line. A semicolon followed by something other than `at` or `when` belongs to the
+//! command, for example `at 1m test-plan 10,5m;0,30s`.
+//!
+//! Conditions are checked once a second while the load test is running, against the
+//! metrics of the last 10 seconds pushed to subscribed Controller clients. A command triggered by a condition runs once each time the condition becomes true.
+//!
+//! Times given with `at` are measured from when the script starts, and keep running while
+//! the load test is paused.
+
+use regex::Regex;
+use std::collections::{BTreeMap, VecDeque};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::{self, error::TryRecvError};
+
+use crate::controller::{
+    response_time_percentile, send_controller_request, ControllerCommand, ControllerEvent,
+    ControllerMetricsDelta, ControllerRequest, ControllerRequestMessage, ControllerResponseMessage,
+};
+use crate::{AttackPhase, GooseError};
+
+// Scripts identify themselves to the parent process as this Controller client.
+const SCRIPT_CLIENT_ID: u32 = 0;
+
+// Conditions are checked against the metrics of this many of the most recent seconds.
+const WINDOW_SECONDS: usize = 10;
+
+/// A script of Controller commands.
+#[derive(Clone, Debug)]
+pub struct ControllerScript {
+    steps: Vec<ControllerScriptStep>,
+}
+
+/// A line of a script: a command and what triggers it.
+#[derive(Clone, Debug)]
+struct ControllerScriptStep {
+    /// The line as written in the script, used when logging.
+    line: String,
+    trigger: ControllerScriptTrigger,
+    command: ControllerScriptCommand,
+}
+
+/// What triggers a command.
+#[derive(Clone, Debug, PartialEq)]
+enum ControllerScriptTrigger {
+    /// Run the command once, this many seconds after the script starts.
+    At(usize),
+    /// Run the command each time the condition becomes true.
+    When(ControllerScriptCondition),
+}
+
+/// A command run by a script.
+#[derive(Clone, Debug)]
+enum ControllerScriptCommand {
+    /// Send the request to the parent process.
+    Request(ControllerRequestMessage),
+    /// Add (or if negative remove) users, relative to how many users the load test is
+    /// ramping to.
+    ChangeUsers(isize),
+}
+
+/// Compares a metric of the running load test to a value.
+#[derive(Clone, Debug, PartialEq)]
+struct ControllerScriptCondition {
+    metric: ControllerScriptMetric,
+    comparison: ControllerScriptComparison,
+    value: f32,
+}
+
+/// The metrics a condition can check.
+#[derive(Clone, Debug, PartialEq)]
+enum ControllerScriptMetric {
+    /// How many users are running.
+    Users,
+    /// How many requests were made per second.
+    RequestsPerSecond,
+    /// The percentage of requests that failed.
+    Failures,
+    /// The average response time, in milliseconds.
+    Average,
+    /// The slowest response time, in milliseconds.
+    Maximum,
+    /// The response time, in milliseconds, that this fraction of requests finished within.
+    Percentile(f32),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum ControllerScriptComparison {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+/// The metrics of the running load test that conditions are checked against, collected
+/// over the most recent seconds.
+struct ControllerScriptSample {
+    users: usize,
+    seconds: usize,
+    requests: usize,
+    fails: usize,
+    times: BTreeMap<usize, usize>,
+}
+
+impl ControllerScript {
+    /// Load a script from a file.
+    pub(crate) fn from_file(path: &str) -> Result<ControllerScript, GooseError> {
+        let contents = std::fs::read_to_string(path).map_err(|e| GooseError::InvalidOption {
+            option: "`configuration.controller_script`".to_string(),
+            value: path.to_string(),
+            detail: format!("failed to read controller script: {}", e),
+        })?;
+        contents.parse()
+    }
+
+    /// Run each command of the script when triggered. Returns once all commands that run
+    /// at a set time have run and no commands are triggered by conditions, or when the
+    /// parent process closes the Controller channel.
+    pub(crate) async fn run(self, channel_tx: flume::Sender<ControllerRequest>) {
+        let started = Instant::now();
+        let watching = self
+            .steps
+            .iter()
+            .any(|step| matches!(step.trigger, ControllerScriptTrigger::When(_)));
+        // Conditions are checked against the metrics pushed each second to subscribers.
+        let mut events_rx = if watching {
+            match request(&channel_tx, ControllerCommand::Subscribe).await {
+                Ok(ControllerResponseMessage::Subscribe(events_rx)) => Some(events_rx),
+                Ok(_) => None,
+                Err(_) => return,
+            }
+        } else {
+            None
+        };
+        // Whether each timed command has run, or each condition was true when last checked.
+        let mut triggered = vec![false; self.steps.len()];
+        // The metrics of the most recent seconds.
+        let mut window: VecDeque<ControllerMetricsDelta> = VecDeque::new();
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+
+        loop {
+            interval.tick().await;
+
+            // Conditions are only checked while the load test is running.
+            let sample = match events_rx.as_mut() {
+                Some(events_rx) => match sample(&channel_tx, events_rx, &mut window).await {
+                    Ok(sample) => sample,
+                    Err(_) => return,
+                },
+                None => None,
+            };
+
+            let elapsed = started.elapsed().as_secs() as usize;
+            for (step, triggered) in self.steps.iter().zip(triggered.iter_mut()) {
+                let run = match &step.trigger {
+                    ControllerScriptTrigger::At(seconds) => {
+                        let run = !*triggered && elapsed >= *seconds;
+                        *triggered |= run;
+                        run
+                    }
+                    ControllerScriptTrigger::When(condition) => {
+                        match sample.as_ref().and_then(|sample| condition.check(sample)) {
+                            Some(holds) => {
+                                let run = holds && !*triggered;
+                                *triggered = holds;
+                                run
+                            }
+                            None => false,
+                        }
+                    }
+                };
+                if run && !step.run(&channel_tx).await {
+                    return;
+                }
+            }
+
+            let finished = self
+                .steps
+                .iter()
+                .zip(triggered.iter())
+                .all(|(step, triggered)| {
+                    matches!(step.trigger, ControllerScriptTrigger::At(_)) && *triggered
+                });
+            if finished {
+                info!("controller script finished");
+                return;
+            }
+        }
+    }
+}
+
+impl FromStr for ControllerScript {
+    type Err = GooseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |line: &str, detail: String| GooseError::InvalidOption {
+            option: "`configuration.controller_script`".to_string(),
+            value: line.to_string(),
+            detail,
+        };
+
+        // Lines are separated by new lines, or by semicolons followed by another trigger.
+        let mut lines: Vec<String> = Vec::new();
+        for line in s.lines() {
+            let line = line.trim();
+            // Skip empty lines and comments.
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split(';');
+            lines.push(parts.next().unwrap_or_default().trim().to_string());
+            for part in parts {
+                let part = part.trim();
+                let keyword = part.split_whitespace().next().unwrap_or_default();
+                if keyword.eq_ignore_ascii_case("at") || keyword.eq_ignore_ascii_case("when") {
+                    lines.push(part.to_string());
+                } else if let Some(last) = lines.last_mut() {
+                    last.push(';');
+                    last.push_str(part);
+                }
+            }
+        }
+
+        let at = Regex::new(r"(?i)^at\s+(\d+|(\d+h)?(\d+m)?(\d+s)?)\s+(.+)$").unwrap();
+        let when =
+            Regex::new(r"(?i)^when\s+([a-z0-9.]+)\s*(>=|<=|>|<)\s*([0-9.]+)\s*(ms|s|%)?\s+(.+)$")
+                .unwrap();
+        let mut steps = Vec::new();
+        for line in lines.into_iter().filter(|line| !line.is_empty()) {
+            let (trigger, command) = if let Some(caps) = at.captures(&line) {
+                (
+                    ControllerScriptTrigger::At(crate::util::parse_timespan(&caps[1])),
+                    caps[5].trim().to_string(),
+                )
+            } else if let Some(caps) = when.captures(&line) {
+                let condition = ControllerScriptCondition::new(
+                    &caps[1],
+                    &caps[2],
+                    &caps[3],
+                    caps.get(4).map_or("", |m| m.as_str()),
+                )
+                .map_err(|detail| error(&line, detail))?;
+                (
+                    ControllerScriptTrigger::When(condition),
+                    caps[5].trim().to_string(),
+                )
+            } else {
+                return Err(error(
+                    &line,
+                    "expected `at TIME COMMAND` or `when METRIC > VALUE COMMAND`".to_string(),
+                ));
+            };
+            let command = ControllerScriptCommand::new(&command).map_err(|e| error(&line, e))?;
+            steps.push(ControllerScriptStep {
+                line,
+                trigger,
+                command,
+            });
+        }
+
+        if steps.is_empty() {
+            return Err(error(
+                s,
+                "a controller script must run at least one command".to_string(),
+            ));
+        }
+        Ok(ControllerScript { steps })
+    }
+}
+
+impl ControllerScriptStep {
+    // Run the command, logging the response. Returns false if the parent process has
+    // closed the Controller channel.
+    async fn run(&self, channel_tx: &flume::Sender<ControllerRequest>) -> bool {
+        let request = match &self.command {
+            ControllerScriptCommand::Request(request) => request.clone(),
+            ControllerScriptCommand::ChangeUsers(change) => {
+                let target_users = match request(channel_tx, ControllerCommand::Status).await {
+                    Ok(ControllerResponseMessage::Status(status)) => status.target_users,
+                    Ok(_) => return true,
+                    Err(_) => return false,
+                };
+                let users = (target_users as isize + change).max(0);
+                ControllerRequestMessage {
+                    command: ControllerCommand::Users,
+                    value: Some(users.to_string()),
+                }
+            }
+        };
+
+        let command = request.command.clone();
+        match send_controller_request(channel_tx, SCRIPT_CLIENT_ID, request).await {
+            Some(response) => {
+                match command.describe_response(response) {
+                    Ok(message) => info!("controller script `{}`: {}", self.line, message),
+                    Err(message) => warn!("controller script `{}`: {}", self.line, message),
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl ControllerScriptCommand {
+    fn new(command: &str) -> Result<Self, String> {
+        let change_users = Regex::new(r"(?i)^users?\s+([+-]\d+)$").unwrap();
+        if let Some(caps) = change_users.captures(command) {
+            return caps[1]
+                .parse()
+                .map(ControllerScriptCommand::ChangeUsers)
+                .map_err(|_| format!("invalid number of users: '{}'", &caps[1]));
+        }
+
+        let request = ControllerCommand::parse_request(command)?;
+        match request.command {
+            // Only interactive Controllers can get help, disconnect or subscribe, and scripts
+            // don't start other scripts.
+            ControllerCommand::Help
+            | ControllerCommand::Exit
+            | ControllerCommand::Subscribe
+            | ControllerCommand::Unsubscribe
+            | ControllerCommand::Script => Err(format!("{} can't be scripted", command)),
+            _ => Ok(ControllerScriptCommand::Request(request)),
+        }
+    }
+}
+
+impl ControllerScriptCondition {
+    fn new(metric: &str, comparison: &str, value: &str, unit: &str) -> Result<Self, String> {
+        let metric = match metric.to_lowercase().as_str() {
+            "users" => ControllerScriptMetric::Users,
+            "rps" => ControllerScriptMetric::RequestsPerSecond,
+            "failures" => ControllerScriptMetric::Failures,
+            "avg" => ControllerScriptMetric::Average,
+            "max" => ControllerScriptMetric::Maximum,
+            percentile => match percentile
+                .strip_prefix('p')
+                .and_then(|percent| percent.parse::<f32>().ok())
+            {
+                Some(percent) if percent > 0.0 && percent <= 100.0 => {
+                    ControllerScriptMetric::Percentile(percent / 100.0)
+                }
+                _ => {
+                    return Err(format!(
+                        "unrecognized metric '{}', expected users, rps, failures, avg, max or a percentile such as p95",
+                        metric
+                    ))
+                }
+            },
+        };
+        let comparison = match comparison {
+            ">" => ControllerScriptComparison::Greater,
+            ">=" => ControllerScriptComparison::GreaterOrEqual,
+            "<" => ControllerScriptComparison::Less,
+            _ => ControllerScriptComparison::LessOrEqual,
+        };
+        let value: f32 = value
+            .parse()
+            .map_err(|_| format!("invalid value: '{}'", value))?;
+        // Response times are compared in milliseconds.
+        let value = match (&metric, unit.to_lowercase().as_str()) {
+            (
+                ControllerScriptMetric::Average
+                | ControllerScriptMetric::Maximum
+                | ControllerScriptMetric::Percentile(_),
+                "s",
+            ) => value * 1_000.0,
+            (
+                ControllerScriptMetric::Average
+                | ControllerScriptMetric::Maximum
+                | ControllerScriptMetric::Percentile(_),
+                "ms" | "",
+            )
+            | (ControllerScriptMetric::Failures, "%" | "")
+            | (ControllerScriptMetric::Users | ControllerScriptMetric::RequestsPerSecond, "") => {
+                value
+            }
+            _ => return Err(format!("unexpected unit '{}' for {:?}", unit, metric)),
+        };
+        Ok(ControllerScriptCondition {
+            metric,
+            comparison,
+            value,
+        })
+    }
+
+    // Whether or not the condition holds, or None if there's nothing to compare yet.
+    fn check(&self, sample: &ControllerScriptSample) -> Option<bool> {
+        let measured = match self.metric {
+            ControllerScriptMetric::Users => sample.users as f32,
+            // Requests per second can't be measured until a second of metrics is received.
+            _ if sample.seconds == 0 => return None,
+            ControllerScriptMetric::RequestsPerSecond => {
+                sample.requests as f32 / sample.seconds as f32
+            }
+            // Failures can't be measured until requests are made.
+            _ if sample.requests == 0 => return None,
+            ControllerScriptMetric::Failures => {
+                sample.fails as f32 * 100.0 / sample.requests as f32
+            }
+            // Response times can't be measured until responses are timed.
+            _ if sample.times.is_empty() => return None,
+            ControllerScriptMetric::Average => {
+                let (total_time, count) = sample
+                    .times
+                    .iter()
+                    .fold((0, 0), |(total_time, count), (time, times)| {
+                        (total_time + time * times, count + times)
+                    });
+                total_time as f32 / count as f32
+            }
+            ControllerScriptMetric::Maximum => {
+                sample.times.keys().next_back().copied().unwrap_or_default() as f32
+            }
+            ControllerScriptMetric::Percentile(percent) => {
+                response_time_percentile(&sample.times, percent) as f32
+            }
+        };
+        Some(match self.comparison {
+            ControllerScriptComparison::Greater => measured > self.value,
+            ControllerScriptComparison::GreaterOrEqual => measured >= self.value,
+            ControllerScriptComparison::Less => measured < self.value,
+            ControllerScriptComparison::LessOrEqual => measured <= self.value,
+        })
+    }
+}
+
+impl ControllerScriptSample {
+    // Aggregate the requests made in each second of the window.
+    fn new(users: usize, window: &VecDeque<ControllerMetricsDelta>) -> Self {
+        let mut sample = ControllerScriptSample {
+            users,
+            seconds: window.len(),
+            requests: 0,
+            fails: 0,
+            times: BTreeMap::new(),
+        };
+        for delta in window {
+            sample.requests += delta.requests as usize;
+            sample.fails += delta.errors as usize;
+            for (time, count) in &delta.response_times {
+                *sample.times.entry(*time).or_insert(0) += count;
+            }
+        }
+        sample
+    }
+}
+
+// Collect the metrics of each second pushed since the last sample, and request the status
+// of the load test, returning None if it's not running. Returns an error if the parent
+// process has closed the Controller channel.
+async fn sample(
+    channel_tx: &flume::Sender<ControllerRequest>,
+    events_rx: &mut broadcast::Receiver<ControllerEvent>,
+    window: &mut VecDeque<ControllerMetricsDelta>,
+) -> Result<Option<ControllerScriptSample>, ()> {
+    loop {
+        match events_rx.try_recv() {
+            Ok(ControllerEvent::Metrics(delta)) => {
+                window.push_back(delta);
+                if window.len() > WINDOW_SECONDS {
+                    window.pop_front();
+                }
+            }
+            // Phases are checked with the status, and skipped seconds are simply missing
+            // from the window.
+            Ok(ControllerEvent::Phase(_)) | Err(TryRecvError::Lagged(_)) => {}
+            Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => break,
+        }
+    }
+
+    let status = match request(channel_tx, ControllerCommand::Status).await? {
+        ControllerResponseMessage::Status(status) => status,
+        _ => return Ok(None),
+    };
+    if !matches!(
+        status.phase,
+        AttackPhase::Increase | AttackPhase::Maintain | AttackPhase::Decrease
+    ) {
+        window.clear();
+        return Ok(None);
+    }
+    Ok(Some(ControllerScriptSample::new(status.users, window)))
+}
+
+// Send a request without a value to the parent process. Returns an error if the parent
+// process has closed the Controller channel.
+async fn request(
+    channel_tx: &flume::Sender<ControllerRequest>,
+    command: ControllerCommand,
+) -> Result<ControllerResponseMessage, ()> {
+    let request = ControllerRequestMessage {
+        command,
+        value: None,
+    };
+    send_controller_request(channel_tx, SCRIPT_CLIENT_ID, request)
+        .await
+        .ok_or(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_script() {
+        let script: ControllerScript = "
+            # Comments and empty lines are ignored.
+
+            at 5m users 200
+            when p95 > 800ms users -50
+            at 1h30m test-plan 10,5m;0,30s; when failures >= 5% pause
+            AT 2 shutdown
+        "
+        .parse()
+        .unwrap();
+        assert_eq!(script.steps.len(), 5);
+
+        assert_eq!(script.steps[0].trigger, ControllerScriptTrigger::At(300));
+        assert!(matches!(
+            &script.steps[0].command,
+            ControllerScriptCommand::Request(request)
+                if request.command == ControllerCommand::Users
+                    && request.value.as_deref() == Some("200")
+        ));
+
+        assert_eq!(
+            script.steps[1].trigger,
+            ControllerScriptTrigger::When(ControllerScriptCondition {
+                metric: ControllerScriptMetric::Percentile(0.95),
+                comparison: ControllerScriptComparison::Greater,
+                value: 800.0,
+            })
+        );
+        assert!(matches!(
+            script.steps[1].command,
+            ControllerScriptCommand::ChangeUsers(-50)
+        ));
+
+        // Semicolons only separate lines when followed by another trigger.
+        assert_eq!(script.steps[2].trigger, ControllerScriptTrigger::At(5_400));
+        assert!(matches!(
+            &script.steps[2].command,
+            ControllerScriptCommand::Request(request)
+                if request.command == ControllerCommand::TestPlan
+                    && request.value.as_deref() == Some("10,5m;0,30s")
+        ));
+        assert_eq!(script.steps[3].line, "when failures >= 5% pause");
+        assert_eq!(script.steps[4].trigger, ControllerScriptTrigger::At(2));
+
+        // Invalid scripts.
+        assert!("".parse::<ControllerScript>().is_err());
+        assert!("# only a comment".parse::<ControllerScript>().is_err());
+        assert!("users 10".parse::<ControllerScript>().is_err());
+        assert!("at soon users 10".parse::<ControllerScript>().is_err());
+        assert!("at 5m users many".parse::<ControllerScript>().is_err());
+        assert!("at 5m dance".parse::<ControllerScript>().is_err());
+        assert!("at 5m help".parse::<ControllerScript>().is_err());
+        assert!("at 5m script at 1m stop"
+            .parse::<ControllerScript>()
+            .is_err());
+        assert!("when p0 > 1 stop".parse::<ControllerScript>().is_err());
+        assert!("when latency > 1 stop".parse::<ControllerScript>().is_err());
+        assert!("when users > 5% stop".parse::<ControllerScript>().is_err());
+    }
+
+    #[test]
+    fn check_conditions() {
+        let condition = |condition: &str| {
+            let script: ControllerScript = format!("when {} stop", condition).parse().unwrap();
+            match &script.steps[0].trigger {
+                ControllerScriptTrigger::When(condition) => condition.clone(),
+                _ => unreachable!(),
+            }
+        };
+
+        let delta = |second: usize, times: &[usize], errors: u32| {
+            let mut response_times = BTreeMap::new();
+            for time in times {
+                *response_times.entry(*time).or_insert(0) += 1;
+            }
+            ControllerMetricsDelta {
+                second,
+                users: 5,
+                requests: times.len() as u32,
+                errors,
+                average_response_time: 0.0,
+                median_response_time: 0,
+                p95_response_time: 0,
+                p99_response_time: 0,
+                response_times,
+            }
+        };
+
+        // Metrics are aggregated over all seconds in the window.
+        let mut window = VecDeque::new();
+        window.push_back(delta(1, &[100, 200], 0));
+        window.push_back(delta(2, &[300, 1_500], 1));
+        let sample = ControllerScriptSample::new(5, &window);
+
+        assert_eq!(condition("users >= 5").check(&sample), Some(true));
+        assert_eq!(condition("users > 5").check(&sample), Some(false));
+        assert_eq!(condition("failures > 20%").check(&sample), Some(true));
+        assert_eq!(condition("avg < 600ms").check(&sample), Some(true));
+        assert_eq!(condition("max > 1s").check(&sample), Some(true));
+        assert_eq!(condition("p50 <= 200").check(&sample), Some(true));
+        assert_eq!(condition("p99 > 800ms").check(&sample), Some(true));
+        assert_eq!(condition("rps >= 2").check(&sample), Some(true));
+        assert_eq!(condition("rps > 2").check(&sample), Some(false));
+
+        // Nothing but users can be measured until metrics are received.
+        let sample = ControllerScriptSample::new(5, &VecDeque::new());
+        assert_eq!(condition("rps > 1").check(&sample), None);
+        assert_eq!(condition("p95 > 800ms").check(&sample), None);
+        assert_eq!(condition("users < 10").check(&sample), Some(true));
+
+        // Response times can't be measured until requests are made.
+        let window = VecDeque::from(vec![delta(3, &[], 0)]);
+        let sample = ControllerScriptSample::new(5, &window);
+        assert_eq!(condition("rps < 1").check(&sample), Some(true));
+        assert_eq!(condition("failures > 0").check(&sample), None);
+        assert_eq!(condition("max > 1s").check(&sample), None);
+    }
+}
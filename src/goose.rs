@@ -68,6 +68,33 @@
 //! let mut foo_transactions = scenario!("FooTransactions").set_wait_time(Duration::from_secs(0), Duration::from_millis(2500)).unwrap();
 //! let mut bar_transactions = scenario!("BarTransactions").set_wait_time(Duration::from_secs(5), Duration::from_secs(10)).unwrap();
 //! ```
+//!
+//! Wait time can instead follow other distributions with [`GooseWaitTime`], such as
+//! exponentially distributed wait times to simulate Poisson arrivals. Wait times can also
+//! be overridden per [`Transaction`], and iterations can be paced to start at a fixed
+//! interval with [`Scenario::set_pacing`].
+//!
+//! ```rust
+//! use goose::goose::GooseWaitTime;
+//! use goose::prelude::*;
+//! use std::time::Duration;
+//!
+//! let mut foo_transactions: Scenario<GooseUser> = scenario!("FooTransactions")
+//!     .set_wait_time_distribution(GooseWaitTime::Exponential {
+//!         mean: Duration::from_secs(2),
+//!     })
+//!     .unwrap();
+//! let mut bar_transactions: Scenario<GooseUser> =
+//!     scenario!("BarTransactions").set_pacing(Duration::from_secs(30));
+//! ```
+//!
+//! ### Scenario Transition Graph
+//!
+//! By default each iteration of a scenario runs all weighted transactions in order. A
+//! [`TransitionGraph`] instead has each iteration start at an entry transaction, then
+//! randomly pick the next transaction from weighted successors until exiting. See
+//! [`Scenario::set_transition_graph`].
+//!
 //! ## Creating Transactions
 //!
 //! A [`Transaction`](./struct.Transaction.html) must include a pointer to a function which
@@ -289,24 +316,31 @@
 
 use downcast_rs::{impl_downcast, Downcast};
 use http::method::Method;
+use rand::Rng;
+use rand_distr::{Distribution, Exp, LogNormal, Normal, Pareto};
 use regex::Regex;
+use flume::Sender;
 use reqwest::{header, Client, ClientBuilder, RequestBuilder, Response};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use std::{fmt, io, str};
 use std::{future::Future, pin::Pin, time::Instant};
-use flume::Sender;
 use tokio::sync::RwLock;
+use crate::goose_trait::Goose;
 use url::Url;
 
 use crate::logger::GooseLog;
 use crate::metrics::{
     GooseCoordinatedOmissionMitigation, GooseMetric, GooseRawRequest, GooseRequestMetric,
+    TransactionMetric,
 };
+use crate::test_plan::TestPlan;
+use crate::throttle::GooseThrottle;
 use crate::{GooseConfiguration, GooseError, GooseLoggerTx, WeightedTransactions};
-use crate::goose_trait::Goose;
 
 /// By default Goose sets the following User-Agent header when making requests.
 static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
@@ -373,6 +407,12 @@ pub enum TransactionError {
         /// The unrecognized HTTP request method.
         method: Method,
     },
+    /// The transaction was canceled as it did not complete within its timeout.
+    Timeout {
+        /// The timeout configured with
+        /// [`Transaction::set_timeout`](./struct.Transaction.html#method.set_timeout).
+        timeout: Duration,
+    },
 }
 
 /// Implement a helper to provide a text description of all possible types of errors.
@@ -389,6 +429,7 @@ impl TransactionError {
             TransactionError::MetricsFailed { .. } => "failed to send metrics to parent thread",
             TransactionError::LoggerFailed { .. } => "failed to send log message to logger thread",
             TransactionError::InvalidMethod { .. } => "unrecognized HTTP request method",
+            TransactionError::Timeout { .. } => "timeout",
         }
     }
 }
@@ -416,6 +457,9 @@ impl fmt::Display for TransactionError {
             TransactionError::LoggerFailed { ref source } => {
                 write!(f, "TransactionError: {} ({})", self.describe(), source)
             }
+            TransactionError::Timeout { ref timeout } => {
+                write!(f, "TransactionError: {} ({:?})", self.describe(), timeout)
+            }
             _ => write!(f, "TransactionError: {}", self.describe()),
         }
     }
@@ -450,8 +494,8 @@ impl From<url::ParseError> for TransactionError {
     }
 }
 
-/// When the throttle is enabled and the load test ends, the throttle channel is
-/// shut down. This causes a
+/// When the throttle is enabled and the load test ends, the throttle is closed and
+/// waiting requests are canceled with a
 /// [`flume::SendError`](https://docs.rs/flume/*/flume/struct.SendError.html),
 /// which gets automatically converted to `RequestCanceled`.
 /// [`RequestCanceled`](./enum.TransactionError.html#variant.RequestCanceled)
@@ -487,9 +531,11 @@ pub struct Scenario<G: Goose> {
     pub scenarios_index: usize,
     /// An integer value that controls the frequency that this scenario will be assigned to a user.
     pub weight: usize,
-    /// A [`Duration`](https://doc.rust-lang.org/std/time/struct.Duration.html) range defining the
-    /// minimum and maximum time a [`GooseUser`] should sleep after running a transaction.
-    pub transaction_wait: Option<(Duration, Duration)>,
+    /// An optional [`GooseWaitTime`] defining how long a [`GooseUser`] should sleep after
+    /// running a transaction.
+    pub transaction_wait: Option<GooseWaitTime>,
+    /// An optional interval at which iterations of this scenario start.
+    pub pacing: Option<Duration>,
     /// A vector containing one copy of each [`Transaction`](./struct.Transaction.html) that will
     /// run by users running this scenario.
     pub transactions: Vec<Transaction<G>>,
@@ -506,6 +552,19 @@ pub struct Scenario<G: Goose> {
     pub weighted_on_stop_transactions: WeightedTransactions,
     /// An optional default host to run this `Scenario` against.
     pub host: Option<String>,
+    /// An optional graph of weighted transitions between transactions, walked by
+    /// [`GooseUser`]s instead of the `weighted_transactions` list.
+    pub transition_graph: Option<TransitionGraph>,
+    /// What a [`GooseUser`] does when a transaction fails.
+    pub failure_policy: GooseFailurePolicy,
+    /// An optional default [`GooseRetryPolicy`] for requests made by this scenario.
+    pub retry_policy: Option<GooseRetryPolicy>,
+    /// An optional maximum number of requests per second made by all users running
+    /// this scenario.
+    pub throttle_requests: Option<usize>,
+    /// An optional test plan controlling how many users run this scenario, instead of
+    /// the load test's test plan.
+    pub(crate) test_plan: Option<TestPlan>,
 }
 
 impl<G: Goose> Scenario<G> {
@@ -528,11 +587,17 @@ impl<G: Goose> Scenario<G> {
             scenarios_index: usize::max_value(),
             weight: 1,
             transaction_wait: None,
+            pacing: None,
             transactions: Vec::new(),
             weighted_transactions: Vec::new(),
             weighted_on_start_transactions: Vec::new(),
             weighted_on_stop_transactions: Vec::new(),
             host: None,
+            transition_graph: None,
+            failure_policy: GooseFailurePolicy::Continue,
+            retry_policy: None,
+            throttle_requests: None,
+            test_plan: None,
         }
     }
 
@@ -645,19 +710,734 @@ impl<G: Goose> Scenario<G> {
             min_wait,
             max_wait
         );
-        if min_wait.as_millis() > max_wait.as_millis() {
-            return Err(GooseError::InvalidWaitTime {
-                min_wait,
-                max_wait,
-                detail:
-                "The min_wait option can not be set to a larger value than the max_wait option."
+        let wait_time = GooseWaitTime::Uniform(min_wait, max_wait);
+        wait_time.validate()?;
+        self.transaction_wait = Some(wait_time);
+
+        Ok(self)
+    }
+
+    /// Configure a scenario to pause after running each transaction for a length of time
+    /// randomly selected from a [`GooseWaitTime`] distribution.
+    ///
+    /// # Example
+    /// ```rust
+    /// use goose::goose::GooseWaitTime;
+    /// use goose::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), GooseError> {
+    ///     let _scenario: Scenario<GooseUser> = scenario!("ExampleTransactions")
+    ///         .set_wait_time_distribution(GooseWaitTime::Normal {
+    ///             mean: Duration::from_secs(3),
+    ///             std_dev: Duration::from_millis(500),
+    ///         })?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_wait_time_distribution(
+        mut self,
+        wait_time: GooseWaitTime,
+    ) -> Result<Self, GooseError> {
+        trace!("{} set_wait_time_distribution: {:?}", self.name, wait_time);
+        wait_time.validate()?;
+        self.transaction_wait = Some(wait_time);
+
+        Ok(self)
+    }
+
+    /// Configure a scenario to start each iteration at a fixed interval. After an iteration
+    /// completes, the user sleeps for the remainder of the interval. If the iteration took
+    /// longer than the interval, the next iteration starts immediately. Pacing is applied
+    /// in addition to any wait time between transactions.
+    ///
+    /// # Example
+    /// ```rust
+    /// use goose::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// // Each user starts a new iteration every 10 seconds.
+    /// let mut example_transactions: Scenario<GooseUser> =
+    ///     scenario!("ExampleTransactions").set_pacing(Duration::from_secs(10));
+    /// ```
+    pub fn set_pacing(mut self, interval: Duration) -> Self {
+        trace!("{} set_pacing: {:?}", self.name, interval);
+        self.pacing = Some(interval);
+        self
+    }
+
+    /// Configure what a [`GooseUser`] running this scenario does when a transaction fails.
+    /// By default the user continues running the remaining transactions. The policy can be
+    /// overridden for individual transactions with
+    /// [`Transaction::set_failure_policy`](./struct.Transaction.html#method.set_failure_policy).
+    ///
+    /// # Example
+    /// ```rust
+    /// use goose::goose::GooseFailurePolicy;
+    /// use goose::prelude::*;
+    ///
+    /// // Skip the rest of the iteration when a transaction fails.
+    /// let mut example_transactions: Scenario<GooseUser> = scenario!("ExampleTransactions")
+    ///     .set_failure_policy(GooseFailurePolicy::RestartIteration);
+    /// ```
+    pub fn set_failure_policy(mut self, failure_policy: GooseFailurePolicy) -> Self {
+        trace!("{} set_failure_policy: {:?}", self.name, failure_policy);
+        self.failure_policy = failure_policy;
+        self
+    }
+
+    /// Retry failed requests made by this scenario according to a [`GooseRetryPolicy`].
+    /// The policy can be overridden for individual requests with
+    /// [`GooseRequestBuilder::retry_policy`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use goose::goose::GooseRetryPolicy;
+    /// use goose::prelude::*;
+    ///
+    /// // Attempt each request up to 3 times.
+    /// let mut example_transactions: Scenario<GooseUser> =
+    ///     scenario!("ExampleTransactions").set_retry_policy(GooseRetryPolicy::new(3));
+    /// ```
+    pub fn set_retry_policy(mut self, retry_policy: GooseRetryPolicy) -> Self {
+        trace!("{} set_retry_policy: {:?}", self.name, retry_policy);
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Limit the maximum number of requests per second made by all [`GooseUser`]s
+    /// running this scenario. This is enforced in addition to `--throttle-requests`,
+    /// which limits requests made by all scenarios together.
+    ///
+    /// # Example
+    /// ```rust
+    /// use goose::prelude::*;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), GooseError> {
+    ///     // Make no more than 5 requests per second from this scenario.
+    ///     let mut admin_transactions: Scenario<GooseUser> =
+    ///         scenario!("AdminTransactions").set_throttle_requests(5)?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_throttle_requests(mut self, throttle_requests: usize) -> Result<Self, GooseError> {
+        trace!("{} set_throttle_requests: {}", self.name, throttle_requests);
+        if throttle_requests == 0 || throttle_requests > 1_000_000 {
+            return Err(GooseError::InvalidOption {
+                option: "`Scenario::set_throttle_requests`".to_string(),
+                value: throttle_requests.to_string(),
+                detail: "The throttle must be set to between 1 and 1,000,000 requests per second."
                     .to_string(),
             });
         }
-        self.transaction_wait = Some((min_wait, max_wait));
+        self.throttle_requests = Some(throttle_requests);
+
+        Ok(self)
+    }
+
+    /// Run this scenario with its own test plan, independent of the load test's test
+    /// plan. The test plan uses the same format as `--test-plan`, and starts when the
+    /// load test starts. Users running this scenario are not allocated by weight, and
+    /// after the last step of the test plan the configured number of users keep running
+    /// until the load test ends.
+    ///
+    /// # Example
+    /// ```rust
+    /// use goose::prelude::*;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), GooseError> {
+    ///     // Always run 10 users with this scenario.
+    ///     let mut admin_transactions: Scenario<GooseUser> =
+    ///         scenario!("AdminTransactions").set_test_plan("10,0s")?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_test_plan(mut self, test_plan: &str) -> Result<Self, GooseError> {
+        trace!("{} set_test_plan: {}", self.name, test_plan);
+        self.test_plan = Some(test_plan.parse::<TestPlan>()?);
 
         Ok(self)
     }
+
+    /// Configure the scenario to walk a [`TransitionGraph`] each iteration, instead of
+    /// running all weighted transactions in order. All transactions referenced by the
+    /// graph must already be registered, and must have unique names. Each transition
+    /// taken is counted in the scenario metrics.
+    ///
+    /// # Example
+    /// ```rust
+    /// use goose::goose::TransitionGraph;
+    /// use goose::prelude::*;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), GooseError> {
+    ///     scenario!("Shopper")
+    ///         .register_transaction(transaction!(search).set_name("search"))
+    ///         .register_transaction(transaction!(product).set_name("product"))
+    ///         .register_transaction(transaction!(cart).set_name("cart"))
+    ///         .set_transition_graph(
+    ///             TransitionGraph::new("search")
+    ///                 // After searching, 60% view a product, 10% go to the cart, and
+    ///                 // the remaining 30% leave.
+    ///                 .add_transition("search", "product", 0.6)
+    ///                 .add_transition("search", "cart", 0.1)
+    ///                 .set_exit_weight("search", 0.3)
+    ///                 // After viewing a product, half search again and half go to
+    ///                 // the cart.
+    ///                 .add_transition("product", "search", 0.5)
+    ///                 .add_transition("product", "cart", 0.5),
+    ///         )?;
+    ///     // The cart has no transitions, so users always exit after it.
+    ///
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn search(user: &mut GooseUser) -> TransactionResult {
+    ///     let _goose = user.get("search?q=goose").await?;
+    ///
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn product(user: &mut GooseUser) -> TransactionResult {
+    ///     let _goose = user.get("product/1").await?;
+    ///
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn cart(user: &mut GooseUser) -> TransactionResult {
+    ///     let _goose = user.get("cart").await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_transition_graph(
+        mut self,
+        mut transition_graph: TransitionGraph,
+    ) -> Result<Self, GooseError> {
+        trace!("{} set_transition_graph: {:?}", self.name, transition_graph);
+        transition_graph.resolve(&self.transactions)?;
+        self.transition_graph = Some(transition_graph);
+
+        Ok(self)
+    }
+}
+
+/// A graph of weighted transitions between the named [`Transaction`]s of a [`Scenario`].
+///
+/// Each iteration starts with the entry transaction. After each transaction completes the
+/// next is randomly selected from its successors, or the iteration ends. The probability
+/// of following a transition is its weight divided by the sum of the weights of all
+/// transitions from the same transaction plus its exit weight. A transaction without any
+/// transitions always ends the iteration. Every transaction that can be reached from the
+/// entry transaction must be able to eventually end the iteration.
+#[derive(Clone, Debug)]
+pub struct TransitionGraph {
+    /// The name of the transaction each iteration starts with.
+    entry: String,
+    /// Transitions from one named transaction to another, and their weight.
+    transitions: Vec<(String, String, f64)>,
+    /// The weight of ending the iteration after a named transaction.
+    exits: Vec<(String, f64)>,
+    /// The index of the entry transaction, set when added to a scenario.
+    pub(crate) entry_index: usize,
+    /// Successors and exit weight of each transaction, by transaction index.
+    nodes: BTreeMap<usize, TransitionNode>,
+}
+
+/// The outgoing transitions of a single transaction.
+#[derive(Clone, Debug, Default)]
+struct TransitionNode {
+    /// Transaction indexes that can run next, and their weight.
+    successors: Vec<(usize, f64)>,
+    /// The weight of ending the iteration.
+    exit: f64,
+}
+
+impl TransitionGraph {
+    /// Create a new transition graph, starting each iteration with the named transaction.
+    pub fn new(entry: &str) -> Self {
+        TransitionGraph {
+            entry: entry.to_string(),
+            transitions: Vec::new(),
+            exits: Vec::new(),
+            entry_index: usize::MAX,
+            nodes: BTreeMap::new(),
+        }
+    }
+
+    /// Add a weighted transition from one named transaction to another.
+    pub fn add_transition(mut self, from: &str, to: &str, weight: f64) -> Self {
+        self.transitions
+            .push((from.to_string(), to.to_string(), weight));
+        self
+    }
+
+    /// Set the weight of ending the iteration after the named transaction.
+    pub fn set_exit_weight(mut self, from: &str, weight: f64) -> Self {
+        self.exits.push((from.to_string(), weight));
+        self
+    }
+
+    /// Resolve transaction names to indexes, validating the graph.
+    fn resolve<G: Goose>(&mut self, transactions: &[Transaction<G>]) -> Result<(), GooseError> {
+        let find = |name: &str| -> Result<usize, GooseError> {
+            let mut matches = transactions
+                .iter()
+                .filter(|t| t.name == name && !t.on_start && !t.on_stop);
+            match (matches.next(), matches.next()) {
+                (Some(t), None) => Ok(t.transactions_index),
+                (None, _) => Err(GooseError::InvalidTransitionGraph {
+                    detail: format!("no transaction named '{}' is registered", name),
+                }),
+                (Some(_), Some(_)) => Err(GooseError::InvalidTransitionGraph {
+                    detail: format!("more than one transaction is named '{}'", name),
+                }),
+            }
+        };
+        let validate = |weight: f64| -> Result<(), GooseError> {
+            if weight.is_finite() && weight >= 0.0 {
+                Ok(())
+            } else {
+                Err(GooseError::InvalidTransitionGraph {
+                    detail: format!("weight {} must be zero or a positive number", weight),
+                })
+            }
+        };
+
+        let mut nodes: BTreeMap<usize, TransitionNode> = BTreeMap::new();
+        let entry_index = find(&self.entry)?;
+        for (from, to, weight) in &self.transitions {
+            validate(*weight)?;
+            let to_index = find(to)?;
+            nodes
+                .entry(find(from)?)
+                .or_default()
+                .successors
+                .push((to_index, *weight));
+        }
+        for (from, weight) in &self.exits {
+            validate(*weight)?;
+            nodes.entry(find(from)?).or_default().exit += weight;
+        }
+
+        // Transitions that can be followed from a transaction, ignoring zero weights.
+        let followed = |index: usize| -> Vec<usize> {
+            nodes.get(&index).map_or_else(Vec::new, |node| {
+                node.successors
+                    .iter()
+                    .filter(|(_, weight)| *weight > 0.0)
+                    .map(|(to_index, _)| *to_index)
+                    .collect()
+            })
+        };
+
+        // Find every transaction that can run, starting from the entry transaction.
+        let mut reachable = BTreeSet::new();
+        reachable.insert(entry_index);
+        let mut pending = vec![entry_index];
+        while let Some(index) = pending.pop() {
+            for to_index in followed(index) {
+                if reachable.insert(to_index) {
+                    pending.push(to_index);
+                }
+            }
+        }
+
+        // Find the transactions that can end the iteration, directly or after following
+        // more transitions.
+        let mut exiting: BTreeSet<usize> = reachable
+            .iter()
+            .copied()
+            .filter(|index| match nodes.get(index) {
+                Some(node) => node.exit > 0.0 || followed(*index).is_empty(),
+                None => true,
+            })
+            .collect();
+        loop {
+            let more: Vec<usize> = reachable
+                .iter()
+                .copied()
+                .filter(|index| {
+                    !exiting.contains(index)
+                        && followed(*index)
+                            .iter()
+                            .any(|to_index| exiting.contains(to_index))
+                })
+                .collect();
+            if more.is_empty() {
+                break;
+            }
+            exiting.extend(more);
+        }
+
+        // Otherwise a GooseUser could walk the transition graph forever.
+        if let Some(index) = reachable.iter().find(|index| !exiting.contains(index)) {
+            return Err(GooseError::InvalidTransitionGraph {
+                detail: format!(
+                    "transaction '{}' can never end the iteration",
+                    transactions[*index].name
+                ),
+            });
+        }
+
+        self.entry_index = entry_index;
+        self.nodes = nodes;
+        Ok(())
+    }
+
+    /// Randomly select the transaction to run after the transaction with the given
+    /// index, returning `None` if the iteration should end.
+    pub(crate) fn next_transaction(&self, transactions_index: usize) -> Option<usize> {
+        let node = self.nodes.get(&transactions_index)?;
+        let total: f64 = node.successors.iter().map(|(_, w)| w).sum::<f64>() + node.exit;
+        if total <= 0.0 {
+            return None;
+        }
+        let mut selected = rand::thread_rng().gen_range(0.0..total);
+        for (index, weight) in &node.successors {
+            if selected < *weight {
+                return Some(*index);
+            }
+            selected -= weight;
+        }
+        None
+    }
+}
+
+impl Hash for TransitionGraph {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.entry.hash(state);
+        for (from, to, weight) in &self.transitions {
+            from.hash(state);
+            to.hash(state);
+            weight.to_bits().hash(state);
+        }
+        for (from, weight) in &self.exits {
+            from.hash(state);
+            weight.to_bits().hash(state);
+        }
+    }
+}
+
+/// A distribution of wait times, from which the time a [`GooseUser`] sleeps after running
+/// a transaction is randomly selected.
+///
+/// Selected wait times are never negative, and are truncated to milliseconds.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GooseWaitTime {
+    /// Always wait the same length of time.
+    Constant(Duration),
+    /// Wait a uniformly random length of time between a minimum and maximum, inclusively.
+    Uniform(Duration, Duration),
+    /// Exponentially distributed wait times with the given mean, simulating users arriving
+    /// as a Poisson process.
+    Exponential {
+        /// The mean wait time.
+        mean: Duration,
+    },
+    /// Normally distributed wait times, any negative wait time is treated as no wait.
+    Normal {
+        /// The mean wait time.
+        mean: Duration,
+        /// The standard deviation of the wait time.
+        std_dev: Duration,
+    },
+    /// Log-normally distributed wait times, with the given mean and standard deviation of
+    /// the wait time itself, not of its logarithm.
+    LogNormal {
+        /// The mean wait time.
+        mean: Duration,
+        /// The standard deviation of the wait time.
+        std_dev: Duration,
+    },
+    /// Pareto distributed wait times, with a long tail of occasional long waits.
+    Pareto {
+        /// The minimum wait time.
+        scale: Duration,
+        /// The shape of the distribution, larger values result in a shorter tail. Must be
+        /// larger than 0.
+        shape: f64,
+    },
+}
+
+impl GooseWaitTime {
+    /// Confirm the distribution can be sampled.
+    pub(crate) fn validate(&self) -> Result<(), GooseError> {
+        match self {
+            GooseWaitTime::Uniform(min_wait, max_wait)
+                if min_wait.as_millis() > max_wait.as_millis() =>
+            {
+                Err(GooseError::InvalidWaitTime {
+                    min_wait: *min_wait,
+                    max_wait: *max_wait,
+                    detail: "The min_wait option can not be set to a larger value than the max_wait option."
+                        .to_string(),
+                })
+            }
+            GooseWaitTime::Pareto { scale, shape } if !shape.is_finite() || *shape <= 0.0 => {
+                Err(GooseError::InvalidWaitTime {
+                    min_wait: *scale,
+                    max_wait: *scale,
+                    detail: format!("The Pareto shape must be larger than 0, not {}.", shape),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Randomly select a wait time from the distribution.
+    pub fn sample(&self) -> Duration {
+        let mut rng = rand::thread_rng();
+        let millis = match self {
+            GooseWaitTime::Constant(wait) => wait.as_millis() as f64,
+            GooseWaitTime::Uniform(min_wait, max_wait) => {
+                rng.gen_range(min_wait.as_millis()..=max_wait.as_millis()) as f64
+            }
+            GooseWaitTime::Exponential { mean } => {
+                let mean = mean.as_millis() as f64;
+                match Exp::new(1.0 / mean) {
+                    Ok(exp) if mean > 0.0 => exp.sample(&mut rng),
+                    _ => 0.0,
+                }
+            }
+            GooseWaitTime::Normal { mean, std_dev } => {
+                match Normal::new(mean.as_millis() as f64, std_dev.as_millis() as f64) {
+                    Ok(normal) => normal.sample(&mut rng),
+                    Err(_) => mean.as_millis() as f64,
+                }
+            }
+            GooseWaitTime::LogNormal { mean, std_dev } => {
+                let mean = mean.as_millis() as f64;
+                let std_dev = std_dev.as_millis() as f64;
+                if mean > 0.0 {
+                    // Convert the mean and standard deviation of the wait time into the
+                    // parameters of the underlying normal distribution.
+                    let sigma = (1.0 + (std_dev * std_dev) / (mean * mean)).ln().sqrt();
+                    let mu = mean.ln() - sigma * sigma / 2.0;
+                    match LogNormal::new(mu, sigma) {
+                        Ok(log_normal) => log_normal.sample(&mut rng),
+                        Err(_) => mean,
+                    }
+                } else {
+                    0.0
+                }
+            }
+            GooseWaitTime::Pareto { scale, shape } => {
+                match Pareto::new(scale.as_millis() as f64, *shape) {
+                    Ok(pareto) if !scale.is_zero() => pareto.sample(&mut rng),
+                    _ => 0.0,
+                }
+            }
+        };
+        if millis.is_finite() && millis > 0.0 {
+            Duration::from_millis(millis as u64)
+        } else {
+            Duration::from_millis(0)
+        }
+    }
+}
+
+impl Hash for GooseWaitTime {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            GooseWaitTime::Constant(wait) => wait.hash(state),
+            GooseWaitTime::Uniform(min_wait, max_wait) => {
+                min_wait.hash(state);
+                max_wait.hash(state);
+            }
+            GooseWaitTime::Exponential { mean } => mean.hash(state),
+            GooseWaitTime::Normal { mean, std_dev }
+            | GooseWaitTime::LogNormal { mean, std_dev } => {
+                mean.hash(state);
+                std_dev.hash(state);
+            }
+            GooseWaitTime::Pareto { scale, shape } => {
+                scale.hash(state);
+                shape.to_bits().hash(state);
+            }
+        }
+    }
+}
+
+/// What a [`GooseUser`] does after a transaction fails.
+///
+/// Configured for all transactions in a scenario with
+/// [`Scenario::set_failure_policy`](./struct.Scenario.html#method.set_failure_policy),
+/// and for individual transactions with
+/// [`Transaction::set_failure_policy`](./struct.Transaction.html#method.set_failure_policy).
+/// Before restarting, the user sleeps for the wait time configured after the failed
+/// transaction.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum GooseFailurePolicy {
+    /// Continue running the remaining transactions (the default).
+    Continue,
+    /// Skip the remaining transactions and start a new iteration. Aborted iterations count
+    /// toward `--iterations`, but aren't included in the scenario metrics. When an
+    /// [`on_start`](./struct.Transaction.html#method.set_on_start) transaction fails, all
+    /// on_start transactions run again.
+    RestartIteration,
+    /// Discard the user's session, including cookies and session data, and start over
+    /// from the [`on_start`](./struct.Transaction.html#method.set_on_start) transactions.
+    RestartUser,
+    /// Stop the user after running its
+    /// [`on_stop`](./struct.Transaction.html#method.set_on_stop) transactions.
+    StopUser,
+}
+
+/// Defines when and how often a failed request is retried.
+///
+/// Each attempt is recorded as its own [`GooseRequestMetric`], with an `attempt` counter
+/// starting at 1. Only failed attempts are retried, if the response has one of the
+/// retryable status codes, or if no response was received and retrying errors is enabled.
+/// Between attempts the [`GooseUser`] sleeps for an exponentially increasing, randomly
+/// jittered backoff, or for the time requested by a `Retry-After` header. If the load
+/// test shuts down or is paused during a backoff longer than 500 milliseconds, the backoff
+/// ends early and the failed attempt isn't retried.
+///
+/// Configured for all requests made by a scenario with
+/// [`Scenario::set_retry_policy`](./struct.Scenario.html#method.set_retry_policy), and
+/// for individual requests with [`GooseRequestBuilder::retry_policy`].
+///
+/// # Example
+/// ```rust
+/// use goose::goose::GooseRetryPolicy;
+/// use std::time::Duration;
+///
+/// // Make up to 5 attempts when rate limited, backing off from 200 milliseconds up to
+/// // 5 seconds, without retrying connection errors.
+/// let retry_policy = GooseRetryPolicy::new(5)
+///     .set_status_codes(&[429])
+///     .set_retry_errors(false)
+///     .set_backoff(Duration::from_millis(200), Duration::from_secs(5));
+/// ```
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct GooseRetryPolicy {
+    /// The maximum number of times a request is attempted, including the first attempt.
+    pub max_attempts: usize,
+    /// Response status codes that are retried, by default 429, 502, 503 and 504.
+    pub status_codes: Vec<u16>,
+    /// Whether requests that failed without a response, for example because of a timeout
+    /// or connection error, are retried. Enabled by default.
+    pub retry_errors: bool,
+    /// The backoff before the first retry, doubled for each following retry. Defaults to
+    /// 100 milliseconds.
+    pub initial_backoff: Duration,
+    /// The longest the [`GooseUser`] waits between attempts, including when honoring a
+    /// `Retry-After` header. Defaults to 10 seconds.
+    pub max_backoff: Duration,
+    /// Whether to wait as long as requested by a `Retry-After` response header, instead of
+    /// backing off. Enabled by default.
+    pub honor_retry_after: bool,
+}
+
+impl GooseRetryPolicy {
+    /// Create a retry policy making up to `max_attempts` attempts at each request.
+    pub fn new(max_attempts: usize) -> Self {
+        GooseRetryPolicy {
+            max_attempts,
+            status_codes: vec![429, 502, 503, 504],
+            retry_errors: true,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            honor_retry_after: true,
+        }
+    }
+
+    /// Replace the response status codes that are retried.
+    pub fn set_status_codes(mut self, status_codes: &[u16]) -> Self {
+        self.status_codes = status_codes.to_vec();
+        self
+    }
+
+    /// Configure whether requests that failed without a response are retried.
+    pub fn set_retry_errors(mut self, retry_errors: bool) -> Self {
+        self.retry_errors = retry_errors;
+        self
+    }
+
+    /// Configure the initial and maximum backoff between attempts.
+    pub fn set_backoff(mut self, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Configure whether `Retry-After` response headers are honored.
+    pub fn set_honor_retry_after(mut self, honor_retry_after: bool) -> Self {
+        self.honor_retry_after = honor_retry_after;
+        self
+    }
+
+    /// Determine how long to wait before retrying a failed attempt, returning `None` if the
+    /// attempt shouldn't be retried.
+    pub(crate) fn retry_delay(
+        &self,
+        attempt: usize,
+        response: &Result<Response, reqwest::Error>,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+
+        match response {
+            Ok(r) => {
+                if !self.status_codes.contains(&r.status().as_u16()) {
+                    return None;
+                }
+                if self.honor_retry_after {
+                    if let Some(retry_after) = r
+                        .headers()
+                        .get(header::RETRY_AFTER)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(parse_retry_after)
+                    {
+                        return Some(retry_after.min(self.max_backoff));
+                    }
+                }
+            }
+            Err(_) => {
+                if !self.retry_errors {
+                    return None;
+                }
+            }
+        }
+
+        Some(self.backoff(attempt))
+    }
+
+    /// Exponential backoff with full jitter: a random delay up to the initial backoff
+    /// doubled for each previous retry, capped at the maximum backoff.
+    fn backoff(&self, attempt: usize) -> Duration {
+        let exponent = (attempt.max(1) - 1).min(31) as u32;
+        let backoff = self
+            .initial_backoff
+            .checked_mul(2_u32.pow(exponent))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+        Duration::from_millis(rand::thread_rng().gen_range(0..=backoff.as_millis() as u64))
+    }
+}
+
+/// Parse a `Retry-After` header, either a number of seconds or an HTTP date.
+fn parse_retry_after(retry_after: &str) -> Option<Duration> {
+    let retry_after = retry_after.trim();
+    if let Ok(seconds) = retry_after.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(retry_after).ok()?;
+    // A date in the past means the request can be retried immediately.
+    Some(
+        (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
 }
 
 /// Commands sent from the parent thread to the user threads, and from the manager to the
@@ -666,8 +1446,10 @@ impl<G: Goose> Scenario<G> {
 pub enum GooseUserCommand {
     /// Tell worker process to pause load test.
     Wait,
-    /// Tell worker process to start load test.
+    /// Tell worker process to start load test, or tell a paused user thread to resume.
     Run,
+    /// Tell user thread to pause at the next transaction boundary, until it receives `Run`.
+    Pause,
     /// Tell user thread or worker process to exit.
     Exit,
 }
@@ -887,8 +1669,13 @@ pub struct GooseUser {
     pub config: GooseConfiguration,
     /// Channel to logger.
     pub logger: Option<flume::Sender<Option<GooseLog>>>,
-    /// Channel to throttle.
-    pub throttle: Option<flume::Sender<bool>>,
+    /// Token bucket shared by all users to throttle requests.
+    pub throttle: Option<Arc<GooseThrottle>>,
+    /// Token bucket shared by all users running the same [`Scenario`] to throttle requests.
+    pub scenario_throttle: Option<Arc<GooseThrottle>>,
+    /// Token buckets shared by all users to throttle requests to specific hosts, keyed by
+    /// the origin of the host (for example `https://example.com`).
+    pub host_throttles: HashMap<String, Arc<GooseThrottle>>,
     /// Normal transactions are optionally throttled,
     /// [`test_start`](../struct.GooseAttack.html#method.test_start) and
     /// [`test_stop`](../struct.GooseAttack.html#method.test_stop) transactions are not.
@@ -897,6 +1684,9 @@ pub struct GooseUser {
     pub metrics_channel: Option<flume::Sender<GooseMetric>>,
     /// Channel for notifying the parent when thread shuts down.
     pub shutdown_channel: Option<flume::Sender<usize>>,
+    /// A copy of the channel the parent uses to control this user, checked for waiting
+    /// commands while backing off between retries.
+    command_receiver: Option<flume::Receiver<GooseUserCommand>>,
     /// An index into the internal [`GooseAttack`](../struct.GooseAttack.html)`.weighted_users`
     /// vector, indicating which weighted `GooseUser` is running.
     pub weighted_users_index: usize,
@@ -909,6 +1699,17 @@ pub struct GooseUser {
     pub(crate) slept: u64,
     /// Current transaction name.
     pub(crate) transaction_name: Option<String>,
+    /// Current transaction index, if running a transaction.
+    pub(crate) transaction_index: Option<usize>,
+    /// Names of the groups currently running, outermost first.
+    pub(crate) groups: Vec<String>,
+    /// Timeout of the current transaction, if set with [`Transaction::set_timeout`].
+    transaction_timeout: Option<Duration>,
+    /// The request awaiting a response and when it started, if any. Recorded as failed if
+    /// the transaction making it times out.
+    request_in_flight: Option<(GooseRequestMetric, Instant)>,
+    /// An optional default retry policy, configured for the [`Scenario`] this user runs.
+    pub(crate) retry_policy: Option<GooseRetryPolicy>,
     /// Optional per-user session data of a generic type implementing the
     /// [`GooseUserData`] trait.
     session_data: Option<Box<dyn GooseUserData>>,
@@ -1005,42 +1806,32 @@ impl Goose for GooseUser {
     ) -> Result<Self, GooseError> {
         trace!("new GooseUser");
 
-        // Either use manually configured timeout, or default.
-        let timeout = if configuration.timeout.is_some() {
-            match crate::util::get_float_from_string(configuration.timeout.clone()) {
-                Some(f) => f as u64 * 1_000,
-                None => GOOSE_REQUEST_TIMEOUT,
-            }
-        } else {
-            GOOSE_REQUEST_TIMEOUT
-        };
-
-        let client = Client::builder()
-            .user_agent(APP_USER_AGENT)
-            .cookie_store(true)
-            .timeout(Duration::from_millis(timeout))
-            // Enable gzip unless `--no-gzip` flag is enabled.
-            .gzip(!configuration.no_gzip)
-            .build()?;
-
         Ok(GooseUser {
             started: Instant::now(),
             iterations: 0,
             scenarios_index,
-            client,
+            client: create_reqwest_client(configuration)?,
             base_url,
             config: configuration.clone(),
             logger: None,
             throttle: None,
+            scenario_throttle: None,
+            host_throttles: HashMap::new(),
             is_throttled: true,
             metrics_channel: None,
             shutdown_channel: None,
+            command_receiver: None,
             // A value of max_value() indicates this user isn't fully initialized yet.
             weighted_users_index: usize::max_value(),
             load_test_hash,
             request_cadence: GooseRequestCadence::new(),
             slept: 0,
             transaction_name: None,
+            transaction_index: None,
+            groups: Vec::new(),
+            transaction_timeout: None,
+            request_in_flight: None,
+            retry_policy: None,
             session_data: None,
         })
     }
@@ -1049,6 +1840,13 @@ impl Goose for GooseUser {
         self.slept += duration
     }
 
+    fn add_paused(&mut self, duration: u64) {
+        // Time spent paused isn't part of the load test, so shift the start time past it
+        // and don't count it against the request cadence.
+        self.started += Duration::from_millis(duration);
+        self.slept += duration;
+    }
+
     fn add_iterations(&mut self, num: usize) {
         self.iterations += num
     }
@@ -1179,28 +1977,73 @@ impl Goose for GooseUser {
         self.logger = logger
     }
 
-    fn logger(&self) -> GooseLoggerTx {
-        self.logger.clone()
+    fn logger(&self) -> GooseLoggerTx {
+        self.logger.clone()
+    }
+
+    fn set_throttle(&mut self, throttle: Option<Arc<GooseThrottle>>) {
+        self.throttle = throttle
+    }
+
+    fn set_scenario_throttle(&mut self, throttle: Option<Arc<GooseThrottle>>) {
+        self.scenario_throttle = throttle
+    }
+
+    fn set_host_throttles(&mut self, throttles: HashMap<String, Arc<GooseThrottle>>) {
+        self.host_throttles = throttles
+    }
+
+    fn set_weighted_users_index(&mut self, total_users: usize) {
+        self.weighted_users_index = total_users
+    }
+
+    fn weighted_users_index(&self) -> usize {
+        self.weighted_users_index
+    }
+
+    fn set_transaction_name(&mut self, transaction_name: String) {
+        self.transaction_name.replace(transaction_name);
+    }
+
+    fn take_transaction_name(&mut self) -> Option<String> {
+        self.transaction_name.take()
+    }
+
+    fn set_transaction_index(&mut self, transaction_index: usize) {
+        self.transaction_index = Some(transaction_index);
+        self.groups.clear();
     }
 
-    fn set_throttle(&mut self, throttle: Option<Sender<bool>>) {
-        self.throttle = throttle
+    fn set_retry_policy(&mut self, retry_policy: Option<GooseRetryPolicy>) {
+        self.retry_policy = retry_policy;
     }
 
-    fn set_weighted_users_index(&mut self, total_users: usize) {
-        self.weighted_users_index = total_users
+    fn set_transaction_timeout(&mut self, timeout: Option<Duration>) {
+        self.transaction_timeout = timeout;
     }
 
-    fn weighted_users_index(&self) -> usize {
-        self.weighted_users_index
+    fn set_command_receiver(
+        &mut self,
+        command_receiver: Option<flume::Receiver<GooseUserCommand>>,
+    ) {
+        self.command_receiver = command_receiver;
     }
 
-    fn set_transaction_name(&mut self, transaction_name: String) {
-        self.transaction_name.replace(transaction_name);
+    fn take_request_in_flight(&mut self) -> Option<GooseRequestMetric> {
+        self.request_in_flight
+            .take()
+            .map(|(mut request_metric, started)| {
+                request_metric.set_response_time(started.elapsed().as_millis());
+                request_metric
+            })
     }
 
-    fn take_transaction_name(&mut self) -> Option<String> {
-        self.transaction_name.take()
+    /// Discard cookies and session data, replacing any custom client with the default.
+    fn reset_session(&mut self) -> Result<(), GooseError> {
+        self.client = create_reqwest_client(&self.config)?;
+        self.session_data = None;
+
+        Ok(())
     }
 
     fn send_request_metric_to_parent(
@@ -1617,6 +2460,103 @@ impl GooseUser {
         self.session_data.replace(Box::new(data));
     }
 
+    /// Run a named group of steps within a transaction, timing them separately.
+    ///
+    /// Each time a group runs a transaction metric is recorded for it, tied to the
+    /// transaction running it. Groups succeed unless the function returns an error, which
+    /// is then returned by `group`. Groups can be nested, and are displayed beneath their
+    /// parent transaction or group in the transaction metrics and the html report. Groups
+    /// run outside of a transaction, such as in
+    /// [`test_start`](../struct.GooseAttack.html#method.test_start), are not timed.
+    ///
+    /// # Example
+    /// ```rust
+    /// use goose::prelude::*;
+    ///
+    /// let mut transaction = transaction!(checkout).set_name("checkout");
+    ///
+    /// /// Check out, separately timing the cart, shipping and payment steps.
+    /// async fn checkout(user: &mut GooseUser) -> TransactionResult {
+    ///     user.group("cart", |user| {
+    ///         Box::pin(async move {
+    ///             let _goose = user.post("cart", "item=1").await?;
+    ///             let _goose = user.get("cart").await?;
+    ///
+    ///             Ok(())
+    ///         })
+    ///     })
+    ///     .await?;
+    ///
+    ///     user.group("shipping", |user| {
+    ///         Box::pin(async move {
+    ///             let _goose = user.post("shipping", "method=ground").await?;
+    ///
+    ///             Ok(())
+    ///         })
+    ///     })
+    ///     .await?;
+    ///
+    ///     user.group("payment", |user| {
+    ///         Box::pin(async move {
+    ///             let _goose = user.post("payment", "card=4111111111111111").await?;
+    ///
+    ///             Ok(())
+    ///         })
+    ///     })
+    ///     .await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn group<F>(&mut self, name: &str, function: F) -> TransactionResult
+    where
+        F: for<'r> FnOnce(
+            &'r mut GooseUser,
+        ) -> Pin<Box<dyn Future<Output = TransactionResult> + Send + 'r>>,
+    {
+        // Groups are only timed when run within a transaction.
+        let transaction_index = match self.transaction_index {
+            Some(transaction_index) => transaction_index,
+            None => return function(self).await,
+        };
+
+        self.groups.push(name.to_string());
+        let started = Instant::now();
+        let mut raw_transaction = TransactionMetric::new(
+            self.started.elapsed().as_millis(),
+            self.scenarios_index,
+            transaction_index,
+            name.to_string(),
+            self.weighted_users_index,
+        );
+        raw_transaction.group = self.groups.clone();
+
+        let result = function(self).await;
+        raw_transaction.set_time(started.elapsed().as_millis(), result.is_ok());
+        self.groups.pop();
+
+        // Exit if all metrics or transaction metrics are disabled.
+        if self.config.no_metrics || self.config.no_transaction_metrics {
+            return result;
+        }
+
+        // If transaction-log is enabled, send a copy of the raw transaction metric to the
+        // logger thread.
+        if !self.config.transaction_log.is_empty() {
+            if let Some(logger) = self.logger.as_ref() {
+                let _ = logger.send(Some(GooseLog::Transaction(raw_transaction.clone())));
+            }
+        }
+
+        // Otherwise send metrics to parent.
+        if let Some(metrics_channel) = self.metrics_channel.as_ref() {
+            // Best effort metrics.
+            let _ = metrics_channel.send(GooseMetric::Transaction(raw_transaction));
+        }
+
+        result
+    }
+
     /// A helper that prepends a `base_url` to all relative paths.
     ///
     /// A `base_url` is determined per user thread, using the following order
@@ -2026,30 +2966,147 @@ impl GooseUser {
         mut request: GooseRequest<'_>,
     ) -> Result<GooseResponse, TransactionError> {
         // If the RequestBuilder is already defined in the GooseRequest use it.
-        let request_builder = if request.request_builder.is_some() {
+        let mut request_builder = if request.request_builder.is_some() {
             request.request_builder.take().unwrap()
             // Otherwise get a new RequestBuilder.
         } else {
             self.get_request_builder(&request.method, request.path)?
         };
 
+        // Optionally override the client timeout for this request.
+        if let Some(timeout) = request.timeout {
+            request_builder = request_builder.timeout(timeout);
+        }
+
         // Determine the name for this request.
-        let request_name = self.get_request_name(&request);
+        let request_name = self.get_request_name(&request).to_string();
+
+        // A retry policy configured on the request overrides the scenario retry policy.
+        let retry_policy = request
+            .retry_policy
+            .clone()
+            .or_else(|| self.retry_policy.clone());
+
+        let mut attempt = 1;
+        loop {
+            // Keep a copy of the request if it may be retried.
+            let retry_request_builder = match retry_policy.as_ref() {
+                Some(retry_policy) if attempt < retry_policy.max_attempts => {
+                    request_builder.try_clone()
+                }
+                _ => None,
+            };
+
+            let (path, goose) = self
+                .request_attempt(&request, request_builder, &request_name, attempt)
+                .await?;
+
+            // Optionally retry a failed attempt.
+            if !goose.request.success {
+                if let (Some(retry_policy), Some(retry_request_builder)) =
+                    (retry_policy.as_ref(), retry_request_builder)
+                {
+                    if let Some(delay) = retry_policy.retry_delay(attempt, &goose.response) {
+                        debug!(
+                            "{:?}: retrying failed attempt {} after {:?}",
+                            &path, attempt, delay
+                        );
+                        if self.retry_backoff(delay).await {
+                            request_builder = retry_request_builder;
+                            attempt += 1;
+                            continue;
+                        }
+                        debug!(
+                            "{:?}: not retrying failed attempt {}, parent sent a command",
+                            &path, attempt
+                        );
+                    }
+                }
+            }
+
+            if request.error_on_fail && !goose.request.success {
+                error!("{:?} {}", &path, &goose.request.error);
+                return Err(TransactionError::RequestFailed {
+                    raw_request: goose.request,
+                });
+            }
+
+            return Ok(goose);
+        }
+    }
+
+    // Wait until a token can be removed from each throttle that applies to a request.
+    // Returns `RequestCanceled` if the load test ends while waiting.
+    async fn wait_on_throttles(&self, url: &Url) -> Result<(), TransactionError> {
+        // Test start and test stop transactions are not throttled.
+        if !self.is_throttled {
+            return Ok(());
+        }
 
-        // If throttle-requests is enabled...
-        if self.is_throttled && self.throttle.is_some() {
-            // ...wait until there's room to add a token to the throttle channel before proceeding.
+        // Wait on the most specific throttles first, so tokens aren't taken from the
+        // global throttle while waiting on a more restrictive throttle.
+        let throttles = self
+            .host_throttles
+            .get(&url.origin().ascii_serialization())
+            .into_iter()
+            .chain(self.scenario_throttle.iter())
+            .chain(self.throttle.iter());
+        for throttle in throttles {
             debug!("GooseUser: waiting on throttle");
-            // Will result in TransactionError::RequestCanceled if this fails.
-            self.throttle.clone().unwrap().send_async(true).await?;
-        };
+            if !throttle.acquire().await {
+                // The load test ended while waiting, cancel the request.
+                return Err(flume::SendError(true).into());
+            }
+        }
+
+        Ok(())
+    }
 
-        // Once past the throttle, the request is officially started.
+    /// Sleeps before retrying a failed request, waking regularly to stop early if the parent
+    /// has sent a command, such as to exit or pause. The command is left for the user thread
+    /// to handle once the transaction finishes. Returns false if the backoff was cut short.
+    async fn retry_backoff(&mut self, delay: Duration) -> bool {
+        // Never sleep more than 500 milliseconds, allowing a backoff to end quickly when the
+        // load test shuts down or is paused.
+        let maximum_sleep_time = Duration::from_millis(500);
         let started = Instant::now();
+        let completed = loop {
+            let remaining = delay.saturating_sub(started.elapsed());
+            if remaining.is_zero() {
+                break true;
+            }
+            tokio::time::sleep(remaining.min(maximum_sleep_time)).await;
+            // Stop backing off if the parent sent a command while sleeping.
+            if started.elapsed() < delay {
+                if let Some(command_receiver) = self.command_receiver.as_ref() {
+                    if !command_receiver.is_empty() {
+                        break false;
+                    }
+                }
+            }
+        };
+        self.slept += started.elapsed().as_millis() as u64;
+        completed
+    }
 
+    /// Makes a single attempt at a request, recording its metrics. Returns the path that
+    /// was requested, for logging.
+    async fn request_attempt(
+        &mut self,
+        request: &GooseRequest<'_>,
+        request_builder: RequestBuilder,
+        request_name: &str,
+        attempt: usize,
+    ) -> Result<(String, GooseResponse), TransactionError> {
         // Create a Reqwest Request object from the RequestBuilder.
         let built_request = request_builder.build()?;
 
+        // If any throttles are enabled, wait until the request is allowed.
+        self.wait_on_throttles(built_request.url()).await?;
+
+        // Once past the throttles, the request is officially started.
+        let started = Instant::now();
+
         // Get a string version of request path for logging.
         let path = match Url::parse(built_request.url().as_ref()) {
             Ok(u) => u.path().to_string(),
@@ -2095,9 +3152,14 @@ impl GooseUser {
             self.started.elapsed().as_millis(),
             self.weighted_users_index,
         );
+        request_metric.attempt = attempt;
 
-        // Make the actual request.
+        // Make the actual request, keeping a copy in case the transaction times out first.
+        if self.transaction_timeout.is_some() && !self.config.no_metrics {
+            self.request_in_flight = Some((request_metric.clone(), started));
+        }
         let response = self.client.execute(built_request).await;
+        self.request_in_flight = None;
         request_metric.set_response_time(started.elapsed().as_millis());
 
         // Determine if the request suceeded or failed.
@@ -2164,14 +3226,7 @@ impl GooseUser {
             self.send_request_metric_to_parent(request_metric.clone())?;
         }
 
-        if request.error_on_fail && !request_metric.success {
-            error!("{:?} {}", &path, &request_metric.error);
-            return Err(TransactionError::RequestFailed {
-                raw_request: request_metric,
-            });
-        }
-
-        Ok(GooseResponse::new(request_metric, response))
+        Ok((path, GooseResponse::new(request_metric, response)))
     }
 
     /// If Coordinated Omission Mitigation is enabled, compares how long has passed since the last
@@ -2498,6 +3553,10 @@ pub struct GooseRequest<'a> {
     // Defaults to [`false`].
     error_on_fail: bool,
     // Defaults to [`None`].
+    timeout: Option<Duration>,
+    // Defaults to [`None`].
+    retry_policy: Option<GooseRetryPolicy>,
+    // Defaults to [`None`].
     request_builder: Option<RequestBuilder>,
 }
 
@@ -2543,6 +3602,8 @@ pub struct GooseRequestBuilder<'a> {
     name: Option<&'a str>,
     expect_status_code: Option<u16>,
     error_on_fail: bool,
+    timeout: Option<Duration>,
+    retry_policy: Option<GooseRetryPolicy>,
     request_builder: Option<RequestBuilder>,
 }
 
@@ -2555,6 +3616,8 @@ impl<'a> GooseRequestBuilder<'a> {
             name: None,
             expect_status_code: None,
             error_on_fail: false,
+            timeout: None,
+            retry_policy: None,
             request_builder: None,
         }
     }
@@ -2730,6 +3793,66 @@ impl<'a> GooseRequestBuilder<'a> {
         self
     }
 
+    /// Set a timeout for the request, overriding the `--timeout` run-time option.
+    ///
+    /// If the request doesn't complete in time it is canceled, and recorded as failed
+    /// with a `timeout` error.
+    ///
+    /// # Example
+    /// Allow a slow report to take up to 30 seconds.
+    /// ```rust
+    /// use goose::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// let mut a_transaction = transaction!(transaction_function);
+    ///
+    /// async fn transaction_function(user: &mut GooseUser) -> TransactionResult {
+    ///     let goose_request = GooseRequest::builder()
+    ///         .path("reports/annual")
+    ///         .timeout(Duration::from_secs(30))
+    ///         .build();
+    ///
+    ///     let _goose = user.request(goose_request).await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retry the request if it fails, according to a [`GooseRetryPolicy`]. Overrides the
+    /// retry policy configured for the [`Scenario`], use `GooseRetryPolicy::new(1)` to
+    /// disable retries for a request.
+    ///
+    /// If the request can't be copied, for example because its body is streamed, it isn't
+    /// retried.
+    ///
+    /// # Example
+    /// Retry a rate limited request up to 3 times.
+    /// ```rust
+    /// use goose::goose::GooseRetryPolicy;
+    /// use goose::prelude::*;
+    ///
+    /// let mut a_transaction = transaction!(transaction_function);
+    ///
+    /// async fn transaction_function(user: &mut GooseUser) -> TransactionResult {
+    ///     let goose_request = GooseRequest::builder()
+    ///         .path("search?q=goose")
+    ///         .retry_policy(GooseRetryPolicy::new(4).set_status_codes(&[429]))
+    ///         .build();
+    ///
+    ///     let _goose = user.request(goose_request).await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn retry_policy(mut self, retry_policy: GooseRetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
     /// Manually create the [`reqwest::RequestBuilder`] used to make a request.
     ///
     /// # Example
@@ -2781,6 +3904,8 @@ impl<'a> GooseRequestBuilder<'a> {
             name,
             expect_status_code,
             error_on_fail,
+            timeout,
+            retry_policy,
             request_builder,
         } = self;
         GooseRequest {
@@ -2789,15 +3914,40 @@ impl<'a> GooseRequestBuilder<'a> {
             name,
             expect_status_code,
             error_on_fail,
+            timeout,
+            retry_policy,
             request_builder,
         }
     }
 }
 
+/// Create the default reqwest client used by each [`GooseUser`].
+fn create_reqwest_client(configuration: &GooseConfiguration) -> Result<Client, reqwest::Error> {
+    // Either use manually configured timeout, or default.
+    let timeout = if configuration.timeout.is_some() {
+        match crate::util::get_float_from_string(configuration.timeout.clone()) {
+            Some(f) => f as u64 * 1_000,
+            None => GOOSE_REQUEST_TIMEOUT,
+        }
+    } else {
+        GOOSE_REQUEST_TIMEOUT
+    };
+
+    Client::builder()
+        .user_agent(APP_USER_AGENT)
+        .cookie_store(true)
+        .timeout(Duration::from_millis(timeout))
+        // Enable gzip unless `--no-gzip` flag is enabled.
+        .gzip(!configuration.no_gzip)
+        .build()
+}
+
 /// Remove path from Reqwest error to avoid having a lot of distincts error
 /// when path parameters are used.
 fn clean_reqwest_error(e: &reqwest::Error, request_name: &str) -> String {
-    let kind = if e.is_builder() {
+    let kind = if e.is_timeout() {
+        "timeout"
+    } else if e.is_builder() {
         "builder error"
     } else if e.is_request() {
         "error sending request"
@@ -2896,8 +4046,19 @@ pub struct Transaction<G: Goose> {
     pub on_start: bool,
     /// A flag indicating that this transaction runs when the user stops.
     pub on_stop: bool,
+    /// An optional [`GooseWaitTime`] overriding how long a [`GooseUser`] sleeps after
+    /// running this transaction.
+    pub wait_time: Option<GooseWaitTime>,
+    /// An optional maximum time this transaction can run before it's canceled.
+    pub timeout: Option<Duration>,
+    /// An optional [`GooseFailurePolicy`] overriding what a [`GooseUser`] does when this
+    /// transaction fails.
+    pub failure_policy: Option<GooseFailurePolicy>,
     /// A required function that is executed each time this transaction runs.
     pub function: TransactionFunction<G>,
+    /// Set by a Controller to skip this transaction while the load test is running, shared
+    /// by every [`GooseUser`] running a copy of this transaction.
+    pub(crate) skipped: Arc<AtomicBool>,
 }
 
 impl<G: Goose> Transaction<G> {
@@ -2910,10 +4071,24 @@ impl<G: Goose> Transaction<G> {
             sequence: 0,
             on_start: false,
             on_stop: false,
+            wait_time: None,
+            timeout: None,
+            failure_policy: None,
             function,
+            skipped: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Returns true if a Controller is skipping this transaction.
+    pub(crate) fn is_skipped(&self) -> bool {
+        self.skipped.load(Ordering::Relaxed)
+    }
+
+    /// Skip this transaction, or stop skipping it.
+    pub(crate) fn set_skipped(&self, skipped: bool) {
+        self.skipped.store(skipped, Ordering::Relaxed);
+    }
+
     /// Set an optional name for the transaction, used when displaying metrics.
     ///
     /// Individual requests can also be named using [`GooseRequestBuilder`], or for GET
@@ -3126,6 +4301,138 @@ impl<G: Goose> Transaction<G> {
         self.sequence = sequence;
         self
     }
+
+    /// Override how long a [`GooseUser`] sleeps after running this transaction, instead of
+    /// the wait time configured for the [`Scenario`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use goose::goose::GooseWaitTime;
+    /// use goose::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), GooseError> {
+    ///     // Users spend longer reading the article after loading it.
+    ///     transaction!(read_article).set_wait_time_distribution(GooseWaitTime::LogNormal {
+    ///         mean: Duration::from_secs(30),
+    ///         std_dev: Duration::from_secs(10),
+    ///     })?;
+    ///
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn read_article(user: &mut GooseUser) -> TransactionResult {
+    ///     let _goose = user.get("/article").await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_wait_time_distribution(
+        mut self,
+        wait_time: GooseWaitTime,
+    ) -> Result<Self, GooseError> {
+        trace!(
+            "{} [{}] set_wait_time_distribution: {:?}",
+            self.name,
+            self.transactions_index,
+            wait_time
+        );
+        wait_time.validate()?;
+        self.wait_time = Some(wait_time);
+
+        Ok(self)
+    }
+
+    /// Configure a [`GooseUser`] to pause after running this transaction, instead of the
+    /// wait time configured for the [`Scenario`]. The length of the pause will be randomly
+    /// selected from `min_wait` to `max_wait` inclusively.
+    ///
+    /// # Example
+    /// ```rust
+    /// use goose::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), GooseError> {
+    ///     transaction!(transaction_function)
+    ///         .set_wait_time(Duration::from_secs(5), Duration::from_secs(10))?;
+    ///
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn transaction_function(user: &mut GooseUser) -> TransactionResult {
+    ///     let _goose = user.get("/").await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_wait_time(self, min_wait: Duration, max_wait: Duration) -> Result<Self, GooseError> {
+        self.set_wait_time_distribution(GooseWaitTime::Uniform(min_wait, max_wait))
+    }
+
+    /// Cancel the transaction if it runs longer than `timeout`. Canceled transactions are
+    /// recorded as failed, and the [`GooseUser`] moves on to its next transaction. Any
+    /// request in progress when the transaction is canceled is recorded as failed with a
+    /// timeout error.
+    ///
+    /// To limit how long individual requests can take, instead use
+    /// [`GooseRequestBuilder::timeout`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use goose::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// let mut a_transaction = transaction!(transaction_function).set_timeout(Duration::from_secs(2));
+    ///
+    /// async fn transaction_function(user: &mut GooseUser) -> TransactionResult {
+    ///     let _goose = user.get("/").await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        trace!(
+            "{} [{}] set_timeout: {:?}",
+            self.name,
+            self.transactions_index,
+            timeout
+        );
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Configure what a [`GooseUser`] does when this transaction fails, instead of the
+    /// failure policy configured for the [`Scenario`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use goose::goose::GooseFailurePolicy;
+    /// use goose::prelude::*;
+    ///
+    /// // Start over with a fresh session if logging in fails.
+    /// let mut a_transaction = transaction!(login)
+    ///     .set_on_start()
+    ///     .set_failure_policy(GooseFailurePolicy::RestartUser);
+    ///
+    /// async fn login(user: &mut GooseUser) -> TransactionResult {
+    ///     let goose = user.post("login", "username=foo&password=bar").await?;
+    ///     goose.response?.error_for_status()?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_failure_policy(mut self, failure_policy: GooseFailurePolicy) -> Self {
+        trace!(
+            "{} [{}] set_failure_policy: {:?}",
+            self.name,
+            self.transactions_index,
+            failure_policy
+        );
+        self.failure_policy = Some(failure_policy);
+        self
+    }
 }
 
 impl<G: Goose> Hash for Transaction<G> {
@@ -3136,6 +4443,9 @@ impl<G: Goose> Hash for Transaction<G> {
         self.sequence.hash(state);
         self.on_start.hash(state);
         self.on_stop.hash(state);
+        self.wait_time.hash(state);
+        self.timeout.hash(state);
+        self.failure_policy.hash(state);
     }
 }
 
@@ -3243,7 +4553,10 @@ mod tests {
             .unwrap();
         assert_eq!(
             scenario.transaction_wait,
-            Some((Duration::from_secs(1), Duration::from_secs(10)))
+            Some(GooseWaitTime::Uniform(
+                Duration::from_secs(1),
+                Duration::from_secs(10)
+            ))
         );
         assert_eq!(scenario.host, Some("https://bar.example.com/".to_string()));
         assert_eq!(scenario.weight, 5);
@@ -3257,7 +4570,148 @@ mod tests {
             .unwrap();
         assert_eq!(
             scenario.transaction_wait,
-            Some((Duration::from_secs(3), Duration::from_secs(9)))
+            Some(GooseWaitTime::Uniform(
+                Duration::from_secs(3),
+                Duration::from_secs(9)
+            ))
+        );
+    }
+
+    #[test]
+    fn goose_wait_time() {
+        // The minimum wait time can not be larger than the maximum wait time.
+        assert!(
+            GooseWaitTime::Uniform(Duration::from_secs(2), Duration::from_secs(1))
+                .validate()
+                .is_err()
+        );
+        // The Pareto shape must be positive.
+        assert!(GooseWaitTime::Pareto {
+            scale: Duration::from_secs(1),
+            shape: 0.0
+        }
+        .validate()
+        .is_err());
+
+        // Constant wait times never change.
+        let wait_time = GooseWaitTime::Constant(Duration::from_millis(250));
+        assert!(wait_time.validate().is_ok());
+        assert_eq!(wait_time.sample(), Duration::from_millis(250));
+
+        // Uniform wait times are inclusive, even when the minimum equals the maximum.
+        let wait_time = GooseWaitTime::Uniform(Duration::from_secs(1), Duration::from_secs(1));
+        assert_eq!(wait_time.sample(), Duration::from_secs(1));
+        let wait_time = GooseWaitTime::Uniform(Duration::from_secs(1), Duration::from_secs(2));
+        for _ in 0..100 {
+            let sample = wait_time.sample();
+            assert!(sample >= Duration::from_secs(1) && sample <= Duration::from_secs(2));
+        }
+
+        // Pareto wait times are never shorter than the scale.
+        let wait_time = GooseWaitTime::Pareto {
+            scale: Duration::from_millis(100),
+            shape: 1.5,
+        };
+        for _ in 0..100 {
+            assert!(wait_time.sample() >= Duration::from_millis(100));
+        }
+
+        // A mean of zero never waits.
+        for wait_time in &[
+            GooseWaitTime::Exponential {
+                mean: Duration::from_secs(0),
+            },
+            GooseWaitTime::Normal {
+                mean: Duration::from_secs(0),
+                std_dev: Duration::from_secs(0),
+            },
+            GooseWaitTime::LogNormal {
+                mean: Duration::from_secs(0),
+                std_dev: Duration::from_secs(1),
+            },
+        ] {
+            assert!(wait_time.validate().is_ok());
+            assert_eq!(wait_time.sample(), Duration::from_secs(0));
+        }
+
+        // The average of many samples approaches the configured mean.
+        for wait_time in &[
+            GooseWaitTime::Exponential {
+                mean: Duration::from_millis(1000),
+            },
+            GooseWaitTime::Normal {
+                mean: Duration::from_millis(1000),
+                std_dev: Duration::from_millis(100),
+            },
+            GooseWaitTime::LogNormal {
+                mean: Duration::from_millis(1000),
+                std_dev: Duration::from_millis(500),
+            },
+        ] {
+            let total: u128 = (0..10_000).map(|_| wait_time.sample().as_millis()).sum();
+            let average = total / 10_000;
+            assert!(
+                average > 900 && average < 1100,
+                "{:?}: {}",
+                wait_time,
+                average
+            );
+        }
+    }
+
+    #[test]
+    fn goose_retry_policy() {
+        // Build a response with the given status code and optional Retry-After header.
+        fn response(
+            status_code: u16,
+            retry_after: Option<&str>,
+        ) -> Result<Response, reqwest::Error> {
+            let mut builder = http::Response::builder().status(status_code);
+            if let Some(retry_after) = retry_after {
+                builder = builder.header(header::RETRY_AFTER, retry_after);
+            }
+            Ok(Response::from(builder.body("").unwrap()))
+        }
+
+        let retry_policy = GooseRetryPolicy::new(3)
+            .set_backoff(Duration::from_millis(100), Duration::from_millis(300));
+
+        // Only retryable status codes are retried.
+        assert!(retry_policy.retry_delay(1, &response(500, None)).is_none());
+        assert!(retry_policy.retry_delay(1, &response(503, None)).is_some());
+
+        // No more than the maximum number of attempts are made.
+        assert!(retry_policy.retry_delay(2, &response(503, None)).is_some());
+        assert!(retry_policy.retry_delay(3, &response(503, None)).is_none());
+
+        // Backoff doubles with each retry, and is capped at the maximum backoff.
+        for _ in 0..100 {
+            assert!(retry_policy.backoff(1) <= Duration::from_millis(100));
+            assert!(retry_policy.backoff(2) <= Duration::from_millis(200));
+            assert!(retry_policy.backoff(10) <= Duration::from_millis(300));
+        }
+
+        // Retry-After headers are honored, up to the maximum backoff.
+        let retry_policy =
+            retry_policy.set_backoff(Duration::from_millis(100), Duration::from_secs(10));
+        assert_eq!(
+            retry_policy.retry_delay(1, &response(429, Some("2"))),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            retry_policy.retry_delay(1, &response(429, Some("60"))),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            retry_policy.retry_delay(1, &response(429, Some("Wed, 21 Oct 2015 07:28:00 GMT"))),
+            Some(Duration::from_secs(0))
+        );
+        let retry_policy = retry_policy.set_honor_retry_after(false);
+        assert!(
+            retry_policy
+                .retry_delay(1, &response(429, Some("2")))
+                .unwrap()
+                <= Duration::from_millis(100)
         );
     }
 
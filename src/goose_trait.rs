@@ -1,9 +1,13 @@
+use std::collections::HashMap;
 use std::hash::Hash;
-use std::time::Instant;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use http::header;
 use url::Url;
 use crate::{GooseConfiguration, GooseError, GooseLoggerTx, GooseMetric};
 use crate::metrics::GooseRequestMetric;
+use crate::goose::{GooseRetryPolicy, GooseUserCommand};
+use crate::throttle::GooseThrottle;
 use crate::prelude::TransactionResult;
 
 pub trait Goose: 'static + Hash + Sized + Send + Sync + Clone {
@@ -11,6 +15,12 @@ pub trait Goose: 'static + Hash + Sized + Send + Sync + Clone {
 
     fn add_slept(&mut self, duration: u64);
 
+    /// Add time spent paused by a Controller. By default it's counted as time slept, so
+    /// it isn't counted against the request cadence.
+    fn add_paused(&mut self, duration: u64) {
+        self.add_slept(duration)
+    }
+
     fn add_iterations(&mut self, num: usize);
 
     fn iterations(&self) -> usize;
@@ -37,7 +47,14 @@ pub trait Goose: 'static + Hash + Sized + Send + Sync + Clone {
 
     fn logger(&self) -> GooseLoggerTx;
 
-    fn set_throttle(&mut self, logger: Option<flume::Sender<bool>>);
+    /// Set the throttle shared by all users. Does nothing by default.
+    fn set_throttle(&mut self, _throttle: Option<Arc<GooseThrottle>>) {}
+
+    /// Set the throttle of the user's scenario. Does nothing by default.
+    fn set_scenario_throttle(&mut self, _throttle: Option<Arc<GooseThrottle>>) {}
+
+    /// Set the throttles of individual hosts. Does nothing by default.
+    fn set_host_throttles(&mut self, _throttles: HashMap<String, Arc<GooseThrottle>>) {}
 
     fn set_weighted_users_index(&mut self, total_users: usize);
 
@@ -47,6 +64,34 @@ pub trait Goose: 'static + Hash + Sized + Send + Sync + Clone {
 
     fn take_transaction_name(&mut self) -> Option<String>;
 
+    /// Set the index of the transaction about to run. Does nothing by default.
+    fn set_transaction_index(&mut self, _transaction_index: usize) {}
+
+    /// Set the timeout of the transaction about to run, if any. Does nothing by default.
+    fn set_transaction_timeout(&mut self, _timeout: Option<Duration>) {}
+
+    /// Set a copy of the channel the parent uses to control the user. Does nothing by
+    /// default.
+    fn set_command_receiver(
+        &mut self,
+        _command_receiver: Option<flume::Receiver<GooseUserCommand>>,
+    ) {
+    }
+
+    /// Take the request awaiting a response, if any. Returns `None` by default.
+    fn take_request_in_flight(&mut self) -> Option<GooseRequestMetric> {
+        None
+    }
+
+    /// Set the default retry policy for requests. Does nothing by default.
+    fn set_retry_policy(&mut self, _retry_policy: Option<GooseRetryPolicy>) {}
+
+    /// Discard any session, called when a failure policy restarts the user. Does nothing
+    /// by default.
+    fn reset_session(&mut self) -> Result<(), GooseError> {
+        Ok(())
+    }
+
     fn send_request_metric_to_parent(&self, request_metric: GooseRequestMetric) -> TransactionResult;
 
     fn set_failure(&self, tag: &str, request: &mut GooseRequestMetric, headers: Option<&header::HeaderMap>, body: Option<&str>) -> TransactionResult;
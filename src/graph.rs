@@ -11,7 +11,7 @@ use itertools::Itertools;
 use serde::Serialize;
 use serde_json::json;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Write;
 use std::marker::PhantomData;
 
@@ -29,6 +29,12 @@ pub(crate) struct GraphData {
     scenarios_per_second: TimeSeries<usize, usize>,
     /// Number of users at the end of each second of the test.
     users_per_second: TimeSeries<usize, usize>,
+    /// Number of users running each scenario at the end of each second of the test, only
+    /// recorded when scenarios have their own test plans.
+    scenario_users_per_second: HashMap<String, TimeSeries<usize, usize>>,
+    /// Response times of all requests made each second, only recorded while Controller
+    /// clients are subscribed to live metrics and discarded once pushed to them.
+    response_times_per_second: HashMap<usize, BTreeMap<usize, usize>>,
 }
 
 impl GraphData {
@@ -42,6 +48,8 @@ impl GraphData {
             transactions_per_second: TimeSeries::new(),
             scenarios_per_second: TimeSeries::new(),
             users_per_second: TimeSeries::new(),
+            scenario_users_per_second: HashMap::new(),
+            response_times_per_second: HashMap::new(),
         }
     }
 
@@ -125,14 +133,88 @@ impl GraphData {
         self.users_per_second.set_and_maintain_last(second, users);
     }
 
-    /// Generate active users graph.
+    /// Records number of users running a scenario for a current second.
+    pub(crate) fn record_scenario_users_per_second(
+        &mut self,
+        scenario: &str,
+        users: usize,
+        second: usize,
+    ) {
+        self.scenario_users_per_second
+            .entry(scenario.to_string())
+            .or_insert_with(TimeSeries::new)
+            .set_and_maintain_last(second, users);
+    }
+
+    /// Record the response time of a request for live metrics.
+    pub(crate) fn record_response_time_per_second(&mut self, second: usize, response_time: u64) {
+        *self
+            .response_times_per_second
+            .entry(second)
+            .or_default()
+            .entry(response_time as usize)
+            .or_insert(0) += 1;
+    }
+
+    /// Total requests made in a given second.
+    pub(crate) fn requests_in_second(&self, second: usize) -> u32 {
+        self.requests_per_second
+            .values()
+            .map(|data| data.get(second))
+            .sum()
+    }
+
+    /// Total errors in a given second.
+    pub(crate) fn errors_in_second(&self, second: usize) -> u32 {
+        self.errors_per_second
+            .values()
+            .map(|data| data.get(second))
+            .sum()
+    }
+
+    /// Average response time of all requests made in a given second.
+    pub(crate) fn average_response_time_in_second(&self, second: usize) -> f32 {
+        let mut total = MovingAverage::new();
+        for data in self.average_response_time_per_second.values() {
+            total.merge(&data.get(second));
+        }
+        total.average
+    }
+
+    /// Remove and return the response times recorded for a given second.
+    pub(crate) fn take_response_times(&mut self, second: usize) -> BTreeMap<usize, usize> {
+        self.response_times_per_second
+            .remove(&second)
+            .unwrap_or_default()
+    }
+
+    /// Discard the request, transaction and scenario data recorded so far, so graphs only
+    /// show what happened after metrics were reset. Users per second are kept.
+    pub(crate) fn reset_metrics(&mut self) {
+        self.requests_per_second = HashMap::new();
+        self.errors_per_second = HashMap::new();
+        self.average_response_time_per_second = HashMap::new();
+        self.transactions_per_second = TimeSeries::new();
+        self.scenarios_per_second = TimeSeries::new();
+    }
+
+    /// Generate active users graph, broken down by scenario if recorded.
     pub(crate) fn get_active_users_graph(&self, granular_data: bool) -> Graph<usize, usize> {
-        self.create_graph_from_single_data(
-            "graph-active-users",
-            "Active users #",
-            granular_data,
-            self.users_per_second.clone(),
-        )
+        if self.scenario_users_per_second.is_empty() {
+            self.create_graph_from_single_data(
+                "graph-active-users",
+                "Active users #",
+                granular_data,
+                self.users_per_second.clone(),
+            )
+        } else {
+            self.create_graph_from_data(
+                "graph-active-users",
+                "Active users #",
+                granular_data,
+                self.scenario_users_per_second.clone(),
+            )
+        }
     }
 
     /// Generate requests per second graph.
@@ -267,8 +349,24 @@ impl<'a, T: Clone + TimeSeriesValue<T, U>, U: Serialize + Copy + PartialEq + Par
         history: &[TestPlanHistory],
         test_started_time: DateTime<Utc>,
     ) -> String {
+        // Graph data is only collected when it's needed, so there may be nothing to graph
+        // in a report written by a Controller.
+        if self.data.is_empty() {
+            return String::new();
+        }
+
         let mut steps = String::new();
+        let mut previous_stage = None;
         for step in history.windows(2) {
+            // Name the first step of each test plan stage.
+            let name = match &step[0].stage {
+                Some(stage) if previous_stage != Some(stage) => format!(
+                    "\n                                name: {},",
+                    serde_json::to_string(stage).unwrap()
+                ),
+                _ => String::new(),
+            };
+            previous_stage = step[0].stage.as_ref();
             let started = Local
                 .timestamp(step[0].timestamp.timestamp(), 0)
                 .format("%Y-%m-%d %H:%M:%S")
@@ -293,7 +391,7 @@ impl<'a, T: Clone + TimeSeriesValue<T, U>, U: Serialize + Copy + PartialEq + Par
                         ],
                         [
                             {{
-                                xAxis: '{started}',
+                                xAxis: '{started}',{name}
                                 itemStyle: {{ color: 'rgba(44, 102, 79, 0.05)' }},
                             }},
                             {{
@@ -311,6 +409,7 @@ impl<'a, T: Clone + TimeSeriesValue<T, U>, U: Serialize + Copy + PartialEq + Par
                         ],"#,
                         started = started,
                         stopped = stopped,
+                        name = name,
                     );
                 }
                 // For decreasing show the new number of users from the current number of users.
@@ -328,7 +427,7 @@ impl<'a, T: Clone + TimeSeriesValue<T, U>, U: Serialize + Copy + PartialEq + Par
                         ],
                         [
                             {{
-                                xAxis: '{started}',
+                                xAxis: '{started}',{name}
                                 itemStyle: {{ color: 'rgba(179, 65, 65, 0.05)' }},
                             }},
                             {{
@@ -346,6 +445,25 @@ impl<'a, T: Clone + TimeSeriesValue<T, U>, U: Serialize + Copy + PartialEq + Par
                         ],"#,
                         started = started,
                         stopped = stopped,
+                        name = name,
+                    );
+                }
+                // For maintaining only show named stages.
+                TestPlanStepAction::Maintaining if !name.is_empty() => {
+                    let _ = write!(
+                        steps,
+                        r#"[
+                            {{
+                                xAxis: '{started}',{name}
+                                itemStyle: {{ color: 'rgba(128, 128, 128, 0.05)' }},
+                            }},
+                            {{
+                                xAxis: '{stopped}'
+                            }}
+                        ],"#,
+                        started = started,
+                        stopped = stopped,
+                        name = name,
                     );
                 }
                 _ => {}
@@ -1410,6 +1528,41 @@ mod test {
         assert_eq!(graph.users_per_second.total(), 187);
     }
 
+    #[test]
+    fn test_reset_metrics() {
+        let mut graph = GraphData::new();
+        graph.record_requests_per_second("GET /", 0);
+        graph.record_errors_per_second("GET /", 0);
+        graph.record_transactions_per_second(0);
+        graph.record_users_per_second(2, 0);
+        graph.reset_metrics();
+
+        // Seconds before the reset are empty, users are kept.
+        graph.record_requests_per_second("GET /", 2);
+        assert_eq!(graph.requests_in_second(0), 0);
+        assert_eq!(graph.requests_in_second(2), 1);
+        assert_eq!(graph.errors_in_second(0), 0);
+        assert_eq!(graph.transactions_per_second.total(), 0);
+        assert_eq!(graph.users_per_second.data, vec![2]);
+    }
+
+    #[test]
+    fn test_record_scenario_users_per_second() {
+        // The active users graph only has totals by default.
+        let mut graph = GraphData::new();
+        graph.record_users_per_second(3, 0);
+        assert_eq!(graph.get_active_users_graph(true).data.len(), 1);
+
+        // Once users are recorded by scenario, the graph is broken down by scenario.
+        graph.record_scenario_users_per_second("Admin", 1, 0);
+        graph.record_scenario_users_per_second("Anonymous", 2, 0);
+        graph.record_scenario_users_per_second("Anonymous", 5, 3);
+        let users_graph = graph.get_active_users_graph(true);
+        assert_eq!(users_graph.data.len(), 2);
+        assert_eq!(users_graph.data["Admin"].data, vec![1]);
+        assert_eq!(users_graph.data["Anonymous"].data, vec![2, 2, 2, 5]);
+    }
+
     #[test]
     fn test_moving_average() {
         let mut moving_average = MovingAverage::new();
@@ -1777,21 +1930,25 @@ mod test {
                 action: TestPlanStepAction::Increasing,
                 timestamp: Utc.ymd(2021, 11, 21).and_hms(21, 20, 32),
                 users: 123,
+                stage: None,
             },
             TestPlanHistory {
                 action: TestPlanStepAction::Decreasing,
                 timestamp: Utc.ymd(2021, 11, 21).and_hms(21, 20, 33),
                 users: 123,
+                stage: None,
             },
             TestPlanHistory {
                 action: TestPlanStepAction::Canceling,
                 timestamp: Utc.ymd(2021, 11, 21).and_hms(21, 20, 34),
                 users: 123,
+                stage: None,
             },
             TestPlanHistory {
                 action: TestPlanStepAction::Finished,
                 timestamp: Utc.ymd(2021, 11, 21).and_hms(21, 20, 35),
                 users: 123,
+                stage: None,
             },
         ];
 
@@ -2090,4 +2247,14 @@ mod test {
             expected
         );
     }
+
+    #[test]
+    fn test_empty_graph_markup() {
+        let graph: HashMap<String, TimeSeries<usize, usize>> = HashMap::new();
+        assert_eq!(
+            Graph::new("graph-rps", "Requests #", true, graph)
+                .get_markup(&Vec::new(), Utc.ymd(2021, 11, 21).and_hms(21, 20, 32)),
+            ""
+        );
+    }
 }
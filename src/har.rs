@@ -0,0 +1,520 @@
+//! Import browser sessions recorded as HTTP Archive (HAR) files.
+//!
+//! Most browsers can export a recorded session as a HAR file. The [`HarImporter`]
+//! turns such a recording into a [`Scenario`] where each recorded page becomes a
+//! [`Transaction`], run in the order it was recorded. Each request keeps its method,
+//! path, headers and body. Requests to third-party hosts (analytics, CDNs, etc) are
+//! filtered out unless explicitly allowed.
+//!
+//! The same recording can instead be turned into Rust source code, providing a
+//! starting point for a hand-maintained load test.
+//!
+//! ## Example
+//! ```rust
+//! use goose::har::HarImporter;
+//! use goose::prelude::*;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), GooseError> {
+//!     let har = r#"{"log": {"pages": [{"id": "page_1", "title": "Front page"}], "entries": [
+//!         {"pageref": "page_1", "startedDateTime": "2022-08-01T10:00:00.000Z", "time": 50,
+//!          "request": {"method": "GET", "url": "http://example.com/", "headers": []}}
+//!     ]}}"#;
+//!
+//!     let scenario = HarImporter::from_json(har)?
+//!         .keep_think_time(true)
+//!         .scenario("Recorded session")?;
+//!     assert_eq!(scenario.transactions.len(), 1);
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use chrono::{DateTime, FixedOffset};
+use http::Method;
+use serde::Deserialize;
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+
+use crate::goose::{
+    goose_method_from_method, GooseMethod, GooseRequest, GooseUser, Scenario, Transaction,
+    TransactionFunction,
+};
+use crate::GooseError;
+
+/// Request headers that are not replayed, as they are either managed by the client or
+/// would carry stale state from the recorded session.
+const SKIPPED_HEADERS: [&str; 6] = [
+    "host",
+    "content-length",
+    "connection",
+    "cookie",
+    "accept-encoding",
+    "transfer-encoding",
+];
+
+/// The top level of a HAR file.
+#[derive(Debug, Deserialize)]
+struct Har {
+    log: HarLog,
+}
+
+/// The recorded log, containing pages and the requests made while loading them.
+#[derive(Debug, Deserialize)]
+struct HarLog {
+    #[serde(default)]
+    pages: Vec<HarPage>,
+    #[serde(default)]
+    entries: Vec<HarEntry>,
+}
+
+/// A page loaded during the recorded session.
+#[derive(Debug, Deserialize)]
+struct HarPage {
+    id: String,
+    #[serde(default)]
+    title: String,
+}
+
+/// A single request recorded during the session.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HarEntry {
+    pageref: Option<String>,
+    started_date_time: Option<String>,
+    #[serde(default)]
+    time: f64,
+    request: HarRequest,
+}
+
+/// The request details of a recorded entry.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HarRequest {
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: Vec<HarHeader>,
+    post_data: Option<HarPostData>,
+}
+
+/// A recorded request header.
+#[derive(Debug, Deserialize)]
+struct HarHeader {
+    name: String,
+    value: String,
+}
+
+/// The body of a recorded request.
+#[derive(Debug, Deserialize)]
+struct HarPostData {
+    text: Option<String>,
+}
+
+/// A request extracted from a HAR entry, ready to be replayed by a [`GooseUser`].
+#[derive(Clone, Debug)]
+pub struct HarRequestDetails {
+    /// The method of the recorded request.
+    pub method: GooseMethod,
+    /// A relative path (including any query string) when the request was made to the
+    /// primary host, otherwise the full recorded URL.
+    pub path: String,
+    /// The recorded request headers, minus headers managed by the client.
+    pub headers: Vec<(String, String)>,
+    /// The recorded request body, if any.
+    pub body: Option<String>,
+}
+
+/// A group of requests made while loading a single page.
+#[derive(Clone, Debug)]
+pub struct HarPageDetails {
+    /// The title of the page, used to name the [`Transaction`].
+    pub title: String,
+    /// All requests made while loading this page, in the order they were made.
+    pub requests: Vec<HarRequestDetails>,
+    // When the first request for this page started.
+    started: Option<DateTime<FixedOffset>>,
+    // When the last request for this page completed.
+    ended: Option<DateTime<FixedOffset>>,
+}
+
+/// Builds a [`Scenario`] or Rust source code from a HAR file.
+///
+/// # Example
+/// ```rust,no_run
+/// use goose::har::HarImporter;
+/// use goose::prelude::*;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), GooseError> {
+///     let scenario = HarImporter::from_file("session.har")?
+///         // Also replay requests made to this host.
+///         .allow_host("api.example.com")
+///         // Sleep between pages as long as the recorded user did.
+///         .keep_think_time(true)
+///         .scenario("Recorded session")?;
+///
+///     GooseAttack::initialize()?
+///         .register_scenario(scenario)
+///         .execute()
+///         .await?;
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct HarImporter {
+    har: Har,
+    host: Option<String>,
+    allowed_hosts: Vec<String>,
+    keep_think_time: bool,
+}
+
+impl HarImporter {
+    /// Load and parse a HAR file from disk.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, GooseError> {
+        let json = std::fs::read_to_string(path)?;
+        HarImporter::from_json(&json)
+    }
+
+    /// Parse HAR formatted json.
+    pub fn from_json(json: &str) -> Result<Self, GooseError> {
+        let har: Har = serde_json::from_str(json).map_err(|e| GooseError::InvalidHar {
+            detail: format!("failed to parse HAR: {}", e),
+        })?;
+
+        Ok(HarImporter {
+            har,
+            host: None,
+            allowed_hosts: Vec::new(),
+            keep_think_time: false,
+        })
+    }
+
+    /// Set the primary host of the recorded session, for example `https://example.com`.
+    ///
+    /// Requests to the primary host are replayed with relative paths, so the host can be
+    /// changed at run time with `--host`. If not set, the host of the first recorded
+    /// request is used.
+    pub fn set_host(mut self, host: &str) -> Self {
+        self.host = Some(host.to_string());
+        self
+    }
+
+    /// Also replay requests made to this host name, for example `api.example.com`.
+    ///
+    /// Requests made to hosts other than the primary host are otherwise filtered out.
+    pub fn allow_host(mut self, host: &str) -> Self {
+        self.allowed_hosts.push(host.to_string());
+        self
+    }
+
+    /// Configure the [`Scenario`] to pause between pages for as long as the recorded
+    /// user did, using [`Scenario::set_wait_time`] with the shortest and longest pauses
+    /// found in the recording.
+    pub fn keep_think_time(mut self, keep_think_time: bool) -> Self {
+        self.keep_think_time = keep_think_time;
+        self
+    }
+
+    /// Determine the primary host of the recorded session.
+    fn primary_host(&self) -> Result<Url, GooseError> {
+        let host = match &self.host {
+            Some(h) => h.to_string(),
+            None => match self.har.log.entries.first() {
+                Some(entry) => entry.request.url.to_string(),
+                None => {
+                    return Err(GooseError::InvalidHar {
+                        detail: "HAR does not contain any entries.".to_string(),
+                    })
+                }
+            },
+        };
+        let url = Url::parse(&host).map_err(|parse_error| GooseError::InvalidHost {
+            host: host.to_string(),
+            detail: "Invalid primary host for HAR import.".to_string(),
+            parse_error,
+        })?;
+        // Strip the path, keeping only the scheme, host and port.
+        Ok(Url::parse(&url.origin().ascii_serialization()).unwrap_or(url))
+    }
+
+    /// Group the recorded requests by page, in the order pages were loaded. Requests to
+    /// hosts that are neither the primary host nor explicitly allowed are dropped, as are
+    /// requests using methods Goose doesn't support.
+    pub fn pages(&self) -> Result<Vec<HarPageDetails>, GooseError> {
+        let primary = self.primary_host()?;
+        let mut pages: Vec<(Option<String>, HarPageDetails)> = Vec::new();
+
+        for entry in &self.har.log.entries {
+            let url = match Url::parse(&entry.request.url) {
+                Ok(u) => u,
+                Err(e) => {
+                    info!("skipping HAR entry {}: {}", entry.request.url, e);
+                    continue;
+                }
+            };
+            let is_primary = url.origin() == primary.origin();
+            let host = url.host_str().unwrap_or_default();
+            if !is_primary && !self.allowed_hosts.iter().any(|h| h == host) {
+                debug!("skipping third-party HAR entry: {}", url);
+                continue;
+            }
+            let method = match Method::from_bytes(entry.request.method.as_bytes())
+                .ok()
+                .and_then(|m| goose_method_from_method(m).ok())
+            {
+                Some(m) => m,
+                None => {
+                    info!(
+                        "skipping HAR entry with unsupported method: {} {}",
+                        entry.request.method, url
+                    );
+                    continue;
+                }
+            };
+            let path = if is_primary {
+                match url.query() {
+                    Some(query) => format!("{}?{}", url.path(), query),
+                    None => url.path().to_string(),
+                }
+            } else {
+                url.to_string()
+            };
+            let headers = entry
+                .request
+                .headers
+                .iter()
+                .filter(|h| {
+                    !h.name.starts_with(':')
+                        && !SKIPPED_HEADERS.contains(&h.name.to_lowercase().as_str())
+                })
+                .map(|h| (h.name.to_string(), h.value.to_string()))
+                .collect();
+            let body = entry
+                .request
+                .post_data
+                .as_ref()
+                .and_then(|p| p.text.clone())
+                .filter(|t| !t.is_empty());
+
+            let started = entry
+                .started_date_time
+                .as_ref()
+                .and_then(|s| DateTime::parse_from_rfc3339(s).ok());
+            let ended =
+                started.map(|s| s + chrono::Duration::milliseconds(entry.time.max(0.0) as i64));
+
+            let index = match pages.iter().position(|(id, _)| id == &entry.pageref) {
+                Some(index) => index,
+                None => {
+                    let title = self
+                        .har
+                        .log
+                        .pages
+                        .iter()
+                        .find(|p| Some(&p.id) == entry.pageref.as_ref())
+                        .map(|p| p.title.to_string())
+                        .filter(|t| !t.is_empty())
+                        .or_else(|| entry.pageref.clone())
+                        .unwrap_or_else(|| format!("page {}", pages.len() + 1));
+                    pages.push((
+                        entry.pageref.clone(),
+                        HarPageDetails {
+                            title,
+                            requests: Vec::new(),
+                            started,
+                            ended,
+                        },
+                    ));
+                    pages.len() - 1
+                }
+            };
+            let page = &mut pages[index].1;
+            page.requests.push(HarRequestDetails {
+                method,
+                path,
+                headers,
+                body,
+            });
+            if let Some(started) = started {
+                if page.started.is_none() || Some(started) < page.started {
+                    page.started = Some(started);
+                }
+            }
+            if let Some(ended) = ended {
+                if Some(ended) > page.ended {
+                    page.ended = Some(ended);
+                }
+            }
+        }
+
+        Ok(pages.into_iter().map(|(_, page)| page).collect())
+    }
+
+    /// Calculate the shortest and longest pause between the end of one page and the
+    /// start of the next, if the recording has timing information for at least two pages.
+    fn think_time(pages: &[HarPageDetails]) -> Option<(Duration, Duration)> {
+        let gaps: Vec<Duration> = pages
+            .windows(2)
+            .filter_map(|w| match (w[0].ended, w[1].started) {
+                (Some(ended), Some(started)) => {
+                    Some((started - ended).to_std().unwrap_or_default())
+                }
+                _ => None,
+            })
+            .collect();
+        let min = gaps.iter().min()?;
+        let max = gaps.iter().max()?;
+        Some((*min, *max))
+    }
+
+    /// Build a [`Scenario`] from the recording, with one [`Transaction`] per page. The
+    /// transactions are sequenced so each [`GooseUser`] loads pages in recorded order.
+    pub fn scenario(&self, name: &str) -> Result<Scenario<GooseUser>, GooseError> {
+        let primary = self.primary_host()?;
+        let pages = self.pages()?;
+        if pages.is_empty() {
+            return Err(GooseError::InvalidHar {
+                detail: "No requests left to replay after filtering HAR entries.".to_string(),
+            });
+        }
+
+        let mut scenario = Scenario::new(name).set_host(primary.as_str().trim_end_matches('/'));
+        if self.keep_think_time {
+            if let Some((min_wait, max_wait)) = HarImporter::think_time(&pages) {
+                scenario = scenario.set_wait_time(min_wait, max_wait)?;
+            }
+        }
+
+        for (index, page) in pages.into_iter().enumerate() {
+            let requests = Arc::new(page.requests);
+            let closure: TransactionFunction<GooseUser> = Arc::new(move |user| {
+                let requests = requests.clone();
+                Box::pin(async move {
+                    for request in requests.iter() {
+                        let mut request_builder =
+                            user.get_request_builder(&request.method, &request.path)?;
+                        for (name, value) in &request.headers {
+                            request_builder = request_builder.header(name, value);
+                        }
+                        if let Some(body) = &request.body {
+                            request_builder = request_builder.body(body.to_string());
+                        }
+                        let goose_request = GooseRequest::builder()
+                            .method(request.method.clone())
+                            .path(request.path.as_str())
+                            .set_request_builder(request_builder)
+                            .build();
+                        let _goose = user.request(goose_request).await?;
+                    }
+
+                    Ok(())
+                })
+            });
+            scenario = scenario.register_transaction(
+                Transaction::new(closure)
+                    .set_name(&page.title)
+                    .set_sequence(index + 1),
+            );
+        }
+
+        Ok(scenario)
+    }
+
+    /// Generate the source code of a complete Goose load test replaying the recording,
+    /// with one transaction function per page.
+    pub fn to_rust(&self, name: &str) -> Result<String, GooseError> {
+        let primary = self.primary_host()?;
+        let pages = self.pages()?;
+        if pages.is_empty() {
+            return Err(GooseError::InvalidHar {
+                detail: "No requests left to replay after filtering HAR entries.".to_string(),
+            });
+        }
+
+        let mut source = String::new();
+        // Writing to a String can't fail, so results are ignored.
+        let _ = writeln!(source, "use goose::prelude::*;");
+        if self.keep_think_time && HarImporter::think_time(&pages).is_some() {
+            let _ = writeln!(source, "use std::time::Duration;");
+        }
+        let _ = writeln!(source);
+        let _ = writeln!(source, "#[tokio::main]");
+        let _ = writeln!(source, "async fn main() -> Result<(), GooseError> {{");
+        let _ = writeln!(source, "    GooseAttack::initialize()?");
+        let _ = writeln!(source, "        .register_scenario(");
+        let _ = writeln!(source, "            scenario!({:?})", name);
+        let _ = writeln!(
+            source,
+            "                .set_host({:?})",
+            primary.as_str().trim_end_matches('/')
+        );
+        if self.keep_think_time {
+            if let Some((min_wait, max_wait)) = HarImporter::think_time(&pages) {
+                let _ = writeln!(
+                    source,
+                    "                .set_wait_time(Duration::from_millis({}), Duration::from_millis({}))?",
+                    min_wait.as_millis(),
+                    max_wait.as_millis()
+                );
+            }
+        }
+        for (index, page) in pages.iter().enumerate() {
+            let _ = writeln!(
+                source,
+                "                .register_transaction(transaction!(page_{}).set_name({:?}).set_sequence({}))",
+                index + 1,
+                page.title,
+                index + 1
+            );
+        }
+        let _ = writeln!(source, "        )");
+        let _ = writeln!(source, "        .execute()");
+        let _ = writeln!(source, "        .await?;");
+        let _ = writeln!(source);
+        let _ = writeln!(source, "    Ok(())");
+        let _ = writeln!(source, "}}");
+
+        for (index, page) in pages.iter().enumerate() {
+            let _ = writeln!(source);
+            let _ = writeln!(source, "/// {}", page.title.replace('\n', " "));
+            let _ = writeln!(
+                source,
+                "async fn page_{}(user: &mut GooseUser) -> TransactionResult {{",
+                index + 1
+            );
+            for request in &page.requests {
+                let _ = write!(
+                    source,
+                    "    let request_builder = user.get_request_builder(&GooseMethod::{:?}, {:?})?",
+                    request.method, request.path
+                );
+                for (name, value) in &request.headers {
+                    let _ = write!(source, "\n        .header({:?}, {:?})", name, value);
+                }
+                if let Some(body) = &request.body {
+                    let _ = write!(source, "\n        .body({:?})", body);
+                }
+                let _ = writeln!(source, ";");
+                let _ = writeln!(source, "    let goose_request = GooseRequest::builder()");
+                let _ = writeln!(source, "        .method(GooseMethod::{:?})", request.method);
+                let _ = writeln!(source, "        .path({:?})", request.path);
+                let _ = writeln!(source, "        .set_request_builder(request_builder)");
+                let _ = writeln!(source, "        .build();");
+                let _ = writeln!(
+                    source,
+                    "    let _goose = user.request(goose_request).await?;"
+                );
+            }
+            let _ = writeln!(source);
+            let _ = writeln!(source, "    Ok(())");
+            let _ = writeln!(source, "}}");
+        }
+
+        Ok(source)
+    }
+}
@@ -40,18 +40,24 @@
 #[macro_use]
 extern crate log;
 
+pub mod capacity;
 pub mod config;
 pub mod controller;
+mod controller_script;
 pub mod goose;
 mod graph;
+pub mod har;
 pub mod logger;
 #[cfg(feature = "gaggle")]
 mod manager;
 pub mod metrics;
 pub mod prelude;
 mod report;
+pub mod scenario_file;
 mod test_plan;
-mod throttle;
+pub mod throttle;
+#[cfg(feature = "tui")]
+mod tui;
 mod user;
 pub mod util;
 #[cfg(feature = "gaggle")]
@@ -65,7 +71,8 @@ use lazy_static::lazy_static;
 use nng::Socket;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
-use std::collections::{hash_map::DefaultHasher, BTreeMap, HashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::{hash_map::DefaultHasher, BTreeMap, HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 use std::sync::{
     atomic::{AtomicUsize, Ordering},
@@ -74,15 +81,20 @@ use std::sync::{
 use std::time::{self, Duration};
 use std::{fmt, io};
 use tokio::fs::File;
+use url::Url;
 
+use crate::capacity::CapacitySearchState;
 use crate::config::{GooseConfiguration, GooseDefaults};
-use crate::controller::{ControllerProtocol, ControllerRequest};
+use crate::controller::{
+    ControllerEvent, ControllerProtocol, ControllerRequest, ControllerSubscriptions, ControllerTls,
+};
 use crate::goose::{GaggleUser, GooseUserCommand, Scenario, Transaction};
 use crate::goose_trait::Goose;
 use crate::graph::GraphData;
 use crate::logger::{GooseLoggerJoinHandle, GooseLoggerTx};
 use crate::metrics::{GooseMetric, GooseMetrics};
 use crate::test_plan::{TestPlan, TestPlanHistory, TestPlanStepAction};
+use crate::throttle::GooseThrottle;
 #[cfg(feature = "gaggle")]
 use crate::worker::{register_shutdown_pipe_handler, GaggleMetrics};
 
@@ -92,6 +104,10 @@ const DEFAULT_TELNET_PORT: &str = "5116";
 /// Constant defining Goose's default WebSocket Controller port.
 const DEFAULT_WEBSOCKET_PORT: &str = "5117";
 
+/// Constant defining Goose's default HTTP Controller port.
+#[cfg(feature = "http-controller")]
+const DEFAULT_HTTP_PORT: &str = "5118";
+
 lazy_static! {
     // WORKER_ID is used to identify different works when running a gaggle.
     static ref WORKER_ID: AtomicUsize = AtomicUsize::new(0);
@@ -111,6 +127,12 @@ type UnsequencedTransactions<G> = Vec<Transaction<G>>;
 /// Internal representation of sequenced transactions.
 type SequencedTransactions<G> = BTreeMap<usize, Vec<Transaction<G>>>;
 
+/// Internal representation of both ends of the channel Controllers send requests over.
+type ControllerChannel = Option<(
+    flume::Sender<ControllerRequest>,
+    flume::Receiver<ControllerRequest>,
+)>;
+
 /// Returns the unique identifier of the running Worker when running in Gaggle mode.
 ///
 /// The first Worker to connect to the Manager is assigned an ID of 1. For each
@@ -175,6 +197,21 @@ pub enum GooseError {
         /// An optional explanation of the error.
         detail: String,
     },
+    /// Invalid transition graph specified.
+    InvalidTransitionGraph {
+        /// An optional explanation of the error.
+        detail: String,
+    },
+    /// Failed to parse or import a HAR file.
+    InvalidHar {
+        /// An optional explanation of the error.
+        detail: String,
+    },
+    /// Failed to parse or build scenarios from a scenario file.
+    InvalidScenarioFile {
+        /// An optional explanation of the error.
+        detail: String,
+    },
     /// Invalid controller command.
     InvalidControllerCommand {
         /// An optional explanation of the error.
@@ -199,6 +236,9 @@ impl GooseError {
             GooseError::InvalidOption { .. } => "invalid option or value specified",
             GooseError::InvalidWaitTime { .. } => "invalid wait_time specified",
             GooseError::InvalidWeight { .. } => "invalid weight specified",
+            GooseError::InvalidTransitionGraph { .. } => "invalid transition graph specified",
+            GooseError::InvalidHar { .. } => "invalid har file",
+            GooseError::InvalidScenarioFile { .. } => "invalid scenario file",
             GooseError::InvalidControllerCommand { .. } => "invalid controller command",
             GooseError::NoScenarios { .. } => "no scenarios defined",
         }
@@ -275,7 +315,7 @@ pub enum AttackMode {
     Worker,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 /// A [`GooseAttack`](./struct.GooseAttack.html) load test moves through each of the following
 /// phases during a complete load test.
 pub enum AttackPhase {
@@ -285,6 +325,9 @@ pub enum AttackPhase {
     Increase,
     /// [`GooseUser`](./goose/struct.GooseUser.html)s have been launched and are generating load.
     Maintain,
+    /// [`GooseUser`](./goose/struct.GooseUser.html)s are held by a Controller, and the load
+    /// test clock is stopped until it's resumed.
+    Pause,
     /// [`GooseUser`](./goose/struct.GooseUser.html)s are stopping.
     Decrease,
     /// Exiting the load test.
@@ -342,13 +385,27 @@ struct GooseAttackRunState {
     /// Optional unbounded sender from all [`GooseUser`](./goose/struct.GooseUser.html)s
     /// to logger thread, if enabled.
     all_threads_logger_tx: GooseLoggerTx,
-    /// Optional receiver for all [`GooseUser`](./goose/struct.GooseUser.html)s from
-    /// throttle thread, if enabled.
-    throttle_threads_tx: Option<flume::Sender<bool>>,
-    /// Optional sender for throttle thread, if enabled.
-    parent_to_throttle_tx: Option<flume::Sender<bool>>,
+    /// Optional token bucket shared by all [`GooseUser`](./goose/struct.GooseUser.html)s
+    /// to throttle requests, if enabled.
+    throttle: Option<Arc<GooseThrottle>>,
+    /// Optional token buckets shared by all [`GooseUser`](./goose/struct.GooseUser.html)s
+    /// running each [`Scenario`](./goose/struct.Scenario.html), indexed by scenarios_index.
+    scenario_throttles: Vec<Option<Arc<GooseThrottle>>>,
+    /// Token buckets shared by all [`GooseUser`](./goose/struct.GooseUser.html)s to
+    /// throttle requests to specific hosts, keyed by the origin of the host.
+    host_throttles: HashMap<String, Arc<GooseThrottle>>,
     /// Optional channel allowing controller thread to make requests, if not disabled.
     controller_channel_rx: Option<flume::Receiver<ControllerRequest>>,
+    /// Optional sender allowing controller scripts to make requests, if the Controller
+    /// channel is not disabled.
+    controller_channel_tx: Option<flume::Sender<ControllerRequest>>,
+    /// Controller scripts running in the background.
+    controller_scripts: Vec<tokio::task::JoinHandle<()>>,
+    /// WebSocket Controller clients subscribed to live metrics.
+    controller_subscriptions: ControllerSubscriptions,
+    /// Optional live dashboard drawn in the terminal, if enabled.
+    #[cfg(feature = "tui")]
+    tui: Option<tui::GooseTui>,
     /// A flag tracking whether or not the header has been written when the metrics
     /// log is enabled.
     metrics_header_displayed: bool,
@@ -358,8 +415,23 @@ struct GooseAttackRunState {
     /// can be stopped later.
     users: Vec<tokio::task::JoinHandle<()>>,
     /// All unbounded senders to allow communication with
-    /// [`GooseUser`](./goose/struct.GooseUser.html) threads.
-    user_channels: Vec<flume::Sender<GooseUserCommand>>,
+    /// [`GooseUser`](./goose/struct.GooseUser.html) threads, paired with the
+    /// scenarios_index of each user.
+    user_channels: Vec<(usize, flume::Sender<GooseUserCommand>)>,
+    /// Unbounded senders to [`GooseUser`](./goose/struct.GooseUser.html) threads launched
+    /// by each [`Scenario`](./goose/struct.Scenario.html)'s own test plan, indexed by
+    /// scenarios_index.
+    scenario_plan_channels: Vec<Vec<flume::Sender<GooseUserCommand>>>,
+    /// When [`Scenario`](./goose/struct.Scenario.html)s with their own test plan started,
+    /// which isn't reset with metrics.
+    scenario_plans_started: std::time::Instant,
+    /// How many [`GooseUser`](./goose/struct.GooseUser.html)s are running each
+    /// [`Scenario`](./goose/struct.Scenario.html), indexed by scenarios_index.
+    scenario_users: Vec<usize>,
+    /// The progress of a capacity search, if enabled.
+    capacity_search: Option<CapacitySearchState>,
+    /// When a Controller paused the load test, and the phase it was paused in.
+    paused: Option<(std::time::Instant, AttackPhase)>,
     /// Timer tracking when to display running metrics, if enabled.
     running_metrics_timer: std::time::Instant,
     /// Boolean flag indicating if running metrics should be displayed.
@@ -387,6 +459,9 @@ pub struct GooseAttack<G: Goose> {
     scenarios: Vec<Scenario<G>>,
     /// A set of all registered scenario names.
     scenario_machine_names: HashSet<String>,
+    /// Scenarios enabled or disabled by a Controller, overriding `--scenarios`, keyed by
+    /// scenarios_index.
+    scenario_overrides: HashMap<usize, bool>,
     /// A weighted vector containing a GooseUser object for each GooseUser that will run during this load test.
     weighted_users: Vec<G>,
     /// A weighted vector containing a lightweight GaggleUser object that is sent to all Workers if running in Gaggle mode.
@@ -404,6 +479,8 @@ pub struct GooseAttack<G: Goose> {
     scheduler: GooseScheduler,
     /// When the load test started.
     started: Option<time::Instant>,
+    /// When a Controller last reset metrics during the load test.
+    metrics_reset: Option<time::Instant>,
     /// Internal Goose test plan representation.
     test_plan: TestPlan,
     /// When the current test plan step started.
@@ -412,6 +489,8 @@ pub struct GooseAttack<G: Goose> {
     metrics: GooseMetrics,
     /// All data for report graphs.
     graph_data: GraphData,
+    /// Maximum requests per second to specific hosts, keyed by the origin of the host.
+    host_throttles: BTreeMap<String, usize>,
 }
 
 /// Goose's internal global state.
@@ -433,6 +512,7 @@ impl<G> GooseAttack<G>
             test_stop_transaction: None,
             scenarios: Vec::new(),
             scenario_machine_names: HashSet::new(),
+            scenario_overrides: HashMap::new(),
             weighted_users: Vec::new(),
             weighted_gaggle_users: Vec::new(),
             defaults: GooseDefaults::default(),
@@ -441,10 +521,12 @@ impl<G> GooseAttack<G>
             attack_phase: AttackPhase::Idle,
             scheduler: GooseScheduler::RoundRobin,
             started: None,
+            metrics_reset: None,
             test_plan: TestPlan::new(),
             step_started: None,
             metrics: GooseMetrics::default(),
             graph_data: GraphData::new(),
+            host_throttles: BTreeMap::new(),
         })
     }
 
@@ -470,6 +552,7 @@ impl<G> GooseAttack<G>
             test_stop_transaction: None,
             scenarios: Vec::new(),
             scenario_machine_names: HashSet::new(),
+            scenario_overrides: HashMap::new(),
             weighted_users: Vec::new(),
             weighted_gaggle_users: Vec::new(),
             defaults: GooseDefaults::default(),
@@ -478,10 +561,12 @@ impl<G> GooseAttack<G>
             attack_phase: AttackPhase::Idle,
             scheduler: GooseScheduler::RoundRobin,
             started: None,
+            metrics_reset: None,
             test_plan: TestPlan::new(),
             step_started: None,
             metrics: GooseMetrics::default(),
             graph_data: GraphData::new(),
+            host_throttles: BTreeMap::new(),
         })
     }
 
@@ -603,6 +688,64 @@ impl<G> GooseAttack<G>
         self
     }
 
+    /// Limit the maximum number of requests per second made to a specific host by all
+    /// [`GooseUser`](./goose/struct.GooseUser.html)s. This is enforced in addition to
+    /// `--throttle-requests`, which limits requests made to all hosts together, and can
+    /// be used to protect a fragile dependency when [`Scenario`](./goose/struct.Scenario.html)s
+    /// use [`set_host`](./goose/struct.Scenario.html#method.set_host).
+    ///
+    /// # Example
+    /// ```rust
+    /// use goose::prelude::*;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), GooseError> {
+    ///     GooseAttack::initialize()?
+    ///         .register_scenario(scenario!("ExampleScenario")
+    ///             .register_transaction(transaction!(example_transaction))
+    ///         )
+    ///         // Make no more than 10 requests per second to the search service.
+    ///         .set_host_throttle("https://search.example.com", 10)?;
+    ///
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn example_transaction(user: &mut GooseUser) -> TransactionResult {
+    ///     let _goose = user.get("/foo").await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_host_throttle(
+        mut self,
+        host: &str,
+        throttle_requests: usize,
+    ) -> Result<Self, GooseError> {
+        if throttle_requests == 0 || throttle_requests > 1_000_000 {
+            return Err(GooseError::InvalidOption {
+                option: "`GooseAttack::set_host_throttle`".to_string(),
+                value: throttle_requests.to_string(),
+                detail: "The throttle must be set to between 1 and 1,000,000 requests per second."
+                    .to_string(),
+            });
+        }
+        // Requests are matched to the throttle by the origin of their URL.
+        let origin = match Url::parse(host) {
+            Ok(url) if util::is_valid_host(host).is_ok() => url.origin().ascii_serialization(),
+            _ => {
+                return Err(GooseError::InvalidOption {
+                    option: "`GooseAttack::set_host_throttle`".to_string(),
+                    value: host.to_string(),
+                    detail: "The host must be a valid URL, for example https://example.com."
+                        .to_string(),
+                });
+            }
+        };
+        self.host_throttles.insert(origin, throttle_requests);
+
+        Ok(self)
+    }
+
     /// Optionally define a transaction to run before users are started and all transactions
     /// start running. This is would generally be used to set up anything required
     /// for the load test.
@@ -672,8 +815,11 @@ impl<G> GooseAttack<G>
 
     /// Internal helper to determine if the scenario is currently active.
     fn scenario_is_active(&self, scenario: &Scenario<G>) -> bool {
+        // Scenarios enabled or disabled by a Controller ignore `--scenarios`.
+        if let Some(active) = self.scenario_overrides.get(&scenario.scenarios_index) {
+            *active
         // All scenarios are enabled by default.
-        if self.configuration.scenarios.active.is_empty() {
+        } else if self.configuration.scenarios.active.is_empty() {
             true
             // Returns true or false depending on if the machine name is included in the
             // configured `--scenarios`.
@@ -697,7 +843,8 @@ impl<G> GooseAttack<G>
         let mut u: usize = 0;
         let mut v: usize;
         for scenario in &self.scenarios {
-            if self.scenario_is_active(scenario) {
+            // Scenarios with their own test plan aren't allocated by weight.
+            if self.scenario_is_active(scenario) && scenario.test_plan.is_none() {
                 if u == 0 {
                     u = scenario.weight;
                 } else {
@@ -715,7 +862,7 @@ impl<G> GooseAttack<G>
         let mut available_scenarios = Vec::with_capacity(self.scenarios.len());
         let mut total_scenarios = 0;
         for (index, scenario) in self.scenarios.iter().enumerate() {
-            if self.scenario_is_active(scenario) {
+            if self.scenario_is_active(scenario) && scenario.test_plan.is_none() {
                 // divide by greatest common divisor so vector is as short as possible
                 let weight = scenario.weight / u;
                 trace!(
@@ -796,6 +943,11 @@ impl<G> GooseAttack<G>
 
         let weighted_scenarios = self.allocate_scenarios();
 
+        // If all scenarios have their own test plans, the test plan launches no users.
+        if weighted_scenarios.is_empty() {
+            return Ok(Vec::new());
+        }
+
         // Allocate a state for each user that will be launched.
         info!(
             "initializing {} user states...",
@@ -879,6 +1031,11 @@ impl<G> GooseAttack<G>
         // Optional debug output.
         info!("entering GooseAttack phase: {:?}", &phase);
 
+        // Notify any Controller clients subscribed to live metrics.
+        goose_attack_run_state
+            .controller_subscriptions
+            .send(ControllerEvent::Phase(phase.clone()));
+
         // Update the current phase.
         self.attack_phase = phase;
     }
@@ -1001,6 +1158,17 @@ impl<G> GooseAttack<G>
         // Build TestPlan.
         self.test_plan = TestPlan::build(&self.configuration);
 
+        // Preview a manually defined TestPlan before starting the load test, unless output
+        // is quieted or the dashboard is taking over the terminal.
+        if self.configuration.test_plan.is_some()
+            && !self.configuration.worker
+            && self.configuration.quiet == 0
+            && !self.configuration.no_print_metrics
+            && !self.configuration.tui
+        {
+            println!("{}", self.test_plan.preview());
+        }
+
         // With a validated GooseConfiguration, enter a run mode.
         self.attack_mode = if self.configuration.manager {
             AttackMode::Manager
@@ -1109,6 +1277,31 @@ impl<G> GooseAttack<G>
         Ok(())
     }
 
+    // Determine the total number of users required by scenarios with their own test plan.
+    fn scenario_plan_users(&self) -> usize {
+        self.scenarios
+            .iter()
+            .filter(|scenario| self.scenario_is_active(scenario))
+            .filter_map(|scenario| scenario.test_plan.as_ref())
+            .map(|test_plan| test_plan.total_users())
+            .sum()
+    }
+
+    // Determine how many users the current test plan step is ramping to, or how many users
+    // an idle load test will start.
+    fn target_users(&self) -> usize {
+        if self.attack_phase == AttackPhase::Idle {
+            self.configuration.users.unwrap_or(0)
+        } else {
+            // Once the last step completes, its users are maintained until shut down.
+            self.test_plan
+                .steps
+                .get(self.test_plan.current)
+                .or_else(|| self.test_plan.steps.last())
+                .map_or(0, |step| step.0)
+        }
+    }
+
     // Create and schedule GooseUsers. This requires that the host that will be load tested
     // has been configured.
     fn prepare_load_test(&mut self) -> Result<(), GooseError> {
@@ -1136,6 +1329,18 @@ impl<G> GooseAttack<G>
             );
         }
 
+        // Scenarios can only have their own test plan when not running in Gaggle mode.
+        if self.attack_mode != AttackMode::StandAlone {
+            if let Some(scenario) = self.scenarios.iter().find(|s| s.test_plan.is_some()) {
+                return Err(GooseError::InvalidOption {
+                    option: "`Scenario::set_test_plan`".to_string(),
+                    value: scenario.name.to_string(),
+                    detail: "Scenarios can not have their own test plan in Gaggle mode."
+                        .to_string(),
+                });
+            }
+        }
+
         if self.attack_mode != AttackMode::Worker {
             // Stand-alone and Manager processes can display metrics.
             if !self.configuration.no_metrics && !self.configuration.no_print_metrics {
@@ -1163,65 +1368,78 @@ impl<G> GooseAttack<G>
         }
     }
 
-    // Helper to spawn a throttle thread if configured. The throttle thread opens
-    // a bounded channel to control how quickly [`GooseUser`](./goose/struct.GooseUser.html)
-    // threads can make requests.
-    async fn setup_throttle(
-        &self,
-    ) -> (
-        // A channel used by [`GooseUser`](./goose/struct.GooseUser.html)s to throttle requests.
-        Option<flume::Sender<bool>>,
-        // A channel used by parent to tell throttle the load test is complete.
-        Option<flume::Sender<bool>>,
-    ) {
-        // If the throttle isn't enabled, return immediately.
-        if self.configuration.throttle_requests == 0 {
-            return (None, None);
+    // Helper to create a throttle if configured. The throttle is a token bucket shared
+    // by all [`GooseUser`](./goose/struct.GooseUser.html) threads controlling how quickly
+    // they can make requests. When Controller commands are enabled a disabled throttle is
+    // always created, so it can be enabled while the load test is running.
+    fn setup_throttle(&self) -> Option<Arc<GooseThrottle>> {
+        // If the throttle isn't enabled and can't be enabled later, return immediately.
+        if self.configuration.throttle_requests == 0 && !self.controller_commands_enabled() {
+            return None;
         }
 
-        // Create a bounded channel allowing single-sender multi-receiver to throttle
-        // [`GooseUser`](./goose/struct.GooseUser.html) threads.
-        let (all_threads_throttle, throttle_receiver): (
-            flume::Sender<bool>,
-            flume::Receiver<bool>,
-        ) = flume::bounded(self.configuration.throttle_requests);
-
-        // Create a channel allowing the parent to inform the throttle thread when the
-        // load test is finished. Even though we only send one message, we can't use a
-        // oneshot channel as we don't want to block waiting for a message.
-        let (parent_to_throttle_tx, throttle_rx) = flume::bounded(1);
-
-        // Launch a new thread for throttling, no need to rejoin it.
-        let _ = Some(tokio::spawn(throttle::throttle_main(
+        Some(Arc::new(GooseThrottle::new(
             self.configuration.throttle_requests,
-            throttle_receiver,
-            throttle_rx,
-        )));
+            self.configuration.throttle_burst,
+        )))
+    }
 
-        let sender = all_threads_throttle.clone();
-        // We start from 1 instead of 0 to intentionally fill all but one slot in the
-        // channel to avoid a burst of traffic during startup. The channel then provides
-        // an implementation of the leaky bucket algorithm as a queue. Requests have to
-        // add a token to the bucket before making a request, and are blocked until this
-        // throttle thread "leaks out" a token thereby creating space. More information
-        // can be found at: https://en.wikipedia.org/wiki/Leaky_bucket
-        for _ in 1..self.configuration.throttle_requests {
-            let _ = sender.send_async(true).await;
-        }
+    // Helper to create a throttle for each [`Scenario`](./goose/struct.Scenario.html)
+    // configured with `set_throttle_requests`, shared by all users running the scenario.
+    fn setup_scenario_throttles(&self) -> Vec<Option<Arc<GooseThrottle>>> {
+        self.scenarios
+            .iter()
+            .map(|scenario| {
+                scenario.throttle_requests.map(|throttle_requests| {
+                    Arc::new(GooseThrottle::new(
+                        throttle_requests,
+                        self.configuration.throttle_burst,
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    // Helper to create a throttle for each host configured with `set_host_throttle`,
+    // shared by all users.
+    fn setup_host_throttles(&self) -> HashMap<String, Arc<GooseThrottle>> {
+        self.host_throttles
+            .iter()
+            .map(|(origin, throttle_requests)| {
+                (
+                    origin.to_string(),
+                    Arc::new(GooseThrottle::new(
+                        *throttle_requests,
+                        self.configuration.throttle_burst,
+                    )),
+                )
+            })
+            .collect()
+    }
 
-        (Some(all_threads_throttle), Some(parent_to_throttle_tx))
+    // Helper to determine if Controller commands can be received while the load test is
+    // running, from the telnet, WebSocket or HTTP Controllers, the dashboard or a script.
+    fn controller_commands_enabled(&self) -> bool {
+        !self.configuration.no_telnet
+            || !self.configuration.no_websocket
+            || !self.configuration.no_http
+            || self.configuration.tui
+            || self.configuration.controller_script.is_some()
     }
 
-    // Helper to optionally spawn a telnet and/or WebSocket Controller thread. The Controller
+    // Helper to optionally spawn a telnet, WebSocket and/or HTTP Controller thread. The Controller
     // threads share a control channel, allowing it to send requests to the parent process. When
     // a response is required, the Controller will also send a one-shot channel allowing a direct
     // reply.
-    async fn setup_controllers(&mut self) -> Option<flume::Receiver<ControllerRequest>> {
-        // If the telnet controller is disabled, return immediately.
-        if self.configuration.no_telnet && self.configuration.no_websocket {
-            return None;
+    async fn setup_controllers(&mut self) -> Result<ControllerChannel, GooseError> {
+        // If all controllers, the dashboard and scripts are disabled, return immediately.
+        if !self.controller_commands_enabled() {
+            return Ok(None);
         }
 
+        // Optionally encrypt connections to the WebSocket and HTTP Controllers.
+        let tls = ControllerTls::load(&self.configuration)?;
+
         // Create an unbounded channel for controller threads to send requests to the parent
         // process.
         let (all_threads_controller_request_tx, controller_request_rx): (
@@ -1256,6 +1474,7 @@ impl<G> GooseAttack<G>
                 self.configuration.clone(),
                 all_threads_controller_request_tx.clone(),
                 ControllerProtocol::Telnet,
+                None,
             )));
         }
 
@@ -1285,13 +1504,49 @@ impl<G> GooseAttack<G>
             // There is no need to rejoin this thread when the load test ends.
             let _ = Some(tokio::spawn(controller::controller_main(
                 self.configuration.clone(),
-                all_threads_controller_request_tx,
+                all_threads_controller_request_tx.clone(),
                 ControllerProtocol::WebSocket,
+                tls.clone(),
+            )));
+        }
+
+        // Configured HTTP Controller if not disabled.
+        #[cfg(feature = "http-controller")]
+        if !self.configuration.no_http {
+            // Configure http_host, using default if run-time option is not set.
+            if self.configuration.http_host.is_empty() {
+                self.configuration.http_host = if let Some(host) = self.defaults.http_host.clone() {
+                    host
+                } else {
+                    "0.0.0.0".to_string()
+                }
+            }
+
+            // Then configure http_port, using default if run-time option is not set.
+            if self.configuration.http_port == 0 {
+                self.configuration.http_port = if let Some(port) = self.defaults.http_port {
+                    port
+                } else {
+                    DEFAULT_HTTP_PORT.to_string().parse().unwrap()
+                };
+            }
+
+            // Spawn the initial controller thread to allow real-time control of the load test.
+            // There is no need to rejoin this thread when the load test ends.
+            let _ = Some(tokio::spawn(controller::controller_main(
+                self.configuration.clone(),
+                all_threads_controller_request_tx.clone(),
+                ControllerProtocol::Http,
+                tls,
             )));
         }
 
-        // Return the parent end of the Controller channel.
-        Some(controller_request_rx)
+        // Return both ends of the Controller channel, the dashboard and scripts also send
+        // requests to the parent process.
+        Ok(Some((
+            all_threads_controller_request_tx,
+            controller_request_rx,
+        )))
     }
 
     // Prepare an asynchronous file writer for `report_file` (if enabled).
@@ -1375,14 +1630,28 @@ impl<G> GooseAttack<G>
         let (all_threads_shutdown_tx, shutdown_rx): (flume::Sender<usize>, flume::Receiver<usize>) =
             flume::unbounded();
 
-        // Optionally spawn a telnet and/or Websocket Controller thread.
-        let controller_channel_rx = self.setup_controllers().await;
+        // Optionally spawn a telnet and/or Websocket Controller thread, and draw the
+        // dashboard.
+        let (controller_channel_tx, controller_channel_rx) = match self.setup_controllers().await? {
+            Some((channel_tx, channel_rx)) => (Some(channel_tx), Some(channel_rx)),
+            None => (None, None),
+        };
+
+        // Optionally draw the live dashboard, controlling the load test through the same
+        // channel as the other Controllers.
+        #[cfg(feature = "tui")]
+        let tui = match &controller_channel_tx {
+            Some(channel_tx) if self.configuration.tui => {
+                Some(tui::GooseTui::start(channel_tx.clone())?)
+            }
+            _ => None,
+        };
 
         // Grab now() once from the standard library, used by multiple timers in
         // the run state.
         let std_now = std::time::Instant::now();
 
-        let goose_attack_run_state = GooseAttackRunState {
+        let mut goose_attack_run_state = GooseAttackRunState {
             adjust_user_timer: std_now,
             adjust_user_in_ms: 0,
             active_users: 0,
@@ -1394,13 +1663,24 @@ impl<G> GooseAttack<G>
             shutdown_rx,
             logger_handle: None,
             all_threads_logger_tx: None,
-            throttle_threads_tx: None,
-            parent_to_throttle_tx: None,
+            throttle: None,
+            scenario_throttles: Vec::new(),
+            host_throttles: HashMap::new(),
             controller_channel_rx,
+            controller_channel_tx,
+            controller_scripts: Vec::new(),
+            controller_subscriptions: ControllerSubscriptions::default(),
+            #[cfg(feature = "tui")]
+            tui,
             metrics_header_displayed: false,
             idle_status_displayed: false,
             users: Vec::new(),
             user_channels: Vec::new(),
+            scenario_plan_channels: vec![Vec::new(); self.scenarios.len()],
+            scenario_plans_started: std_now,
+            scenario_users: vec![0; self.scenarios.len()],
+            capacity_search: None,
+            paused: None,
             running_metrics_timer: std_now,
             display_running_metrics: false,
             users_shutdown: HashSet::new(),
@@ -1413,6 +1693,11 @@ impl<G> GooseAttack<G>
         // Access socket to avoid errors.
         trace!("socket: {:?}", &goose_attack_run_state.socket);
 
+        // Optionally start running the script of Controller commands.
+        if let Some(script) = self.configuration.controller_script.clone() {
+            self.run_controller_script(&mut goose_attack_run_state, script);
+        }
+
         // Catch ctrl-c to allow clean shutdown to display metrics.
         util::setup_ctrlc_handler();
 
@@ -1431,6 +1716,258 @@ impl<G> GooseAttack<G>
         }
     }
 
+    // Rebuild the active test plan to ramp from the current number of users to `new_users`
+    // at the configured hatch rate, used when the user count is changed on a running load test.
+    fn change_running_users(
+        &mut self,
+        goose_attack_run_state: &mut GooseAttackRunState,
+        new_users: usize,
+    ) -> Result<(), GooseError> {
+        // Determine how long has elapsed since this step started.
+        let elapsed = self.step_elapsed() as usize;
+
+        // Determine how quickly to adjust user account.
+        let hatch_rate = if let Some(hatch_rate) = self.configuration.hatch_rate.as_ref() {
+            util::get_hatch_rate(Some(hatch_rate.to_string()))
+        } else {
+            util::get_hatch_rate(None)
+        };
+        // Convert hatch_rate to milliseconds.
+        let ms_hatch_rate = 1.0 / hatch_rate * 1_000.0;
+        // Determine how many users to increase or decrease by.
+        let user_difference =
+            (goose_attack_run_state.active_users as isize - new_users as isize).abs();
+        // Multiply the user difference by the hatch rate to get the total_time required.
+        let total_time = (ms_hatch_rate * user_difference as f32) as usize;
+
+        // Reset the test_plan to adjust to the newly specified users.
+        self.test_plan.steps = vec![
+            // Record how many active users there are currently.
+            (goose_attack_run_state.active_users, elapsed),
+            // Configure the new user count.
+            (new_users, total_time),
+        ];
+        self.test_plan.stages.clear();
+
+        // Reset the current step to what was happening when reconfiguration happened.
+        self.test_plan.current = 0;
+
+        // Allocate more users if increasing users.
+        if new_users > goose_attack_run_state.active_users {
+            self.weighted_users = self.weight_scenario_users(user_difference as usize)?;
+        }
+
+        // Also update the running configurtion (this impacts if the test is stopped and then
+        // restarted through the controller).
+        self.configuration.users = Some(new_users);
+
+        // Finally, advance to the next step to adjust user count.
+        self.advance_test_plan(goose_attack_run_state);
+
+        Ok(())
+    }
+
+    // Determine if a Controller can change which scenarios run and their weights, returning
+    // Some(true) if running users need to be re-allocated, or None if the load test can't be
+    // changed now.
+    fn scenarios_changeable(&self) -> Option<bool> {
+        match self.attack_phase {
+            AttackPhase::Idle => Some(false),
+            // Users are only re-allocated in stand-alone mode.
+            AttackPhase::Increase | AttackPhase::Maintain
+                if self.attack_mode == AttackMode::StandAlone =>
+            {
+                Some(true)
+            }
+            _ => None,
+        }
+    }
+
+    // Determine if any active scenarios are allocated users by weight, rather than by
+    // their own test plan.
+    fn has_weighted_scenarios(&self) -> bool {
+        self.scenarios
+            .iter()
+            .any(|scenario| scenario.test_plan.is_none() && self.scenario_is_active(scenario))
+    }
+
+    // Enable or disable the named scenario, re-allocating running users. Returns false if
+    // the scenario doesn't exist, if the load test can't be changed now, or if no scenario
+    // would be left to allocate users to.
+    fn set_scenario_active(
+        &mut self,
+        goose_attack_run_state: &mut GooseAttackRunState,
+        machine_name: &str,
+        active: bool,
+    ) -> Result<bool, GooseError> {
+        let machine_name = machine_name.to_lowercase();
+        let scenarios_index = match self
+            .scenarios
+            .iter()
+            .position(|scenario| scenario.machine_name == machine_name)
+        {
+            Some(scenarios_index) => scenarios_index,
+            None => return Ok(false),
+        };
+        let reallocate = match self.scenarios_changeable() {
+            Some(reallocate) => reallocate,
+            None => return Ok(false),
+        };
+
+        let had_weighted_scenarios = self.has_weighted_scenarios();
+        let previous = self.scenario_overrides.insert(scenarios_index, active);
+        if had_weighted_scenarios && !self.has_weighted_scenarios() {
+            // Don't disable the last scenario users can be allocated to.
+            match previous {
+                Some(previous) => self.scenario_overrides.insert(scenarios_index, previous),
+                None => self.scenario_overrides.remove(&scenarios_index),
+            };
+            return Ok(false);
+        }
+
+        info!(
+            "{} scenario {}",
+            if active { "enabling" } else { "disabling" },
+            self.scenarios[scenarios_index].name
+        );
+        if reallocate {
+            self.reallocate_users(goose_attack_run_state)?;
+        }
+
+        Ok(true)
+    }
+
+    // Change the weight of the named scenario, re-allocating running users. Returns false
+    // if the scenario doesn't exist or has its own test plan, or if the load test can't be
+    // changed now.
+    fn set_scenario_weight(
+        &mut self,
+        goose_attack_run_state: &mut GooseAttackRunState,
+        machine_name: &str,
+        weight: usize,
+    ) -> Result<bool, GooseError> {
+        let machine_name = machine_name.to_lowercase();
+        let scenarios_index = match self.scenarios.iter().position(|scenario| {
+            scenario.machine_name == machine_name && scenario.test_plan.is_none()
+        }) {
+            Some(scenarios_index) => scenarios_index,
+            None => return Ok(false),
+        };
+        let reallocate = match self.scenarios_changeable() {
+            Some(reallocate) => reallocate,
+            None => return Ok(false),
+        };
+
+        info!(
+            "changing {} weight from {} to {}",
+            self.scenarios[scenarios_index].name, self.scenarios[scenarios_index].weight, weight
+        );
+        self.scenarios[scenarios_index].weight = weight;
+        if reallocate {
+            self.reallocate_users(goose_attack_run_state)?;
+        }
+
+        Ok(true)
+    }
+
+    // Skip, or stop skipping, all transactions with the given name. Returns false if no
+    // transaction has the name, or if the transactions are running on Workers.
+    fn skip_transactions(&self, name: &str, skipped: bool) -> bool {
+        if self.attack_mode == AttackMode::Manager {
+            return false;
+        }
+        let mut found = false;
+        for transaction in self
+            .scenarios
+            .iter()
+            .flat_map(|scenario| scenario.transactions.iter())
+            .filter(|transaction| transaction.name == name)
+        {
+            transaction.set_skipped(skipped);
+            found = true;
+        }
+        if found {
+            info!(
+                "{} transactions named {}",
+                if skipped {
+                    "skipping"
+                } else {
+                    "no longer skipping"
+                },
+                name
+            );
+        }
+        found
+    }
+
+    // Re-allocate running [`GooseUser`](./goose/struct.GooseUser.html)s across
+    // [`Scenario`](./goose/struct.Scenario.html)s after a Controller changed which are
+    // active or their weights, stopping users of scenarios running too many and launching
+    // users for scenarios running too few. The number of running users doesn't change.
+    fn reallocate_users(
+        &mut self,
+        goose_attack_run_state: &mut GooseAttackRunState,
+    ) -> Result<(), GooseError> {
+        // Determine how many users each scenario should be running.
+        let weighted_scenarios = self.allocate_scenarios();
+        let mut wanted_users = vec![0; self.scenarios.len()];
+        for scenarios_index in weighted_scenarios
+            .iter()
+            .cycle()
+            .take(goose_attack_run_state.user_channels.len())
+        {
+            wanted_users[*scenarios_index] += 1;
+        }
+        let mut running_users = vec![0; self.scenarios.len()];
+        for (scenarios_index, _) in &goose_attack_run_state.user_channels {
+            running_users[*scenarios_index] += 1;
+        }
+
+        // Stop the most recently launched users of scenarios running too many.
+        let mut index = goose_attack_run_state.user_channels.len();
+        while index > 0 {
+            index -= 1;
+            let scenarios_index = goose_attack_run_state.user_channels[index].0;
+            if running_users[scenarios_index] > wanted_users[scenarios_index] {
+                let (_, send_to_user) = goose_attack_run_state.user_channels.remove(index);
+                // An error is expected if this user already shut down.
+                let _ = send_to_user.send(GooseUserCommand::Exit);
+                running_users[scenarios_index] -= 1;
+                goose_attack_run_state.scenario_users[scenarios_index] -= 1;
+                goose_attack_run_state.completed_users += 1;
+            }
+        }
+
+        // Launch users for scenarios running too few.
+        for scenarios_index in 0..self.scenarios.len() {
+            while running_users[scenarios_index] < wanted_users[scenarios_index] {
+                let base_url = goose::get_base_url(
+                    self.get_configuration_host(),
+                    self.scenarios[scenarios_index].host.clone(),
+                    self.defaults.host.clone(),
+                )?;
+                let thread_user = G::new(
+                    scenarios_index,
+                    base_url,
+                    &self.configuration,
+                    self.metrics.hash,
+                )?;
+                let parent_sender = self.launch_user(goose_attack_run_state, thread_user);
+                goose_attack_run_state
+                    .user_channels
+                    .push((scenarios_index, parent_sender));
+                running_users[scenarios_index] += 1;
+            }
+        }
+
+        // Users that haven't launched yet are also re-allocated.
+        if !self.weighted_users.is_empty() {
+            self.weighted_users = self.weight_scenario_users(self.weighted_users.len())?;
+        }
+
+        Ok(())
+    }
+
     // Add delay before starting next step if there's time remaining.
     async fn end_of_step_delay(&mut self) {
         // Determine if there's remaining time in this step.
@@ -1450,6 +1987,168 @@ impl<G> GooseAttack<G>
         }
     }
 
+    // Launch a [`GooseUser`](./goose/struct.GooseUser.html) thread, returning a channel
+    // allowing the parent thread to control it.
+    fn launch_user(
+        &mut self,
+        goose_attack_run_state: &mut GooseAttackRunState,
+        mut thread_user: G,
+    ) -> flume::Sender<GooseUserCommand> {
+        let scenarios_index = thread_user.scenarios_index();
+
+        // Remember which task group this user is using.
+        thread_user.set_weighted_users_index(self.metrics.total_users);
+
+        // Create a per-thread channel allowing parent thread to control child threads.
+        let (parent_sender, thread_receiver): (
+            flume::Sender<GooseUserCommand>,
+            flume::Receiver<GooseUserCommand>,
+        ) = flume::unbounded();
+
+        // Let the user check for commands while backing off between retries.
+        thread_user.set_command_receiver(Some(thread_receiver.clone()));
+
+        // Clone the logger_tx if enabled, otherwise is None.
+        thread_user.set_logger(goose_attack_run_state.all_threads_logger_tx.clone());
+
+        // Share the throttles, if enabled, with all threads.
+        thread_user.set_throttle(goose_attack_run_state.throttle.clone());
+        thread_user.set_scenario_throttle(
+            goose_attack_run_state.scenario_throttles[scenarios_index].clone(),
+        );
+        thread_user.set_host_throttles(goose_attack_run_state.host_throttles.clone());
+
+        // Copy the GooseUser-metrics sender channel, used by all threads.
+        thread_user
+            .set_metrics_channel(Some(goose_attack_run_state.all_threads_metrics_tx.clone()));
+
+        // Copy the GooseUser-shutdown sender channel, used by all threads.
+        thread_user
+            .set_shutdown_channel(Some(goose_attack_run_state.all_threads_shutdown_tx.clone()));
+
+        // Copy the appropriate task_set into the thread.
+        let thread_scenario = self.scenarios[scenarios_index].clone();
+
+        // Start at 1 as this is human visible.
+        let thread_number = self.metrics.total_users + 1;
+
+        let is_worker = self.attack_mode == AttackMode::Worker;
+
+        // If running on Worker, use Worker configuration in GooseUser.
+        if is_worker {
+            thread_user.set_config(self.configuration.clone());
+        }
+
+        // Launch a new user.
+        let user = tokio::spawn(user::user_main(
+            thread_number,
+            thread_scenario,
+            thread_user,
+            thread_receiver,
+            is_worker,
+        ));
+
+        goose_attack_run_state.users.push(user);
+        goose_attack_run_state.scenario_users[scenarios_index] += 1;
+        self.metrics.total_users += 1;
+        let running_users = goose_attack_run_state.scenario_users.iter().sum();
+        if running_users > self.metrics.maximum_users {
+            self.metrics.maximum_users = running_users;
+        }
+
+        parent_sender
+    }
+
+    // Launch and stop [`GooseUser`](./goose/struct.GooseUser.html)s running
+    // [`Scenario`](./goose/struct.Scenario.html)s that have their own test plan, so each
+    // is running as many users as its test plan currently requires.
+    fn adjust_scenario_plans(
+        &mut self,
+        goose_attack_run_state: &mut GooseAttackRunState,
+    ) -> Result<(), GooseError> {
+        let elapsed = goose_attack_run_state
+            .scenario_plans_started
+            .elapsed()
+            .as_millis() as usize;
+        for scenarios_index in 0..self.scenarios.len() {
+            let scenario = &self.scenarios[scenarios_index];
+            let users = match scenario.test_plan.as_ref() {
+                Some(test_plan) if self.scenario_is_active(scenario) => test_plan.users_at(elapsed),
+                // A Controller can disable a scenario while its users are running.
+                Some(_) => 0,
+                None => continue,
+            };
+            // Launch users until the scenario is running enough users.
+            while goose_attack_run_state.scenario_plan_channels[scenarios_index].len() < users {
+                let base_url = goose::get_base_url(
+                    self.get_configuration_host(),
+                    self.scenarios[scenarios_index].host.clone(),
+                    self.defaults.host.clone(),
+                )?;
+                let thread_user = G::new(
+                    scenarios_index,
+                    base_url,
+                    &self.configuration,
+                    self.metrics.hash,
+                )?;
+                let parent_sender = self.launch_user(goose_attack_run_state, thread_user);
+                goose_attack_run_state.scenario_plan_channels[scenarios_index].push(parent_sender);
+            }
+            // Stop users until the scenario isn't running too many users.
+            while goose_attack_run_state.scenario_plan_channels[scenarios_index].len() > users {
+                if let Some(send_to_user) =
+                    goose_attack_run_state.scenario_plan_channels[scenarios_index].pop()
+                {
+                    // An error is expected if this user already shut down.
+                    let _ = send_to_user.send(GooseUserCommand::Exit);
+                    goose_attack_run_state.scenario_users[scenarios_index] -= 1;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Stop all [`GooseUser`](./goose/struct.GooseUser.html)s launched by
+    // [`Scenario`](./goose/struct.Scenario.html) test plans.
+    fn stop_scenario_plans(&self, goose_attack_run_state: &mut GooseAttackRunState) {
+        for (scenarios_index, channels) in goose_attack_run_state
+            .scenario_plan_channels
+            .iter_mut()
+            .enumerate()
+        {
+            for send_to_user in channels.drain(..) {
+                // An error is expected if this user already shut down.
+                let _ = send_to_user.send(GooseUserCommand::Exit);
+                goose_attack_run_state.scenario_users[scenarios_index] -= 1;
+            }
+        }
+    }
+
+    // Record the current users for the users per second graph in the HTML report, broken
+    // down by scenario if any scenarios have their own test plan.
+    fn record_users_per_second(&mut self, goose_attack_run_state: &GooseAttackRunState) {
+        if let Some(started) = self.started {
+            let second = started.elapsed().as_secs() as usize;
+            self.graph_data
+                .record_users_per_second(goose_attack_run_state.active_users, second);
+            if self.scenarios.iter().any(|s| s.test_plan.is_some()) {
+                for (scenarios_index, users) in
+                    goose_attack_run_state.scenario_users.iter().enumerate()
+                {
+                    let scenario = &self.scenarios[scenarios_index];
+                    if self.scenario_is_active(scenario) {
+                        self.graph_data.record_scenario_users_per_second(
+                            &scenario.name,
+                            *users,
+                            second,
+                        );
+                    }
+                }
+            }
+        };
+    }
+
     // Increase the number of active [`GooseUser`](./goose/struct.GooseUser.html) threads in the
     // active [`GooseAttack`](./struct.GooseAttack.html).
     async fn increase_attack(
@@ -1463,9 +2162,11 @@ impl<G> GooseAttack<G>
                 self.weighted_users.is_empty()
             }
             _ => {
-                // If not running in Gaggle mode, all users for current step must be launched.
+                // If not running in Gaggle mode, all users for current step must be launched,
+                // unless all scenarios have their own test plan.
                 goose_attack_run_state.active_users
                     >= self.test_plan.steps[self.test_plan.current].0
+                    || self.weighted_users.is_empty()
             }
         };
 
@@ -1492,13 +2193,8 @@ impl<G> GooseAttack<G>
                 self.advance_test_plan(goose_attack_run_state);
             }
         } else {
-            // If this is the first load plan step, then there were no previously started users.
-            let previous_users = if self.test_plan.current == 0 {
-                0
-                // Otherwise retreive the number of users configured in the previous step.
-            } else {
-                self.test_plan.steps[self.test_plan.current - 1].0
-            };
+            // Retreive the number of users running when this step started.
+            let previous_users = self.step_users();
 
             // Sanity check: increase_attack can only be called if the number of users is increasing
             // in the current step.
@@ -1518,7 +2214,7 @@ impl<G> GooseAttack<G>
                 goose_attack_run_state.adjust_user_in_ms,
             )
             {
-                let mut thread_user = self
+                let thread_user = self
                     .weighted_users
                     .pop()
                     .expect("insufficent weighted_users");
@@ -1536,57 +2232,13 @@ impl<G> GooseAttack<G>
                         self.configuration.expect_workers.unwrap() as usize;
                 };
 
-                // Remember which task group this user is using.
-                thread_user.set_weighted_users_index(self.metrics.total_users);
-
-                // Create a per-thread channel allowing parent thread to control child threads.
-                let (parent_sender, thread_receiver): (
-                    flume::Sender<GooseUserCommand>,
-                    flume::Receiver<GooseUserCommand>,
-                ) = flume::unbounded();
-                goose_attack_run_state.user_channels.push(parent_sender);
-
-                // Clone the logger_tx if enabled, otherwise is None.
-                thread_user.set_logger(goose_attack_run_state.all_threads_logger_tx.clone());
-
-                // Copy the GooseUser-throttle receiver channel, used by all threads.
-                let throttle = if self.configuration.throttle_requests > 0 {
-                    Some(goose_attack_run_state.throttle_threads_tx.clone().unwrap())
-                } else {
-                    None
-                };
-                thread_user.set_throttle(throttle);
-
-                // Copy the GooseUser-metrics sender channel, used by all threads.
-                thread_user.set_metrics_channel(Some(goose_attack_run_state.all_threads_metrics_tx.clone()));
-
-                // Copy the GooseUser-shutdown sender channel, used by all threads.
-                thread_user.set_shutdown_channel(Some(goose_attack_run_state.all_threads_shutdown_tx.clone()));
-
-                // Copy the appropriate task_set into the thread.
-                let thread_scenario = self.scenarios[thread_user.scenarios_index()].clone();
-
-                // Start at 1 as this is human visible.
-                let thread_number = self.metrics.total_users + 1;
-
-                let is_worker = self.attack_mode == AttackMode::Worker;
-
-                // If running on Worker, use Worker configuration in GooseUser.
-                if is_worker {
-                    thread_user.set_config(self.configuration.clone());
-                }
-
                 // Launch a new user.
-                let user = tokio::spawn(
-                    user::user_main(thread_number, thread_scenario, thread_user, thread_receiver, is_worker)
-                );
-
-                goose_attack_run_state.users.push(user);
+                let scenarios_index = thread_user.scenarios_index();
+                let parent_sender = self.launch_user(goose_attack_run_state, thread_user);
+                goose_attack_run_state
+                    .user_channels
+                    .push((scenarios_index, parent_sender));
                 goose_attack_run_state.active_users += 1;
-                self.metrics.total_users += 1;
-                if goose_attack_run_state.active_users > self.metrics.maximum_users {
-                    self.metrics.maximum_users = goose_attack_run_state.active_users;
-                }
 
                 if let Some(running_metrics) = self.configuration.running_metrics {
                     if self.attack_mode != AttackMode::Worker
@@ -1623,6 +2275,12 @@ impl<G> GooseAttack<G>
         &mut self,
         goose_attack_run_state: &mut GooseAttackRunState,
     ) -> Result<(), GooseError> {
+        // If searching for capacity, evaluate the current level.
+        self.update_capacity_search(goose_attack_run_state).await?;
+        if self.attack_phase != AttackPhase::Maintain {
+            return Ok(());
+        }
+
         // Determine if it's time to move to the next test plan step.
         if self.test_plan.current < self.test_plan.steps.len()
             && util::ms_timer_expired(
@@ -1650,12 +2308,6 @@ impl<G> GooseAttack<G>
         &mut self,
         goose_attack_run_state: &mut GooseAttackRunState,
     ) -> Result<(), GooseError> {
-        // Sanity check: if there's more than one step the first step can't decrease the attack. If there's
-        // only one step then an idle load test may be being shut down through the controller.
-        if self.test_plan.steps.len() > 1 {
-            assert!(self.test_plan.current > 0);
-        }
-
         // If this is the last step of the load test and there are 0 users, shut down.
         if goose_attack_run_state.active_users == 0
             // Subtract 1 from len() as it starts at 1 while current starts at 0.
@@ -1669,11 +2321,20 @@ impl<G> GooseAttack<G>
                 register_shutdown_pipe_handler(&manager);
             }
 
-            // If throttle is enabled, tell throttle thread the load test is over.
-            if let Some(throttle_tx) = goose_attack_run_state.parent_to_throttle_tx.clone() {
-                let _ = throttle_tx.send(false);
+            // If throttles are enabled, close them to cancel any waiting requests.
+            let throttles = goose_attack_run_state
+                .throttle
+                .iter()
+                .chain(goose_attack_run_state.scenario_throttles.iter().flatten())
+                .chain(goose_attack_run_state.host_throttles.values());
+            for throttle in throttles {
+                info!("load test complete, closing throttle");
+                throttle.close();
             }
 
+            // Stop users running scenarios with their own test plan.
+            self.stop_scenario_plans(goose_attack_run_state);
+
             // Take the users vector out of the GooseAttackRunState object so it can be
             // consumed by futures::future::join_all().
             let users = std::mem::take(&mut goose_attack_run_state.users);
@@ -1725,22 +2386,17 @@ impl<G> GooseAttack<G>
             // Collect all metrics sent by GooseUser threads.
             self.sync_metrics(goose_attack_run_state, true).await?;
             // Record last users for users per second graph in HTML report.
-            if let Some(started) = self.started {
-                self.graph_data.record_users_per_second(
-                    goose_attack_run_state.active_users,
-                    started.elapsed().as_secs() as usize,
-                );
-            };
+            self.record_users_per_second(goose_attack_run_state);
             // The load test is fully stopped at this point.
             self.metrics
                 .history
-                .push(TestPlanHistory::step(TestPlanStepAction::Finished, 0));
+                .push(TestPlanHistory::step(TestPlanStepAction::Finished, 0, None));
             // Shutdown Goose or go into an idle waiting state.
             if goose_attack_run_state.shutdown_after_stop {
                 self.set_attack_phase(goose_attack_run_state, AttackPhase::Shutdown);
             } else {
-                // Print metrics, if enabled.
-                if !self.configuration.no_metrics {
+                // Print metrics, if enabled and the dashboard isn't drawing the terminal.
+                if !self.configuration.no_metrics && !self.configuration.tui {
                     println!("{}", self.metrics);
                 }
                 // Write an html report, if enabled.
@@ -1765,8 +2421,8 @@ impl<G> GooseAttack<G>
             }
             // Otherwise, decrease a user when ready.
         } else {
-            // Retreive the number of users configured in the previous step.
-            let previous_users = self.test_plan.steps[self.test_plan.current - 1].0;
+            // Retreive the number of users running when this step started.
+            let previous_users = self.step_users();
 
             // Divide the number of users to decrease by the time configured to decrease them.
             let decrease_rate: f32 = (previous_users - self.test_plan.steps[self.test_plan.current].0)
@@ -1789,7 +2445,9 @@ impl<G> GooseAttack<G>
                 // milliseconds and divide by the decrease_rate.
                 goose_attack_run_state.adjust_user_in_ms = (1_000.0 / decrease_rate) as usize;
 
-                if let Some(send_to_user) = goose_attack_run_state.user_channels.pop() {
+                if let Some((scenarios_index, send_to_user)) =
+                    goose_attack_run_state.user_channels.pop()
+                {
                     match send_to_user.send(GooseUserCommand::Exit) {
                         Ok(_) => {
                             debug!(
@@ -1812,6 +2470,7 @@ impl<G> GooseAttack<G>
                     }
                     goose_attack_run_state.completed_users += 1;
                     goose_attack_run_state.active_users -= 1;
+                    goose_attack_run_state.scenario_users[scenarios_index] -= 1;
                 }
             } else {
                 // Wake up twice a second to handle messages and allow for a quick shutdown if the
@@ -1831,11 +2490,107 @@ impl<G> GooseAttack<G>
         Ok(())
     }
 
+    // Hold all running [`GooseUser`](./goose/struct.GooseUser.html)s at their next
+    // transaction boundary and stop the load test clock. Returns false if the load test
+    // isn't running.
+    fn pause_attack(&mut self, goose_attack_run_state: &mut GooseAttackRunState) -> bool {
+        // Only a starting or running load test can be paused.
+        if ![AttackPhase::Increase, AttackPhase::Maintain].contains(&self.attack_phase) {
+            return false;
+        }
+
+        self.update_duration();
+        goose_attack_run_state.paused =
+            Some((std::time::Instant::now(), self.attack_phase.clone()));
+        self.send_to_all_users(goose_attack_run_state, GooseUserCommand::Pause);
+        self.set_attack_phase(goose_attack_run_state, AttackPhase::Pause);
+
+        true
+    }
+
+    // Release all paused [`GooseUser`](./goose/struct.GooseUser.html)s, continuing the
+    // load test from where it was paused. Returns false if the load test isn't paused.
+    fn resume_attack(&mut self, goose_attack_run_state: &mut GooseAttackRunState) -> bool {
+        let phase = match self.restart_clock(goose_attack_run_state) {
+            Some(phase) => phase,
+            None => return false,
+        };
+
+        self.send_to_all_users(goose_attack_run_state, GooseUserCommand::Run);
+        self.set_attack_phase(goose_attack_run_state, phase);
+
+        true
+    }
+
+    // If the load test is paused, shift all timers forward by the time spent paused so the
+    // load test continues from where it was paused. Returns the phase the load test was
+    // paused in.
+    fn restart_clock(
+        &mut self,
+        goose_attack_run_state: &mut GooseAttackRunState,
+    ) -> Option<AttackPhase> {
+        let (paused, phase) = goose_attack_run_state.paused.take()?;
+        let paused_for = paused.elapsed();
+        info!("resuming load test paused for {:?}", paused_for);
+
+        if let Some(started) = self.started.as_mut() {
+            *started += paused_for;
+        }
+        if let Some(step_started) = self.step_started.as_mut() {
+            *step_started += paused_for;
+        }
+        if let Some(metrics_reset) = self.metrics_reset.as_mut() {
+            // Metrics reset while paused are measured from when the load test resumes.
+            if *metrics_reset > paused {
+                *metrics_reset = time::Instant::now();
+            } else {
+                *metrics_reset += paused_for;
+            }
+        }
+        goose_attack_run_state.adjust_user_timer += paused_for;
+        goose_attack_run_state.running_metrics_timer += paused_for;
+        goose_attack_run_state.scenario_plans_started += paused_for;
+        goose_attack_run_state
+            .controller_subscriptions
+            .resume(paused_for);
+        if let Some(capacity_search) = goose_attack_run_state.capacity_search.as_mut() {
+            capacity_search.resume(paused_for);
+        }
+
+        Some(phase)
+    }
+
+    // Send a command to all running [`GooseUser`](./goose/struct.GooseUser.html)s,
+    // including those launched by a [`Scenario`](./goose/struct.Scenario.html)'s own
+    // test plan.
+    fn send_to_all_users(
+        &self,
+        goose_attack_run_state: &GooseAttackRunState,
+        command: GooseUserCommand,
+    ) {
+        let scenario_plan_channels = goose_attack_run_state
+            .scenario_plan_channels
+            .iter()
+            .flatten();
+        for send_to_user in goose_attack_run_state
+            .user_channels
+            .iter()
+            .map(|(_, send_to_user)| send_to_user)
+            .chain(scenario_plan_channels)
+        {
+            // An error only means the user already exited.
+            let _ = send_to_user.send(command.clone());
+        }
+    }
+
     // Quickly abort and shut down an active [`GooseAttack`](./struct.GooseAttack.html).
     async fn cancel_attack(
         &mut self,
         goose_attack_run_state: &mut GooseAttackRunState,
     ) -> Result<(), GooseError> {
+        // Paused users are still running, and will exit when told to.
+        self.restart_clock(goose_attack_run_state);
+
         // Determine how long has elapsed since this step started.
         let elapsed = self.step_elapsed() as usize;
 
@@ -1846,6 +2601,7 @@ impl<G> GooseAttack<G>
             // Record how long the attack ran in this step.
             (0, 0),
         ];
+        self.test_plan.stages.clear();
         // Reset the current step to what was happening when canceled.
         self.test_plan.current = 0;
 
@@ -1912,10 +2668,18 @@ impl<G> GooseAttack<G>
         goose_attack_run_state.idle_status_displayed = false;
         goose_attack_run_state.users = Vec::new();
         goose_attack_run_state.user_channels = Vec::new();
+        goose_attack_run_state.scenario_plan_channels = vec![Vec::new(); self.scenarios.len()];
+        goose_attack_run_state.scenario_plans_started = std_now;
+        goose_attack_run_state.scenario_users = vec![0; self.scenarios.len()];
+        goose_attack_run_state.capacity_search =
+            self.configuration.capacity_search.as_ref().map(|search| {
+                CapacitySearchState::new(self.configuration.users.unwrap_or(search.step))
+            });
         goose_attack_run_state.running_metrics_timer = std_now;
         goose_attack_run_state.display_running_metrics = false;
         goose_attack_run_state.shutdown_after_stop = !self.configuration.no_autostart;
         goose_attack_run_state.all_users_spawned = false;
+        goose_attack_run_state.controller_subscriptions.restart();
 
         // If enabled, spawn a logger thread.
         let (logger_handle, all_threads_logger_tx) =
@@ -1923,10 +2687,11 @@ impl<G> GooseAttack<G>
         goose_attack_run_state.logger_handle = logger_handle;
         goose_attack_run_state.all_threads_logger_tx = all_threads_logger_tx;
 
-        // If enabled, spawn a throttle thread.
-        let (throttle_threads_tx, parent_to_throttle_tx) = self.setup_throttle().await;
-        goose_attack_run_state.throttle_threads_tx = throttle_threads_tx;
-        goose_attack_run_state.parent_to_throttle_tx = parent_to_throttle_tx;
+        // If enabled, create throttles.
+        goose_attack_run_state.throttle = self.setup_throttle();
+        goose_attack_run_state.scenario_throttles = self.setup_scenario_throttles();
+        goose_attack_run_state.host_throttles = self.setup_host_throttles();
+        self.metrics.throttle_requests = self.configuration.throttle_requests;
 
         // If enabled, try to create the report file to confirm access.
         let _report_file = match self.prepare_report_file().await {
@@ -1942,6 +2707,7 @@ impl<G> GooseAttack<G>
 
         // Record when the GooseAttack officially started.
         self.started = Some(time::Instant::now());
+        self.metrics_reset = None;
 
         Ok(())
     }
@@ -1982,9 +2748,11 @@ impl<G> GooseAttack<G>
                     } else {
                         // Prepare to start the load test, resetting timers and counters.
                         self.reset_run_state(&mut goose_attack_run_state).await?;
-                        self.metrics
-                            .history
-                            .push(TestPlanHistory::step(TestPlanStepAction::Increasing, 0));
+                        self.metrics.history.push(TestPlanHistory::step(
+                            TestPlanStepAction::Increasing,
+                            0,
+                            self.test_plan.stage(0),
+                        ));
                         //self.graph_data.set_starting(Utc::now());
                         self.set_attack_phase(&mut goose_attack_run_state, AttackPhase::Increase);
                     }
@@ -1999,6 +2767,15 @@ impl<G> GooseAttack<G>
                     self.update_duration();
                     self.maintain_attack(&mut goose_attack_run_state).await?;
                 }
+                // In the Pause phase, GooseUser threads wait for a Controller to resume the
+                // load test, and otherwise nothing happens but sleeping and checking for messages.
+                AttackPhase::Pause => {
+                    let sleep_duration = Duration::from_millis(250);
+                    debug!("sleeping {:?}...", sleep_duration);
+                    goose_attack_run_state.drift_timer =
+                        util::sleep_minus_drift(sleep_duration, goose_attack_run_state.drift_timer)
+                            .await;
+                }
                 // In the Decrease phase, Goose stops GooseUser threads.
                 AttackPhase::Decrease => {
                     // If displaying metrics, update internal state reflecting how long load test
@@ -2011,18 +2788,30 @@ impl<G> GooseAttack<G>
                 AttackPhase::Shutdown => break,
             }
 
-            // Record current users for users per second graph in HTML report.
-            if let Some(started) = self.started {
-                self.graph_data.record_users_per_second(
-                    goose_attack_run_state.active_users,
-                    started.elapsed().as_secs() as usize,
-                );
-            };
+            // Launch and stop users running scenarios with their own test plan.
+            if matches!(
+                self.attack_phase,
+                AttackPhase::Increase | AttackPhase::Maintain | AttackPhase::Decrease
+            ) {
+                self.adjust_scenario_plans(&mut goose_attack_run_state)?;
+            }
+
+            // Record current users for users per second graph in HTML report, unless the
+            // load test clock is stopped.
+            if self.attack_phase != AttackPhase::Pause {
+                self.record_users_per_second(&goose_attack_run_state);
+            }
 
             // Regularly synchronize metrics.
             self.sync_metrics(&mut goose_attack_run_state, false)
                 .await?;
 
+            // Push live metrics to subscribed Controller clients, unless the load test
+            // clock is stopped.
+            if self.attack_phase != AttackPhase::Pause {
+                self.publish_controller_events(&mut goose_attack_run_state);
+            }
+
             // Check if a Controller has made a request.
             self.handle_controller_requests(&mut goose_attack_run_state)
                 .await?;
@@ -2044,7 +2833,9 @@ impl<G> GooseAttack<G>
                 }
                 // In Stand-alone mode, all users are started.
                 #[cfg(not(feature = "gaggle"))]
-                if goose_attack_run_state.users_shutdown.len() == self.test_plan.total_users() {
+                if goose_attack_run_state.users_shutdown.len()
+                    == self.test_plan.total_users() + self.scenario_plan_users()
+                {
                     self.cancel_attack(&mut goose_attack_run_state).await?;
                 }
 
@@ -2072,6 +2863,17 @@ impl<G> GooseAttack<G>
             }
         }
 
+        // Scripts stop running when the load test shuts down.
+        for script in goose_attack_run_state.controller_scripts.drain(..) {
+            script.abort();
+        }
+
+        // Restore the terminal before final metrics are displayed.
+        #[cfg(feature = "tui")]
+        if let Some(tui) = goose_attack_run_state.tui.take() {
+            tui.exit().await;
+        }
+
         Ok(self)
     }
 }
@@ -239,7 +239,7 @@ fn error_csv_header() -> String {
 fn requests_csv_header() -> String {
     // No quotes needed in header.
     format!(
-        "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+        "{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
         "elapsed",
         "raw",
         "name",
@@ -253,6 +253,7 @@ fn requests_csv_header() -> String {
         "error",
         "coordinated_omission_elapsed",
         "user_cadence",
+        "attempt",
     )
 }
 
@@ -260,8 +261,15 @@ fn requests_csv_header() -> String {
 fn transactions_csv_header() -> String {
     format!(
         // No quotes needed in header.
-        "{},{},{},{},{},{},{}",
-        "elapsed", "scenario_index", "transaction_index", "name", "run_time", "success", "user",
+        "{},{},{},{},{},{},{},{}",
+        "elapsed",
+        "scenario_index",
+        "transaction_index",
+        "name",
+        "run_time",
+        "success",
+        "user",
+        "group",
     )
 }
 
@@ -376,7 +384,7 @@ impl GooseLogger<GooseRequestMetric> for GooseConfiguration {
     fn prepare_csv(&self, request: &GooseRequestMetric) -> String {
         format!(
             // Put quotes around name, url and final_url as they are strings.
-            "{},\"{:?}\",\"{}\",\"{}\",{},{},{},{},{},{},{},{},{}",
+            "{},\"{:?}\",\"{}\",\"{}\",{},{},{},{},{},{},{},{},{},{}",
             request.elapsed,
             request.raw,
             request.name,
@@ -390,6 +398,7 @@ impl GooseLogger<GooseRequestMetric> for GooseConfiguration {
             request.error,
             request.coordinated_omission_elapsed,
             request.user_cadence,
+            request.attempt,
         )
     }
 }
@@ -417,8 +426,8 @@ impl GooseLogger<TransactionMetric> for GooseConfiguration {
     /// Converts a TransactionMetric structure to a CSV row.
     fn prepare_csv(&self, request: &TransactionMetric) -> String {
         format!(
-            // Put quotes around name as it is a string.
-            "{},{},{},\"{}\",{},{},{}",
+            // Put quotes around name and group as they are strings.
+            "{},{},{},\"{}\",{},{},{},\"{}\"",
             request.elapsed,
             request.scenario_index,
             request.transaction_index,
@@ -426,6 +435,7 @@ impl GooseLogger<TransactionMetric> for GooseConfiguration {
             request.run_time,
             request.success,
             request.user,
+            request.group.join(" > "),
         )
     }
 }
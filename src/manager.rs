@@ -91,6 +91,11 @@ fn merge_transactions_from_worker(
     merged_transaction.success_count += &user_transaction.success_count;
     // Increment total fail counter.
     merged_transaction.fail_count += &user_transaction.fail_count;
+    // Recursively merge metrics of any groups run by this transaction.
+    for user_group in &user_transaction.groups {
+        let merged_group = merged_transaction.group_mut(&[user_group.transaction_name.clone()]);
+        *merged_group = merge_transactions_from_worker(merged_group, user_group);
+    }
     merged_transaction
 }
 
@@ -114,6 +119,16 @@ fn merge_scenarios_from_worker(
     // If user had new slowest scenario time, update global slowest scenario time.
     merged_scenario.max_time =
         metrics::update_max_time(merged_scenario.max_time, user_scenario.max_time);
+    // Add up the number of times each transition was taken.
+    for (from, destinations) in &user_scenario.transitions {
+        let merged_destinations = merged_scenario
+            .transitions
+            .entry(from.to_string())
+            .or_default();
+        for (to, count) in destinations {
+            *merged_destinations.entry(to.to_string()).or_insert(0) += count;
+        }
+    }
     merged_scenario
 }
 
@@ -148,6 +163,8 @@ fn merge_requests_from_worker(
     merged_request.success_count += &user_request.success_count;
     // Increment total fail counter.
     merged_request.fail_count += &user_request.fail_count;
+    // Increment total retry counter.
+    merged_request.retry_count += &user_request.retry_count;
     // Only accrue overhead of merging status_code_counts if we're going to display the results
     if !no_status_codes {
         for (status_code, count) in &user_request.status_code_counts {
@@ -8,6 +8,7 @@
 //! contained [`TransactionMetrics`], [`GooseRequestMetrics`], and
 //! [`GooseErrorMetrics`] are displayed in tables.
 
+use crate::capacity::CapacitySearchLevel;
 use crate::config::GooseDefaults;
 use crate::goose::{get_base_url, GooseMethod, Scenario};
 use crate::logger::GooseLog;
@@ -19,7 +20,9 @@ use crate::{
     worker::{self, GaggleMetrics},
     SHUTDOWN_GAGGLE,
 };
-use crate::{AttackMode, GooseAttack, GooseAttackRunState, GooseConfiguration, GooseError};
+use crate::{
+    AttackMode, AttackPhase, GooseAttack, GooseAttackRunState, GooseConfiguration, GooseError,
+};
 use chrono::prelude::*;
 use http::StatusCode;
 use itertools::Itertools;
@@ -360,6 +363,9 @@ pub struct GooseRequestMetric {
     /// [`Transaction`](../goose/struct.Transaction.html)s by this
     /// [`GooseUser`](../goose/struct.GooseUser.html) thread.
     pub user_cadence: u64,
+    /// Which attempt at the request this was, greater than 1 if the request was retried
+    /// by a [`GooseRetryPolicy`](../goose/struct.GooseRetryPolicy.html).
+    pub attempt: usize,
 }
 
 impl GooseRequestMetric {
@@ -378,6 +384,7 @@ impl GooseRequestMetric {
             error: "".to_string(),
             coordinated_omission_elapsed: 0,
             user_cadence: 0,
+            attempt: 1,
         }
     }
 
@@ -432,6 +439,9 @@ pub struct GooseRequestMetricAggregate {
     ///
     /// A count of how many requests resulted in a non-2xx status code.
     pub fail_count: usize,
+    /// Total number of retried attempts at this path-method request, included in the
+    /// success and fail counts.
+    pub retry_count: usize,
     /// Load test hash.
     ///
     /// The hash is primarily used when running a distributed Gaggle, allowing the Manager to confirm
@@ -451,6 +461,7 @@ impl GooseRequestMetricAggregate {
             status_code_counts: HashMap::new(),
             success_count: 0,
             fail_count: 0,
+            retry_count: 0,
             load_test_hash,
         }
     }
@@ -637,6 +648,9 @@ pub struct ScenarioMetric {
     pub run_time: u64,
     /// Which GooseUser thread processed the request.
     pub user: usize,
+    /// The transitions taken from one transaction to the next (or to "exit") if the
+    /// scenario has a [`TransitionGraph`](../goose/struct.TransitionGraph.html).
+    pub transitions: Vec<(String, String)>,
 }
 
 impl ScenarioMetric {
@@ -647,6 +661,7 @@ impl ScenarioMetric {
         index: usize,
         run_time: u128,
         user: usize,
+        transitions: Vec<(String, String)>,
     ) -> Self {
         ScenarioMetric {
             elapsed: elapsed as u64,
@@ -654,6 +669,7 @@ impl ScenarioMetric {
             index,
             run_time: run_time as u64,
             user,
+            transitions,
         }
     }
 }
@@ -669,6 +685,10 @@ pub struct TransactionMetric {
     pub transaction_index: usize,
     /// The optional name of the transaction.
     pub name: String,
+    /// The names of the [`GooseUser::group`](../goose/struct.GooseUser.html#method.group)s
+    /// this metric is nested in, ending with its own name. Empty if this metric times the
+    /// entire transaction.
+    pub group: Vec<String>,
     /// How long transaction ran.
     pub run_time: u64,
     /// Whether or not the request was successful.
@@ -691,6 +711,7 @@ impl TransactionMetric {
             scenario_index,
             transaction_index,
             name,
+            group: Vec::new(),
             run_time: 0,
             success: true,
             user,
@@ -735,6 +756,8 @@ pub struct TransactionMetricAggregate {
     pub success_count: usize,
     /// Total number of times transaction has failed.
     pub fail_count: usize,
+    /// Metrics for each named group run by this transaction, in the order first run.
+    pub groups: Vec<TransactionMetricAggregate>,
 }
 
 impl TransactionMetricAggregate {
@@ -757,9 +780,32 @@ impl TransactionMetricAggregate {
             counter: 0,
             success_count: 0,
             fail_count: 0,
+            groups: Vec::new(),
         }
     }
 
+    /// Return the metrics of the group nested at the given path of group names, creating
+    /// any that don't exist yet. An empty path returns this transaction's own metrics.
+    pub(crate) fn group_mut(&mut self, path: &[String]) -> &mut TransactionMetricAggregate {
+        let (name, path) = match path.split_first() {
+            Some(split) => split,
+            None => return self,
+        };
+        let index = match self.groups.iter().position(|g| &g.transaction_name == name) {
+            Some(index) => index,
+            None => {
+                self.groups.push(TransactionMetricAggregate::new(
+                    self.scenario_index,
+                    &self.scenario_name,
+                    self.transaction_index,
+                    name,
+                ));
+                self.groups.len() - 1
+            }
+        };
+        self.groups[index].group_mut(path)
+    }
+
     /// Track transaction function elapsed time in milliseconds.
     pub(crate) fn set_time(&mut self, time: u64, success: bool) {
         // Perform this conversion only once, then re-use throughout this function.
@@ -835,6 +881,10 @@ pub struct ScenarioMetricAggregate {
     pub total_time: usize,
     /// Total number of times scenario has run.
     pub counter: usize,
+    /// Per-transition counters if the scenario has a
+    /// [`TransitionGraph`](../goose/struct.TransitionGraph.html), keyed by the transaction
+    /// transitioned from, then by the transaction transitioned to (or "exit").
+    pub transitions: BTreeMap<String, BTreeMap<String, usize>>,
 }
 
 impl ScenarioMetricAggregate {
@@ -849,6 +899,19 @@ impl ScenarioMetricAggregate {
             max_time: 0,
             total_time: 0,
             counter: 0,
+            transitions: BTreeMap::new(),
+        }
+    }
+
+    /// Count transitions taken while walking a transition graph.
+    pub(crate) fn record_transitions(&mut self, transitions: &[(String, String)]) {
+        for (from, to) in transitions {
+            *self
+                .transitions
+                .entry(from.to_string())
+                .or_default()
+                .entry(to.to_string())
+                .or_insert(0) += 1;
         }
     }
 
@@ -1047,6 +1110,15 @@ pub struct GooseMetrics {
     pub errors: GooseErrorMetrics,
     /// Tracks all hosts that the load test is run against.
     pub hosts: HashSet<String>,
+    /// The current maximum requests per second, or 0 if the throttle is disabled.
+    ///
+    /// Configured with the `--throttle-requests` run-time option, and can be changed
+    /// by a Controller while the load test is running.
+    pub throttle_requests: usize,
+    /// The result of each level of a capacity search, if enabled.
+    ///
+    /// Configured with the `--capacity-search` run-time option.
+    pub capacity_search: Vec<CapacitySearchLevel>,
     /// Flag indicating whether or not these are the final metrics, used to determine
     /// which metrics should be displayed. Defaults to false.
     pub(crate) final_metrics: bool,
@@ -1308,15 +1380,6 @@ impl GooseMetrics {
             for transaction in scenario {
                 transaction_count += 1;
                 let total_count = transaction.success_count + transaction.fail_count;
-                let fail_percent = if transaction.fail_count > 0 {
-                    transaction.fail_count as f32 / total_count as f32 * 100.0
-                } else {
-                    0.0
-                };
-                let (runs, fails) =
-                    per_second_calculations(self.duration, total_count, transaction.fail_count);
-                let runs_precision = determine_precision(runs);
-                let fails_precision = determine_precision(fails);
 
                 // First time through display name of scenario.
                 if !displayed_scenario {
@@ -1335,55 +1398,22 @@ impl GooseMetrics {
                     displayed_scenario = true;
                 }
 
-                if fail_percent as usize == 100 || fail_percent as usize == 0 {
-                    let fail_and_percent = format!(
-                        "{} ({}%)",
-                        transaction.fail_count.to_formatted_string(&Locale::en),
-                        fail_percent as usize
-                    );
-                    writeln!(
-                        fmt,
-                        " {:<24} | {:>13} | {:>14} | {:>8.runs_p$} | {:>7.fails_p$}",
-                        util::truncate_string(
-                            &format!(
-                                "  {}: {}",
-                                transaction.transaction_index + 1,
-                                transaction.transaction_name
-                            ),
-                            24,
-                        ),
-                        total_count.to_formatted_string(&Locale::en),
-                        fail_and_percent,
-                        runs,
-                        fails,
-                        runs_p = runs_precision,
-                        fails_p = fails_precision,
-                    )?;
-                } else {
-                    let fail_and_percent = format!(
-                        "{} ({:.1}%)",
-                        transaction.fail_count.to_formatted_string(&Locale::en),
-                        fail_percent
-                    );
-                    writeln!(
-                        fmt,
-                        " {:<24} | {:>13} | {:>14} | {:>8.runs_p$} | {:>7.fails_p$}",
-                        util::truncate_string(
-                            &format!(
-                                "  {}: {}",
-                                transaction.transaction_index + 1,
-                                transaction.transaction_name
-                            ),
-                            24,
-                        ),
-                        total_count.to_formatted_string(&Locale::en),
-                        fail_and_percent,
-                        runs,
-                        fails,
-                        runs_p = runs_precision,
-                        fails_p = fails_precision,
-                    )?;
-                }
+                self.fmt_transaction_row(
+                    fmt,
+                    &format!(
+                        "  {}: {}",
+                        transaction.transaction_index + 1,
+                        transaction.transaction_name
+                    ),
+                    transaction,
+                )?;
+                self.fmt_transaction_groups(
+                    fmt,
+                    transaction,
+                    &(transaction.transaction_index + 1).to_string(),
+                    1,
+                    false,
+                )?;
                 aggregate_total_count += total_count;
                 aggregate_fail_count += transaction.fail_count;
             }
@@ -1444,6 +1474,112 @@ impl GooseMetrics {
         Ok(())
     }
 
+    /// Writes a single row of the per-transaction metrics table.
+    fn fmt_transaction_row(
+        &self,
+        fmt: &mut fmt::Formatter<'_>,
+        name: &str,
+        transaction: &TransactionMetricAggregate,
+    ) -> fmt::Result {
+        let total_count = transaction.success_count + transaction.fail_count;
+        let fail_percent = if transaction.fail_count > 0 {
+            transaction.fail_count as f32 / total_count as f32 * 100.0
+        } else {
+            0.0
+        };
+        let (runs, fails) =
+            per_second_calculations(self.duration, total_count, transaction.fail_count);
+        let runs_precision = determine_precision(runs);
+        let fails_precision = determine_precision(fails);
+
+        // Compress 100.0 and 0.0 to 100 and 0 respectively to save width.
+        let fail_and_percent = if fail_percent as usize == 100 || fail_percent as usize == 0 {
+            format!(
+                "{} ({}%)",
+                transaction.fail_count.to_formatted_string(&Locale::en),
+                fail_percent as usize
+            )
+        } else {
+            format!(
+                "{} ({:.1}%)",
+                transaction.fail_count.to_formatted_string(&Locale::en),
+                fail_percent
+            )
+        };
+        writeln!(
+            fmt,
+            " {:<24} | {:>13} | {:>14} | {:>8.runs_p$} | {:>7.fails_p$}",
+            util::truncate_string(name, 24),
+            total_count.to_formatted_string(&Locale::en),
+            fail_and_percent,
+            runs,
+            fails,
+            runs_p = runs_precision,
+            fails_p = fails_precision,
+        )
+    }
+
+    /// Writes a single row of the per-transaction times table.
+    fn fmt_transaction_time_row(
+        &self,
+        fmt: &mut fmt::Formatter<'_>,
+        name: &str,
+        transaction: &TransactionMetricAggregate,
+    ) -> fmt::Result {
+        let average = match transaction.counter {
+            0 => 0.00,
+            _ => transaction.total_time as f32 / transaction.counter as f32,
+        };
+        let average_precision = determine_precision(average);
+
+        writeln!(
+            fmt,
+            " {:<24} | {:>11.avg_precision$} | {:>10} | {:>11} | {:>10}",
+            util::truncate_string(name, 24),
+            average,
+            format_number(transaction.min_time),
+            format_number(transaction.max_time),
+            format_number(util::median(
+                &transaction.times,
+                transaction.counter,
+                transaction.min_time,
+                transaction.max_time,
+            )),
+            avg_precision = average_precision,
+        )
+    }
+
+    /// Writes a row for each group run by a transaction, recursively indenting nested
+    /// groups beneath their parent. Groups are not included in the aggregated totals as
+    /// their time is already part of the transaction.
+    fn fmt_transaction_groups(
+        &self,
+        fmt: &mut fmt::Formatter<'_>,
+        transaction: &TransactionMetricAggregate,
+        label: &str,
+        depth: usize,
+        times: bool,
+    ) -> fmt::Result {
+        for (index, group) in transaction.groups.iter().enumerate() {
+            let label = format!("{}.{}", label, index + 1);
+            let name = format!(
+                "{:indent$}{}: {}",
+                "",
+                label,
+                group.transaction_name,
+                indent = 2 * (depth + 1)
+            );
+            if times {
+                self.fmt_transaction_time_row(fmt, &name, group)?;
+            } else {
+                self.fmt_transaction_row(fmt, &name, group)?;
+            }
+            self.fmt_transaction_groups(fmt, group, &label, depth + 1, times)?;
+        }
+
+        Ok(())
+    }
+
     /// Optionally prepares a table of transaction times.
     ///
     /// This function is invoked by `GooseMetrics::print()` and
@@ -1512,33 +1648,21 @@ impl GooseMetrics {
                 aggregate_max_transaction_time =
                     update_max_time(aggregate_max_transaction_time, transaction.max_time);
 
-                let average = match transaction.counter {
-                    0 => 0.00,
-                    _ => transaction.total_time as f32 / transaction.counter as f32,
-                };
-                let average_precision = determine_precision(average);
-
-                writeln!(
+                self.fmt_transaction_time_row(
                     fmt,
-                    " {:<24} | {:>11.avg_precision$} | {:>10} | {:>11} | {:>10}",
-                    util::truncate_string(
-                        &format!(
-                            "  {}: {}",
-                            transaction.transaction_index + 1,
-                            transaction.transaction_name
-                        ),
-                        24,
+                    &format!(
+                        "  {}: {}",
+                        transaction.transaction_index + 1,
+                        transaction.transaction_name
                     ),
-                    average,
-                    format_number(transaction.min_time),
-                    format_number(transaction.max_time),
-                    format_number(util::median(
-                        &transaction.times,
-                        transaction.counter,
-                        transaction.min_time,
-                        transaction.max_time,
-                    )),
-                    avg_precision = average_precision,
+                    transaction,
+                )?;
+                self.fmt_transaction_groups(
+                    fmt,
+                    transaction,
+                    &(transaction.transaction_index + 1).to_string(),
+                    1,
+                    true,
                 )?;
             }
         }
@@ -1748,6 +1872,57 @@ impl GooseMetrics {
         Ok(())
     }
 
+    /// Optionally prepares a table of transitions taken in scenarios with a transition graph.
+    ///
+    /// This function is invoked by `GooseMetrics::print()` and
+    /// `GooseMetrics::print_running()`.
+    pub(crate) fn fmt_scenario_transitions(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // If there's nothing to display, exit immediately.
+        if self.scenarios.iter().all(|s| s.transitions.is_empty()) || !self.display_metrics {
+            return Ok(());
+        }
+
+        writeln!(
+            fmt,
+            "\n === PER SCENARIO TRANSITIONS ===\n ------------------------------------------------------------------------------"
+        )?;
+        writeln!(
+            fmt,
+            " {:<24} | {:<24} | {:>12} | {:>9}",
+            "Name", "Next", "# times", "% of from"
+        )?;
+        writeln!(
+            fmt,
+            " ------------------------------------------------------------------------------"
+        )?;
+        for scenario in &self.scenarios {
+            if scenario.transitions.is_empty() {
+                continue;
+            }
+            writeln!(
+                fmt,
+                " {:<24} |",
+                util::truncate_string(&format!("{}: {}", scenario.index + 1, scenario.name), 24),
+            )?;
+            for (from, destinations) in &scenario.transitions {
+                let total: usize = destinations.values().sum();
+                for (to, count) in destinations {
+                    let percent = *count as f32 / total as f32 * 100.0;
+                    writeln!(
+                        fmt,
+                        " {:<24} | {:<24} | {:>12} | {:>8.2}%",
+                        util::truncate_string(&format!("  {}", from), 24),
+                        util::truncate_string(to, 24),
+                        format_number(*count),
+                        percent,
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Optionally prepares a table of response times.
     ///
     /// This function is invoked by `GooseMetrics::print()` and
@@ -2366,6 +2541,65 @@ impl GooseMetrics {
         Ok(())
     }
 
+    /// Optionally prepares a table of retried requests, comparing the raw number of
+    /// attempts with the effective results after retrying.
+    ///
+    /// This function is invoked by `GooseMetrics::print()` and
+    /// `GooseMetrics::print_running()`.
+    pub(crate) fn fmt_retries(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // If no requests were retried, exit immediately.
+        if !self
+            .requests
+            .values()
+            .any(|request| request.retry_count > 0)
+        {
+            return Ok(());
+        }
+
+        writeln!(
+            fmt,
+            "\n === PER REQUEST RETRIES ===\n ------------------------------------------------------------------------------"
+        )?;
+        writeln!(
+            fmt,
+            " {:<24} | {:>11} | {:>10} | {:>8} | {:>11}",
+            "Name", "# attempts", "# retries", "# reqs", "# fails"
+        )?;
+        writeln!(
+            fmt,
+            " ------------------------------------------------------------------------------"
+        )?;
+        for (request_key, request) in self.requests.iter().sorted() {
+            if request.retry_count == 0 {
+                continue;
+            }
+            let attempts = request.success_count + request.fail_count;
+            // Only failed attempts are retried, so each retry replaces a failure.
+            let total_count = attempts - request.retry_count;
+            let fail_count = request.fail_count.saturating_sub(request.retry_count);
+            let fail_percent = if fail_count > 0 {
+                fail_count as f32 / total_count as f32 * 100.0
+            } else {
+                0.0
+            };
+            writeln!(
+                fmt,
+                " {:<24} | {:>11} | {:>10} | {:>8} | {:>11}",
+                util::truncate_string(request_key, 24),
+                attempts.to_formatted_string(&Locale::en),
+                request.retry_count.to_formatted_string(&Locale::en),
+                total_count.to_formatted_string(&Locale::en),
+                format!(
+                    "{} ({:.1}%)",
+                    fail_count.to_formatted_string(&Locale::en),
+                    fail_percent
+                ),
+            )?;
+        }
+
+        Ok(())
+    }
+
     /// Optionally prepares a table of errors.
     ///
     /// This function is invoked by `GooseMetrics::print()` and
@@ -2422,6 +2656,89 @@ impl GooseMetrics {
         (seconds, minutes, hours)
     }
 
+    /// Optionally displays the current throttle rate.
+    ///
+    /// This function is invoked by `GooseMetrics::print()` and
+    /// `GooseMetrics::print_running()`.
+    pub(crate) fn fmt_throttle(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Only display the throttle if it's enabled.
+        if self.throttle_requests == 0 {
+            return Ok(());
+        }
+
+        writeln!(
+            fmt,
+            "\n === THROTTLE ===\n ------------------------------------------------------------------------------"
+        )?;
+        writeln!(
+            fmt,
+            " Maximum requests per second: {}",
+            format_number(self.throttle_requests)
+        )?;
+        writeln!(
+            fmt,
+            " ------------------------------------------------------------------------------"
+        )?;
+
+        Ok(())
+    }
+
+    /// The highest number of users that passed a capacity search, if any.
+    pub fn capacity_search_result(&self) -> Option<usize> {
+        self.capacity_search
+            .iter()
+            .filter(|level| level.passed)
+            .map(|level| level.users)
+            .max()
+    }
+
+    /// Optionally prepares a table of each level tried by a capacity search.
+    ///
+    /// This function is invoked by [`GooseMetrics::print()`].
+    pub(crate) fn fmt_capacity_search(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Only display the capacity search in the final metrics, if enabled.
+        if !self.final_metrics || self.capacity_search.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(
+            fmt,
+            "\n === CAPACITY SEARCH ===\n ------------------------------------------------------------------------------"
+        )?;
+        writeln!(
+            fmt,
+            " {:<10} | {:>14} | {:>16} | {:>10} | Result",
+            "Users", "Requests", "Percentile (ms)", "Errors"
+        )?;
+        writeln!(
+            fmt,
+            " ------------------------------------------------------------------------------"
+        )?;
+        for level in &self.capacity_search {
+            writeln!(
+                fmt,
+                " {:<10} | {:>14} | {:>16} | {:>9.2}% | {}",
+                format_number(level.users),
+                format_number(level.requests),
+                level
+                    .percentile_time
+                    .map_or_else(|| "-".to_string(), format_number),
+                level.error_percent,
+                if level.passed { "passed" } else { "failed" },
+            )?;
+        }
+        writeln!(
+            fmt,
+            " ------------------------------------------------------------------------------"
+        )?;
+        match self.capacity_search_result() {
+            Some(users) => writeln!(fmt, " Maximum sustainable users: {}", format_number(users))?,
+            None => writeln!(fmt, " No level met the capacity search criteria.")?,
+        }
+
+        Ok(())
+    }
+
     /// Optionally prepares an overview table.
     ///
     /// This function is invoked by [`GooseMetrics::print()`].
@@ -2464,7 +2781,7 @@ impl GooseMetrics {
                     writeln!(
                         fmt,
                         " {:<12} {} - {} ({:02}:{:02}:{:02}, {})",
-                        format!("{:?}:", step[0].action),
+                        format!("{}:", step[0].label()),
                         started,
                         stopped,
                         hours,
@@ -2478,7 +2795,7 @@ impl GooseMetrics {
                     writeln!(
                         fmt,
                         " {:<12} {} - {} ({:02}:{:02}:{:02}, {} -> {})",
-                        format!("{:?}:", step[0].action),
+                        format!("{}:", step[0].label()),
                         started,
                         stopped,
                         hours,
@@ -2493,7 +2810,7 @@ impl GooseMetrics {
                     writeln!(
                         fmt,
                         " {:<12} {} - {} ({:02}:{:02}:{:02}, {} <- {})",
-                        format!("{:?}:", step[0].action),
+                        format!("{}:", step[0].label()),
                         started,
                         stopped,
                         hours,
@@ -2549,7 +2866,7 @@ impl Serialize for GooseMetrics {
         where
             S: Serializer,
     {
-        let mut s = serializer.serialize_struct("GooseMetrics", 10)?;
+        let mut s = serializer.serialize_struct("GooseMetrics", 12)?;
         s.serialize_field("hash", &self.hash)?;
         s.serialize_field("duration", &self.duration)?;
         s.serialize_field("maximum_users", &self.maximum_users)?;
@@ -2557,6 +2874,8 @@ impl Serialize for GooseMetrics {
         s.serialize_field("requests", &self.requests)?;
         s.serialize_field("transactions", &self.transactions)?;
         s.serialize_field("errors", &self.errors)?;
+        s.serialize_field("throttle_requests", &self.throttle_requests)?;
+        s.serialize_field("capacity_search", &self.capacity_search)?;
         s.serialize_field("final_metrics", &self.final_metrics)?;
         s.serialize_field("display_status_codes", &self.display_status_codes)?;
         s.serialize_field("display_metrics", &self.display_metrics)?;
@@ -2572,13 +2891,17 @@ impl fmt::Display for GooseMetrics {
         // flags are set.
         self.fmt_scenarios(fmt)?;
         self.fmt_scenario_times(fmt)?;
+        self.fmt_scenario_transitions(fmt)?;
         self.fmt_transactions(fmt)?;
         self.fmt_transaction_times(fmt)?;
         self.fmt_requests(fmt)?;
+        self.fmt_retries(fmt)?;
         self.fmt_response_times(fmt)?;
         self.fmt_percentiles(fmt)?;
         self.fmt_status_codes(fmt)?;
         self.fmt_errors(fmt)?;
+        self.fmt_throttle(fmt)?;
+        self.fmt_capacity_search(fmt)?;
         self.fmt_overview(fmt)
     }
 }
@@ -2673,9 +2996,11 @@ impl<G: Goose> GooseAttack<G> {
         flush: bool,
     ) -> Result<(), GooseError> {
         if !self.configuration.no_metrics {
-            // Check if we're displaying running metrics.
+            // Check if we're displaying running metrics, unless the dashboard is drawing the
+            // terminal.
             if let Some(running_metrics) = self.configuration.running_metrics {
                 if self.attack_mode != AttackMode::Worker
+                    && !self.configuration.tui
                     && util::timer_expired(
                     goose_attack_run_state.running_metrics_timer,
                     running_metrics,
@@ -2747,13 +3072,16 @@ impl<G: Goose> GooseAttack<G> {
                 let users = self.configuration.users.unwrap();
                 // Only reset metrics on startup if not using `--no-reset-metrics`.
                 if !self.configuration.no_reset_metrics {
-                    // Display the running metrics collected so far, before resetting them.
+                    // Display the running metrics collected so far, before resetting them,
+                    // unless the dashboard is drawing the terminal.
                     self.update_duration();
-                    self.metrics.print_running();
+                    if !self.configuration.tui {
+                        self.metrics.print_running();
+                    }
                     // Reset running_metrics_timer.
                     goose_attack_run_state.running_metrics_timer = std::time::Instant::now();
 
-                    if self.metrics.display_metrics {
+                    if self.metrics.display_metrics && !self.configuration.tui {
                         // Users is required here so unwrap() is safe.
                         if goose_attack_run_state.active_users < users {
                             println!(
@@ -2766,29 +3094,25 @@ impl<G: Goose> GooseAttack<G> {
                         }
                     }
 
-                    self.metrics.requests = HashMap::new();
-                    self.metrics
-                        .initialize_scenario_metrics(&self.scenarios, &self.configuration);
-                    self.metrics.initialize_transaction_metrics(
-                        &self.scenarios,
-                        &self.configuration,
-                        &self.defaults,
-                    )?;
+                    self.discard_metrics(goose_attack_run_state)?;
 
                     // Restart the timer now that all threads are launched.
                     self.started = Some(std::time::Instant::now());
-                } else if goose_attack_run_state.active_users < users {
-                    println!(
-                        "{} of {} users hatched, timer expired.\n",
-                        goose_attack_run_state.active_users, users
-                    );
-                } else {
-                    println!(
-                        "All {} users hatched.\n",
-                        goose_attack_run_state.active_users
-                    );
+                    self.metrics_reset = None;
+                } else if !self.configuration.tui {
+                    if goose_attack_run_state.active_users < users {
+                        println!(
+                            "{} of {} users hatched, timer expired.\n",
+                            goose_attack_run_state.active_users, users
+                        );
+                    } else {
+                        println!(
+                            "All {} users hatched.\n",
+                            goose_attack_run_state.active_users
+                        );
+                    }
                 }
-            } else {
+            } else if !self.configuration.tui {
                 println!("{} users hatched.", goose_attack_run_state.active_users);
             }
         }
@@ -2796,6 +3120,50 @@ impl<G: Goose> GooseAttack<G> {
         Ok(())
     }
 
+    // Discard the request, scenario and transaction metrics collected so far.
+    fn discard_metrics(
+        &mut self,
+        goose_attack_run_state: &mut GooseAttackRunState,
+    ) -> Result<(), GooseError> {
+        self.metrics.requests = HashMap::new();
+        self.metrics
+            .initialize_scenario_metrics(&self.scenarios, &self.configuration);
+        self.metrics.initialize_transaction_metrics(
+            &self.scenarios,
+            &self.configuration,
+            &self.defaults,
+        )?;
+        // A capacity search level can no longer be measured from the discarded requests.
+        if let Some(search) = goose_attack_run_state.capacity_search.as_mut() {
+            search.restart_window();
+        }
+
+        Ok(())
+    }
+
+    // Reset metrics when requested by a Controller, so they only describe the load test
+    // from this point on.
+    pub(crate) async fn reset_controller_metrics(
+        &mut self,
+        goose_attack_run_state: &mut GooseAttackRunState,
+    ) -> Result<(), GooseError> {
+        // Receive metrics before resetting them.
+        self.sync_metrics(goose_attack_run_state, true).await?;
+
+        self.discard_metrics(goose_attack_run_state)?;
+        self.metrics.errors = GooseErrorMetrics::new();
+        // Graphs in reports written later also start from the reset.
+        self.graph_data.reset_metrics();
+
+        // Measure the duration from now, without restarting the load test clock which
+        // drives the test plan.
+        self.metrics_reset = Some(std::time::Instant::now());
+        self.metrics.duration = 0;
+        info!("metrics reset by controller");
+
+        Ok(())
+    }
+
     // Store `GooseRequestMetric` in a `GooseRequestMetricAggregate` within the
     // `GooseMetrics.requests` `HashMap`, merging if already existing, or creating new.
     // Also writes it to the request_file if enabled.
@@ -2834,6 +3202,9 @@ impl<G: Goose> GooseAttack<G> {
             } else {
                 merge_request.fail_count += 1;
             }
+            if request_metric.attempt > 1 {
+                merge_request.retry_count += 1;
+            }
         }
 
         self.metrics.requests.insert(key, merge_request);
@@ -2895,7 +3266,11 @@ impl<G: Goose> GooseAttack<G> {
                         // `GooseMetrics.requests`, and write to the requests log if enabled.
                         self.record_request_metric(&request_metric).await;
 
-                        if !self.configuration.report_file.is_empty() {
+                        // Graph data is also used to push live metrics to subscribed
+                        // Controller clients.
+                        let subscribed =
+                            goose_attack_run_state.controller_subscriptions.is_active();
+                        if !self.configuration.report_file.is_empty() || subscribed {
                             let seconds_since_start = (request_metric.elapsed / 1000) as usize;
 
                             let key =
@@ -2912,16 +3287,27 @@ impl<G: Goose> GooseAttack<G> {
                                 self.graph_data
                                     .record_errors_per_second(&key, seconds_since_start);
                             }
+
+                            if subscribed {
+                                self.graph_data.record_response_time_per_second(
+                                    seconds_since_start,
+                                    request_metric.response_time,
+                                );
+                            }
                         }
                     }
                 }
                 GooseMetric::Transaction(raw_transaction) => {
-                    // Store a new metric.
+                    // Store a new metric, in the appropriate group if any.
                     self.metrics.transactions[raw_transaction.scenario_index]
                         [raw_transaction.transaction_index]
+                        .group_mut(&raw_transaction.group)
                         .set_time(raw_transaction.run_time, raw_transaction.success);
 
-                    if !self.configuration.report_file.is_empty() {
+                    // Groups are part of a transaction, so only transactions are graphed.
+                    if !self.configuration.report_file.is_empty()
+                        && raw_transaction.group.is_empty()
+                    {
                         self.graph_data.record_transactions_per_second(
                             (raw_transaction.elapsed / 1000) as usize,
                         );
@@ -2931,6 +3317,8 @@ impl<G: Goose> GooseAttack<G> {
                     // Store a new metric.
                     self.metrics.scenarios[raw_scenario.index]
                         .update(raw_scenario.run_time, raw_scenario.user);
+                    self.metrics.scenarios[raw_scenario.index]
+                        .record_transitions(&raw_scenario.transitions);
 
                     if !self.configuration.report_file.is_empty() {
                         self.graph_data
@@ -3002,10 +3390,14 @@ impl<G: Goose> GooseAttack<G> {
     // Update metrics showing how long the load test has been running.
     // 1.2 seconds will round down to 1 second. 1.6 seconds will round up to 2 seconds.
     pub(crate) fn update_duration(&mut self) {
-        self.metrics.duration = if self.started.is_some() {
-            self.started.unwrap().elapsed().as_secs_f32().round() as usize
-        } else {
-            0
+        // The load test clock is stopped while paused.
+        if self.attack_phase == AttackPhase::Pause {
+            return;
+        }
+        // A Controller can reset metrics without restarting the load test clock.
+        self.metrics.duration = match self.metrics_reset.or(self.started) {
+            Some(started) => started.elapsed().as_secs_f32().round() as usize,
+            None => 0,
         };
     }
 
@@ -3025,592 +3417,731 @@ impl<G: Goose> GooseAttack<G> {
 
         // Only write the report if enabled.
         if let Some(mut report_file) = report_file {
-            let test_start_time = self.metrics.history.first().unwrap().timestamp;
-
-            // Prepare report summary variables.
-            let users = self.metrics.maximum_users.to_string();
-
-            let mut steps_overview = String::new();
-            for step in self.metrics.history.windows(2) {
-                let (seconds, minutes, hours) = self
-                    .metrics
-                    .get_seconds_minutes_hours(&step[0].timestamp, &step[1].timestamp);
-                let started = step[0].timestamp.format("%y-%m-%d %H:%M:%S");
-                let stopped = step[1].timestamp.format("%y-%m-%d %H:%M:%S");
-                match &step[0].action {
-                    // For maintaining just show the current number of users.
-                    TestPlanStepAction::Maintaining => {
-                        let _ = write!(steps_overview,
-                                       "<tr><td>{:?}</td><td>{}</td><td>{}</td><td>{:02}:{:02}:{:02}</td><td>{}</td></tr>",
-                                       step[0].action,
-                                       started,
-                                       stopped,
-                                       hours,
-                                       minutes,
-                                       seconds,
-                                       step[0].users,
-                        );
-                    }
-                    // For increasing show the current number of users to the new number of users.
-                    TestPlanStepAction::Increasing => {
-                        let _ = write!(steps_overview,
-                                       "<tr><td>{:?}</td><td>{}</td><td>{}</td><td>{:02}:{:02}:{:02}</td><td>{} &rarr; {}</td></tr>",
-                                       step[0].action,
-                                       started,
-                                       stopped,
-                                       hours,
-                                       minutes,
-                                       seconds,
-                                       step[0].users,
-                                       step[1].users,
-                        );
-                    }
-                    // For decreasing show the new number of users from the current number of users.
-                    TestPlanStepAction::Decreasing | TestPlanStepAction::Canceling => {
-                        let _ = write!(steps_overview,
-                                       "<tr><td>{:?}</td><td>{}</td><td>{}</td><td>{:02}:{:02}:{:02}</td><td>{} &larr; {}</td></tr>",
-                                       step[0].action,
-                                       started,
-                                       stopped,
-                                       hours,
-                                       minutes,
-                                       seconds,
-                                       step[1].users,
-                                       step[0].users,
-                        );
-                    }
-                    TestPlanStepAction::Finished => {
-                        unreachable!("there shouldn't be a step after finished");
-                    }
-                }
-            }
-
-            // Build a comma separated list of hosts.
-            let hosts = &self.metrics.hosts.clone().into_iter().join(", ");
-
-            // Prepare requests and responses variables.
-            let mut raw_request_metrics = Vec::new();
-            let mut co_request_metrics = Vec::new();
-            let mut raw_response_metrics = Vec::new();
-            let mut co_response_metrics = Vec::new();
-            let mut raw_aggregate_total_count = 0;
-            let mut co_aggregate_total_count = 0;
-            let mut raw_aggregate_fail_count = 0;
-            let mut raw_aggregate_response_time_counter: usize = 0;
-            let mut raw_aggregate_response_time_minimum: usize = 0;
-            let mut raw_aggregate_response_time_maximum: usize = 0;
-            let mut raw_aggregate_response_times: BTreeMap<usize, usize> = BTreeMap::new();
-            let mut co_aggregate_response_time_counter: usize = 0;
-            let mut co_aggregate_response_time_maximum: usize = 0;
-            let mut co_aggregate_response_times: BTreeMap<usize, usize> = BTreeMap::new();
-            let mut co_data = false;
-            for (request_key, request) in self.metrics.requests.iter().sorted() {
-                // Determine whether or not to include Coordinated Omission data.
-                if !co_data && request.coordinated_omission_data.is_some() {
-                    co_data = true;
-                }
-                let method = format!("{}", request.method);
-                // The request_key is "{method} {name}", so by stripping the "{method} "
-                // prefix we get the name.
-                let name = request_key
-                    .strip_prefix(&format!("{} ", request.method))
-                    .unwrap()
-                    .to_string();
-                let total_request_count = request.success_count + request.fail_count;
-                let (requests_per_second, failures_per_second) = per_second_calculations(
-                    self.metrics.duration,
-                    total_request_count,
-                    request.fail_count,
-                );
-                // Prepare per-request metrics.
-                raw_request_metrics.push(report::RequestMetric {
-                    method: method.to_string(),
-                    name: name.to_string(),
-                    number_of_requests: total_request_count,
-                    number_of_failures: request.fail_count,
-                    response_time_average: format!(
-                        "{:.2}",
-                        request.raw_data.total_time as f32 / request.raw_data.counter as f32
-                    ),
-                    response_time_minimum: request.raw_data.minimum_time,
-                    response_time_maximum: request.raw_data.maximum_time,
-                    requests_per_second: format!("{:.2}", requests_per_second),
-                    failures_per_second: format!("{:.2}", failures_per_second),
-                });
+            let report = self.build_html_report();
 
-                // Prepare per-response metrics.
-                raw_response_metrics.push(report::get_response_metric(
-                    &method,
-                    &name,
-                    &request.raw_data.times,
-                    request.raw_data.counter,
-                    request.raw_data.minimum_time,
-                    request.raw_data.maximum_time,
+            // Write the report to file.
+            if let Err(e) = report_file.write_all(report.as_ref()).await {
+                return Err(GooseError::InvalidOption {
+                    option: "--report-file".to_string(),
+                    value: self.get_report_file_path().unwrap(),
+                    detail: format!("Failed to create report file: {}", e),
+                });
+            };
+            // Be sure the file flushes to disk.
+            report_file.flush().await?;
+
+            info!(
+                "html report file written to: {}",
+                self.get_report_file_path().unwrap()
+            );
+        }
+
+        Ok(())
+    }
+
+    // Write a report of the load test so far to `path` when requested by a Controller,
+    // formatted as json if the path ends with `.json` and otherwise as HTML. Controller
+    // clients can only write reports under `--controller-report-dir`.
+    pub(crate) async fn write_controller_report(
+        &mut self,
+        goose_attack_run_state: &mut GooseAttackRunState,
+        path: &str,
+    ) -> Result<(), GooseError> {
+        // Refuse absolute paths and `..`, which could overwrite any file Goose can write.
+        let relative_path = std::path::Path::new(path);
+        if !relative_path.components().all(|component| {
+            matches!(
+                component,
+                std::path::Component::Normal(_) | std::path::Component::CurDir
+            )
+        }) {
+            return Err(GooseError::InvalidOption {
+                option: "`report`".to_string(),
+                value: path.to_string(),
+                detail: "Report paths must be relative to --controller-report-dir, and can't contain `..`."
+                    .to_string(),
+            });
+        }
+        let report_path =
+            std::path::Path::new(&self.configuration.controller_report_dir).join(relative_path);
+
+        // Receive metrics before writing them.
+        self.sync_metrics(goose_attack_run_state, true).await?;
+        self.update_duration();
+
+        let report = if path.to_lowercase().ends_with(".json") {
+            serde_json::to_string(&self.metrics).expect("unexpected serde failure")
+        } else {
+            // Close the current step at the time of the report so it's included in the
+            // steps overview and graphs, then remove it again as the load test continues.
+            let running = self.attack_phase != AttackPhase::Idle;
+            if running {
+                self.metrics.history.push(TestPlanHistory::step(
+                    TestPlanStepAction::Finished,
+                    goose_attack_run_state.active_users,
+                    None,
                 ));
+            }
+            let report = self.build_html_report();
+            if running {
+                self.metrics.history.pop();
+            }
+            report
+        };
 
-                // Collect aggregated request and response metrics.
-                raw_aggregate_total_count += total_request_count;
-                raw_aggregate_fail_count += request.fail_count;
-                raw_aggregate_response_time_counter += request.raw_data.total_time;
-                raw_aggregate_response_time_minimum = update_min_time(
-                    raw_aggregate_response_time_minimum,
-                    request.raw_data.minimum_time,
-                );
-                raw_aggregate_response_time_maximum = update_max_time(
-                    raw_aggregate_response_time_maximum,
-                    request.raw_data.maximum_time,
-                );
-                raw_aggregate_response_times =
-                    merge_times(raw_aggregate_response_times, request.raw_data.times.clone());
+        let mut report_file = tokio::fs::File::create(&report_path).await?;
+        report_file.write_all(report.as_ref()).await?;
+        // Be sure the file flushes to disk.
+        report_file.flush().await?;
+
+        info!("report file written to: {}", report_path.display());
+
+        Ok(())
+    }
+
+    // Build an HTML-formatted report of the load test so far.
+    pub(crate) fn build_html_report(&self) -> String {
+        let test_start_time = self.metrics.history.first().unwrap().timestamp;
+
+        // Prepare report summary variables.
+        let users = self.metrics.maximum_users.to_string();
+
+        let mut steps_overview = String::new();
+        for step in self.metrics.history.windows(2) {
+            let (seconds, minutes, hours) = self
+                .metrics
+                .get_seconds_minutes_hours(&step[0].timestamp, &step[1].timestamp);
+            let started = step[0].timestamp.format("%y-%m-%d %H:%M:%S");
+            let stopped = step[1].timestamp.format("%y-%m-%d %H:%M:%S");
+            // Stage names are user-defined, escape them for HTML.
+            let action = step[0]
+                .label()
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;");
+            match &step[0].action {
+                // For maintaining just show the current number of users.
+                TestPlanStepAction::Maintaining => {
+                    let _ = write!(steps_overview,
+                                   "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:02}:{:02}:{:02}</td><td>{}</td></tr>",
+                                   action,
+                                   started,
+                                   stopped,
+                                   hours,
+                                   minutes,
+                                   seconds,
+                                   step[0].users,
+                    );
+                }
+                // For increasing show the current number of users to the new number of users.
+                TestPlanStepAction::Increasing => {
+                    let _ = write!(steps_overview,
+                                   "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:02}:{:02}:{:02}</td><td>{} &rarr; {}</td></tr>",
+                                   action,
+                                   started,
+                                   stopped,
+                                   hours,
+                                   minutes,
+                                   seconds,
+                                   step[0].users,
+                                   step[1].users,
+                    );
+                }
+                // For decreasing show the new number of users from the current number of users.
+                TestPlanStepAction::Decreasing | TestPlanStepAction::Canceling => {
+                    let _ = write!(steps_overview,
+                                   "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:02}:{:02}:{:02}</td><td>{} &larr; {}</td></tr>",
+                                   action,
+                                   started,
+                                   stopped,
+                                   hours,
+                                   minutes,
+                                   seconds,
+                                   step[1].users,
+                                   step[0].users,
+                    );
+                }
+                TestPlanStepAction::Finished => {
+                    unreachable!("there shouldn't be a step after finished");
+                }
             }
+        }
 
-            // Prepare aggregate per-request metrics.
-            let (raw_aggregate_requests_per_second, raw_aggregate_failures_per_second) =
-                per_second_calculations(
-                    self.metrics.duration,
-                    raw_aggregate_total_count,
-                    raw_aggregate_fail_count,
-                );
+        // Build a comma separated list of hosts.
+        let hosts = &self.metrics.hosts.clone().into_iter().join(", ");
+
+        // Display the throttle rate, if enabled.
+        let throttle = if self.metrics.throttle_requests > 0 {
+            format!(
+                "<p>Throttle: <span>{} requests per second</span></p>",
+                format_number(self.metrics.throttle_requests)
+            )
+        } else {
+            "".to_string()
+        };
+
+        // Prepare requests and responses variables.
+        let mut raw_request_metrics = Vec::new();
+        let mut co_request_metrics = Vec::new();
+        let mut raw_response_metrics = Vec::new();
+        let mut co_response_metrics = Vec::new();
+        let mut raw_aggregate_total_count = 0;
+        let mut co_aggregate_total_count = 0;
+        let mut raw_aggregate_fail_count = 0;
+        let mut raw_aggregate_response_time_counter: usize = 0;
+        let mut raw_aggregate_response_time_minimum: usize = 0;
+        let mut raw_aggregate_response_time_maximum: usize = 0;
+        let mut raw_aggregate_response_times: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut co_aggregate_response_time_counter: usize = 0;
+        let mut co_aggregate_response_time_maximum: usize = 0;
+        let mut co_aggregate_response_times: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut co_data = false;
+        for (request_key, request) in self.metrics.requests.iter().sorted() {
+            // Determine whether or not to include Coordinated Omission data.
+            if !co_data && request.coordinated_omission_data.is_some() {
+                co_data = true;
+            }
+            let method = format!("{}", request.method);
+            // The request_key is "{method} {name}", so by stripping the "{method} "
+            // prefix we get the name.
+            let name = request_key
+                .strip_prefix(&format!("{} ", request.method))
+                .unwrap()
+                .to_string();
+            let total_request_count = request.success_count + request.fail_count;
+            let (requests_per_second, failures_per_second) = per_second_calculations(
+                self.metrics.duration,
+                total_request_count,
+                request.fail_count,
+            );
+            // Prepare per-request metrics.
             raw_request_metrics.push(report::RequestMetric {
-                method: "".to_string(),
-                name: "Aggregated".to_string(),
-                number_of_requests: raw_aggregate_total_count,
-                number_of_failures: raw_aggregate_fail_count,
+                method: method.to_string(),
+                name: name.to_string(),
+                number_of_requests: total_request_count,
+                number_of_failures: request.fail_count,
                 response_time_average: format!(
                     "{:.2}",
-                    raw_aggregate_response_time_counter as f32 / raw_aggregate_total_count as f32
+                    request.raw_data.total_time as f32 / request.raw_data.counter as f32
                 ),
-                response_time_minimum: raw_aggregate_response_time_minimum,
-                response_time_maximum: raw_aggregate_response_time_maximum,
-                requests_per_second: format!("{:.2}", raw_aggregate_requests_per_second),
-                failures_per_second: format!("{:.2}", raw_aggregate_failures_per_second),
+                response_time_minimum: request.raw_data.minimum_time,
+                response_time_maximum: request.raw_data.maximum_time,
+                requests_per_second: format!("{:.2}", requests_per_second),
+                failures_per_second: format!("{:.2}", failures_per_second),
             });
 
-            // Prepare aggregate per-response metrics.
+            // Prepare per-response metrics.
             raw_response_metrics.push(report::get_response_metric(
-                "",
-                "Aggregated",
-                &raw_aggregate_response_times,
-                raw_aggregate_total_count,
+                &method,
+                &name,
+                &request.raw_data.times,
+                request.raw_data.counter,
+                request.raw_data.minimum_time,
+                request.raw_data.maximum_time,
+            ));
+
+            // Collect aggregated request and response metrics.
+            raw_aggregate_total_count += total_request_count;
+            raw_aggregate_fail_count += request.fail_count;
+            raw_aggregate_response_time_counter += request.raw_data.total_time;
+            raw_aggregate_response_time_minimum = update_min_time(
                 raw_aggregate_response_time_minimum,
+                request.raw_data.minimum_time,
+            );
+            raw_aggregate_response_time_maximum = update_max_time(
                 raw_aggregate_response_time_maximum,
-            ));
+                request.raw_data.maximum_time,
+            );
+            raw_aggregate_response_times =
+                merge_times(raw_aggregate_response_times, request.raw_data.times.clone());
+        }
 
-            // Compile the request metrics template.
-            let mut raw_requests_rows = Vec::new();
-            for metric in raw_request_metrics {
-                raw_requests_rows.push(report::raw_request_metrics_row(metric));
-            }
+        // Prepare aggregate per-request metrics.
+        let (raw_aggregate_requests_per_second, raw_aggregate_failures_per_second) =
+            per_second_calculations(
+                self.metrics.duration,
+                raw_aggregate_total_count,
+                raw_aggregate_fail_count,
+            );
+        raw_request_metrics.push(report::RequestMetric {
+            method: "".to_string(),
+            name: "Aggregated".to_string(),
+            number_of_requests: raw_aggregate_total_count,
+            number_of_failures: raw_aggregate_fail_count,
+            response_time_average: format!(
+                "{:.2}",
+                raw_aggregate_response_time_counter as f32 / raw_aggregate_total_count as f32
+            ),
+            response_time_minimum: raw_aggregate_response_time_minimum,
+            response_time_maximum: raw_aggregate_response_time_maximum,
+            requests_per_second: format!("{:.2}", raw_aggregate_requests_per_second),
+            failures_per_second: format!("{:.2}", raw_aggregate_failures_per_second),
+        });
+
+        // Prepare aggregate per-response metrics.
+        raw_response_metrics.push(report::get_response_metric(
+            "",
+            "Aggregated",
+            &raw_aggregate_response_times,
+            raw_aggregate_total_count,
+            raw_aggregate_response_time_minimum,
+            raw_aggregate_response_time_maximum,
+        ));
+
+        // Compile the request metrics template.
+        let mut raw_requests_rows = Vec::new();
+        for metric in raw_request_metrics {
+            raw_requests_rows.push(report::raw_request_metrics_row(metric));
+        }
 
-            // Compile the response metrics template.
-            let mut raw_responses_rows = Vec::new();
-            for metric in raw_response_metrics {
-                raw_responses_rows.push(report::response_metrics_row(metric));
-            }
+        // Compile the response metrics template.
+        let mut raw_responses_rows = Vec::new();
+        for metric in raw_response_metrics {
+            raw_responses_rows.push(report::response_metrics_row(metric));
+        }
 
-            let co_requests_template: String;
-            let co_responses_template: String;
-            if co_data {
-                for (request_key, request) in self.metrics.requests.iter().sorted() {
-                    if let Some(coordinated_omission_data) =
-                    request.coordinated_omission_data.as_ref()
-                    {
-                        let method = format!("{}", request.method);
-                        // The request_key is "{method} {name}", so by stripping the "{method} "
-                        // prefix we get the name.
-                        let name = request_key
-                            .strip_prefix(&format!("{} ", request.method))
-                            .unwrap()
-                            .to_string();
-                        let raw_average =
-                            request.raw_data.total_time as f32 / request.raw_data.counter as f32;
-                        let co_average = coordinated_omission_data.total_time as f32
-                            / coordinated_omission_data.counter as f32;
-                        // Prepare per-request metrics.
-                        co_request_metrics.push(report::CORequestMetric {
-                            method: method.to_string(),
-                            name: name.to_string(),
-                            response_time_average: format!("{:.2}", co_average),
-                            response_time_standard_deviation: format!(
-                                "{:.2}",
-                                util::standard_deviation(raw_average, co_average)
-                            ),
-                            response_time_maximum: coordinated_omission_data.maximum_time,
-                        });
+        let co_requests_template: String;
+        let co_responses_template: String;
+        if co_data {
+            for (request_key, request) in self.metrics.requests.iter().sorted() {
+                if let Some(coordinated_omission_data) = request.coordinated_omission_data.as_ref()
+                {
+                    let method = format!("{}", request.method);
+                    // The request_key is "{method} {name}", so by stripping the "{method} "
+                    // prefix we get the name.
+                    let name = request_key
+                        .strip_prefix(&format!("{} ", request.method))
+                        .unwrap()
+                        .to_string();
+                    let raw_average =
+                        request.raw_data.total_time as f32 / request.raw_data.counter as f32;
+                    let co_average = coordinated_omission_data.total_time as f32
+                        / coordinated_omission_data.counter as f32;
+                    // Prepare per-request metrics.
+                    co_request_metrics.push(report::CORequestMetric {
+                        method: method.to_string(),
+                        name: name.to_string(),
+                        response_time_average: format!("{:.2}", co_average),
+                        response_time_standard_deviation: format!(
+                            "{:.2}",
+                            util::standard_deviation(raw_average, co_average)
+                        ),
+                        response_time_maximum: coordinated_omission_data.maximum_time,
+                    });
 
-                        // Prepare per-response metrics.
-                        co_response_metrics.push(report::get_response_metric(
-                            &method,
-                            &name,
-                            &coordinated_omission_data.times,
-                            coordinated_omission_data.counter,
-                            coordinated_omission_data.minimum_time,
-                            coordinated_omission_data.maximum_time,
-                        ));
+                    // Prepare per-response metrics.
+                    co_response_metrics.push(report::get_response_metric(
+                        &method,
+                        &name,
+                        &coordinated_omission_data.times,
+                        coordinated_omission_data.counter,
+                        coordinated_omission_data.minimum_time,
+                        coordinated_omission_data.maximum_time,
+                    ));
 
-                        // Collect aggregated request and response metrics.
-                        co_aggregate_response_time_counter += coordinated_omission_data.total_time;
-                        co_aggregate_response_time_maximum = update_max_time(
-                            co_aggregate_response_time_maximum,
-                            coordinated_omission_data.maximum_time,
-                        );
-                        co_aggregate_response_times = merge_times(
-                            co_aggregate_response_times,
-                            coordinated_omission_data.times.clone(),
-                        );
-                    }
-                    let total_request_count = request.success_count + request.fail_count;
-                    co_aggregate_total_count += total_request_count;
+                    // Collect aggregated request and response metrics.
+                    co_aggregate_response_time_counter += coordinated_omission_data.total_time;
+                    co_aggregate_response_time_maximum = update_max_time(
+                        co_aggregate_response_time_maximum,
+                        coordinated_omission_data.maximum_time,
+                    );
+                    co_aggregate_response_times = merge_times(
+                        co_aggregate_response_times,
+                        coordinated_omission_data.times.clone(),
+                    );
                 }
-                let co_average =
-                    co_aggregate_response_time_counter as f32 / co_aggregate_total_count as f32;
-                let raw_average =
-                    raw_aggregate_response_time_counter as f32 / raw_aggregate_total_count as f32;
-                co_request_metrics.push(report::CORequestMetric {
-                    method: "".to_string(),
-                    name: "Aggregated".to_string(),
-                    response_time_average: format!(
-                        "{:.2}",
-                        co_aggregate_response_time_counter as f32 / co_aggregate_total_count as f32
-                    ),
-                    response_time_standard_deviation: format!(
-                        "{:.2}",
-                        util::standard_deviation(raw_average, co_average),
-                    ),
-                    response_time_maximum: co_aggregate_response_time_maximum,
-                });
-
-                // Prepare aggregate per-response metrics.
-                co_response_metrics.push(report::get_response_metric(
-                    "",
-                    "Aggregated",
-                    &co_aggregate_response_times,
-                    co_aggregate_total_count,
-                    raw_aggregate_response_time_minimum,
-                    co_aggregate_response_time_maximum,
-                ));
+                let total_request_count = request.success_count + request.fail_count;
+                co_aggregate_total_count += total_request_count;
+            }
+            let co_average =
+                co_aggregate_response_time_counter as f32 / co_aggregate_total_count as f32;
+            let raw_average =
+                raw_aggregate_response_time_counter as f32 / raw_aggregate_total_count as f32;
+            co_request_metrics.push(report::CORequestMetric {
+                method: "".to_string(),
+                name: "Aggregated".to_string(),
+                response_time_average: format!(
+                    "{:.2}",
+                    co_aggregate_response_time_counter as f32 / co_aggregate_total_count as f32
+                ),
+                response_time_standard_deviation: format!(
+                    "{:.2}",
+                    util::standard_deviation(raw_average, co_average),
+                ),
+                response_time_maximum: co_aggregate_response_time_maximum,
+            });
 
-                // Compile the co_request metrics rows.
-                let mut co_request_rows = Vec::new();
-                for metric in co_request_metrics {
-                    co_request_rows.push(report::coordinated_omission_request_metrics_row(metric));
-                }
+            // Prepare aggregate per-response metrics.
+            co_response_metrics.push(report::get_response_metric(
+                "",
+                "Aggregated",
+                &co_aggregate_response_times,
+                co_aggregate_total_count,
+                raw_aggregate_response_time_minimum,
+                co_aggregate_response_time_maximum,
+            ));
 
-                // Compile the status_code metrics template.
-                co_requests_template = report::coordinated_omission_request_metrics_template(
-                    &co_request_rows.join("\n"),
-                );
+            // Compile the co_request metrics rows.
+            let mut co_request_rows = Vec::new();
+            for metric in co_request_metrics {
+                co_request_rows.push(report::coordinated_omission_request_metrics_row(metric));
+            }
 
-                // Compile the co_request metrics rows.
-                let mut co_response_rows = Vec::new();
-                for metric in co_response_metrics {
-                    co_response_rows
-                        .push(report::coordinated_omission_response_metrics_row(metric));
-                }
+            // Compile the status_code metrics template.
+            co_requests_template =
+                report::coordinated_omission_request_metrics_template(&co_request_rows.join("\n"));
 
-                // Compile the status_code metrics template.
-                co_responses_template = report::coordinated_omission_response_metrics_template(
-                    &co_response_rows.join("\n"),
-                );
-            } else {
-                // If --status-codes is not enabled, return an empty template.
-                co_requests_template = "".to_string();
-                co_responses_template = "".to_string();
+            // Compile the co_request metrics rows.
+            let mut co_response_rows = Vec::new();
+            for metric in co_response_metrics {
+                co_response_rows.push(report::coordinated_omission_response_metrics_row(metric));
             }
 
-            // Only build the transactions template if --no-transaction-metrics isn't enabled.
-            let transactions_template: String;
-            if !self.configuration.no_transaction_metrics {
-                let mut transaction_metrics = Vec::new();
-                let mut aggregate_total_count = 0;
-                let mut aggregate_fail_count = 0;
-                let mut aggregate_transaction_time_counter: usize = 0;
-                let mut aggregate_transaction_time_minimum: usize = 0;
-                let mut aggregate_transaction_time_maximum: usize = 0;
-                let mut aggregate_transaction_times: BTreeMap<usize, usize> = BTreeMap::new();
-                for (scenario_counter, scenario) in self.metrics.transactions.iter().enumerate() {
-                    for (transaction_counter, transaction) in scenario.iter().enumerate() {
-                        if transaction_counter == 0 {
-                            // Only the scenario_name is used for scenarios.
-                            transaction_metrics.push(report::TransactionMetric {
-                                is_scenario: true,
-                                transaction: "".to_string(),
-                                name: transaction.scenario_name.to_string(),
-                                number_of_requests: 0,
-                                number_of_failures: 0,
-                                response_time_average: "".to_string(),
-                                response_time_minimum: 0,
-                                response_time_maximum: 0,
-                                requests_per_second: "".to_string(),
-                                failures_per_second: "".to_string(),
-                            });
-                        }
-                        let total_run_count = transaction.success_count + transaction.fail_count;
-                        let (requests_per_second, failures_per_second) = per_second_calculations(
-                            self.metrics.duration,
-                            total_run_count,
-                            transaction.fail_count,
-                        );
-                        let average = match transaction.counter {
-                            0 => 0.00,
-                            _ => transaction.total_time as f32 / transaction.counter as f32,
-                        };
+            // Compile the status_code metrics template.
+            co_responses_template = report::coordinated_omission_response_metrics_template(
+                &co_response_rows.join("\n"),
+            );
+        } else {
+            // If --status-codes is not enabled, return an empty template.
+            co_requests_template = "".to_string();
+            co_responses_template = "".to_string();
+        }
+
+        // Only build the transactions template if --no-transaction-metrics isn't enabled.
+        let transactions_template: String;
+        if !self.configuration.no_transaction_metrics {
+            let mut transaction_metrics = Vec::new();
+            let mut aggregate_total_count = 0;
+            let mut aggregate_fail_count = 0;
+            let mut aggregate_transaction_time_counter: usize = 0;
+            let mut aggregate_transaction_time_minimum: usize = 0;
+            let mut aggregate_transaction_time_maximum: usize = 0;
+            let mut aggregate_transaction_times: BTreeMap<usize, usize> = BTreeMap::new();
+            for (scenario_counter, scenario) in self.metrics.transactions.iter().enumerate() {
+                for (transaction_counter, transaction) in scenario.iter().enumerate() {
+                    if transaction_counter == 0 {
+                        // Only the scenario_name is used for scenarios.
                         transaction_metrics.push(report::TransactionMetric {
-                            is_scenario: false,
-                            transaction: format!("{}.{}", scenario_counter, transaction_counter),
-                            name: transaction.transaction_name.to_string(),
-                            number_of_requests: total_run_count,
-                            number_of_failures: transaction.fail_count,
-                            response_time_average: format!("{:.2}", average),
-                            response_time_minimum: transaction.min_time,
-                            response_time_maximum: transaction.max_time,
-                            requests_per_second: format!("{:.2}", requests_per_second),
-                            failures_per_second: format!("{:.2}", failures_per_second),
+                            is_scenario: true,
+                            transaction: "".to_string(),
+                            name: transaction.scenario_name.to_string(),
+                            number_of_requests: 0,
+                            number_of_failures: 0,
+                            response_time_average: "".to_string(),
+                            response_time_minimum: 0,
+                            response_time_maximum: 0,
+                            requests_per_second: "".to_string(),
+                            failures_per_second: "".to_string(),
                         });
-
-                        aggregate_total_count += total_run_count;
-                        aggregate_fail_count += transaction.fail_count;
-                        aggregate_transaction_times =
-                            merge_times(aggregate_transaction_times, transaction.times.clone());
-                        aggregate_transaction_time_counter += &transaction.counter;
-                        aggregate_transaction_time_minimum = update_min_time(
-                            aggregate_transaction_time_minimum,
-                            transaction.min_time,
-                        );
-                        aggregate_transaction_time_maximum = update_max_time(
-                            aggregate_transaction_time_maximum,
-                            transaction.max_time,
-                        );
                     }
-                }
-
-                let (aggregate_requests_per_second, aggregate_failures_per_second) =
-                    per_second_calculations(
+                    let total_run_count = transaction.success_count + transaction.fail_count;
+                    let (requests_per_second, failures_per_second) = per_second_calculations(
                         self.metrics.duration,
-                        aggregate_total_count,
-                        aggregate_fail_count,
+                        total_run_count,
+                        transaction.fail_count,
                     );
-                transaction_metrics.push(report::TransactionMetric {
-                    is_scenario: false,
-                    transaction: "".to_string(),
-                    name: "Aggregated".to_string(),
-                    number_of_requests: aggregate_total_count,
-                    number_of_failures: aggregate_fail_count,
-                    response_time_average: format!(
-                        "{:.2}",
-                        raw_aggregate_response_time_counter as f32 / aggregate_total_count as f32
-                    ),
-                    response_time_minimum: aggregate_transaction_time_minimum,
-                    response_time_maximum: aggregate_transaction_time_maximum,
-                    requests_per_second: format!("{:.2}", aggregate_requests_per_second),
-                    failures_per_second: format!("{:.2}", aggregate_failures_per_second),
-                });
-                let mut transactions_rows = Vec::new();
-                // Compile the transaction metrics template.
-                for metric in transaction_metrics {
-                    transactions_rows.push(report::transaction_metrics_row(metric));
-                }
-
-                transactions_template = report::transaction_metrics_template(
-                    &transactions_rows.join("\n"),
-                    self.graph_data
-                        .get_transactions_per_second_graph(!self.configuration.no_granular_report)
-                        .get_markup(&self.metrics.history, test_start_time),
-                );
-            } else {
-                transactions_template = "".to_string();
-            }
-
-            // Only build the scenarios template if --no-senario-metrics isn't enabled.
-            let scenarios_template: String;
-            if !self.configuration.no_scenario_metrics {
-                let mut scenario_metrics = Vec::new();
-                let mut aggregate_users = 0;
-                let mut aggregate_count = 0;
-                let mut aggregate_scenario_time_counter: usize = 0;
-                let mut aggregate_scenario_time_minimum: usize = 0;
-                let mut aggregate_scenario_time_maximum: usize = 0;
-                let mut aggregate_scenario_times: BTreeMap<usize, usize> = BTreeMap::new();
-                let mut aggregate_iterations = 0.0;
-                let mut aggregate_response_time_counter = 0.0;
-                for scenario in &self.metrics.scenarios {
-                    let (count_per_second, _failures_per_second) =
-                        per_second_calculations(self.metrics.duration, scenario.counter, 0);
-                    let average = match scenario.counter {
+                    let average = match transaction.counter {
                         0 => 0.00,
-                        _ => scenario.total_time as f32 / scenario.counter as f32,
+                        _ => transaction.total_time as f32 / transaction.counter as f32,
                     };
-                    let iterations = scenario.counter as f32 / scenario.users.len() as f32;
-                    scenario_metrics.push(report::ScenarioMetric {
-                        name: scenario.name.to_string(),
-                        users: scenario.users.len(),
-                        count: scenario.counter,
+                    transaction_metrics.push(report::TransactionMetric {
+                        is_scenario: false,
+                        transaction: format!("{}.{}", scenario_counter, transaction_counter),
+                        name: transaction.transaction_name.to_string(),
+                        number_of_requests: total_run_count,
+                        number_of_failures: transaction.fail_count,
                         response_time_average: format!("{:.2}", average),
-                        response_time_minimum: scenario.min_time,
-                        response_time_maximum: scenario.max_time,
-                        count_per_second: format!("{:.2}", count_per_second),
-                        iterations: format!("{:.2}", iterations),
+                        response_time_minimum: transaction.min_time,
+                        response_time_maximum: transaction.max_time,
+                        requests_per_second: format!("{:.2}", requests_per_second),
+                        failures_per_second: format!("{:.2}", failures_per_second),
                     });
+                    report_transaction_groups(
+                        &mut transaction_metrics,
+                        self.metrics.duration,
+                        transaction,
+                        &format!("{}.{}", scenario_counter, transaction_counter),
+                    );
 
-                    aggregate_users += scenario.users.len();
-                    aggregate_count += scenario.counter;
-                    aggregate_scenario_times =
-                        merge_times(aggregate_scenario_times, scenario.times.clone());
-                    aggregate_scenario_time_counter += &scenario.counter;
-                    aggregate_scenario_time_minimum =
-                        update_min_time(aggregate_scenario_time_minimum, scenario.min_time);
-                    aggregate_scenario_time_maximum =
-                        update_max_time(aggregate_scenario_time_maximum, scenario.max_time);
-                    aggregate_iterations += iterations;
-                    aggregate_response_time_counter += scenario.total_time as f32;
+                    aggregate_total_count += total_run_count;
+                    aggregate_fail_count += transaction.fail_count;
+                    aggregate_transaction_times =
+                        merge_times(aggregate_transaction_times, transaction.times.clone());
+                    aggregate_transaction_time_counter += &transaction.counter;
+                    aggregate_transaction_time_minimum =
+                        update_min_time(aggregate_transaction_time_minimum, transaction.min_time);
+                    aggregate_transaction_time_maximum =
+                        update_max_time(aggregate_transaction_time_maximum, transaction.max_time);
                 }
+            }
 
-                let (aggregate_count_per_second, _aggregate_failures_per_second) =
-                    per_second_calculations(self.metrics.duration, aggregate_count, 0);
+            let (aggregate_requests_per_second, aggregate_failures_per_second) =
+                per_second_calculations(
+                    self.metrics.duration,
+                    aggregate_total_count,
+                    aggregate_fail_count,
+                );
+            transaction_metrics.push(report::TransactionMetric {
+                is_scenario: false,
+                transaction: "".to_string(),
+                name: "Aggregated".to_string(),
+                number_of_requests: aggregate_total_count,
+                number_of_failures: aggregate_fail_count,
+                response_time_average: format!(
+                    "{:.2}",
+                    raw_aggregate_response_time_counter as f32 / aggregate_total_count as f32
+                ),
+                response_time_minimum: aggregate_transaction_time_minimum,
+                response_time_maximum: aggregate_transaction_time_maximum,
+                requests_per_second: format!("{:.2}", aggregate_requests_per_second),
+                failures_per_second: format!("{:.2}", aggregate_failures_per_second),
+            });
+            let mut transactions_rows = Vec::new();
+            // Compile the transaction metrics template.
+            for metric in transaction_metrics {
+                transactions_rows.push(report::transaction_metrics_row(metric));
+            }
+
+            transactions_template = report::transaction_metrics_template(
+                &transactions_rows.join("\n"),
+                self.graph_data
+                    .get_transactions_per_second_graph(!self.configuration.no_granular_report)
+                    .get_markup(&self.metrics.history, test_start_time),
+            );
+        } else {
+            transactions_template = "".to_string();
+        }
+
+        // Only build the scenarios template if --no-senario-metrics isn't enabled.
+        let scenarios_template: String;
+        if !self.configuration.no_scenario_metrics {
+            let mut scenario_metrics = Vec::new();
+            let mut aggregate_users = 0;
+            let mut aggregate_count = 0;
+            let mut aggregate_scenario_time_counter: usize = 0;
+            let mut aggregate_scenario_time_minimum: usize = 0;
+            let mut aggregate_scenario_time_maximum: usize = 0;
+            let mut aggregate_scenario_times: BTreeMap<usize, usize> = BTreeMap::new();
+            let mut aggregate_iterations = 0.0;
+            let mut aggregate_response_time_counter = 0.0;
+            for scenario in &self.metrics.scenarios {
+                let (count_per_second, _failures_per_second) =
+                    per_second_calculations(self.metrics.duration, scenario.counter, 0);
+                let average = match scenario.counter {
+                    0 => 0.00,
+                    _ => scenario.total_time as f32 / scenario.counter as f32,
+                };
+                let iterations = scenario.counter as f32 / scenario.users.len() as f32;
                 scenario_metrics.push(report::ScenarioMetric {
-                    name: "Aggregated".to_string(),
-                    users: aggregate_users,
-                    count: aggregate_count,
-                    response_time_average: format!(
-                        "{:.2}",
-                        aggregate_response_time_counter as f32 / aggregate_count as f32
-                    ),
-                    response_time_minimum: aggregate_scenario_time_minimum,
-                    response_time_maximum: aggregate_scenario_time_maximum,
-                    count_per_second: format!("{:.2}", aggregate_count_per_second),
-                    iterations: format!("{:.2}", aggregate_iterations),
+                    name: scenario.name.to_string(),
+                    users: scenario.users.len(),
+                    count: scenario.counter,
+                    response_time_average: format!("{:.2}", average),
+                    response_time_minimum: scenario.min_time,
+                    response_time_maximum: scenario.max_time,
+                    count_per_second: format!("{:.2}", count_per_second),
+                    iterations: format!("{:.2}", iterations),
                 });
-                let mut scenarios_rows = Vec::new();
-                // Compile the scenario metrics template.
-                for metric in scenario_metrics {
-                    scenarios_rows.push(report::scenario_metrics_row(metric));
-                }
 
-                scenarios_template = report::scenario_metrics_template(
-                    &scenarios_rows.join("\n"),
-                    self.graph_data
-                        .get_scenarios_per_second_graph(!self.configuration.no_granular_report)
-                        .get_markup(&self.metrics.history, test_start_time),
-                );
-            } else {
-                scenarios_template = "".to_string();
+                aggregate_users += scenario.users.len();
+                aggregate_count += scenario.counter;
+                aggregate_scenario_times =
+                    merge_times(aggregate_scenario_times, scenario.times.clone());
+                aggregate_scenario_time_counter += &scenario.counter;
+                aggregate_scenario_time_minimum =
+                    update_min_time(aggregate_scenario_time_minimum, scenario.min_time);
+                aggregate_scenario_time_maximum =
+                    update_max_time(aggregate_scenario_time_maximum, scenario.max_time);
+                aggregate_iterations += iterations;
+                aggregate_response_time_counter += scenario.total_time as f32;
             }
 
-            // Only build the transactions template if --no-transaction-metrics isn't enabled.
-            let errors_template: String = if !self.metrics.errors.is_empty() {
-                let mut error_rows = Vec::new();
-                for error in self.metrics.errors.values() {
-                    error_rows.push(report::error_row(error));
-                }
+            let (aggregate_count_per_second, _aggregate_failures_per_second) =
+                per_second_calculations(self.metrics.duration, aggregate_count, 0);
+            scenario_metrics.push(report::ScenarioMetric {
+                name: "Aggregated".to_string(),
+                users: aggregate_users,
+                count: aggregate_count,
+                response_time_average: format!(
+                    "{:.2}",
+                    aggregate_response_time_counter as f32 / aggregate_count as f32
+                ),
+                response_time_minimum: aggregate_scenario_time_minimum,
+                response_time_maximum: aggregate_scenario_time_maximum,
+                count_per_second: format!("{:.2}", aggregate_count_per_second),
+                iterations: format!("{:.2}", aggregate_iterations),
+            });
+            let mut scenarios_rows = Vec::new();
+            // Compile the scenario metrics template.
+            for metric in scenario_metrics {
+                scenarios_rows.push(report::scenario_metrics_row(metric));
+            }
 
-                report::errors_template(
-                    &error_rows.join("\n"),
-                    self.graph_data
-                        .get_errors_per_second_graph(!self.configuration.no_granular_report)
-                        .get_markup(&self.metrics.history, test_start_time),
-                )
-            } else {
-                "".to_string()
-            };
+            scenarios_template = report::scenario_metrics_template(
+                &scenarios_rows.join("\n"),
+                self.graph_data
+                    .get_scenarios_per_second_graph(!self.configuration.no_granular_report)
+                    .get_markup(&self.metrics.history, test_start_time),
+            );
+        } else {
+            scenarios_template = "".to_string();
+        }
 
-            // Only build the status_code template if --no-status-codes is not enabled.
-            let status_code_template: String = if !self.configuration.no_status_codes {
-                let mut status_code_metrics = Vec::new();
-                let mut aggregated_status_code_counts: HashMap<u16, usize> = HashMap::new();
-                for (request_key, request) in self.metrics.requests.iter().sorted() {
-                    let method = format!("{}", request.method);
-                    // The request_key is "{method} {name}", so by stripping the "{method} "
-                    // prefix we get the name.
-                    let name = request_key
-                        .strip_prefix(&format!("{} ", request.method))
-                        .unwrap()
-                        .to_string();
+        // Only build the transactions template if --no-transaction-metrics isn't enabled.
+        let errors_template: String = if !self.metrics.errors.is_empty() {
+            let mut error_rows = Vec::new();
+            for error in self.metrics.errors.values() {
+                error_rows.push(report::error_row(error));
+            }
 
-                    // Build a list of status codes, and update the aggregate record.
-                    let codes = prepare_status_codes(
-                        &request.status_code_counts,
-                        &mut Some(&mut aggregated_status_code_counts),
-                    );
+            report::errors_template(
+                &error_rows.join("\n"),
+                self.graph_data
+                    .get_errors_per_second_graph(!self.configuration.no_granular_report)
+                    .get_markup(&self.metrics.history, test_start_time),
+            )
+        } else {
+            "".to_string()
+        };
 
-                    // Add a row of data for the status code table.
-                    status_code_metrics.push(report::StatusCodeMetric {
-                        method,
-                        name,
-                        status_codes: codes,
-                    });
-                }
+        // Only build the status_code template if --no-status-codes is not enabled.
+        let status_code_template: String = if !self.configuration.no_status_codes {
+            let mut status_code_metrics = Vec::new();
+            let mut aggregated_status_code_counts: HashMap<u16, usize> = HashMap::new();
+            for (request_key, request) in self.metrics.requests.iter().sorted() {
+                let method = format!("{}", request.method);
+                // The request_key is "{method} {name}", so by stripping the "{method} "
+                // prefix we get the name.
+                let name = request_key
+                    .strip_prefix(&format!("{} ", request.method))
+                    .unwrap()
+                    .to_string();
 
-                // Build a list of aggregate status codes.
-                let aggregated_codes =
-                    prepare_status_codes(&aggregated_status_code_counts, &mut None);
+                // Build a list of status codes, and update the aggregate record.
+                let codes = prepare_status_codes(
+                    &request.status_code_counts,
+                    &mut Some(&mut aggregated_status_code_counts),
+                );
 
-                // Add a final row of aggregate data for the status code table.
+                // Add a row of data for the status code table.
                 status_code_metrics.push(report::StatusCodeMetric {
-                    method: "".to_string(),
-                    name: "Aggregated".to_string(),
-                    status_codes: aggregated_codes,
+                    method,
+                    name,
+                    status_codes: codes,
                 });
+            }
 
-                // Compile the status_code metrics rows.
-                let mut status_code_rows = Vec::new();
-                for metric in status_code_metrics {
-                    status_code_rows.push(report::status_code_metrics_row(metric));
-                }
+            // Build a list of aggregate status codes.
+            let aggregated_codes = prepare_status_codes(&aggregated_status_code_counts, &mut None);
 
-                // Compile the status_code metrics template.
-                report::status_code_metrics_template(&status_code_rows.join("\n"))
-            } else {
-                // If --status-codes is not enabled, return an empty template.
-                "".to_string()
-            };
+            // Add a final row of aggregate data for the status code table.
+            status_code_metrics.push(report::StatusCodeMetric {
+                method: "".to_string(),
+                name: "Aggregated".to_string(),
+                status_codes: aggregated_codes,
+            });
 
-            // Compile the report template.
-            let report = report::build_report(
-                &users,
-                &steps_overview,
-                hosts,
-                report::GooseReportTemplates {
-                    raw_requests_template: &raw_requests_rows.join("\n"),
-                    raw_responses_template: &raw_responses_rows.join("\n"),
-                    co_requests_template: &co_requests_template,
-                    co_responses_template: &co_responses_template,
-                    transactions_template: &transactions_template,
-                    scenarios_template: &scenarios_template,
-                    status_codes_template: &status_code_template,
-                    errors_template: &errors_template,
-                    graph_rps_template: &self
-                        .graph_data
-                        .get_requests_per_second_graph(!self.configuration.no_granular_report)
-                        .get_markup(&self.metrics.history, test_start_time),
-                    graph_average_response_time_template: &self
-                        .graph_data
-                        .get_average_response_time_graph(!self.configuration.no_granular_report)
-                        .get_markup(&self.metrics.history, test_start_time),
-                    graph_users_per_second: &self
-                        .graph_data
-                        .get_active_users_graph(!self.configuration.no_granular_report)
-                        .get_markup(&self.metrics.history, test_start_time),
-                },
-            );
+            // Compile the status_code metrics rows.
+            let mut status_code_rows = Vec::new();
+            for metric in status_code_metrics {
+                status_code_rows.push(report::status_code_metrics_row(metric));
+            }
 
-            // Write the report to file.
-            if let Err(e) = report_file.write_all(report.as_ref()).await {
-                return Err(GooseError::InvalidOption {
-                    option: "--report-file".to_string(),
-                    value: self.get_report_file_path().unwrap(),
-                    detail: format!("Failed to create report file: {}", e),
-                });
+            // Compile the status_code metrics template.
+            report::status_code_metrics_template(&status_code_rows.join("\n"))
+        } else {
+            // If --status-codes is not enabled, return an empty template.
+            "".to_string()
+        };
+
+        // Only build the capacity search template if a capacity search ran.
+        let capacity_search_template = if !self.metrics.capacity_search.is_empty() {
+            let capacity_search_rows: Vec<String> = self
+                .metrics
+                .capacity_search
+                .iter()
+                .map(report::capacity_search_row)
+                .collect();
+            let result = match self.metrics.capacity_search_result() {
+                Some(users) => format!(
+                    "Maximum sustainable users: <span>{}</span>",
+                    format_number(users)
+                ),
+                None => "No level met the capacity search criteria.".to_string(),
             };
-            // Be sure the file flushes to disk.
-            report_file.flush().await?;
+            report::capacity_search_template(&capacity_search_rows.join("\n"), &result)
+        } else {
+            "".to_string()
+        };
 
-            info!(
-                "html report file written to: {}",
-                self.get_report_file_path().unwrap()
-            );
-        }
+        // Compile the report template.
+        report::build_report(
+            &users,
+            &steps_overview,
+            hosts,
+            &throttle,
+            report::GooseReportTemplates {
+                raw_requests_template: &raw_requests_rows.join("\n"),
+                raw_responses_template: &raw_responses_rows.join("\n"),
+                co_requests_template: &co_requests_template,
+                co_responses_template: &co_responses_template,
+                transactions_template: &transactions_template,
+                scenarios_template: &scenarios_template,
+                status_codes_template: &status_code_template,
+                capacity_search_template: &capacity_search_template,
+                errors_template: &errors_template,
+                graph_rps_template: &self
+                    .graph_data
+                    .get_requests_per_second_graph(!self.configuration.no_granular_report)
+                    .get_markup(&self.metrics.history, test_start_time),
+                graph_average_response_time_template: &self
+                    .graph_data
+                    .get_average_response_time_graph(!self.configuration.no_granular_report)
+                    .get_markup(&self.metrics.history, test_start_time),
+                graph_users_per_second: &self
+                    .graph_data
+                    .get_active_users_graph(!self.configuration.no_granular_report)
+                    .get_markup(&self.metrics.history, test_start_time),
+            },
+        )
+    }
+}
 
-        Ok(())
+/// Helper to add a row to the html report for each group run by a transaction, recursively
+/// adding nested groups beneath their parent.
+fn report_transaction_groups(
+    transaction_metrics: &mut Vec<report::TransactionMetric>,
+    duration: usize,
+    transaction: &TransactionMetricAggregate,
+    label: &str,
+) {
+    for (index, group) in transaction.groups.iter().enumerate() {
+        let label = format!("{}.{}", label, index);
+        let total_run_count = group.success_count + group.fail_count;
+        let (requests_per_second, failures_per_second) =
+            per_second_calculations(duration, total_run_count, group.fail_count);
+        let average = match group.counter {
+            0 => 0.00,
+            _ => group.total_time as f32 / group.counter as f32,
+        };
+        transaction_metrics.push(report::TransactionMetric {
+            is_scenario: false,
+            transaction: label.to_string(),
+            // Indent groups beneath their parent.
+            name: format!(
+                "{}{}",
+                "&nbsp;&nbsp;".repeat(label.matches('.').count() - 1),
+                group.transaction_name
+            ),
+            number_of_requests: total_run_count,
+            number_of_failures: group.fail_count,
+            response_time_average: format!("{:.2}", average),
+            response_time_minimum: group.min_time,
+            response_time_maximum: group.max_time,
+            requests_per_second: format!("{:.2}", requests_per_second),
+            failures_per_second: format!("{:.2}", failures_per_second),
+        });
+        report_transaction_groups(transaction_metrics, duration, group, &label);
     }
 }
 
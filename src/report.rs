@@ -1,5 +1,6 @@
 //! Optionally writes an html-formatted summary report after running a load test.
 
+use crate::capacity::CapacitySearchLevel;
 use crate::metrics;
 
 use std::collections::BTreeMap;
@@ -17,6 +18,7 @@ pub(crate) struct GooseReportTemplates<'a> {
     pub transactions_template: &'a str,
     pub scenarios_template: &'a str,
     pub status_codes_template: &'a str,
+    pub capacity_search_template: &'a str,
     pub errors_template: &'a str,
     pub graph_rps_template: &'a str,
     pub graph_average_response_time_template: &'a str,
@@ -313,6 +315,52 @@ pub(crate) fn status_code_metrics_template(status_code_rows: &str) -> String {
     )
 }
 
+/// If a capacity search ran, add a table of each level tried to the html report.
+pub(crate) fn capacity_search_template(capacity_search_rows: &str, result: &str) -> String {
+    format!(
+        r#"<div class="capacity_search">
+        <h2>Capacity Search</h2>
+        <p>{result}</p>
+        <table>
+            <thead>
+                <tr>
+                    <th>Users</th>
+                    <th># Requests</th>
+                    <th>Percentile (ms)</th>
+                    <th>Errors</th>
+                    <th>Result</th>
+                </tr>
+            </thead>
+            <tbody>
+                {capacity_search_rows}
+            </tbody>
+        </table>
+    </div>"#,
+        result = result,
+        capacity_search_rows = capacity_search_rows,
+    )
+}
+
+/// Build an individual row of the capacity search in the html report.
+pub(crate) fn capacity_search_row(level: &CapacitySearchLevel) -> String {
+    format!(
+        r#"<tr>
+        <td>{users}</td>
+        <td>{requests}</td>
+        <td>{percentile_time}</td>
+        <td>{error_percent:.2}%</td>
+        <td>{result}</td>
+    </tr>"#,
+        users = level.users,
+        requests = level.requests,
+        percentile_time = level
+            .percentile_time
+            .map_or_else(|| "-".to_string(), |time| time.to_string()),
+        error_percent = level.error_percent,
+        result = if level.passed { "passed" } else { "failed" },
+    )
+}
+
 /// Build an individual row of status code metrics in the html report.
 pub(crate) fn status_code_metrics_row(metric: StatusCodeMetric) -> String {
     format!(
@@ -489,6 +537,7 @@ pub(crate) fn build_report(
     users: &str,
     steps_rows: &str,
     hosts: &str,
+    throttle: &str,
     templates: GooseReportTemplates,
 ) -> String {
     let pkg_name = env!("CARGO_PKG_NAME");
@@ -566,6 +615,7 @@ pub(crate) fn build_report(
         <div class="info">
             <p>Users: <span>{users}</span> </p>
             <p>Target Host: <span>{hosts}</span></p>
+            {throttle}
             <p><span><small><em>{pkg_name} v{pkg_version}</em></small></span></p>
             <h2>Plan overview</h2>
                 <table>
@@ -584,6 +634,8 @@ pub(crate) fn build_report(
                 </table>
         </div>
 
+        {capacity_search_template}
+
         <div class="requests">
             <h2>Request Metrics</h2>
 
@@ -658,6 +710,7 @@ pub(crate) fn build_report(
         users = users,
         steps_rows = steps_rows,
         hosts = hosts,
+        throttle = throttle,
         pkg_name = pkg_name,
         pkg_version = pkg_version,
         raw_requests_template = templates.raw_requests_template,
@@ -667,6 +720,7 @@ pub(crate) fn build_report(
         transactions_template = templates.transactions_template,
         scenarios_template = templates.scenarios_template,
         status_codes_template = templates.status_codes_template,
+        capacity_search_template = templates.capacity_search_template,
         errors_template = templates.errors_template,
         graph_rps_template = templates.graph_rps_template,
         graph_average_response_time_template = templates.graph_average_response_time_template,
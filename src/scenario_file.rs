@@ -0,0 +1,531 @@
+//! Build scenarios from declarative YAML or JSON files.
+//!
+//! Scenario files make it possible to define and tweak a load test without writing or
+//! recompiling Rust. Each [`Scenario`] is made up of [`Transaction`]s, and each
+//! transaction makes one or more requests in order. Responses can be validated with
+//! assertions, and values can be extracted from responses into per-user variables that
+//! later requests reference as `{{name}}` in their path, headers or body.
+//!
+//! ```yaml
+//! scenarios:
+//!   - name: Authenticated user
+//!     weight: 2
+//!     # Milliseconds to pause after each transaction.
+//!     wait_time: { min: 500, max: 1500 }
+//!     transactions:
+//!       - name: log in
+//!         on_start: true
+//!         requests:
+//!           - path: /user/login
+//!             extract:
+//!               - name: form_build_id
+//!                 regex: 'name="form_build_id" value="([^"]+)"'
+//!           - method: POST
+//!             path: /user/login
+//!             headers:
+//!               Content-Type: application/x-www-form-urlencoded
+//!             body: "name=foo&pass=bar&form_build_id={{form_build_id}}"
+//!             assert:
+//!               - contains: Log out
+//!       - name: front page
+//!         weight: 3
+//!         requests:
+//!           - path: /
+//!             expect_status: 200
+//! ```
+//!
+//! Files ending in `.json` are parsed as JSON, all others as YAML.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use goose::prelude::*;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), GooseError> {
+//!     GooseAttack::initialize()?
+//!         .register_scenarios_from_file("plan.yaml")?
+//!         .execute()
+//!         .await?;
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use http::Method;
+use lazy_static::lazy_static;
+use regex::Regex;
+use reqwest::header::HeaderMap;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::goose::{
+    goose_method_from_method, GooseMethod, GooseRequest, GooseUser, Scenario, Transaction,
+    TransactionFunction, TransactionResult,
+};
+use crate::goose_trait::Goose;
+use crate::{GooseAttack, GooseError};
+
+lazy_static! {
+    // Matches `{{name}}` variable references.
+    static ref VARIABLE: Regex = Regex::new(r"\{\{\s*([A-Za-z0-9_]+)\s*\}\}").unwrap();
+}
+
+/// The top level of a scenario file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioFile {
+    scenarios: Vec<ScenarioSpec>,
+}
+
+/// A [`Scenario`] defined in a scenario file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScenarioSpec {
+    name: String,
+    weight: Option<usize>,
+    host: Option<String>,
+    wait_time: Option<WaitTimeSpec>,
+    transactions: Vec<TransactionSpec>,
+}
+
+/// The range of milliseconds to pause after each transaction.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WaitTimeSpec {
+    min: u64,
+    max: u64,
+}
+
+/// A [`Transaction`] defined in a scenario file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TransactionSpec {
+    name: Option<String>,
+    weight: Option<usize>,
+    sequence: Option<usize>,
+    #[serde(default)]
+    on_start: bool,
+    #[serde(default)]
+    on_stop: bool,
+    requests: Vec<RequestSpec>,
+}
+
+/// A request made by a transaction defined in a scenario file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RequestSpec {
+    method: Option<String>,
+    path: String,
+    name: Option<String>,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    body: Option<String>,
+    json: Option<serde_json::Value>,
+    expect_status: Option<u16>,
+    #[serde(default)]
+    assert: Vec<AssertionSpec>,
+    #[serde(default)]
+    extract: Vec<ExtractSpec>,
+}
+
+/// A check made against a response, failing the request if it doesn't pass.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AssertionSpec {
+    /// The response body must contain this text.
+    Contains(String),
+    /// The response body must not contain this text.
+    NotContains(String),
+    /// The response must include this header.
+    Header(String),
+}
+
+/// A value extracted from a response into a per-user variable.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExtractSpec {
+    name: String,
+    regex: Option<String>,
+    header: Option<String>,
+}
+
+/// Where an extracted value comes from.
+#[derive(Clone, Debug)]
+enum ExtractSource {
+    /// The first capture group (or the entire match) of a regex run against the body.
+    Body(Regex),
+    /// The value of a response header.
+    Header(String),
+}
+
+/// A validated request, ready to be run by a [`GooseUser`].
+#[derive(Clone, Debug)]
+struct RequestStep {
+    method: GooseMethod,
+    path: String,
+    name: Option<String>,
+    headers: Vec<(String, String)>,
+    body: Option<String>,
+    expect_status: Option<u16>,
+    assertions: Vec<AssertionSpec>,
+    extract: Vec<(String, ExtractSource)>,
+}
+
+/// Per-user variables extracted from responses, stored as [`GooseUser`] session data.
+#[derive(Debug, Default)]
+struct ScenarioFileVariables(HashMap<String, String>);
+
+/// Helper to build a [`GooseError::InvalidScenarioFile`].
+fn invalid(detail: String) -> GooseError {
+    GooseError::InvalidScenarioFile { detail }
+}
+
+impl ScenarioFile {
+    /// Load and parse a scenario file. Files ending in `.json` are parsed as JSON, all
+    /// others as YAML.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, GooseError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => ScenarioFile::from_json(&contents),
+            _ => ScenarioFile::from_yaml(&contents),
+        }
+    }
+
+    /// Parse a YAML scenario definition.
+    pub fn from_yaml(yaml: &str) -> Result<Self, GooseError> {
+        serde_yaml::from_str(yaml).map_err(|e| invalid(format!("failed to parse YAML: {}", e)))
+    }
+
+    /// Parse a JSON scenario definition.
+    pub fn from_json(json: &str) -> Result<Self, GooseError> {
+        serde_json::from_str(json).map_err(|e| invalid(format!("failed to parse JSON: {}", e)))
+    }
+
+    /// Build all [`Scenario`]s defined in the file.
+    pub fn scenarios(&self) -> Result<Vec<Scenario<GooseUser>>, GooseError> {
+        self.scenarios.iter().map(|s| s.build()).collect()
+    }
+}
+
+impl ScenarioSpec {
+    /// Build a [`Scenario`] from its definition.
+    fn build(&self) -> Result<Scenario<GooseUser>, GooseError> {
+        let mut scenario = Scenario::new(&self.name);
+        if let Some(weight) = self.weight {
+            scenario = scenario.set_weight(weight)?;
+        }
+        if let Some(host) = &self.host {
+            scenario = scenario.set_host(host);
+        }
+        if let Some(wait_time) = &self.wait_time {
+            scenario = scenario.set_wait_time(
+                Duration::from_millis(wait_time.min),
+                Duration::from_millis(wait_time.max),
+            )?;
+        }
+        if self.transactions.is_empty() {
+            return Err(invalid(format!(
+                "scenario '{}' does not define any transactions",
+                self.name
+            )));
+        }
+        for transaction in &self.transactions {
+            scenario = scenario.register_transaction(transaction.build(&self.name)?);
+        }
+
+        Ok(scenario)
+    }
+}
+
+impl TransactionSpec {
+    /// Build a [`Transaction`] from its definition, validating all requests up front so
+    /// errors are reported before the load test starts.
+    fn build(&self, scenario_name: &str) -> Result<Transaction<GooseUser>, GooseError> {
+        if self.requests.is_empty() {
+            return Err(invalid(format!(
+                "transaction '{}' in scenario '{}' does not define any requests",
+                self.name.as_deref().unwrap_or_default(),
+                scenario_name
+            )));
+        }
+        let steps = self
+            .requests
+            .iter()
+            .map(|r| r.build())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let steps = Arc::new(steps);
+        let closure: TransactionFunction<GooseUser> = Arc::new(move |user| {
+            let steps = steps.clone();
+            Box::pin(async move {
+                for step in steps.iter() {
+                    step.run(user).await?;
+                }
+
+                Ok(())
+            })
+        });
+
+        let mut transaction = Transaction::new(closure);
+        if let Some(name) = &self.name {
+            transaction = transaction.set_name(name);
+        }
+        if let Some(weight) = self.weight {
+            transaction = transaction.set_weight(weight)?;
+        }
+        if let Some(sequence) = self.sequence {
+            transaction = transaction.set_sequence(sequence);
+        }
+        if self.on_start {
+            transaction = transaction.set_on_start();
+        }
+        if self.on_stop {
+            transaction = transaction.set_on_stop();
+        }
+
+        Ok(transaction)
+    }
+}
+
+impl RequestSpec {
+    /// Validate a request definition, compiling any regular expressions.
+    fn build(&self) -> Result<RequestStep, GooseError> {
+        let method_name = self.method.as_deref().unwrap_or("GET").to_uppercase();
+        let method = Method::from_bytes(method_name.as_bytes())
+            .ok()
+            .and_then(|m| goose_method_from_method(m).ok())
+            .ok_or_else(|| {
+                invalid(format!(
+                    "unsupported method '{}' for {}",
+                    method_name, self.path
+                ))
+            })?;
+
+        let mut headers: Vec<(String, String)> = self
+            .headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        let body = match (&self.body, &self.json) {
+            (Some(_), Some(_)) => {
+                return Err(invalid(format!(
+                    "request to {} can not define both body and json",
+                    self.path
+                )))
+            }
+            (Some(body), None) => Some(body.to_string()),
+            (None, Some(json)) => {
+                if !headers
+                    .iter()
+                    .any(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+                {
+                    headers.push(("Content-Type".to_string(), "application/json".to_string()));
+                }
+                Some(json.to_string())
+            }
+            (None, None) => None,
+        };
+
+        let mut extract = Vec::new();
+        for e in &self.extract {
+            let source = match (&e.regex, &e.header) {
+                (Some(regex), None) => ExtractSource::Body(Regex::new(regex).map_err(|err| {
+                    invalid(format!("invalid regex for variable '{}': {}", e.name, err))
+                })?),
+                (None, Some(header)) => ExtractSource::Header(header.to_string()),
+                _ => {
+                    return Err(invalid(format!(
+                        "variable '{}' must be extracted with either regex or header",
+                        e.name
+                    )))
+                }
+            };
+            extract.push((e.name.to_string(), source));
+        }
+
+        Ok(RequestStep {
+            method,
+            path: self.path.to_string(),
+            name: self.name.clone(),
+            headers,
+            body,
+            expect_status: self.expect_status,
+            assertions: self.assert.clone(),
+            extract,
+        })
+    }
+}
+
+impl AssertionSpec {
+    /// Returns a description of the failure if the response doesn't pass the assertion.
+    fn check(&self, headers: &HeaderMap, body: &str) -> Option<String> {
+        match self {
+            AssertionSpec::Contains(text) if !body.contains(text.as_str()) => {
+                Some(format!("body does not contain '{}'", text))
+            }
+            AssertionSpec::NotContains(text) if body.contains(text.as_str()) => {
+                Some(format!("body contains '{}'", text))
+            }
+            AssertionSpec::Header(name) if !headers.contains_key(name.as_str()) => {
+                Some(format!("header '{}' not set", name))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl ExtractSource {
+    /// Returns the extracted value, if found.
+    fn extract(&self, headers: &HeaderMap, body: &str) -> Option<String> {
+        match self {
+            ExtractSource::Body(regex) => regex.captures(body).and_then(|c| {
+                c.get(1)
+                    .or_else(|| c.get(0))
+                    .map(|m| m.as_str().to_string())
+            }),
+            ExtractSource::Header(name) => headers
+                .get(name.as_str())
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string()),
+        }
+    }
+}
+
+/// Replace `{{name}}` with the value of variables previously extracted by this user.
+/// References to unknown variables are left unchanged.
+fn substitute(user: &GooseUser, value: &str) -> String {
+    let variables = match user.get_session_data::<ScenarioFileVariables>() {
+        Some(v) if value.contains("{{") => v,
+        _ => return value.to_string(),
+    };
+    VARIABLE
+        .replace_all(value, |captures: &regex::Captures| {
+            match variables.0.get(&captures[1]) {
+                Some(v) => v.to_string(),
+                None => captures[0].to_string(),
+            }
+        })
+        .to_string()
+}
+
+impl RequestStep {
+    /// Make the request, then validate the response and extract variables.
+    async fn run(&self, user: &mut GooseUser) -> TransactionResult {
+        let path = substitute(user, &self.path);
+        let mut request_builder = user.get_request_builder(&self.method, &path)?;
+        for (name, value) in &self.headers {
+            request_builder = request_builder.header(name.as_str(), substitute(user, value));
+        }
+        if let Some(body) = &self.body {
+            request_builder = request_builder.body(substitute(user, body));
+        }
+
+        let mut goose_request = GooseRequest::builder()
+            .method(self.method.clone())
+            .path(path.as_str())
+            .set_request_builder(request_builder);
+        if let Some(name) = &self.name {
+            goose_request = goose_request.name(name.as_str());
+        }
+        if let Some(status_code) = self.expect_status {
+            goose_request = goose_request.expect_status_code(status_code);
+        }
+        let mut goose = user.request(goose_request.build()).await?;
+
+        // Only read the response body if it's needed.
+        if self.assertions.is_empty() && self.extract.is_empty() {
+            return Ok(());
+        }
+        let response = match goose.response {
+            Ok(r) => r,
+            Err(e) => {
+                return user.set_failure(
+                    &format!("{}: no response ({})", path, e),
+                    &mut goose.request,
+                    None,
+                    None,
+                )
+            }
+        };
+        let headers = response.headers().clone();
+        let body = match response.text().await {
+            Ok(b) => b,
+            Err(e) => {
+                return user.set_failure(
+                    &format!("{}: failed to read body ({})", path, e),
+                    &mut goose.request,
+                    Some(&headers),
+                    None,
+                )
+            }
+        };
+
+        for assertion in &self.assertions {
+            if let Some(failure) = assertion.check(&headers, &body) {
+                return user.set_failure(
+                    &format!("{}: {}", path, failure),
+                    &mut goose.request,
+                    Some(&headers),
+                    Some(&body),
+                );
+            }
+        }
+
+        for (name, source) in &self.extract {
+            match source.extract(&headers, &body) {
+                Some(value) => {
+                    if user.get_session_data::<ScenarioFileVariables>().is_none() {
+                        user.set_session_data(ScenarioFileVariables::default());
+                    }
+                    user.get_session_data_unchecked_mut::<ScenarioFileVariables>()
+                        .0
+                        .insert(name.to_string(), value);
+                }
+                None => {
+                    return user.set_failure(
+                        &format!("{}: failed to extract '{}'", path, name),
+                        &mut goose.request,
+                        Some(&headers),
+                        Some(&body),
+                    )
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl GooseAttack<GooseUser> {
+    /// Register all [`Scenario`]s defined in a YAML or JSON scenario file. See the
+    /// [`scenario_file`](./scenario_file/index.html) module for the file format.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use goose::prelude::*;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), GooseError> {
+    ///     GooseAttack::initialize()?
+    ///         .register_scenarios_from_file("plan.yaml")?
+    ///         .execute()
+    ///         .await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn register_scenarios_from_file<P: AsRef<Path>>(
+        mut self,
+        path: P,
+    ) -> Result<Self, GooseError> {
+        for scenario in ScenarioFile::from_file(path)?.scenarios()? {
+            self = self.register_scenario(scenario);
+        }
+
+        Ok(self)
+    }
+}
@@ -3,10 +3,12 @@
 //! Internally, Goose represents all load tests as a series of Test Plan steps.
 
 use chrono::prelude::*;
-use gumdrop::Options;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::f64::consts::PI;
+use std::fmt::Write;
+use std::path::Path;
 use std::str::FromStr;
 use std::time;
 
@@ -15,12 +17,14 @@ use crate::{Goose, util};
 use crate::{AttackPhase, GooseAttack, GooseAttackRunState, GooseError};
 
 /// Internal data structure representing a test plan.
-#[derive(Options, Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Hash, PartialEq, Serialize, Deserialize)]
 pub(crate) struct TestPlan {
     // A test plan is a vector of tuples each indicating a # of users and milliseconds.
     pub(crate) steps: Vec<(usize, usize)>,
     // Which step of the test_plan is currently running.
     pub(crate) current: usize,
+    // The name of the stage each step belongs to, if loaded from a test plan file.
+    pub(crate) stages: Vec<Option<String>>,
 }
 
 /// Automatically represent all load tests internally as a test plan.
@@ -33,6 +37,7 @@ impl TestPlan {
         TestPlan {
             steps: Vec::new(),
             current: 0,
+            stages: Vec::new(),
         }
     }
 
@@ -76,10 +81,113 @@ impl TestPlan {
             }
 
             // Define test plan from options.
-            TestPlan { steps, current: 0 }
+            TestPlan {
+                steps,
+                current: 0,
+                stages: Vec::new(),
+            }
+        }
+    }
+
+    /// Load a test plan with named stages from a TOML, YAML or JSON file.
+    pub(crate) fn from_file(path: &str) -> Result<TestPlan, GooseError> {
+        let error = |detail: String| GooseError::InvalidOption {
+            option: "`configuration.test_plan_file`".to_string(),
+            value: path.to_string(),
+            detail,
+        };
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| error(format!("failed to read test plan file: {}", e)))?;
+        // The format is determined by the file extension, defaulting to YAML.
+        let file: TestPlanFile = match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(&contents).map_err(|e| error(e.to_string()))?,
+            Some("json") => serde_json::from_str(&contents).map_err(|e| error(e.to_string()))?,
+            _ => serde_yaml::from_str(&contents).map_err(|e| error(e.to_string()))?,
+        };
+        if file.stages.is_empty() {
+            return Err(error(
+                "a test plan file must define at least one stage".to_string(),
+            ));
+        }
+
+        let mut test_plan = TestPlan::new();
+        for stage in &file.stages {
+            let previous_users = test_plan.steps.last().map_or(0, |step| step.0);
+            let steps = stage
+                .steps(previous_users)
+                .ok_or_else(|| error(format!("invalid test plan stage: '{}'", stage.name)))?;
+            test_plan
+                .stages
+                .extend(steps.iter().map(|_| Some(stage.name.clone())));
+            test_plan.steps.extend(steps);
+        }
+        Ok(test_plan)
+    }
+
+    /// The name of the stage a step belongs to, if any.
+    pub(crate) fn stage(&self, step: usize) -> Option<String> {
+        self.stages.get(step).cloned().flatten()
+    }
+
+    /// Append steps to the test plan, with shapes starting from the number of users
+    /// configured by the last step.
+    pub(crate) fn append(&mut self, plan: &str) -> Result<(), GooseError> {
+        let previous_users = self.steps.last().map_or(0, |step| step.0);
+        let steps = parse_steps(plan, previous_users)?;
+        // Appended steps don't belong to a stage.
+        if !self.stages.is_empty() {
+            self.stages.resize(self.steps.len() + steps.len(), None);
+        }
+        self.steps.extend(steps);
+        Ok(())
+    }
+
+    /// Find a step by its number in the test plan preview, starting from 1, or by the name
+    /// of the stage it starts.
+    pub(crate) fn find_step(&self, step: &str) -> Option<usize> {
+        match step.parse::<usize>() {
+            Ok(number) if number > 0 && number <= self.steps.len() => Some(number - 1),
+            Ok(_) => None,
+            Err(_) => self
+                .stages
+                .iter()
+                .position(|stage| stage.as_deref() == Some(step)),
         }
     }
 
+    /// Determine how many more users need to be launched to complete the test plan from
+    /// the current step, when `active_users` users are running.
+    pub(crate) fn remaining_users(&self, active_users: usize) -> usize {
+        let mut remaining_users: usize = 0;
+        let mut previous = active_users;
+        for step in self.steps.iter().skip(self.current) {
+            // Add to remaining_users every time there is an increase.
+            if step.0 > previous {
+                remaining_users += step.0 - previous;
+            }
+            previous = step.0
+        }
+        remaining_users
+    }
+
+    /// Determine how many users should be running after `elapsed` milliseconds, launching
+    /// and stopping users evenly during each step. After the last step, the number of
+    /// users configured by the last step is maintained.
+    pub(crate) fn users_at(&self, elapsed: usize) -> usize {
+        let mut previous_users = 0;
+        let mut started = 0;
+        for (users, ms) in &self.steps {
+            if elapsed < started + ms {
+                let progress = (elapsed - started) as f64 / *ms as f64;
+                let difference = *users as f64 - previous_users as f64;
+                return (previous_users as f64 + difference * progress).round() as usize;
+            }
+            previous_users = *users;
+            started += ms;
+        }
+        previous_users
+    }
+
     // Determine the total number of users required by the test plan.
     pub(crate) fn total_users(&self) -> usize {
         let mut total_users: usize = 0;
@@ -93,6 +201,269 @@ impl TestPlan {
         }
         total_users
     }
+
+    /// Build a preview of the test plan, displayed before the load test starts.
+    pub(crate) fn preview(&self) -> String {
+        let mut preview = String::new();
+        let _ = writeln!(
+            preview,
+            " === TEST PLAN ===\n ------------------------------------------------------------------------------"
+        );
+        if self.stages.is_empty() {
+            let _ = writeln!(
+                preview,
+                " {:<8} {:<10} {:<12} Elapsed",
+                "Step", "Users", "Duration"
+            );
+        } else {
+            let _ = writeln!(
+                preview,
+                " {:<8} {:<10} {:<12} {:<12} Stage",
+                "Step", "Users", "Duration", "Elapsed"
+            );
+        }
+        let _ = writeln!(
+            preview,
+            " ------------------------------------------------------------------------------"
+        );
+        let mut elapsed = 0;
+        for (index, (users, ms)) in self.steps.iter().enumerate() {
+            elapsed += ms;
+            match self.stage(index) {
+                Some(stage) => {
+                    let _ = writeln!(
+                        preview,
+                        " {:<8} {:<10} {:<12} {:<12} {}",
+                        index + 1,
+                        users,
+                        format_ms(*ms),
+                        format_ms(elapsed),
+                        stage
+                    );
+                }
+                None => {
+                    let _ = writeln!(
+                        preview,
+                        " {:<8} {:<10} {:<12} {}",
+                        index + 1,
+                        users,
+                        format_ms(*ms),
+                        format_ms(elapsed)
+                    );
+                }
+            }
+        }
+        let _ = writeln!(
+            preview,
+            " ------------------------------------------------------------------------------"
+        );
+        preview
+    }
+}
+
+// Format milliseconds as seconds for the test plan preview.
+fn format_ms(ms: usize) -> String {
+    match ms % 1_000 {
+        0 => format!("{}s", ms / 1_000),
+        _ => format!("{:.3}s", ms as f64 / 1_000.0),
+    }
+}
+
+/// A test plan file, loaded with `--test-plan-file`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TestPlanFile {
+    /// The stages of the test plan, run in order.
+    stages: Vec<TestPlanStage>,
+}
+
+/// A named stage of a test plan file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TestPlanStage {
+    /// The name of the stage, displayed in the metrics, graphs and report.
+    name: String,
+    /// How many users to launch or stop until there are this many users.
+    users: Option<usize>,
+    /// How many users to launch or stop per second, instead of a `duration`.
+    rate: Option<f32>,
+    /// How long to take launching or stopping users.
+    duration: Option<Timespan>,
+    /// How long to hold the number of users after launching or stopping them.
+    hold: Option<Timespan>,
+    /// A test plan step or shape, such as `sine(10,100,1m,1h)`, instead of `users`.
+    shape: Option<String>,
+}
+
+/// A timespan in a test plan file, either integer seconds or a string such as "1h30m".
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Timespan {
+    Seconds(usize),
+    Text(String),
+}
+
+impl Timespan {
+    /// Convert the timespan to milliseconds, returning `None` if it's invalid.
+    fn ms(&self) -> Option<usize> {
+        match self {
+            Timespan::Seconds(seconds) => Some(seconds * 1_000),
+            Timespan::Text(timespan) => parse_ms(timespan),
+        }
+    }
+}
+
+impl TestPlanStage {
+    /// Convert the stage into internal test plan steps, starting from the number of
+    /// users configured by the previous step. Returns `None` if the stage is invalid.
+    fn steps(&self, previous_users: usize) -> Option<Vec<(usize, usize)>> {
+        let mut steps = match (&self.shape, self.users) {
+            (Some(shape), None) if self.rate.is_none() && self.duration.is_none() => {
+                parse_step(shape, previous_users)?
+            }
+            (None, Some(users)) => {
+                let ms = match (self.rate, &self.duration) {
+                    (Some(rate), None) if rate > 0.0 => {
+                        let difference = (users as isize - previous_users as isize).abs();
+                        (difference as f32 / rate * 1_000.0) as usize
+                    }
+                    (None, Some(duration)) => duration.ms()?,
+                    (None, None) => 0,
+                    _ => return None,
+                };
+                vec![(users, ms)]
+            }
+            _ => return None,
+        };
+        if let Some(hold) = &self.hold {
+            let users = steps.last()?.0;
+            steps.push((users, hold.ms()?));
+        }
+        Some(steps)
+    }
+}
+
+/// Parse a timespan into milliseconds, returning `None` if it's invalid.
+fn parse_ms(timespan: &str) -> Option<usize> {
+    let re = Regex::new(r"^(\d+|((\d+?)h)?((\d+?)m)?((\d+?)s)?)$").unwrap();
+    if timespan.is_empty() || !re.is_match(timespan) {
+        return None;
+    }
+    Some(util::parse_timespan(timespan) * 1_000)
+}
+
+/// Parse a single `"users,timespan"` test plan step or shape, starting from the number
+/// of users configured by the previous step. Returns `None` if the step is invalid.
+fn parse_step(line: &str, previous_users: usize) -> Option<Vec<(usize, usize)>> {
+    // Each line of the test plan must be in the format "{users},{timespan}", white space is ignored
+    let re = Regex::new(r"^\s*(\d+)\s*,\s*(\d+|((\d+?)h)?((\d+?)m)?((\d+?)s)?)\s*$").unwrap();
+    // Or in the format "{shape}({arguments})".
+    let shape_re = Regex::new(r"^\s*([a-zA-Z]+)\s*\(([^)]*)\)\s*$").unwrap();
+    if let Some(cap) = re.captures(line) {
+        let left = cap[1]
+            .parse::<usize>()
+            .expect("failed to convert \\d to usize");
+        let right = util::parse_timespan(&cap[2]) * 1_000;
+        Some(vec![(left, right)])
+    } else {
+        let cap = shape_re.captures(line)?;
+        let args: Vec<&str> = cap[2].split(',').map(|arg| arg.trim()).collect();
+        expand_shape(&cap[1], &args, previous_users)
+    }
+}
+
+/// The maximum number of steps an exponential ramp is expanded into.
+const EXPONENTIAL_STEPS: usize = 20;
+
+/// How many steps each period of a sine wave is expanded into.
+const SINE_STEPS_PER_PERIOD: usize = 12;
+
+/// Expand a test plan shape into internal test plan steps, starting from the number of
+/// users configured by the previous step. Returns `None` if the arguments are invalid.
+///
+/// The following shapes are supported:
+///  - `linear(USERS,TIMESPAN)`: evenly launch or stop users until there are USERS users.
+///  - `step(USERS,TIMESPAN)`: immediately change to USERS users, and hold for TIMESPAN.
+///  - `exponential(USERS,TIMESPAN)`: exponentially grow or shrink to USERS users.
+///  - `sine(MIN,MAX,PERIOD,TIMESPAN)`: oscillate between MIN and MAX users every PERIOD.
+///  - `spike(USERS,TIMESPAN)`: immediately change to USERS users, hold for TIMESPAN, and
+///    then immediately return to the previous number of users.
+///  - `stairs(USERS,STEPS,HOLD)`: climb or descend to USERS users in STEPS equal
+///    steps, holding each step for HOLD.
+fn expand_shape(shape: &str, args: &[&str], previous_users: usize) -> Option<Vec<(usize, usize)>> {
+    // Parse a number of users.
+    let users = |index: usize| args.get(index)?.parse::<usize>().ok();
+    // Parse a timespan into milliseconds.
+    let ms = |index: usize| parse_ms(args.get(index)?);
+
+    let mut steps = Vec::new();
+    match shape.to_lowercase().as_str() {
+        "linear" if args.len() == 2 => steps.push((users(0)?, ms(1)?)),
+        "step" if args.len() == 2 => {
+            let users = users(0)?;
+            steps.push((users, 0));
+            steps.push((users, ms(1)?));
+        }
+        "exponential" | "exp" if args.len() == 2 => {
+            let (users, ms) = (users(0)?, ms(1)?);
+            let count = (ms / 1_000).clamp(1, EXPONENTIAL_STEPS);
+            // Geometric growth can't start from or end at 0 users.
+            let from = previous_users.max(1) as f64;
+            let to = users.max(1) as f64;
+            for step in 1..=count {
+                let step_users = if step == count {
+                    users
+                } else {
+                    (from * (to / from).powf(step as f64 / count as f64)).round() as usize
+                };
+                steps.push((step_users, ms / count));
+            }
+            // Include any remainder in the final step.
+            steps.last_mut()?.1 += ms % count;
+        }
+        "sine" if args.len() == 4 => {
+            let (minimum, maximum, period, ms) = (users(0)?, users(1)?, ms(2)?, ms(3)?);
+            if minimum > maximum || period == 0 {
+                return None;
+            }
+            let middle = (minimum + maximum) as f64 / 2.0;
+            let amplitude = (maximum - minimum) as f64 / 2.0;
+            // Steps are at least 1 second long.
+            let step_ms = (period / SINE_STEPS_PER_PERIOD).max(1_000);
+            let count = (ms / step_ms).max(1);
+            for step in 1..=count {
+                let elapsed = (step * step_ms) as f64;
+                let step_users =
+                    (middle + amplitude * (2.0 * PI * elapsed / period as f64).sin()).round();
+                steps.push((step_users as usize, step_ms));
+            }
+            // Include any remainder in the final step.
+            steps.last_mut()?.1 = ms - step_ms * (count - 1);
+        }
+        "spike" if args.len() == 2 => {
+            let users = users(0)?;
+            steps.push((users, 0));
+            steps.push((users, ms(1)?));
+            steps.push((previous_users, 0));
+        }
+        "stairs" if args.len() == 3 => {
+            let (users, count, hold) = (users(0)?, users(1)?, ms(2)?);
+            if count == 0 {
+                return None;
+            }
+            for step in 1..=count {
+                let step_users = if users >= previous_users {
+                    previous_users + (users - previous_users) * step / count
+                } else {
+                    previous_users - (previous_users - users) * step / count
+                };
+                steps.push((step_users, 0));
+                steps.push((step_users, hold));
+            }
+        }
+        _ => return None,
+    }
+    Some(steps)
 }
 
 /// Implement [`FromStr`] to convert `"users,timespan"` string formatted test plans to Goose's
@@ -103,39 +474,54 @@ impl TestPlan {
 /// Time span can be specified as an integer, indicating seconds. Or can use integers together
 /// with one or more of "h", "m", and "s", in that order, indicating "hours", "minutes", and
 /// "seconds". Valid formats include: 20, 20s, 3m, 2h, 1h20m, 3h30m10s, etc.
+///
+/// A step can instead be a shape, such as `exponential(100,5m)` or `sine(10,100,1m,1h)`,
+/// which is expanded into multiple internal steps.
 impl FromStr for TestPlan {
     type Err = GooseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         // Convert string into a TestPlan.
-        let mut steps: Vec<(usize, usize)> = Vec::new();
-        // Each line of the test plan must be in the format "{users},{timespan}", white space is ignored
-        let re = Regex::new(r"^\s*(\d+)\s*,\s*(\d+|((\d+?)h)?((\d+?)m)?((\d+?)s)?)\s*$").unwrap();
-        // A test plan can have multiple lines split by the semicolon ";".
-        let lines = s.split(';');
-        for line in lines {
-            if let Some(cap) = re.captures(line) {
-                let left = cap[1]
-                    .parse::<usize>()
-                    .expect("failed to convert \\d to usize");
-                let right = util::parse_timespan(&cap[2]) * 1_000;
-                steps.push((left, right));
-            } else {
-                // Logger isn't initialized yet, provide helpful debug output.
-                eprintln!("ERROR: invalid `configuration.test_plan` value: '{}'", line);
-                eprintln!("  Expected format: --test-plan \"{{users}},{{timespan}};{{users}},{{timespan}}\"");
-                eprintln!("    {{users}} must be an integer, ie \"100\"");
-                eprintln!("    {{timespan}} can be integer seconds or \"30s\", \"20m\", \"3h\", \"1h30m\", etc");
-                return Err(GooseError::InvalidOption {
-                    option: "`configuration.test_plan".to_string(),
-                    value: line.to_string(),
-                    detail: "invalid `configuration.test_plan` value.".to_string(),
-                });
-            }
+        Ok(TestPlan {
+            steps: parse_steps(s, 0)?,
+            current: 0,
+            stages: Vec::new(),
+        })
+    }
+}
+
+/// Parse test plan steps separated by semicolons, with shapes starting from the number of
+/// users configured by the previous step.
+fn parse_steps(s: &str, mut previous_users: usize) -> Result<Vec<(usize, usize)>, GooseError> {
+    let mut steps: Vec<(usize, usize)> = Vec::new();
+    // A test plan can have multiple lines split by the semicolon ";".
+    let lines = s.split(';');
+    for line in lines {
+        if let Some(line_steps) = parse_step(line, previous_users) {
+            previous_users = line_steps.last().map_or(previous_users, |step| step.0);
+            steps.extend(line_steps);
+        } else {
+            // Logger isn't initialized yet, provide helpful debug output.
+            eprintln!("ERROR: invalid `configuration.test_plan` value: '{}'", line);
+            eprintln!(
+                "  Expected format: --test-plan \"{{users}},{{timespan}};{{users}},{{timespan}}\""
+            );
+            eprintln!("    {{users}} must be an integer, ie \"100\"");
+            eprintln!("    {{timespan}} can be integer seconds or \"30s\", \"20m\", \"3h\", \"1h30m\", etc");
+            eprintln!(
+                "  Or a shape: linear({{users}},{{timespan}}), step({{users}},{{timespan}}),"
+            );
+            eprintln!("    exponential({{users}},{{timespan}}), sine({{min}},{{max}},{{period}},{{timespan}}),");
+            eprintln!("    spike({{users}},{{timespan}}), stairs({{users}},{{steps}},{{hold}})");
+            return Err(GooseError::InvalidOption {
+                option: "`configuration.test_plan".to_string(),
+                value: line.to_string(),
+                detail: "invalid `configuration.test_plan` value.".to_string(),
+            });
         }
-        // The steps are only valid if the logic gets this far.
-        Ok(TestPlan { steps, current: 0 })
     }
+    // The steps are only valid if the logic gets this far.
+    Ok(steps)
 }
 
 /// A test plan is a series of steps performing one of the following actions.
@@ -162,15 +548,30 @@ pub struct TestPlanHistory {
     pub timestamp: DateTime<Utc>,
     /// The number of users when the step started.
     pub users: usize,
+    /// The name of the test plan stage, if loaded from a test plan file.
+    pub stage: Option<String>,
 }
 
 impl TestPlanHistory {
     /// A helper to record a new test plan step in the historical record.
-    pub(crate) fn step(action: TestPlanStepAction, users: usize) -> TestPlanHistory {
+    pub(crate) fn step(
+        action: TestPlanStepAction,
+        users: usize,
+        stage: Option<String>,
+    ) -> TestPlanHistory {
         TestPlanHistory {
             action,
             timestamp: Utc::now(),
             users,
+            stage,
+        }
+    }
+
+    /// Describe the step, including the stage name if there is one.
+    pub(crate) fn label(&self) -> String {
+        match &self.stage {
+            Some(stage) => format!("{} ({:?})", stage, self.action),
+            None => format!("{:?}", self.action),
         }
     }
 }
@@ -178,27 +579,38 @@ impl TestPlanHistory {
 impl<G: Goose> GooseAttack<G> {
     // Advance the active [`GooseAttack`](./struct.GooseAttack.html) to the next TestPlan step.
     pub(crate) fn advance_test_plan(&mut self, goose_attack_run_state: &mut GooseAttackRunState) {
+        // The next step starts with the users configured by the step that just completed.
+        let users = self.test_plan.steps[self.test_plan.current].0;
+
+        // Always advance the TestPlan step
+        self.test_plan.current += 1;
+
+        self.start_test_plan_step(goose_attack_run_state, users);
+    }
+
+    // Start the current TestPlan step, with `users` users running when it starts.
+    fn start_test_plan_step(
+        &mut self,
+        goose_attack_run_state: &mut GooseAttackRunState,
+        users: usize,
+    ) {
         // Record the instant this new step starts, for use with timers.
         self.step_started = Some(time::Instant::now());
 
-        let action = if self.test_plan.current == self.test_plan.steps.len() - 1 {
-            // If this is the last TestPlan step and there are 0 users, shut down.
-            if self.test_plan.steps[self.test_plan.current].0 == 0 {
+        let action = match self.test_plan.steps.get(self.test_plan.current) {
+            // If the last TestPlan step completed and there are 0 users, shut down.
+            None if users == 0 => {
                 // @TODO: don't shut down if stopped by a controller...
                 self.set_attack_phase(goose_attack_run_state, AttackPhase::Shutdown);
                 TestPlanStepAction::Finished
             }
             // Otherwise maintain the number of GooseUser threads until canceled.
-            else {
+            None => {
                 self.set_attack_phase(goose_attack_run_state, AttackPhase::Maintain);
                 TestPlanStepAction::Maintaining
             }
             // If this is not the last TestPlan step, determine what happens next.
-        } else if self.test_plan.current < self.test_plan.steps.len() {
-            match self.test_plan.steps[self.test_plan.current]
-                .0
-                .cmp(&self.test_plan.steps[self.test_plan.current + 1].0)
-            {
+            Some(step) => match users.cmp(&step.0) {
                 Ordering::Less => {
                     self.set_attack_phase(goose_attack_run_state, AttackPhase::Increase);
                     TestPlanStepAction::Increasing
@@ -211,18 +623,318 @@ impl<G: Goose> GooseAttack<G> {
                     self.set_attack_phase(goose_attack_run_state, AttackPhase::Maintain);
                     TestPlanStepAction::Maintaining
                 }
-            }
-        } else {
-            unreachable!("Advanced 2 steps beyond the end of the TestPlan.")
+            },
         };
 
         // Record details about new new TestPlan step that is starting.
         self.metrics.history.push(TestPlanHistory::step(
             action,
-            self.test_plan.steps[self.test_plan.current].0,
+            users,
+            self.test_plan.stage(self.test_plan.current),
         ));
+    }
 
-        // Always advance the TestPlan step
-        self.test_plan.current += 1;
+    // How many users were running when the current TestPlan step started.
+    pub(crate) fn step_users(&self) -> usize {
+        self.metrics.history.last().map_or(0, |step| step.users)
+    }
+
+    // Determine if a Controller can change the running TestPlan.
+    pub(crate) fn test_plan_changeable(&self) -> bool {
+        matches!(
+            self.attack_phase,
+            AttackPhase::Increase | AttackPhase::Decrease | AttackPhase::Maintain
+        ) && !matches!(
+            // A canceled load test is stopping all users.
+            self.metrics.history.last().map(|step| &step.action),
+            Some(TestPlanStepAction::Canceling)
+        )
+    }
+
+    // Allocate the users the rest of the TestPlan launches from `active_users`. If it only
+    // stops users, nothing is left to launch.
+    fn allocate_remaining_users(&mut self, active_users: usize) -> Result<(), GooseError> {
+        let remaining_users = self.test_plan.remaining_users(active_users);
+        self.weighted_users = if remaining_users > 0 {
+            self.weight_scenario_users(remaining_users)?
+        } else {
+            Vec::new()
+        };
+        Ok(())
+    }
+
+    // Append steps to the running TestPlan. If the last step already completed, the first
+    // appended step starts immediately.
+    pub(crate) fn append_test_plan(
+        &mut self,
+        goose_attack_run_state: &mut GooseAttackRunState,
+        plan: &str,
+    ) -> Result<(), GooseError> {
+        let completed = self.test_plan.current >= self.test_plan.steps.len();
+        self.test_plan.append(plan)?;
+
+        let active_users = goose_attack_run_state.active_users;
+        self.allocate_remaining_users(active_users)?;
+
+        if completed {
+            goose_attack_run_state.adjust_user_in_ms = 0;
+            self.start_test_plan_step(goose_attack_run_state, active_users);
+        }
+        Ok(())
+    }
+
+    // Extend how long the current TestPlan step runs. Returns false if the last step
+    // already completed, as its users are then maintained until the load test stops.
+    pub(crate) fn extend_test_plan_step(&mut self, ms: usize) -> bool {
+        if let Some(step) = self.test_plan.steps.get_mut(self.test_plan.current) {
+            step.1 += ms;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Jump to a TestPlan step, launching or stopping users from however many are running.
+    pub(crate) fn jump_test_plan(
+        &mut self,
+        goose_attack_run_state: &mut GooseAttackRunState,
+        step: usize,
+    ) -> Result<(), GooseError> {
+        self.test_plan.current = step;
+
+        let active_users = goose_attack_run_state.active_users;
+        self.allocate_remaining_users(active_users)?;
+
+        goose_attack_run_state.adjust_user_in_ms = 0;
+        self.start_test_plan_step(goose_attack_run_state, active_users);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_shapes() {
+        // Plain steps and linear shapes are equivalent.
+        let plain = "10,5s;0,1s".parse::<TestPlan>().unwrap();
+        let linear = "linear(10,5s);linear(0,1s)".parse::<TestPlan>().unwrap();
+        assert_eq!(plain.steps, linear.steps);
+
+        // A step immediately changes the number of users, then holds.
+        let step = "step(10,1m)".parse::<TestPlan>().unwrap();
+        assert_eq!(step.steps, vec![(10, 0), (10, 60_000)]);
+
+        // A spike returns to the previous number of users.
+        let spike = "5,10s;spike(50,30s);5,10s".parse::<TestPlan>().unwrap();
+        assert_eq!(
+            spike.steps,
+            vec![(5, 10_000), (50, 0), (50, 30_000), (5, 0), (5, 10_000)]
+        );
+
+        // Stairs climb in equal steps from the previous number of users.
+        let stairs = "10,1s;stairs(40,3,1m)".parse::<TestPlan>().unwrap();
+        assert_eq!(
+            stairs.steps,
+            vec![
+                (10, 1_000),
+                (20, 0),
+                (20, 60_000),
+                (30, 0),
+                (30, 60_000),
+                (40, 0),
+                (40, 60_000)
+            ]
+        );
+
+        // Exponential ramps grow slowly and then quickly, ending at the configured users.
+        let exponential = "exponential(100,10s)".parse::<TestPlan>().unwrap();
+        assert_eq!(exponential.steps.len(), 10);
+        assert_eq!(exponential.steps.last().unwrap().0, 100);
+        assert!(exponential.steps[4].0 < 20);
+        assert!(exponential.steps.windows(2).all(|s| s[0].0 <= s[1].0));
+        let total: usize = exponential.steps.iter().map(|s| s.1).sum();
+        assert_eq!(total, 10_000);
+
+        // Sine waves oscillate between the minimum and maximum.
+        let sine = "sine(10,30,1m,2m)".parse::<TestPlan>().unwrap();
+        assert_eq!(sine.steps.len(), 24);
+        assert_eq!(sine.steps[2].0, 30);
+        assert_eq!(sine.steps[8].0, 10);
+        assert_eq!(sine.steps[11].0, 20);
+        assert!(sine.steps.iter().all(|s| s.0 >= 10 && s.0 <= 30));
+        let total: usize = sine.steps.iter().map(|s| s.1).sum();
+        assert_eq!(total, 120_000);
+
+        // Invalid shapes and arguments are rejected.
+        assert!("wave(10,1m)".parse::<TestPlan>().is_err());
+        assert!("step(10)".parse::<TestPlan>().is_err());
+        assert!("sine(30,10,1m,1m)".parse::<TestPlan>().is_err());
+        assert!("stairs(10,0,1m)".parse::<TestPlan>().is_err());
+        assert!("exponential(10,foo)".parse::<TestPlan>().is_err());
+    }
+
+    #[test]
+    fn test_plan_users_at() {
+        let test_plan = "10,10s;10,1m;0,0s".parse::<TestPlan>().unwrap();
+        assert_eq!(test_plan.users_at(0), 0);
+        assert_eq!(test_plan.users_at(5_000), 5);
+        assert_eq!(test_plan.users_at(10_000), 10);
+        assert_eq!(test_plan.users_at(69_999), 10);
+        assert_eq!(test_plan.users_at(70_000), 0);
+
+        // The last step is maintained.
+        let test_plan = "20,0s".parse::<TestPlan>().unwrap();
+        assert_eq!(test_plan.users_at(0), 20);
+        assert_eq!(test_plan.users_at(1_000_000), 20);
+
+        // Users are stopped evenly.
+        let test_plan = "10,0s;0,10s".parse::<TestPlan>().unwrap();
+        assert_eq!(test_plan.users_at(0), 10);
+        assert_eq!(test_plan.users_at(2_000), 8);
+        assert_eq!(test_plan.users_at(10_000), 0);
+    }
+
+    #[test]
+    fn test_plan_append() {
+        let mut test_plan = TestPlan {
+            steps: vec![(10, 10_000), (10, 3_600_000)],
+            current: 0,
+            stages: vec![Some("warmup".to_string()), Some("soak".to_string())],
+        };
+
+        // Appended shapes start from the users configured by the last step.
+        test_plan.append("spike(50,30s);0,30s").unwrap();
+        assert_eq!(
+            test_plan.steps[2..],
+            [(50, 0), (50, 30_000), (10, 0), (0, 30_000)]
+        );
+        assert_eq!(test_plan.stages.len(), test_plan.steps.len());
+        assert_eq!(test_plan.stage(2), None);
+        assert!(test_plan.append("sine(30,10,1m,1m)").is_err());
+        assert_eq!(test_plan.steps.len(), 6);
+
+        // Steps are found by number, starting from 1, or by the stage they start.
+        assert_eq!(test_plan.find_step("1"), Some(0));
+        assert_eq!(test_plan.find_step("6"), Some(5));
+        assert_eq!(test_plan.find_step("0"), None);
+        assert_eq!(test_plan.find_step("7"), None);
+        assert_eq!(test_plan.find_step("soak"), Some(1));
+        assert_eq!(test_plan.find_step("cooldown"), None);
+
+        // Only users launched by the rest of the test plan need to be allocated.
+        assert_eq!(test_plan.remaining_users(0), 50);
+        test_plan.current = 1;
+        assert_eq!(test_plan.remaining_users(10), 40);
+        test_plan.current = 3;
+        assert_eq!(test_plan.remaining_users(50), 0);
+    }
+
+    #[test]
+    fn test_plan_file() {
+        // Write a test plan file, returning its path.
+        let write = |name: &str, contents: &str| {
+            let path = std::env::temp_dir().join(format!(
+                "goose-test-plan-{}-{}",
+                std::process::id(),
+                name
+            ));
+            std::fs::write(&path, contents).unwrap();
+            path.to_str().unwrap().to_string()
+        };
+
+        // The same stages can be defined in TOML, YAML and JSON.
+        let toml = write(
+            "plan.toml",
+            r#"
+[[stages]]
+name = "warmup"
+users = 10
+duration = "10s"
+hold = "1m"
+
+[[stages]]
+name = "ramp"
+users = 30
+rate = 2
+
+[[stages]]
+name = "spike"
+shape = "spike(100,30s)"
+
+[[stages]]
+name = "shutdown"
+users = 0
+"#,
+        );
+        let yaml = write(
+            "plan.yaml",
+            r#"
+stages:
+  - name: warmup
+    users: 10
+    duration: 10
+    hold: 1m
+  - name: ramp
+    users: 30
+    rate: 2
+  - name: spike
+    shape: spike(100,30s)
+  - name: shutdown
+    users: 0
+"#,
+        );
+        let json = write(
+            "plan.json",
+            r#"{"stages": [
+                {"name": "warmup", "users": 10, "duration": "10s", "hold": 60},
+                {"name": "ramp", "users": 30, "rate": 2.0},
+                {"name": "spike", "shape": "spike(100,30s)"},
+                {"name": "shutdown", "users": 0}
+            ]}"#,
+        );
+        for path in [&toml, &yaml, &json] {
+            let test_plan = TestPlan::from_file(path).unwrap();
+            assert_eq!(
+                test_plan.steps,
+                vec![
+                    (10, 10_000),
+                    (10, 60_000),
+                    (30, 10_000),
+                    (100, 0),
+                    (100, 30_000),
+                    (30, 0),
+                    (0, 0)
+                ]
+            );
+            assert_eq!(test_plan.stage(0), Some("warmup".to_string()));
+            assert_eq!(test_plan.stage(1), Some("warmup".to_string()));
+            assert_eq!(test_plan.stage(2), Some("ramp".to_string()));
+            assert_eq!(test_plan.stage(5), Some("spike".to_string()));
+            assert_eq!(test_plan.stage(6), Some("shutdown".to_string()));
+            assert_eq!(test_plan.stage(7), None);
+            assert!(test_plan.preview().contains("spike"));
+        }
+
+        // Invalid stages are rejected.
+        let invalid = [
+            "stages: []",
+            "stages: [{name: a}]",
+            "stages: [{name: a, users: 10, duration: foo}]",
+            "stages: [{name: a, users: 10, duration: 1m, rate: 2}]",
+            "stages: [{name: a, users: 10, shape: step(10,1m)}]",
+            "stages: [{name: a, users: 10, unknown: 1}]",
+        ];
+        for (index, contents) in invalid.iter().enumerate() {
+            let path = write(&format!("invalid-{}.yaml", index), contents);
+            assert!(TestPlan::from_file(&path).is_err(), "{}", contents);
+            std::fs::remove_file(path).unwrap();
+        }
+        assert!(TestPlan::from_file("/does/not/exist.toml").is_err());
+
+        for path in [toml, yaml, json] {
+            std::fs::remove_file(path).unwrap();
+        }
     }
 }
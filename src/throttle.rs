@@ -1,70 +1,159 @@
-use tokio::time;
+//! A token bucket limiting how quickly all [`GooseUser`](../goose/struct.GooseUser.html)s
+//! can make requests.
+//!
+//! The throttle is enabled with `--throttle-requests`, and the number of requests that
+//! can be made at once after a quiet period is configured with `--throttle-burst`. When
+//! a Controller is enabled the throttle rate can also be changed, enabled or disabled
+//! while a load test is running.
 
-use crate::util;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-/// This throttle thread limits the maximum number of requests that can be made across
-/// all GooseUser threads. When enabled, GooseUser threads must add a token to the
-/// bounded channel before making a request, and this thread limits how frequently
-/// tokens are removed thereby throttling how fast requests can be made. It is an
-/// implementation of the leaky bucket algorithm as a queue: instead of leaking the
-/// overflow we asynchronously block. More information on the leaky bucket algorithm
-/// can be found at: https://en.wikipedia.org/wiki/Leaky_bucket
-pub async fn throttle_main(
-    throttle_requests: usize,
-    throttle_receiver: flume::Receiver<bool>,
-    parent_receiver: flume::Receiver<bool>,
-) {
-    // Use microseconds to allow configurations up to 1,000,000 requests per second.
-    let mut sleep_duration = time::Duration::from_micros(1_000_000 / throttle_requests as u64);
-    let tokens_per_duration;
+/// Never sleep longer than this while waiting for a token, so changes to the throttle
+/// rate and the end of the load test are noticed quickly.
+const MAXIMUM_WAIT: Duration = Duration::from_millis(100);
 
-    let ten_milliseconds = time::Duration::from_millis(10);
-    debug!(
-        "sleep_duration: {:?} ten_milliseconds: {:?}",
-        sleep_duration, ten_milliseconds
-    );
+/// The state of the token bucket.
+#[derive(Debug)]
+struct TokenBucket {
+    /// How many tokens are currently available.
+    tokens: f64,
+    /// The maximum number of tokens that can accumulate.
+    burst: f64,
+    /// When tokens were last added to the bucket.
+    refilled: Instant,
+}
 
-    // Keep sleep_duration at least ~10ms as `delay_for` has millisecond granularity.
-    if sleep_duration < ten_milliseconds {
-        tokens_per_duration = (ten_milliseconds.as_nanos() / sleep_duration.as_nanos()) as u32;
-        sleep_duration *= tokens_per_duration;
-    } else {
-        tokens_per_duration = 1;
+/// A token bucket shared by all [`GooseUser`](../goose/struct.GooseUser.html) threads.
+///
+/// Tokens are added to the bucket at the configured rate, up to the configured burst.
+/// Each throttled request must remove a token from the bucket before it is made, waiting
+/// if none are available. More information on the token bucket algorithm can be found
+/// at: https://en.wikipedia.org/wiki/Token_bucket
+#[derive(Debug)]
+pub struct GooseThrottle {
+    /// Maximum requests per second, or 0 if the throttle is disabled.
+    rate: AtomicUsize,
+    /// Set when the load test ends, canceling any further requests.
+    closed: AtomicBool,
+    /// The token bucket.
+    bucket: Mutex<TokenBucket>,
+}
+impl GooseThrottle {
+    /// Create a new throttle allowing `rate` requests per second, and bursts of up to
+    /// `burst` requests. A `rate` of 0 creates a disabled throttle.
+    pub fn new(rate: usize, burst: usize) -> Self {
+        if rate > 0 {
+            info!(
+                "throttle allowing {} request(s) per second with a burst of {}",
+                rate, burst
+            );
+        }
+        GooseThrottle {
+            rate: AtomicUsize::new(rate),
+            closed: AtomicBool::new(false),
+            // Start with a single token to avoid a burst of traffic during startup.
+            bucket: Mutex::new(TokenBucket {
+                tokens: 1.0,
+                burst: burst.max(1) as f64,
+                refilled: Instant::now(),
+            }),
+        }
     }
 
-    info!(
-        "throttle allowing {} request(s) every {:?}",
-        tokens_per_duration, sleep_duration
-    );
+    /// The current maximum requests per second, or 0 if the throttle is disabled.
+    pub fn rate(&self) -> usize {
+        self.rate.load(Ordering::Relaxed)
+    }
 
-    // One or more token gets removed from the throttle_receiver bucket at regular
-    // intervals. The throttle_drift variable tracks how much time is spent on
-    // everything else, and is subtracted from the time spent sleeping.
-    let mut throttle_drift = tokio::time::Instant::now();
+    /// Change the maximum requests per second. Setting the rate to 0 disables the throttle.
+    pub fn set_rate(&self, rate: usize) {
+        let mut bucket = self.bucket.lock().unwrap();
+        // Account for tokens earned at the previous rate before switching.
+        self.refill(&mut bucket, self.rate());
+        self.rate.store(rate, Ordering::Relaxed);
+        info!("throttle changed to {} request(s) per second", rate);
+    }
 
-    // Loop and remove tokens from channel at controlled rate until load test ends.
-    loop {
-        debug!(
-            "throttle removing {} token(s) from channel",
-            tokens_per_duration
-        );
-        throttle_drift = util::sleep_minus_drift(sleep_duration, throttle_drift).await;
+    /// Cancel all pending and future requests, called when the load test ends.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+    }
 
-        // A message will be received when the load test is over.
-        if parent_receiver.try_recv().is_ok() {
-            // Close throttle channel to prevent any further requests.
-            info!("load test complete, closing throttle channel");
-            drop(throttle_receiver);
-            break;
+    /// Wait until a request is allowed. Returns `false` if the load test ended while
+    /// waiting and the request should be canceled.
+    pub async fn acquire(&self) -> bool {
+        loop {
+            // A disabled throttle behaves as if there's no throttle at all.
+            let rate = self.rate();
+            if rate == 0 {
+                return true;
+            }
+            if self.closed.load(Ordering::Relaxed) {
+                return false;
+            }
+
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap();
+                self.refill(&mut bucket, rate);
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return true;
+                }
+                Duration::from_secs_f64((1.0 - bucket.tokens) / rate as f64)
+            };
+            tokio::time::sleep(wait.min(MAXIMUM_WAIT)).await;
         }
+    }
 
-        // Remove tokens from the channel, freeing spots for request to be made.
-        for token in 0..tokens_per_duration {
-            // If the channel is empty, we will get an error, so stop trying to remove tokens.
-            if throttle_receiver.try_recv().is_err() {
-                debug!("empty channel, exit after removing {} tokens", token);
-                break;
-            }
+    // Add tokens earned since the bucket was last refilled, up to the burst.
+    fn refill(&self, bucket: &mut TokenBucket, rate: usize) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate as f64).min(bucket.burst);
+        bucket.refilled = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn token_bucket() {
+        // A disabled throttle never waits.
+        let throttle = GooseThrottle::new(0, 1);
+        let started = Instant::now();
+        for _ in 0..100 {
+            assert!(throttle.acquire().await);
         }
+        assert!(started.elapsed() < Duration::from_millis(100));
+
+        // After a quiet period, up to `burst` requests are allowed immediately.
+        let throttle = GooseThrottle::new(10, 5);
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        let started = Instant::now();
+        for _ in 0..5 {
+            assert!(throttle.acquire().await);
+        }
+        assert!(started.elapsed() < Duration::from_millis(50));
+        // The next request waits for a token.
+        assert!(throttle.acquire().await);
+        assert!(started.elapsed() >= Duration::from_millis(50));
+
+        // Disabling the throttle at runtime stops requests from waiting.
+        throttle.set_rate(0);
+        assert_eq!(throttle.rate(), 0);
+        let started = Instant::now();
+        for _ in 0..100 {
+            assert!(throttle.acquire().await);
+        }
+        assert!(started.elapsed() < Duration::from_millis(100));
+
+        // Closing the throttle cancels requests.
+        throttle.set_rate(1);
+        throttle.close();
+        assert!(!throttle.acquire().await);
     }
 }
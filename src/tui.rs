@@ -0,0 +1,654 @@
+//! Optional live full-screen dashboard, enabled with `--tui` when Goose is compiled with
+//! the `tui` feature.
+//!
+//! The dashboard is a Controller client that draws the terminal instead of listening on a
+//! TCP port. Once a second it requests the status and metrics of the load test from the
+//! parent process, and it sends the same requests as the telnet, WebSocket and HTTP
+//! Controllers when a key is pressed:
+//!  - `+` and `-`: add or remove a user, `]` and `[`: add or remove 10 users.
+//!  - `p`: pause or resume the load test.
+//!  - `s`: stop a running load test, or start an idle load test.
+//!  - `r`: reset metrics.
+//!  - `q` or `ctrl-c`: shut down the load test.
+
+use crossterm::cursor::Show;
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use futures::StreamExt;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Stdout};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+use std::time::Duration;
+use tokio::sync::watch;
+use tui_rs::backend::{Backend, CrosstermBackend};
+use tui_rs::layout::{Constraint, Direction, Layout};
+use tui_rs::style::{Color, Modifier, Style};
+use tui_rs::text::{Span, Spans};
+use tui_rs::widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Row, Sparkline, Table};
+use tui_rs::{Frame, Terminal};
+
+use crate::controller::{
+    send_controller_request, ControllerCommand, ControllerRequest, ControllerRequestMessage,
+    ControllerResponseMessage, ControllerStatus,
+};
+use crate::metrics::GooseMetrics;
+use crate::{AttackPhase, GooseError};
+
+// How many seconds of requests and errors the sparklines remember.
+const SPARKLINE_SECONDS: usize = 300;
+
+// How many recent errors the dashboard remembers.
+const RECENT_ERRORS: usize = 20;
+
+// The dashboard identifies itself to the parent process as this Controller client.
+const TUI_CLIENT_ID: u32 = 0;
+
+// Whether the dashboard has taken over the terminal and not yet restored it.
+static TERMINAL_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+// Restores the terminal before a panic message is printed, installed once.
+static PANIC_HOOK: Once = Once::new();
+
+/// A running dashboard, drawing the terminal until told to exit.
+#[derive(Debug)]
+pub(crate) struct GooseTui {
+    // Tells the dashboard to exit.
+    exit_tx: watch::Sender<bool>,
+    // The task drawing the dashboard.
+    handle: tokio::task::JoinHandle<()>,
+    // Restores the terminal if the load test returns early, without calling exit().
+    _terminal_guard: TerminalGuard,
+}
+
+/// Restores the terminal when dropped, unless it was already restored.
+#[derive(Debug)]
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = restore_terminal();
+    }
+}
+
+impl GooseTui {
+    /// Take over the terminal and start drawing the dashboard, sending requests to the
+    /// parent process through the Controller channel.
+    pub(crate) fn start(
+        channel_tx: flume::Sender<ControllerRequest>,
+    ) -> Result<GooseTui, GooseError> {
+        // Created first, so the terminal is also restored if setting it up fails.
+        let terminal_guard = TerminalGuard;
+        let terminal = setup_terminal().map_err(|e| GooseError::InvalidOption {
+            option: "--tui".to_string(),
+            value: true.to_string(),
+            detail: format!("failed to initialize the terminal: {}", e),
+        })?;
+        let (exit_tx, exit_rx) = watch::channel(false);
+        let handle = tokio::spawn(tui_main(terminal, channel_tx, exit_rx));
+        Ok(GooseTui {
+            exit_tx,
+            handle,
+            _terminal_guard: terminal_guard,
+        })
+    }
+
+    /// Stop drawing the dashboard and restore the terminal, so final metrics can be
+    /// displayed.
+    pub(crate) async fn exit(self) {
+        let _ = self.exit_tx.send(true);
+        if let Err(e) = self.handle.await {
+            warn!("failed to exit the dashboard: {}", e);
+        }
+    }
+}
+
+// Switch to a full-screen terminal that reports every key press.
+fn setup_terminal() -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
+    // Panic messages would otherwise be printed to the alternate screen, and lost.
+    PANIC_HOOK.call_once(|| {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = restore_terminal();
+            default_hook(info);
+        }));
+    });
+
+    TERMINAL_ACTIVE.store(true, Ordering::SeqCst);
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+    terminal.hide_cursor()?;
+    Ok(terminal)
+}
+
+// Return the terminal to how it was before the dashboard was drawn. Does nothing if the
+// terminal was already restored.
+fn restore_terminal() -> io::Result<()> {
+    if !TERMINAL_ACTIVE.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, Show)
+}
+
+// Draw the dashboard until the parent process tells it to exit, or closes the Controller
+// channel.
+async fn tui_main(
+    mut terminal: Terminal<CrosstermBackend<Stdout>>,
+    channel_tx: flume::Sender<ControllerRequest>,
+    mut exit_rx: watch::Receiver<bool>,
+) {
+    let mut dashboard = Dashboard::default();
+    let mut events = EventStream::new();
+    let mut refresh = tokio::time::interval(Duration::from_secs(1));
+
+    loop {
+        let command = tokio::select! {
+            _ = exit_rx.changed() => break,
+            _ = refresh.tick() => None,
+            event = events.next() => match event {
+                Some(Ok(Event::Key(key))) => dashboard.key_command(key),
+                // Redraw after the terminal is resized.
+                Some(Ok(_)) => None,
+                _ => break,
+            },
+        };
+
+        // Wait for the parent process to reply, unless it's telling the dashboard to exit.
+        let replied = tokio::select! {
+            _ = exit_rx.changed() => break,
+            replied = dashboard.update(&channel_tx, command) => replied,
+        };
+        if !replied {
+            break;
+        }
+
+        if let Err(e) = terminal.draw(|f| draw(f, &dashboard)) {
+            warn!("failed to draw the dashboard: {}", e);
+            break;
+        }
+    }
+
+    if let Err(e) = restore_terminal() {
+        warn!("failed to restore the terminal: {}", e);
+    }
+}
+
+// Send a request to the parent process, and wait for a reply.
+async fn request(
+    channel_tx: &flume::Sender<ControllerRequest>,
+    command: ControllerCommand,
+    value: Option<String>,
+) -> Option<ControllerResponseMessage> {
+    let request = ControllerRequestMessage { command, value };
+    send_controller_request(channel_tx, TUI_CLIENT_ID, request).await
+}
+
+// What the dashboard knows about the load test.
+#[derive(Default)]
+struct Dashboard {
+    // The status of the load test, once received.
+    status: Option<ControllerStatus>,
+    // The metrics of the load test, once received.
+    metrics: Option<GooseMetrics>,
+    // Requests made each second, most recent last.
+    requests_per_second: VecDeque<u64>,
+    // Requests that failed each second, most recent last.
+    errors_per_second: VecDeque<u64>,
+    // How many requests and errors had been made when the metrics were last received.
+    totals: Option<(u64, u64)>,
+    // How many times each error had occurred when the metrics were last received.
+    error_occurrences: HashMap<String, usize>,
+    // Errors that most recently occurred again, most recent first.
+    recent_errors: VecDeque<String>,
+    // The reply to the most recent key press.
+    message: String,
+}
+
+impl Dashboard {
+    // Map a key press to the Controller command it runs, if any.
+    fn key_command(&self, key: KeyEvent) -> Option<(ControllerCommand, Option<String>)> {
+        let phase = self.status.as_ref().map(|status| &status.phase);
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                Some((ControllerCommand::Shutdown, None))
+            }
+            KeyCode::Char('q') => Some((ControllerCommand::Shutdown, None)),
+            KeyCode::Char('+') => self.change_users(1),
+            KeyCode::Char('-') => self.change_users(-1),
+            KeyCode::Char(']') => self.change_users(10),
+            KeyCode::Char('[') => self.change_users(-10),
+            KeyCode::Char('p') if phase == Some(&AttackPhase::Pause) => {
+                Some((ControllerCommand::Resume, None))
+            }
+            KeyCode::Char('p') => Some((ControllerCommand::Pause, None)),
+            KeyCode::Char('s') if phase == Some(&AttackPhase::Idle) => {
+                Some((ControllerCommand::Start, None))
+            }
+            KeyCode::Char('s') => Some((ControllerCommand::Stop, None)),
+            KeyCode::Char('r') => Some((ControllerCommand::ResetMetrics, None)),
+            _ => None,
+        }
+    }
+
+    // Build a command changing the number of users the load test is ramping to.
+    fn change_users(&self, change: isize) -> Option<(ControllerCommand, Option<String>)> {
+        let target_users = self.status.as_ref()?.target_users as isize;
+        let users = (target_users + change).max(0);
+        Some((ControllerCommand::Users, Some(users.to_string())))
+    }
+
+    // Run the command of a key press if any, then refresh the status and metrics of the
+    // load test. Returns false if the parent process has closed the Controller channel.
+    async fn update(
+        &mut self,
+        channel_tx: &flume::Sender<ControllerRequest>,
+        command: Option<(ControllerCommand, Option<String>)>,
+    ) -> bool {
+        if let Some((command, value)) = command {
+            let response = match request(channel_tx, command.clone(), value).await {
+                Some(response) => response,
+                None => return false,
+            };
+            self.message = match command.describe_response(response) {
+                Ok(message) | Err(message) => message,
+            };
+        }
+
+        match request(channel_tx, ControllerCommand::Status, None).await {
+            Some(ControllerResponseMessage::Status(status)) => self.status = Some(*status),
+            Some(_) => (),
+            None => return false,
+        }
+        match request(channel_tx, ControllerCommand::Metrics, None).await {
+            Some(ControllerResponseMessage::Metrics(metrics)) => self.record_metrics(*metrics),
+            Some(_) => (),
+            None => return false,
+        }
+        true
+    }
+
+    // Update the sparklines and recent errors from newly received metrics.
+    fn record_metrics(&mut self, metrics: GooseMetrics) {
+        let (requests, errors) =
+            metrics
+                .requests
+                .values()
+                .fold((0, 0), |(requests, errors), request| {
+                    (
+                        requests + (request.success_count + request.fail_count) as u64,
+                        errors + request.fail_count as u64,
+                    )
+                });
+
+        // Only graph seconds the load test clock is running.
+        let running = matches!(
+            self.status.as_ref().map(|status| &status.phase),
+            Some(AttackPhase::Increase) | Some(AttackPhase::Maintain) | Some(AttackPhase::Decrease)
+        );
+        if let (true, Some((last_requests, last_errors))) = (running, self.totals) {
+            // Metrics can be reset, in which case totals start again from zero.
+            push_sample(
+                &mut self.requests_per_second,
+                requests.saturating_sub(last_requests),
+            );
+            push_sample(
+                &mut self.errors_per_second,
+                errors.saturating_sub(last_errors),
+            );
+        }
+        self.totals = Some((requests, errors));
+
+        for (key, error) in &metrics.errors {
+            if self.error_occurrences.get(key) != Some(&error.occurrences) {
+                self.error_occurrences
+                    .insert(key.to_string(), error.occurrences);
+                self.recent_errors.retain(|recent| recent != key);
+                self.recent_errors.push_front(key.to_string());
+            }
+        }
+        self.error_occurrences
+            .retain(|key, _| metrics.errors.contains_key(key));
+        self.recent_errors
+            .retain(|key| metrics.errors.contains_key(key));
+        self.recent_errors.truncate(RECENT_ERRORS);
+
+        self.metrics = Some(metrics);
+    }
+
+    // The requests with the highest average response time, slowest first, as the name,
+    // average and maximum response time, and how many requests were made and failed.
+    fn slowest_requests(&self) -> Vec<(String, f32, usize, usize, usize)> {
+        let mut requests: Vec<(String, f32, usize, usize, usize)> = match &self.metrics {
+            Some(metrics) => metrics
+                .requests
+                .iter()
+                .filter(|(_, request)| request.raw_data.counter > 0)
+                .map(|(key, request)| {
+                    (
+                        key.to_string(),
+                        request.raw_data.total_time as f32 / request.raw_data.counter as f32,
+                        request.raw_data.maximum_time,
+                        request.success_count + request.fail_count,
+                        request.fail_count,
+                    )
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        requests.sort_by(|a, b| b.1.total_cmp(&a.1));
+        requests
+    }
+}
+
+// Add a sample to a sparkline, forgetting the oldest sample once it's full.
+fn push_sample(samples: &mut VecDeque<u64>, sample: u64) {
+    if samples.len() == SPARKLINE_SECONDS {
+        samples.pop_front();
+    }
+    samples.push_back(sample);
+}
+
+// Draw the dashboard.
+fn draw<B: Backend>(f: &mut Frame<B>, dashboard: &Dashboard) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(7),
+                Constraint::Min(5),
+                Constraint::Length(1),
+            ]
+            .as_ref(),
+        )
+        .split(f.size());
+    let halves = |row| {
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(row)
+    };
+
+    // The phase and current test plan step of the load test.
+    let (phase, users, target_users, duration, host) = match &dashboard.status {
+        Some(status) => (
+            format!("{:?}", status.phase),
+            status.users,
+            status.target_users,
+            status.duration,
+            status.host.as_str(),
+        ),
+        None => ("Starting".to_string(), 0, 0, 0, ""),
+    };
+    let step = dashboard
+        .metrics
+        .as_ref()
+        .and_then(|metrics| metrics.history.last())
+        .map_or_else(|| "-".to_string(), |step| step.label());
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let overview = Paragraph::new(Spans::from(vec![
+        Span::styled("Phase: ", bold),
+        Span::raw(format!("{}  ", phase)),
+        Span::styled("Step: ", bold),
+        Span::raw(format!("{}  ", step)),
+        Span::styled("Duration: ", bold),
+        Span::raw(format!(
+            "{:02}:{:02}:{:02}  ",
+            duration / 3600,
+            duration / 60 % 60,
+            duration % 60
+        )),
+        Span::styled("Host: ", bold),
+        Span::raw(host),
+    ]))
+    .block(Block::default().borders(Borders::ALL).title(" Goose "));
+    f.render_widget(overview, rows[0]);
+
+    // Active users compared to how many users the test plan is ramping to.
+    let ratio = if target_users > 0 {
+        (users as f64 / target_users as f64).min(1.0)
+    } else {
+        0.0
+    };
+    let users_gauge = Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title(" Users "))
+        .gauge_style(Style::default().fg(Color::Green))
+        .ratio(ratio)
+        .label(format!("{} of {} users", users, target_users));
+    f.render_widget(users_gauge, rows[1]);
+
+    // Requests and errors per second.
+    let sparklines = halves(rows[2]);
+    let requests: Vec<u64> = dashboard.requests_per_second.iter().copied().collect();
+    let errors: Vec<u64> = dashboard.errors_per_second.iter().copied().collect();
+    for (area, title, data, color) in [
+        (sparklines[0], "Requests/s", &requests, Color::Green),
+        (sparklines[1], "Errors/s", &errors, Color::Red),
+    ] {
+        // Only show as many seconds as fit.
+        let width = area.width.saturating_sub(2) as usize;
+        let data = &data[data.len().saturating_sub(width)..];
+        let sparkline = Sparkline::default()
+            .block(Block::default().borders(Borders::ALL).title(format!(
+                " {}: {} ",
+                title,
+                data.last().copied().unwrap_or(0)
+            )))
+            .style(Style::default().fg(color))
+            .data(data);
+        f.render_widget(sparkline, area);
+    }
+
+    // The slowest requests, and the most recent errors.
+    let details = halves(rows[3]);
+    let slowest = dashboard.slowest_requests();
+    let table = Table::new(
+        slowest
+            .iter()
+            .map(|(name, average, maximum, count, fails)| {
+                Row::new(vec![
+                    name.to_string(),
+                    format!("{:.2}", average),
+                    maximum.to_string(),
+                    count.to_string(),
+                    fails.to_string(),
+                ])
+            }),
+    )
+    .header(Row::new(vec!["Name", "Avg (ms)", "Max (ms)", "# reqs", "# fails"]).style(bold))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Slowest requests "),
+    )
+    .widths(
+        [
+            Constraint::Percentage(40),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+        ]
+        .as_ref(),
+    );
+    f.render_widget(table, details[0]);
+
+    let errors: Vec<ListItem> = match &dashboard.metrics {
+        Some(metrics) => dashboard
+            .recent_errors
+            .iter()
+            .filter_map(|key| metrics.errors.get(key))
+            .map(|error| {
+                ListItem::new(format!(
+                    "{}x {} {}: {}",
+                    error.occurrences, error.method, error.name, error.error
+                ))
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    let errors = List::new(errors)
+        .style(Style::default().fg(Color::Red))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Recent errors "),
+        );
+    f.render_widget(errors, details[1]);
+
+    // The keys that control the load test, and the reply to the last key pressed.
+    let keys = Paragraph::new(Spans::from(vec![
+        Span::styled(
+            " +/- users  [/] 10 users  p pause  s start/stop  r reset metrics  q quit ",
+            Style::default().add_modifier(Modifier::REVERSED),
+        ),
+        Span::raw(format!(" {}", dashboard.message)),
+    ]));
+    f.render_widget(keys, rows[4]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::goose::GooseMethod;
+    use crate::metrics::{GooseErrorMetricAggregate, GooseRequestMetricAggregate};
+    use tui_rs::backend::TestBackend;
+
+    fn status(phase: AttackPhase, target_users: usize) -> ControllerStatus {
+        ControllerStatus {
+            phase,
+            users: 5,
+            target_users,
+            host: "http://127.0.0.1/".to_string(),
+            duration: 65,
+            throttle_requests: 0,
+        }
+    }
+
+    fn metrics(requests: usize, fails: usize, errors: &[(&str, usize)]) -> GooseMetrics {
+        let mut metrics = GooseMetrics::default();
+        let mut request = GooseRequestMetricAggregate::new("/", GooseMethod::Get, 0);
+        request.success_count = requests - fails;
+        request.fail_count = fails;
+        request.raw_data.record_time(20);
+        metrics.requests.insert("GET /".to_string(), request);
+        for (error, occurrences) in errors {
+            let mut aggregate = GooseErrorMetricAggregate::new(
+                GooseMethod::Get,
+                "/".to_string(),
+                error.to_string(),
+            );
+            aggregate.occurrences = *occurrences;
+            metrics.errors.insert(error.to_string(), aggregate);
+        }
+        metrics
+    }
+
+    #[test]
+    fn key_commands() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut dashboard = Dashboard::default();
+
+        // Users can't be changed until the status is known.
+        assert!(dashboard.key_command(key(KeyCode::Char('+'))).is_none());
+
+        dashboard.status = Some(status(AttackPhase::Maintain, 5));
+        assert_eq!(
+            dashboard.key_command(key(KeyCode::Char('+'))),
+            Some((ControllerCommand::Users, Some("6".to_string())))
+        );
+        assert_eq!(
+            dashboard.key_command(key(KeyCode::Char('['))),
+            Some((ControllerCommand::Users, Some("0".to_string())))
+        );
+        assert_eq!(
+            dashboard.key_command(key(KeyCode::Char('p'))),
+            Some((ControllerCommand::Pause, None))
+        );
+        assert_eq!(
+            dashboard.key_command(key(KeyCode::Char('s'))),
+            Some((ControllerCommand::Stop, None))
+        );
+        assert_eq!(
+            dashboard.key_command(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Some((ControllerCommand::Shutdown, None))
+        );
+        assert!(dashboard.key_command(key(KeyCode::Char('x'))).is_none());
+
+        dashboard.status = Some(status(AttackPhase::Pause, 5));
+        assert_eq!(
+            dashboard.key_command(key(KeyCode::Char('p'))),
+            Some((ControllerCommand::Resume, None))
+        );
+        dashboard.status = Some(status(AttackPhase::Idle, 5));
+        assert_eq!(
+            dashboard.key_command(key(KeyCode::Char('s'))),
+            Some((ControllerCommand::Start, None))
+        );
+    }
+
+    #[test]
+    fn record_metrics() {
+        let mut dashboard = Dashboard {
+            status: Some(status(AttackPhase::Maintain, 5)),
+            ..Default::default()
+        };
+
+        // The first metrics received are the baseline for the sparklines.
+        dashboard.record_metrics(metrics(10, 1, &[("timeout", 1)]));
+        assert!(dashboard.requests_per_second.is_empty());
+        dashboard.record_metrics(metrics(25, 3, &[("timeout", 1), ("500", 2)]));
+        assert_eq!(dashboard.requests_per_second, [15]);
+        assert_eq!(dashboard.errors_per_second, [2]);
+        assert_eq!(dashboard.recent_errors, ["500", "timeout"]);
+
+        // Errors move to the front when they occur again.
+        dashboard.record_metrics(metrics(30, 4, &[("timeout", 2), ("500", 2)]));
+        assert_eq!(dashboard.recent_errors, ["timeout", "500"]);
+
+        // Reset metrics start counting again from zero.
+        dashboard.record_metrics(metrics(2, 0, &[]));
+        assert_eq!(dashboard.requests_per_second, [15, 5, 0]);
+        assert!(dashboard.recent_errors.is_empty());
+
+        // Seconds aren't graphed while the load test is paused.
+        dashboard.status = Some(status(AttackPhase::Pause, 5));
+        dashboard.record_metrics(metrics(4, 0, &[]));
+        assert_eq!(dashboard.requests_per_second.len(), 3);
+
+        assert_eq!(dashboard.slowest_requests()[0].0, "GET /");
+    }
+
+    #[test]
+    fn draw_dashboard() {
+        let mut dashboard = Dashboard {
+            status: Some(status(AttackPhase::Maintain, 10)),
+            ..Default::default()
+        };
+        dashboard.record_metrics(metrics(10, 1, &[("timeout", 1)]));
+        dashboard.message = "metrics reset".to_string();
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|f| draw(f, &dashboard)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol.as_str())
+            .collect();
+        assert!(screen.contains("Phase: Maintain"));
+        assert!(screen.contains("Duration: 00:01:05"));
+        assert!(screen.contains("5 of 10 users"));
+        assert!(screen.contains("GET /"));
+        assert!(screen.contains("1x GET /: timeout"));
+        assert!(screen.contains("metrics reset"));
+    }
+}
@@ -1,8 +1,9 @@
-use rand::Rng;
 use std::time::{self, Duration};
 
 use crate::{get_worker_id, Goose};
-use crate::goose::{GooseUserCommand, Scenario, TransactionFunction};
+use crate::goose::{
+    GooseFailurePolicy, GooseUserCommand, Scenario, TransactionError, TransactionFunction,
+};
 use crate::logger::GooseLog;
 use crate::metrics::{GooseMetric, ScenarioMetric, TransactionMetric};
 
@@ -27,107 +28,170 @@ pub(crate) async fn user_main<G: Goose>(
         );
     }
 
+    // Requests made by the user default to the scenario retry policy.
+    thread_user.set_retry_policy(thread_scenario.retry_policy.clone());
+
     // User is starting, first invoke the weighted on_start transactions.
-    if !thread_scenario.weighted_on_start_transactions.is_empty() {
-        // Transactions are already weighted and scheduled, execute each in order.
-        for (thread_transaction_index, thread_transaction_name) in
-        &thread_scenario.weighted_on_start_transactions
-        {
-            // Determine which transaction we're going to run next.
-            let function = &thread_scenario.transactions[*thread_transaction_index].function;
-            debug!(
-                "[user {}]: launching on_start {} transaction from {}",
-                thread_number, thread_transaction_name, thread_scenario.name
-            );
-            // Invoke the transaction function.
-            let _todo = invoke_transaction_function(
-                function,
-                &mut thread_user,
-                *thread_transaction_index,
-                thread_transaction_name,
-            )
-                .await;
-        }
-    }
+    let started = start_user(
+        thread_number,
+        &thread_scenario,
+        &mut thread_user,
+        &thread_receiver,
+    )
+    .await;
 
     // If normal transactions are defined, loop launching transactions until parent tells us to stop.
-    if !thread_scenario.weighted_transactions.is_empty() {
+    if started && !thread_scenario.weighted_transactions.is_empty() {
         'launch_transactions: loop {
             // Tracks the time it takes to loop through all Transactions when Coordinated Omission
             // Mitigation is enabled.
             thread_user.update_request_cadence(thread_number);
             let scenario_started = time::Instant::now();
+            // Transitions taken when walking a transition graph.
+            let mut transitions: Vec<(String, String)> = Vec::new();
+            // Set if a transaction failed and the iteration was aborted.
+            let mut aborted: Option<GooseFailurePolicy> = None;
+            // Set once a transaction runs, as a Controller can skip transactions.
+            let mut ran_transaction = false;
 
-            for (thread_transaction_index, thread_transaction_name) in
-            &thread_scenario.weighted_transactions
-            {
-                // Determine which transaction we're going to run next.
-                let function = &thread_scenario.transactions[*thread_transaction_index].function;
-                debug!(
-                    "[user {}]: launching {} transaction from {}",
-                    thread_number, thread_transaction_name, thread_scenario.name
-                );
-                // Invoke the transaction function.
-                let _todo = invoke_transaction_function(
-                    function,
-                    &mut thread_user,
-                    *thread_transaction_index,
-                    thread_transaction_name,
-                )
+            if let Some(transition_graph) = &thread_scenario.transition_graph {
+                // Walk the transition graph from the entry transaction until exiting.
+                let mut next_transaction = Some(transition_graph.entry_index);
+                while let Some(thread_transaction_index) = next_transaction {
+                    let thread_transaction =
+                        &thread_scenario.transactions[thread_transaction_index];
+                    // Walk past transactions skipped by a Controller without running them.
+                    if thread_transaction.is_skipped() {
+                        next_transaction =
+                            transition_graph.next_transaction(thread_transaction_index);
+                        continue;
+                    }
+                    ran_transaction = true;
+                    debug!(
+                        "[user {}]: launching {} transaction from {}",
+                        thread_number, thread_transaction.name, thread_scenario.name
+                    );
+                    // Invoke the transaction function.
+                    let success = invoke_transaction_function(
+                        &thread_transaction.function,
+                        thread_transaction.timeout,
+                        &mut thread_user,
+                        thread_transaction_index,
+                        &thread_transaction.name,
+                    )
                     .await;
 
-                if received_exit(&thread_receiver) {
-                    break 'launch_transactions;
-                }
+                    // Determine which transaction we're going to run next, if any.
+                    next_transaction = transition_graph.next_transaction(thread_transaction_index);
+                    transitions.push((
+                        transition_label(&thread_scenario, Some(thread_transaction_index)),
+                        transition_label(&thread_scenario, next_transaction),
+                    ));
+
+                    if received_exit(&mut thread_user, &thread_receiver, thread_number).await {
+                        break 'launch_transactions;
+                    }
 
-                // If the transaction_wait is defined, wait for a random time between transaction.
-                if let Some((min, max)) = thread_scenario.transaction_wait {
-                    // Total time left to wait before running the next transaction.
-                    let mut wait_time = rand::thread_rng().gen_range(min..max).as_millis();
-                    // Track the time slept for Coordinated Omission Mitigation.
-                    let sleep_timer = time::Instant::now();
-                    // Never sleep more than 500 milliseconds, allowing a sleeping transaction to shut
-                    // down quickly when the load test ends.
-                    let maximum_sleep_time = 500;
-
-                    while wait_time > 0 {
-                        // Exit immediately if message received from parent.
-                        if received_exit(&thread_receiver) {
-                            break 'launch_transactions;
+                    if wait_between_transactions(
+                        &thread_scenario,
+                        thread_transaction_index,
+                        &mut thread_user,
+                        &thread_receiver,
+                        thread_number,
+                    )
+                    .await
+                    {
+                        break 'launch_transactions;
+                    }
+
+                    if !success {
+                        aborted = abort_iteration(&thread_scenario, thread_transaction_index);
+                        if aborted.is_some() {
+                            break;
                         }
+                    }
+                }
+            } else {
+                for (thread_transaction_index, thread_transaction_name) in
+                    &thread_scenario.weighted_transactions
+                {
+                    // Determine which transaction we're going to run next.
+                    let thread_transaction =
+                        &thread_scenario.transactions[*thread_transaction_index];
+                    // Don't run transactions skipped by a Controller.
+                    if thread_transaction.is_skipped() {
+                        continue;
+                    }
+                    ran_transaction = true;
+                    debug!(
+                        "[user {}]: launching {} transaction from {}",
+                        thread_number, thread_transaction_name, thread_scenario.name
+                    );
+                    // Invoke the transaction function.
+                    let success = invoke_transaction_function(
+                        &thread_transaction.function,
+                        thread_transaction.timeout,
+                        &mut thread_user,
+                        *thread_transaction_index,
+                        thread_transaction_name,
+                    )
+                    .await;
+                    if received_exit(&mut thread_user, &thread_receiver, thread_number).await {
+                        break 'launch_transactions;
+                    }
+
+                    if wait_between_transactions(
+                        &thread_scenario,
+                        *thread_transaction_index,
+                        &mut thread_user,
+                        &thread_receiver,
+                        thread_number,
+                    )
+                    .await
+                    {
+                        break 'launch_transactions;
+                    }
 
-                        // Wake regularly to detect if the load test has shut down.
-                        let sleep_duration = if wait_time > maximum_sleep_time {
-                            wait_time -= maximum_sleep_time;
-                            Duration::from_millis(maximum_sleep_time as u64)
-                        } else {
-                            let sleep_duration = Duration::from_millis(wait_time as u64);
-                            wait_time = 0;
-                            sleep_duration
-                        };
-
-                        debug!(
-                            "user {} from {} sleeping {:?} ...",
-                            thread_number, thread_scenario.name, sleep_duration
-                        );
-
-                        tokio::time::sleep(sleep_duration).await;
+                    if !success {
+                        aborted = abort_iteration(&thread_scenario, *thread_transaction_index);
+                        if aborted.is_some() {
+                            break;
+                        }
                     }
-                    // Track how much time the GooseUser sleeps during this loop through all Transactions,
-                    // used by Coordinated Omission Mitigation.
-                    thread_user.add_slept((time::Instant::now() - sleep_timer).as_millis() as u64);
                 }
             }
+
+            // If a Controller is skipping every transaction, wait instead of spinning, and
+            // don't count the iteration.
+            if !ran_transaction {
+                if sleep_unless_exit(
+                    Duration::from_secs(1),
+                    &thread_scenario,
+                    &mut thread_user,
+                    &thread_receiver,
+                    thread_number,
+                )
+                .await
+                {
+                    break 'launch_transactions;
+                }
+                continue;
+            }
+
             // Record a complete iteration running this Scenario.
             thread_user.add_iterations(1);
 
-            // Send scenario metrics to parent and logger if enabled, ignoring errors.
-            let _ = record_scenario(
-                &thread_scenario,
-                &thread_user,
-                scenario_started.elapsed().as_millis(),
-            )
+            // Send scenario metrics to parent and logger if enabled, ignoring errors. Aborted
+            // iterations aren't recorded.
+            if aborted.is_none() {
+                let _ = record_scenario(
+                    &thread_scenario,
+                    &thread_user,
+                    scenario_started.elapsed().as_millis(),
+                    transitions,
+                )
                 .await;
+            }
 
             // Check if configured to exit after a certain number of iterations, and exit if
             // that number of iterations have run.
@@ -163,6 +227,42 @@ pub(crate) async fn user_main<G: Goose>(
                 }
                 break 'launch_transactions;
             }
+
+            // Optionally stop or restart the user after a failed transaction.
+            if aborted == Some(GooseFailurePolicy::StopUser) {
+                stop_user(thread_number, &thread_scenario, &thread_user);
+                break 'launch_transactions;
+            }
+            if aborted == Some(GooseFailurePolicy::RestartUser)
+                && (!reset_session(thread_number, &thread_scenario, &mut thread_user)
+                    || !start_user(
+                        thread_number,
+                        &thread_scenario,
+                        &mut thread_user,
+                        &thread_receiver,
+                    )
+                    .await)
+            {
+                break 'launch_transactions;
+            }
+
+            // If pacing is enabled, sleep for the remainder of the interval before starting
+            // the next iteration.
+            if let Some(pacing) = thread_scenario.pacing {
+                if let Some(remaining) = pacing.checked_sub(scenario_started.elapsed()) {
+                    if sleep_unless_exit(
+                        remaining,
+                        &thread_scenario,
+                        &mut thread_user,
+                        &thread_receiver,
+                        thread_number,
+                    )
+                    .await
+                    {
+                        break 'launch_transactions;
+                    }
+                }
+            }
         }
     }
 
@@ -173,14 +273,15 @@ pub(crate) async fn user_main<G: Goose>(
         &thread_scenario.weighted_on_stop_transactions
         {
             // Determine which transaction we're going to run next.
-            let function = &thread_scenario.transactions[*thread_transaction_index].function;
+            let thread_transaction = &thread_scenario.transactions[*thread_transaction_index];
             debug!(
                 "[user: {}]: launching on_stop {} transaction from {}",
                 thread_number, thread_transaction_name, thread_scenario.name
             );
-            // Invoke the transaction function.
-            let _todo = invoke_transaction_function(
-                function,
+            // Invoke the transaction function, ignoring failures as the user is exiting.
+            invoke_transaction_function(
+                &thread_transaction.function,
+                thread_transaction.timeout,
                 &mut thread_user,
                 *thread_transaction_index,
                 thread_transaction_name,
@@ -205,8 +306,221 @@ pub(crate) async fn user_main<G: Goose>(
     }
 }
 
-// Determine if the parent has sent a GooseUserCommand::Exit message.
-fn received_exit(thread_receiver: &flume::Receiver<GooseUserCommand>) -> bool {
+// Invoke the weighted on_start transactions, starting them over if one fails and the
+// failure policy restarts the iteration or user. Returns false if the user should stop.
+async fn start_user<G: Goose>(
+    thread_number: usize,
+    thread_scenario: &Scenario<G>,
+    thread_user: &mut G,
+    thread_receiver: &flume::Receiver<GooseUserCommand>,
+) -> bool {
+    'start_user: loop {
+        // Transactions are already weighted and scheduled, execute each in order.
+        for (thread_transaction_index, thread_transaction_name) in
+            &thread_scenario.weighted_on_start_transactions
+        {
+            // Determine which transaction we're going to run next.
+            let thread_transaction = &thread_scenario.transactions[*thread_transaction_index];
+            debug!(
+                "[user {}]: launching on_start {} transaction from {}",
+                thread_number, thread_transaction_name, thread_scenario.name
+            );
+            // Invoke the transaction function.
+            if invoke_transaction_function(
+                &thread_transaction.function,
+                thread_transaction.timeout,
+                thread_user,
+                *thread_transaction_index,
+                thread_transaction_name,
+            )
+            .await
+            {
+                continue;
+            }
+
+            match abort_iteration(thread_scenario, *thread_transaction_index) {
+                None => {}
+                Some(GooseFailurePolicy::StopUser) => {
+                    stop_user(thread_number, thread_scenario, thread_user);
+                    return false;
+                }
+                Some(failure_policy) => {
+                    // Pause before starting over, exiting if the load test ends.
+                    if received_exit(thread_user, thread_receiver, thread_number).await
+                        || wait_between_transactions(
+                            thread_scenario,
+                            *thread_transaction_index,
+                            thread_user,
+                            thread_receiver,
+                            thread_number,
+                        )
+                        .await
+                    {
+                        return false;
+                    }
+                    if failure_policy == GooseFailurePolicy::RestartUser
+                        && !reset_session(thread_number, thread_scenario, thread_user)
+                    {
+                        return false;
+                    }
+                    continue 'start_user;
+                }
+            }
+        }
+        return true;
+    }
+}
+
+// Determine the failure policy of a failed transaction, returning `None` if the user
+// continues running the iteration.
+fn abort_iteration<G: Goose>(
+    thread_scenario: &Scenario<G>,
+    thread_transaction_index: usize,
+) -> Option<GooseFailurePolicy> {
+    // A failure policy configured on the transaction overrides the scenario policy.
+    match thread_scenario.transactions[thread_transaction_index]
+        .failure_policy
+        .unwrap_or(thread_scenario.failure_policy)
+    {
+        GooseFailurePolicy::Continue => None,
+        failure_policy => Some(failure_policy),
+    }
+}
+
+// Discard the user's session before starting over. Returns false if the session can't
+// be reset, in which case the user stops.
+fn reset_session<G: Goose>(
+    thread_number: usize,
+    thread_scenario: &Scenario<G>,
+    thread_user: &mut G,
+) -> bool {
+    debug!(
+        "user {} from {} restarting with a fresh session...",
+        thread_number, thread_scenario.name
+    );
+    if let Err(e) = thread_user.reset_session() {
+        warn!(
+            "user {} from {} failed to reset session: {}",
+            thread_number, thread_scenario.name, e
+        );
+        stop_user(thread_number, thread_scenario, thread_user);
+        return false;
+    }
+    true
+}
+
+// Stop the user after a transaction failed, notifying the parent.
+fn stop_user<G: Goose>(thread_number: usize, thread_scenario: &Scenario<G>, thread_user: &G) {
+    info!(
+        "user {} from {} stopping after a failed transaction...",
+        thread_number, thread_scenario.name
+    );
+    // Attempt to notify the parent this thread is shutting down.
+    if let Some(shutdown_channel) = thread_user.shutdown_channel() {
+        let _ = shutdown_channel.send(thread_number);
+    }
+}
+
+// If the transaction or scenario defines a wait time, wait for a random time between
+// transactions. Returns true if the parent sent a GooseUserCommand::Exit message while
+// waiting.
+async fn wait_between_transactions<G: Goose>(
+    thread_scenario: &Scenario<G>,
+    thread_transaction_index: usize,
+    thread_user: &mut G,
+    thread_receiver: &flume::Receiver<GooseUserCommand>,
+    thread_number: usize,
+) -> bool {
+    // A wait time configured on the transaction overrides the scenario wait time.
+    let wait_time = thread_scenario.transactions[thread_transaction_index]
+        .wait_time
+        .as_ref()
+        .or(thread_scenario.transaction_wait.as_ref());
+    if let Some(wait_time) = wait_time {
+        sleep_unless_exit(
+            wait_time.sample(),
+            thread_scenario,
+            thread_user,
+            thread_receiver,
+            thread_number,
+        )
+        .await
+    } else {
+        false
+    }
+}
+
+// Sleep for the specified duration, waking regularly to check if the parent sent a
+// GooseUserCommand::Exit message. Returns true if the message was received.
+async fn sleep_unless_exit<G: Goose>(
+    duration: Duration,
+    thread_scenario: &Scenario<G>,
+    thread_user: &mut G,
+    thread_receiver: &flume::Receiver<GooseUserCommand>,
+    thread_number: usize,
+) -> bool {
+    // Total time left to sleep.
+    let mut wait_time = duration.as_millis();
+    // Track the time slept for Coordinated Omission Mitigation, not including any time
+    // spent paused.
+    let mut slept = Duration::from_millis(0);
+    // Never sleep more than 500 milliseconds, allowing a sleeping transaction to shut
+    // down quickly when the load test ends.
+    let maximum_sleep_time = 500;
+
+    while wait_time > 0 {
+        // Exit immediately if message received from parent.
+        if received_exit(thread_user, thread_receiver, thread_number).await {
+            return true;
+        }
+
+        // Wake regularly to detect if the load test has shut down.
+        let sleep_duration = if wait_time > maximum_sleep_time {
+            wait_time -= maximum_sleep_time;
+            Duration::from_millis(maximum_sleep_time as u64)
+        } else {
+            let sleep_duration = Duration::from_millis(wait_time as u64);
+            wait_time = 0;
+            sleep_duration
+        };
+
+        debug!(
+            "user {} from {} sleeping {:?} ...",
+            thread_number, thread_scenario.name, sleep_duration
+        );
+
+        let sleep_timer = time::Instant::now();
+        tokio::time::sleep(sleep_duration).await;
+        slept += sleep_timer.elapsed();
+    }
+    // Track how much time the GooseUser sleeps during this loop through all Transactions,
+    // used by Coordinated Omission Mitigation.
+    thread_user.add_slept(slept.as_millis() as u64);
+    false
+}
+
+// Label a transaction in transition graph metrics, `None` indicating the iteration ended.
+fn transition_label<G: Goose>(
+    thread_scenario: &Scenario<G>,
+    transactions_index: Option<usize>,
+) -> String {
+    match transactions_index {
+        Some(index) => format!(
+            "{}: {}",
+            index + 1,
+            thread_scenario.transactions[index].name
+        ),
+        None => "exit".to_string(),
+    }
+}
+
+// Determine if the parent has sent a GooseUserCommand::Exit message. If the parent has
+// sent a GooseUserCommand::Pause message, first wait for the load test to be resumed.
+async fn received_exit<G: Goose>(
+    thread_user: &mut G,
+    thread_receiver: &flume::Receiver<GooseUserCommand>,
+    thread_number: usize,
+) -> bool {
     let mut message = thread_receiver.try_recv();
     while message.is_ok() {
         match message.unwrap() {
@@ -214,6 +528,12 @@ fn received_exit(thread_receiver: &flume::Receiver<GooseUserCommand>) -> bool {
             GooseUserCommand::Exit => {
                 return true;
             }
+            // GooseUserCommand::Pause received.
+            GooseUserCommand::Pause => {
+                if wait_until_resumed(thread_user, thread_receiver, thread_number).await {
+                    return true;
+                }
+            }
             command => {
                 debug!("ignoring unexpected GooseUserCommand: {:?}", command);
             }
@@ -224,11 +544,37 @@ fn received_exit(thread_receiver: &flume::Receiver<GooseUserCommand>) -> bool {
     false
 }
 
+// Hold a paused GooseUser until the parent sends a GooseUserCommand::Run message. Returns
+// true if a GooseUserCommand::Exit message was received instead.
+async fn wait_until_resumed<G: Goose>(
+    thread_user: &mut G,
+    thread_receiver: &flume::Receiver<GooseUserCommand>,
+    thread_number: usize,
+) -> bool {
+    debug!("user {} paused", thread_number);
+    let paused = time::Instant::now();
+    let exit = loop {
+        match thread_receiver.recv_async().await {
+            // GooseUserCommand::Run received, resume the load test.
+            Ok(GooseUserCommand::Run) => break false,
+            // GooseUserCommand::Exit received, or the parent has gone away.
+            Ok(GooseUserCommand::Exit) | Err(_) => break true,
+            Ok(command) => {
+                debug!("ignoring unexpected GooseUserCommand: {:?}", command);
+            }
+        }
+    };
+    debug!("user {} resumed", thread_number);
+    thread_user.add_paused(paused.elapsed().as_millis() as u64);
+    exit
+}
+
 // Send scenario metric to parent and logger when enabled.
 async fn record_scenario<G: Goose>(
     thread_scenario: &Scenario<G>,
     thread_user: &G,
     run_time: u128,
+    transitions: Vec<(String, String)>,
 ) -> Result<(), flume::SendError<Option<GooseLog>>> {
     if !thread_user.config().no_scenario_metrics && !thread_user.config().no_metrics {
         let raw_scenario = ScenarioMetric::new(
@@ -237,6 +583,7 @@ async fn record_scenario<G: Goose>(
             thread_user.scenarios_index(),
             run_time,
             thread_user.weighted_users_index(),
+            transitions,
         );
         if let Some(metrics_channel) = thread_user.metrics_channel() {
             // Best effort metrics.
@@ -252,13 +599,15 @@ async fn record_scenario<G: Goose>(
     Ok(())
 }
 
-// Invoke the transaction function, collecting transaction metrics.
+// Invoke the transaction function, collecting transaction metrics. Returns true if the
+// transaction succeeded.
 async fn invoke_transaction_function<G: Goose>(
     function: &TransactionFunction<G>,
+    timeout: Option<Duration>,
     thread_user: &mut G,
     thread_transaction_index: usize,
     thread_transaction_name: &str,
-) -> Result<(), flume::SendError<Option<GooseLog>>> {
+) -> bool {
     let started = time::Instant::now();
     let mut raw_transaction = TransactionMetric::new(
         thread_user.started().elapsed().as_millis(),
@@ -267,24 +616,49 @@ async fn invoke_transaction_function<G: Goose>(
         thread_transaction_name.to_string(),
         thread_user.weighted_users_index(),
     );
+    thread_user.set_transaction_index(thread_transaction_index);
+    thread_user.set_transaction_timeout(timeout);
     if !thread_transaction_name.is_empty() {
         thread_user.set_transaction_name(thread_transaction_name.to_string());
     } else {
         thread_user.take_transaction_name();
     }
 
-    let success = function(thread_user).await.is_ok();
+    let success = if let Some(timeout) = timeout {
+        // Cancel the transaction if it doesn't complete in time.
+        match tokio::time::timeout(timeout, function(thread_user)).await {
+            Ok(result) => result.is_ok(),
+            Err(_) => {
+                let error = TransactionError::Timeout { timeout };
+                warn!(
+                    "{} [{}] canceled: {}",
+                    thread_transaction_name, thread_transaction_index, error
+                );
+                // Record the request that was canceled, if any, as a timeout error.
+                if let Some(mut request_metric) = thread_user.take_request_in_flight() {
+                    request_metric.success = false;
+                    request_metric.error = error.to_string();
+                    // Best effort metrics.
+                    let _ = thread_user.send_request_metric_to_parent(request_metric);
+                }
+                false
+            }
+        }
+    } else {
+        function(thread_user).await.is_ok()
+    };
     raw_transaction.set_time(started.elapsed().as_millis(), success);
 
     // Exit if all metrics or transaction metrics are disabled.
     if thread_user.config().no_metrics || thread_user.config().no_transaction_metrics {
-        return Ok(());
+        return success;
     }
 
     // If transaction-log is enabled, send a copy of the raw transaction metric to the logger thread.
     if !thread_user.config().transaction_log.is_empty() {
         if let Some(logger) = thread_user.logger().as_ref() {
-            logger.send(Some(GooseLog::Transaction(raw_transaction.clone())))?;
+            // Best effort logging.
+            let _ = logger.send(Some(GooseLog::Transaction(raw_transaction.clone())));
         }
     }
 
@@ -294,5 +668,5 @@ async fn invoke_transaction_function<G: Goose>(
         let _ = metrics_channel.send(GooseMetric::Transaction(raw_transaction));
     }
 
-    Ok(())
+    success
 }
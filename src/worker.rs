@@ -249,6 +249,8 @@ pub(crate) async fn worker_main(goose_attack: GooseAttack) -> GooseAttack {
     // The throttle_requests option is set on the Worker.
     worker_goose_attack.configuration.throttle_requests =
         goose_attack.configuration.throttle_requests;
+    // The throttle_burst option is set on the Worker.
+    worker_goose_attack.configuration.throttle_burst = goose_attack.configuration.throttle_burst;
     worker_goose_attack.attack_mode = AttackMode::Worker;
     worker_goose_attack.defaults = goose_attack.defaults.clone();
     worker_goose_attack.test_plan = TestPlan::build(&worker_goose_attack.configuration);
@@ -0,0 +1,96 @@
+use httpmock::{Method::GET, MockServer};
+use std::process::Command;
+
+// Paths used in load tests performed during these tests.
+const INDEX_PATH: &str = "/";
+const ABOUT_PATH: &str = "/about.html";
+
+// A scenario file loading two pages.
+const SCENARIO_YAML: &str = r#"
+scenarios:
+  - name: LoadTest
+    transactions:
+      - requests:
+          - path: /
+      - requests:
+          - path: /about.html
+"#;
+
+#[test]
+// Run a load test defined in a scenario file with the goose binary.
+fn test_bin_run() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let index = server.mock(|when, then| {
+        when.method(GET).path(INDEX_PATH);
+        then.status(200);
+    });
+    let about = server.mock(|when, then| {
+        when.method(GET).path(ABOUT_PATH);
+        then.status(200);
+    });
+
+    // Write the scenario file to disk.
+    let file_name = "bin-test.yaml";
+    std::fs::write(file_name, SCENARIO_YAML).unwrap();
+
+    // Run the load test, passing standard Goose run-time options.
+    let output = Command::new(env!("CARGO_BIN_EXE_goose"))
+        .args([
+            "run",
+            file_name,
+            "--host",
+            &server.base_url(),
+            "--users",
+            "2",
+            "--hatch-rate",
+            "4",
+            "--run-time",
+            "1",
+            "--quiet",
+        ])
+        .output()
+        .expect("failed to run goose binary");
+
+    // Cleanup from test.
+    std::fs::remove_file(file_name).expect("failed to remove file");
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(index.hits() > 0);
+    assert!(about.hits() > 0);
+}
+
+#[test]
+// Confirm the goose binary rejects invalid usage.
+fn test_bin_invalid() {
+    // A scenario file is required.
+    let output = Command::new(env!("CARGO_BIN_EXE_goose"))
+        .args(["run", "--users", "1"])
+        .output()
+        .expect("failed to run goose binary");
+    assert!(!output.status.success());
+
+    // Unknown commands are rejected.
+    let output = Command::new(env!("CARGO_BIN_EXE_goose"))
+        .args(["foo"])
+        .output()
+        .expect("failed to run goose binary");
+    assert!(!output.status.success());
+
+    // Missing scenario files are an error.
+    let output = Command::new(env!("CARGO_BIN_EXE_goose"))
+        .args(["run", "no-such-file.yaml", "--host", "http://127.0.0.1"])
+        .output()
+        .expect("failed to run goose binary");
+    assert!(!output.status.success());
+
+    // Help is displayed with all Goose run-time options.
+    let output = Command::new(env!("CARGO_BIN_EXE_goose"))
+        .args(["run", "--help"])
+        .output()
+        .expect("failed to run goose binary");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("--users"));
+}
@@ -0,0 +1,199 @@
+use httpmock::{Method::GET, Mock, MockServer};
+
+mod common;
+
+use goose::config::GooseConfiguration;
+use goose::prelude::*;
+
+// Paths used in load tests performed during these tests.
+const INDEX_PATH: &str = "/";
+const ERROR_PATH: &str = "/error";
+
+// Indexes to the above paths.
+const INDEX_KEY: usize = 0;
+const ERROR_KEY: usize = 1;
+
+// Test transaction.
+pub async fn get_index(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+// Test transaction.
+pub async fn get_error(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(ERROR_PATH).await?;
+    Ok(())
+}
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up INDEX_PATH, store in vector at INDEX_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(INDEX_PATH);
+            then.status(200);
+        }),
+        // Next set up ERROR_PATH, store in vector at ERROR_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(ERROR_PATH);
+            then.status(500);
+        }),
+    ]
+}
+
+// Build a configuration for a capacity search, which replaces the default users and
+// run time.
+fn build_configuration(server: &MockServer, capacity_search: &str) -> GooseConfiguration {
+    let mut configuration = common::build_configuration(
+        server,
+        vec!["--hatch-rate", "10", "--capacity-search", capacity_search],
+    );
+    configuration.users = None;
+    configuration.run_time = "".to_string();
+    configuration
+}
+
+#[tokio::test]
+// Add users one step at a time until reaching the maximum.
+async fn test_capacity_search_incremental() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    let configuration = build_configuration(&server, "errors=1%,step=1,max=3,hold=1s");
+
+    // Run the Goose Attack.
+    let goose_metrics = common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![scenario!("Index").register_transaction(transaction!(get_index))],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    assert!(mock_endpoints[INDEX_KEY].hits() > 0);
+
+    // Each level was held and passed.
+    let users: Vec<usize> = goose_metrics
+        .capacity_search
+        .iter()
+        .map(|level| level.users)
+        .collect();
+    assert_eq!(users, vec![1, 2, 3]);
+    assert!(goose_metrics
+        .capacity_search
+        .iter()
+        .all(|level| level.passed && level.requests > 0 && level.error_percent == 0.0));
+    assert_eq!(goose_metrics.capacity_search_result(), Some(3));
+    assert_eq!(goose_metrics.maximum_users, 3);
+}
+
+#[tokio::test]
+// Double the users until reaching the maximum.
+async fn test_capacity_search_binary() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    let configuration = build_configuration(&server, "p100=10s,step=1,max=4,hold=1s,mode=binary");
+
+    // Run the Goose Attack.
+    let goose_metrics = common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![scenario!("Index").register_transaction(transaction!(get_index))],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    assert!(mock_endpoints[INDEX_KEY].hits() > 0);
+
+    let users: Vec<usize> = goose_metrics
+        .capacity_search
+        .iter()
+        .map(|level| level.users)
+        .collect();
+    assert_eq!(users, vec![1, 2, 4]);
+    assert!(goose_metrics
+        .capacity_search
+        .iter()
+        .all(|level| level.percentile_time.is_some()));
+    assert_eq!(goose_metrics.capacity_search_result(), Some(4));
+}
+
+#[tokio::test]
+// Stop searching when the first level fails.
+async fn test_capacity_search_fails() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    let configuration = build_configuration(&server, "errors=1%,step=1,max=3,hold=1s");
+
+    // Run the Goose Attack.
+    let goose_metrics = common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![scenario!("Error").register_transaction(transaction!(get_error))],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    assert!(mock_endpoints[ERROR_KEY].hits() > 0);
+
+    assert_eq!(goose_metrics.capacity_search.len(), 1);
+    assert!(!goose_metrics.capacity_search[0].passed);
+    assert_eq!(goose_metrics.capacity_search[0].error_percent, 100.0);
+    assert_eq!(goose_metrics.capacity_search_result(), None);
+}
+
+#[tokio::test]
+// A capacity search can't be combined with a run time.
+async fn test_capacity_search_run_time() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    let mut configuration = build_configuration(&server, "errors=1%");
+    configuration.run_time = "10".to_string();
+
+    let goose_attack = common::build_load_test(
+        configuration,
+        vec![scenario!("Index").register_transaction(transaction!(get_index))],
+        None,
+        None,
+    );
+    assert!(goose_attack.execute().await.is_err());
+}
+
+#[tokio::test]
+// A capacity search needs metrics to evaluate each level.
+async fn test_capacity_search_no_metrics() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    let mut configuration = build_configuration(&server, "errors=1%");
+    configuration.no_metrics = true;
+
+    let goose_attack = common::build_load_test(
+        configuration,
+        vec![scenario!("Index").register_transaction(transaction!(get_index))],
+        None,
+        None,
+    );
+    assert!(goose_attack.execute().await.is_err());
+}
@@ -1,16 +1,26 @@
 use futures::future::join_all;
+use futures::{SinkExt, StreamExt};
 use gumdrop::Options;
 use httpmock::MockServer;
+use serde_json::{json, Value};
 use std::io::{self, BufRead};
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
 
 use goose::config::GooseConfiguration;
+use goose::controller::ControllerStatus;
 use goose::goose::{Scenario, Transaction};
 use goose::metrics::GooseMetrics;
-use goose::GooseAttack;
 use goose::prelude::Goose;
+use goose::{AttackPhase, GooseAttack};
 
 type WorkerHandles = Vec<tokio::task::JoinHandle<GooseMetrics>>;
 
+/// A client connected to the WebSocket Controller.
+#[allow(dead_code)]
+pub type ControllerWebSocket =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
 /// Not all functions are used by all tests, so we enable allow(dead_code) to avoid
 /// compiler warnings during testing.
 
@@ -149,3 +159,70 @@ pub fn cleanup_files(files: Vec<&str>) {
         }
     }
 }
+
+/// Connect to the WebSocket Controller listening on a local port, waiting for it to start.
+#[allow(dead_code)]
+pub async fn connect_websocket_controller(port: &str) -> ControllerWebSocket {
+    let url = format!("ws://127.0.0.1:{}", port);
+    let mut retries = 0;
+    loop {
+        if let Ok((websocket, _)) = tokio_tungstenite::connect_async(&url).await {
+            return websocket;
+        }
+        retries += 1;
+        assert!(retries < 50, "WebSocket Controller never started");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// Send a typed request to the WebSocket Controller, optionally with a value, and return
+/// the json response, ignoring any pushed events.
+#[allow(dead_code)]
+pub async fn websocket_command(
+    websocket: &mut ControllerWebSocket,
+    command: &str,
+    value: Option<Value>,
+) -> Value {
+    let mut request = json!({ "command": command });
+    if let Some(value) = value {
+        request["value"] = value;
+    }
+    websocket
+        .send(Message::Text(request.to_string()))
+        .await
+        .unwrap();
+    loop {
+        let response: Value = match websocket.next().await {
+            Some(Ok(Message::Text(message))) => serde_json::from_str(&message).unwrap(),
+            other => panic!("unexpected message: {:?}", other),
+        };
+        if response.get("event").is_none() {
+            return response;
+        }
+    }
+}
+
+/// Request the load test status from the WebSocket Controller.
+#[allow(dead_code)]
+pub async fn websocket_status(websocket: &mut ControllerWebSocket) -> ControllerStatus {
+    let response = websocket_command(websocket, "status", None).await;
+    serde_json::from_value(response["data"]["status"].clone()).unwrap()
+}
+
+/// Wait until the load test is in the expected phase, returning its status.
+#[allow(dead_code)]
+pub async fn websocket_wait_for_phase(
+    websocket: &mut ControllerWebSocket,
+    phase: AttackPhase,
+) -> ControllerStatus {
+    let mut retries = 0;
+    loop {
+        let status = websocket_status(websocket).await;
+        if status.phase == phase {
+            return status;
+        }
+        retries += 1;
+        assert!(retries < 100, "load test never reached {:?}", phase);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
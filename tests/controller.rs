@@ -672,6 +672,52 @@ async fn run_standalone_test<G: Goose>(test_type: TestType) {
                         }
                     }
                 }
+                // Changing the throttle isn't tested here.
+                ControllerCommand::ThrottleRequests => {
+                    // Move onto the next command.
+                    test_state = update_state(Some(test_state), &test_type);
+                }
+                // The status command is tested in tests/http_controller.rs.
+                ControllerCommand::Status => {
+                    // Move onto the next command.
+                    test_state = update_state(Some(test_state), &test_type);
+                }
+                // Subscriptions are tested in tests/websocket_protocol.rs.
+                ControllerCommand::Subscribe | ControllerCommand::Unsubscribe => {
+                    // Move onto the next command.
+                    test_state = update_state(Some(test_state), &test_type);
+                }
+                // Pausing is tested in tests/controller_pause.rs.
+                ControllerCommand::Pause | ControllerCommand::Resume => {
+                    // Move onto the next command.
+                    test_state = update_state(Some(test_state), &test_type);
+                }
+                // Changing scenarios is tested in tests/controller_scenarios.rs.
+                ControllerCommand::EnableScenario
+                | ControllerCommand::DisableScenario
+                | ControllerCommand::ScenarioWeight
+                | ControllerCommand::SkipTransaction
+                | ControllerCommand::UnskipTransaction => {
+                    // Move onto the next command.
+                    test_state = update_state(Some(test_state), &test_type);
+                }
+                // Resetting metrics and reporting are tested in tests/controller_report.rs.
+                ControllerCommand::ResetMetrics | ControllerCommand::Report => {
+                    // Move onto the next command.
+                    test_state = update_state(Some(test_state), &test_type);
+                }
+                // Scripts are tested in tests/controller_script.rs.
+                ControllerCommand::Script => {
+                    // Move onto the next command.
+                    test_state = update_state(Some(test_state), &test_type);
+                }
+                // Changing a running test plan is tested in tests/controller_test_plan.rs.
+                ControllerCommand::AppendTestPlan
+                | ControllerCommand::ExtendTestPlanStep
+                | ControllerCommand::JumpTestPlanStep => {
+                    // Move onto the next command.
+                    test_state = update_state(Some(test_state), &test_type);
+                }
             }
             // Flush the buffer.
             test_state.buf = [0; 2048];
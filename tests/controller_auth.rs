@@ -0,0 +1,249 @@
+use futures::{SinkExt, StreamExt};
+use httpmock::MockServer;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+
+mod common;
+
+use goose::prelude::*;
+
+// Paths used in load tests performed during these tests.
+const INDEX_PATH: &str = "/";
+
+// The Controllers listen on ports not used by any other test.
+const TELNET_PORT: &str = "5421";
+const WEBSOCKET_PORT: &str = "5422";
+#[cfg(feature = "rustls-tls")]
+const TLS_WEBSOCKET_PORT: &str = "5424";
+
+// The tokens Controller clients authenticate with.
+const CONTROL_TOKEN: &str = "s3cr3t";
+const READ_TOKEN: &str = "viewer";
+
+// Test transaction.
+pub async fn get_index(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+// Start an idle load test with the Controllers configured by the options.
+fn start_load_test(
+    server: &MockServer,
+    options: Vec<&str>,
+) -> tokio::task::JoinHandle<GooseMetrics> {
+    let mut configuration = common::build_configuration(server, options);
+    configuration.users = None;
+    configuration.run_time = "".to_string();
+
+    let goose_attack = common::build_load_test(
+        configuration,
+        vec![scenario!("Index").register_transaction(transaction!(get_index))],
+        None,
+        None,
+    );
+    tokio::spawn(common::run_load_test(goose_attack, None))
+}
+
+// Connect to a Controller once it starts listening.
+async fn connect(port: &str) -> TcpStream {
+    let mut retries = 0;
+    loop {
+        if let Ok(stream) = TcpStream::connect(format!("127.0.0.1:{}", port)).await {
+            return stream;
+        }
+        retries += 1;
+        assert!(retries < 50, "Controller never started");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+// Read from a telnet Controller until it sends the expected text.
+async fn read_telnet(telnet: &mut TcpStream, expected: &str) {
+    let mut received = String::new();
+    let mut buf = [0; 1024];
+    while !received.ends_with(expected) {
+        let n = tokio::time::timeout(Duration::from_secs(5), telnet.read(&mut buf))
+            .await
+            .expect("telnet Controller didn't respond")
+            .unwrap();
+        assert!(
+            n > 0,
+            "telnet Controller disconnected, received: {}",
+            received
+        );
+        received.push_str(std::str::from_utf8(&buf[..n]).unwrap());
+    }
+}
+
+// Send a json request to the WebSocket Controller and return the json response.
+async fn request<S>(websocket: &mut tokio_tungstenite::WebSocketStream<S>, request: Value) -> Value
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    websocket
+        .send(Message::Text(request.to_string()))
+        .await
+        .unwrap();
+    match websocket.next().await {
+        Some(Ok(Message::Text(message))) => serde_json::from_str(&message).unwrap(),
+        other => panic!("unexpected message: {:?}", other),
+    }
+}
+
+#[tokio::test]
+// Require Controller clients to authenticate with control or read-only tokens.
+async fn test_controller_tokens() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    let tokens = format!("control:{},read:{}", CONTROL_TOKEN, READ_TOKEN);
+    let load_test = start_load_test(
+        &server,
+        vec![
+            "--no-autostart",
+            "--telnet-host",
+            "127.0.0.1",
+            "--telnet-port",
+            TELNET_PORT,
+            "--websocket-host",
+            "127.0.0.1",
+            "--websocket-port",
+            WEBSOCKET_PORT,
+            "--no-http",
+            "--controller-token",
+            &tokens,
+        ],
+    );
+
+    // Wait for the WebSocket Controller to start listening.
+    connect(WEBSOCKET_PORT).await;
+
+    // WebSocket handshakes without a valid token are refused.
+    let websocket_url = format!("ws://127.0.0.1:{}", WEBSOCKET_PORT);
+    assert!(tokio_tungstenite::connect_async(&websocket_url)
+        .await
+        .is_err());
+    assert!(
+        tokio_tungstenite::connect_async(format!("{}/?token=wrong", websocket_url))
+            .await
+            .is_err()
+    );
+
+    // Read-only WebSocket clients can only run read-only commands.
+    let (mut websocket, _) =
+        tokio_tungstenite::connect_async(format!("{}/?token={}", websocket_url, READ_TOKEN))
+            .await
+            .unwrap();
+    let response = request(&mut websocket, json!({"command": "users", "value": 3})).await;
+    assert_eq!(response["success"], false);
+    assert_eq!(response["error"]["code"], "permission_denied");
+    let response = request(&mut websocket, json!({"request": "users 3"})).await;
+    assert_eq!(response["success"], false);
+    let response = request(&mut websocket, json!({"command": "status"})).await;
+    assert_eq!(response["data"]["status"]["phase"], "Idle");
+
+    // WebSocket clients can authenticate with a header.
+    let mut handshake = websocket_url.into_client_request().unwrap();
+    handshake.headers_mut().insert(
+        "Authorization",
+        format!("Bearer {}", CONTROL_TOKEN).parse().unwrap(),
+    );
+    let (mut websocket, _) = tokio_tungstenite::connect_async(handshake).await.unwrap();
+    let response = request(&mut websocket, json!({"command": "users", "value": 3})).await;
+    assert_eq!(response["success"], true);
+
+    // Telnet clients are prompted for a token, and disconnected if it isn't valid.
+    let telnet_address = format!("127.0.0.1:{}", TELNET_PORT);
+    let mut telnet = TcpStream::connect(&telnet_address).await.unwrap();
+    read_telnet(&mut telnet, "token: ").await;
+    telnet.write_all(b"wrong\r\n").await.unwrap();
+    read_telnet(&mut telnet, "invalid token\n").await;
+    assert_eq!(telnet.read(&mut [0; 1024]).await.unwrap(), 0);
+
+    // Read-only telnet clients can only run read-only commands.
+    let mut telnet = TcpStream::connect(&telnet_address).await.unwrap();
+    read_telnet(&mut telnet, "token: ").await;
+    telnet
+        .write_all(format!("{}\r\n", READ_TOKEN).as_bytes())
+        .await
+        .unwrap();
+    read_telnet(&mut telnet, "goose> ").await;
+    telnet.write_all(b"users 4\r\n").await.unwrap();
+    read_telnet(
+        &mut telnet,
+        "permission denied, users requires a control token\ngoose> ",
+    )
+    .await;
+
+    // Shut down the load test with the control token.
+    let response = request(&mut websocket, json!({"command": "shutdown"})).await;
+    assert_eq!(response["success"], true);
+
+    load_test.await.unwrap();
+}
+
+#[cfg(feature = "rustls-tls")]
+#[tokio::test]
+// Encrypt connections to the WebSocket Controller.
+async fn test_controller_tls() {
+    use std::convert::TryFrom;
+    use std::sync::Arc;
+    use tokio_rustls::rustls;
+
+    const TLS_CERT: &str = "tests/tls/controller-cert.pem";
+    const TLS_KEY: &str = "tests/tls/controller-key.pem";
+
+    // Start the mock server.
+    let server = MockServer::start();
+
+    let load_test = start_load_test(
+        &server,
+        vec![
+            "--no-autostart",
+            "--no-telnet",
+            "--websocket-host",
+            "127.0.0.1",
+            "--websocket-port",
+            TLS_WEBSOCKET_PORT,
+            "--no-http",
+            "--controller-tls-cert",
+            TLS_CERT,
+            "--controller-tls-key",
+            TLS_KEY,
+        ],
+    );
+
+    // Connect to the WebSocket Controller, trusting the self-signed certificate.
+    let mut roots = rustls::RootCertStore::empty();
+    let mut cert_file = std::io::BufReader::new(std::fs::File::open(TLS_CERT).unwrap());
+    for cert in rustls_pemfile::certs(&mut cert_file).unwrap() {
+        roots.add(&rustls::Certificate(cert)).unwrap();
+    }
+    let connector = tokio_rustls::TlsConnector::from(Arc::new(
+        rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth(),
+    ));
+    let stream = connect(TLS_WEBSOCKET_PORT).await;
+    let stream = connector
+        .connect(rustls::ServerName::try_from("localhost").unwrap(), stream)
+        .await
+        .unwrap();
+    let (mut websocket, _) =
+        tokio_tungstenite::client_async(format!("wss://localhost:{}", TLS_WEBSOCKET_PORT), stream)
+            .await
+            .unwrap();
+    let response = request(&mut websocket, json!({"command": "status"})).await;
+    assert_eq!(response["data"]["status"]["phase"], "Idle");
+
+    // Shut down the load test.
+    let response = request(&mut websocket, json!({"command": "shutdown"})).await;
+    assert_eq!(response["message"], "load test shut down");
+
+    load_test.await.unwrap();
+}
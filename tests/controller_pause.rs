@@ -0,0 +1,131 @@
+use httpmock::{Method::GET, Mock, MockServer};
+use std::time::Duration;
+
+mod common;
+
+use goose::prelude::*;
+use goose::AttackPhase;
+
+// Paths used in load tests performed during these tests.
+const INDEX_PATH: &str = "/";
+
+// Indexes to the above paths.
+const INDEX_KEY: usize = 0;
+
+// The WebSocket Controller listens on a port not used by any other test.
+const WEBSOCKET_PORT: &str = "5426";
+
+// Test transaction.
+pub async fn get_index(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up INDEX_PATH, store in vector at INDEX_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(INDEX_PATH);
+            then.status(200);
+        }),
+    ]
+}
+
+#[tokio::test]
+// Pause and resume a running load test with the WebSocket Controller.
+async fn test_pause_resume() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Only enable the WebSocket Controller, and run until shut down.
+    let mut configuration = common::build_configuration(
+        &server,
+        vec![
+            "--no-telnet",
+            "--no-http",
+            "--websocket-host",
+            "127.0.0.1",
+            "--websocket-port",
+            WEBSOCKET_PORT,
+            "--users",
+            "2",
+            "--hatch-rate",
+            "10",
+            "--throttle-requests",
+            "20",
+        ],
+    );
+    configuration.run_time = "".to_string();
+
+    let goose_attack = common::build_load_test(
+        configuration,
+        vec![scenario!("Index").register_transaction(transaction!(get_index))],
+        None,
+        None,
+    );
+    let load_test = tokio::spawn(common::run_load_test(goose_attack, None));
+
+    // Wait for the WebSocket Controller to start listening, and all users to start.
+    let mut websocket = common::connect_websocket_controller(WEBSOCKET_PORT).await;
+    common::websocket_wait_for_phase(&mut websocket, AttackPhase::Maintain).await;
+
+    // A running load test can't be resumed.
+    let response = common::websocket_command(&mut websocket, "resume", None).await;
+    assert_eq!(response["error"]["code"], "command_failed");
+    assert_eq!(
+        response["error"]["message"],
+        "load test not paused, failed to resume"
+    );
+
+    // Pause the load test.
+    let response = common::websocket_command(&mut websocket, "pause", None).await;
+    assert_eq!(response["success"], true);
+    assert_eq!(response["message"], "load test paused");
+    let response = common::websocket_command(&mut websocket, "pause", None).await;
+    assert_eq!(response["error"]["code"], "command_failed");
+
+    // Give users time to finish their current transaction.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let status = common::websocket_status(&mut websocket).await;
+    assert_eq!(status.phase, AttackPhase::Pause);
+    assert_eq!(status.users, 2);
+    let paused_hits = mock_endpoints[INDEX_KEY].hits();
+    assert!(paused_hits > 0);
+
+    // Paused users make no requests, and the load test clock is stopped.
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert_eq!(mock_endpoints[INDEX_KEY].hits(), paused_hits);
+    assert_eq!(
+        common::websocket_status(&mut websocket).await.duration,
+        status.duration
+    );
+
+    // Resume the load test from where it was paused.
+    let response = common::websocket_command(&mut websocket, "resume", None).await;
+    assert_eq!(response["success"], true);
+    assert_eq!(response["message"], "load test resumed");
+    assert_eq!(
+        common::websocket_status(&mut websocket).await.phase,
+        AttackPhase::Maintain
+    );
+    let mut retries = 0;
+    while mock_endpoints[INDEX_KEY].hits() == paused_hits {
+        retries += 1;
+        assert!(retries < 50, "load test never resumed");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    // A paused load test can be shut down.
+    let response = common::websocket_command(&mut websocket, "pause", None).await;
+    assert_eq!(response["success"], true);
+    let response = common::websocket_command(&mut websocket, "shutdown", None).await;
+    assert_eq!(response["success"], true);
+    assert_eq!(response["message"], "load test shut down");
+
+    let goose_metrics = load_test.await.unwrap();
+    assert_eq!(goose_metrics.maximum_users, 2);
+}
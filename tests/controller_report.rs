@@ -0,0 +1,162 @@
+use httpmock::{Method::GET, Mock, MockServer};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+mod common;
+
+use goose::prelude::*;
+use goose::AttackPhase;
+
+// Paths used in load tests performed during these tests.
+const INDEX_PATH: &str = "/";
+
+// Indexes to the above paths.
+const INDEX_KEY: usize = 0;
+
+// The WebSocket Controller listens on a port not used by any other test.
+const WEBSOCKET_PORT: &str = "5428";
+
+// Test transaction.
+pub async fn get_index(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up INDEX_PATH, store in vector at INDEX_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(INDEX_PATH);
+            then.status(200);
+        }),
+    ]
+}
+
+// Request how many successful requests of the index have been counted.
+async fn get_index_requests(websocket: &mut common::ControllerWebSocket) -> u64 {
+    let response = common::websocket_command(websocket, "metrics-json", None).await;
+    response["data"]["metrics"]["requests"]["GET /"]["success_count"]
+        .as_u64()
+        .unwrap_or(0)
+}
+
+#[tokio::test]
+// Reset metrics and write reports while the load test is running.
+async fn test_reset_metrics_and_report() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Reports are written under the Controller report directory.
+    let report_dir = std::env::temp_dir();
+    let report_file = |extension: &str| {
+        format!(
+            "goose-{}-controller-report.{}",
+            std::process::id(),
+            extension
+        )
+    };
+    let html_report = report_file("html");
+    let json_report = report_file("json");
+
+    // Only enable the WebSocket Controller, and run until shut down.
+    let mut configuration = common::build_configuration(
+        &server,
+        vec![
+            "--no-telnet",
+            "--no-http",
+            "--websocket-host",
+            "127.0.0.1",
+            "--websocket-port",
+            WEBSOCKET_PORT,
+            "--users",
+            "2",
+            "--hatch-rate",
+            "10",
+            "--throttle-requests",
+            "20",
+            "--controller-report-dir",
+            report_dir.to_str().unwrap(),
+        ],
+    );
+    configuration.run_time = "".to_string();
+
+    let goose_attack = common::build_load_test(
+        configuration,
+        vec![scenario!("Index").register_transaction(transaction!(get_index))],
+        None,
+        None,
+    );
+    let load_test = tokio::spawn(common::run_load_test(goose_attack, None));
+
+    // Wait for the WebSocket Controller to start listening, and all users to start.
+    let mut websocket = common::connect_websocket_controller(WEBSOCKET_PORT).await;
+    common::websocket_wait_for_phase(&mut websocket, AttackPhase::Maintain).await;
+
+    // Let the load test run long enough to collect some metrics.
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    let requests = get_index_requests(&mut websocket).await;
+    assert!(requests > 10);
+
+    // Reset metrics without stopping the load test.
+    let response = common::websocket_command(&mut websocket, "reset-metrics", None).await;
+    assert_eq!(response["success"], true);
+    assert_eq!(response["message"], "metrics reset");
+    let reset_hits = mock_endpoints[INDEX_KEY].hits();
+    assert!(get_index_requests(&mut websocket).await < requests);
+
+    // A report path is required.
+    let response = common::websocket_command(&mut websocket, "report", None).await;
+    assert_eq!(response["error"]["code"], "invalid_value");
+
+    // Write a json and an HTML report of the metrics since they were reset.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let response =
+        common::websocket_command(&mut websocket, "report", Some(json!(json_report))).await;
+    assert_eq!(response["success"], true);
+    assert_eq!(response["message"], "report written");
+    let report: Value =
+        serde_json::from_str(&std::fs::read_to_string(report_dir.join(&json_report)).unwrap())
+            .unwrap();
+    assert!(
+        report["requests"]["GET /"]["success_count"]
+            .as_u64()
+            .unwrap()
+            > 0
+    );
+    let response =
+        common::websocket_command(&mut websocket, "report", Some(json!(html_report))).await;
+    assert_eq!(response["success"], true);
+    let report = std::fs::read_to_string(report_dir.join(&html_report)).unwrap();
+    assert!(report.contains("<html"));
+    assert!(report.contains("Maintaining"));
+
+    // Reports can't be written to invalid paths, or outside the report directory.
+    for path in [
+        "goose/does/not/exist.html".to_string(),
+        report_dir.join(&html_report).to_str().unwrap().to_string(),
+        format!("../{}", html_report),
+    ] {
+        let response = common::websocket_command(&mut websocket, "report", Some(json!(path))).await;
+        assert_eq!(response["error"]["code"], "command_failed");
+        assert_eq!(
+            response["error"]["message"],
+            "failed to write report, be sure load test has started and path is writable"
+        );
+    }
+
+    // The load test is still running.
+    let response = common::websocket_command(&mut websocket, "shutdown", None).await;
+    assert_eq!(response["success"], true);
+
+    // Final metrics only include requests made after the reset.
+    let goose_metrics = load_test.await.unwrap();
+    let index = goose_metrics.requests.get("GET /").unwrap();
+    assert!(index.success_count <= mock_endpoints[INDEX_KEY].hits() - reset_hits + 3);
+
+    std::fs::remove_file(report_dir.join(json_report)).unwrap();
+    std::fs::remove_file(report_dir.join(html_report)).unwrap();
+}
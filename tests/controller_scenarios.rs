@@ -0,0 +1,170 @@
+use httpmock::{Method::GET, Mock, MockServer};
+use serde_json::json;
+use std::time::Duration;
+
+mod common;
+
+use goose::prelude::*;
+use goose::AttackPhase;
+
+// Paths used in load tests performed during these tests.
+const BROWSE_PATH: &str = "/browse";
+const CHECKOUT_PATH: &str = "/checkout";
+
+// Indexes to the above paths.
+const BROWSE_KEY: usize = 0;
+const CHECKOUT_KEY: usize = 1;
+
+// The WebSocket Controller listens on a port not used by any other test.
+const WEBSOCKET_PORT: &str = "5427";
+
+// Test transaction.
+pub async fn get_browse(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(BROWSE_PATH).await?;
+    Ok(())
+}
+
+// Test transaction.
+pub async fn get_checkout(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(CHECKOUT_PATH).await?;
+    Ok(())
+}
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up BROWSE_PATH, store in vector at BROWSE_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(BROWSE_PATH);
+            then.status(200);
+        }),
+        // Next set up CHECKOUT_PATH, store in vector at CHECKOUT_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(CHECKOUT_PATH);
+            then.status(200);
+        }),
+    ]
+}
+
+// Wait until the mock endpoint receives more requests.
+async fn wait_for_hits(mock: &Mock<'_>) {
+    let hits = mock.hits();
+    let mut retries = 0;
+    while mock.hits() == hits {
+        retries += 1;
+        assert!(retries < 50, "endpoint never requested");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+// Confirm the mock endpoint receives no more requests, after giving users time to finish
+// their current transaction.
+async fn assert_no_hits(mock: &Mock<'_>) {
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let hits = mock.hits();
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+    assert_eq!(mock.hits(), hits);
+}
+
+#[tokio::test]
+// Enable and disable scenarios, change their weights and skip transactions while the load
+// test is running.
+async fn test_change_scenarios() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Only enable the WebSocket Controller, and start with only the browse scenario.
+    let mut configuration = common::build_configuration(
+        &server,
+        vec![
+            "--no-telnet",
+            "--no-http",
+            "--websocket-host",
+            "127.0.0.1",
+            "--websocket-port",
+            WEBSOCKET_PORT,
+            "--scenarios",
+            "browse",
+            "--users",
+            "4",
+            "--hatch-rate",
+            "10",
+            "--throttle-requests",
+            "40",
+        ],
+    );
+    configuration.run_time = "".to_string();
+
+    let goose_attack = common::build_load_test(
+        configuration,
+        vec![
+            scenario!("Browse").register_transaction(transaction!(get_browse)),
+            scenario!("Checkout")
+                .register_transaction(transaction!(get_checkout).set_name("checkout page")),
+        ],
+        None,
+        None,
+    );
+    let load_test = tokio::spawn(common::run_load_test(goose_attack, None));
+
+    // Wait for the WebSocket Controller to start listening, and all users to start.
+    let mut websocket = common::connect_websocket_controller(WEBSOCKET_PORT).await;
+    common::websocket_wait_for_phase(&mut websocket, AttackPhase::Maintain).await;
+    assert!(mock_endpoints[BROWSE_KEY].hits() > 0);
+    assert_eq!(mock_endpoints[CHECKOUT_KEY].hits(), 0);
+
+    // Unknown scenarios and transactions can't be changed.
+    let response = common::websocket_command(&mut websocket, "enable", Some(json!("foo"))).await;
+    assert_eq!(response["error"]["code"], "command_failed");
+    let response =
+        common::websocket_command(&mut websocket, "weight", Some(json!("browse 0"))).await;
+    assert_eq!(response["error"]["code"], "invalid_value");
+    let response = common::websocket_command(&mut websocket, "skip", Some(json!("foo"))).await;
+    assert_eq!(response["error"]["code"], "command_failed");
+
+    // Add checkout traffic on top of browsing traffic.
+    let response =
+        common::websocket_command(&mut websocket, "enable", Some(json!("checkout"))).await;
+    assert_eq!(response["success"], true);
+    assert_eq!(response["message"], "scenario enabled");
+    wait_for_hits(&mock_endpoints[CHECKOUT_KEY]).await;
+    let response =
+        common::websocket_command(&mut websocket, "weight", Some(json!("checkout 3"))).await;
+    assert_eq!(response["success"], true);
+    assert_eq!(response["message"], "scenario weight configured");
+
+    // Only run the checkout scenario, with the same number of users.
+    let response =
+        common::websocket_command(&mut websocket, "disable", Some(json!("browse"))).await;
+    assert_eq!(response["success"], true);
+    assert_eq!(response["message"], "scenario disabled");
+    assert_no_hits(&mock_endpoints[BROWSE_KEY]).await;
+    assert_eq!(common::websocket_status(&mut websocket).await.users, 4);
+
+    // The last enabled scenario can't be disabled.
+    let response =
+        common::websocket_command(&mut websocket, "disable", Some(json!("checkout"))).await;
+    assert_eq!(response["error"]["code"], "command_failed");
+
+    // Skip the checkout transaction, then run it again.
+    let response =
+        common::websocket_command(&mut websocket, "skip", Some(json!("checkout page"))).await;
+    assert_eq!(response["success"], true);
+    assert_eq!(response["message"], "transaction skipped");
+    assert_no_hits(&mock_endpoints[CHECKOUT_KEY]).await;
+    let response =
+        common::websocket_command(&mut websocket, "unskip", Some(json!("checkout page"))).await;
+    assert_eq!(response["success"], true);
+    assert_eq!(response["message"], "transaction no longer skipped");
+    wait_for_hits(&mock_endpoints[CHECKOUT_KEY]).await;
+
+    // Shut down the load test.
+    let response = common::websocket_command(&mut websocket, "shutdown", None).await;
+    assert_eq!(response["success"], true);
+
+    let goose_metrics = load_test.await.unwrap();
+    assert_eq!(goose_metrics.maximum_users, 4);
+}
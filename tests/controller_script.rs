@@ -0,0 +1,258 @@
+use gumdrop::Options;
+use httpmock::{Method::GET, Mock, MockServer};
+use serde_json::json;
+use std::time::Duration;
+
+mod common;
+
+use goose::config::GooseConfiguration;
+use goose::prelude::*;
+
+// Paths used in load tests performed during these tests.
+const INDEX_PATH: &str = "/";
+
+// Indexes to the above paths.
+const INDEX_KEY: usize = 0;
+
+// The WebSocket Controller listens on a port not used by any other test.
+const WEBSOCKET_PORT: &str = "5429";
+
+// Test transaction.
+pub async fn get_index(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up INDEX_PATH, store in vector at INDEX_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(INDEX_PATH);
+            then.status(200);
+        }),
+    ]
+}
+
+#[tokio::test]
+// Run a script of timed commands passed on the command line.
+async fn test_controller_script_file() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    let script_file = std::env::temp_dir().join(format!(
+        "goose-{}-controller-script.txt",
+        std::process::id()
+    ));
+    std::fs::write(
+        &script_file,
+        "# Add users, then shut down.\nat 1s users 4\nat 3s shutdown\n",
+    )
+    .unwrap();
+
+    // Disable all network Controllers, the script shuts down the load test.
+    let configuration = common::build_configuration(
+        &server,
+        vec![
+            "--no-telnet",
+            "--no-websocket",
+            "--no-http",
+            "--users",
+            "2",
+            "--hatch-rate",
+            "10",
+            "--run-time",
+            "0",
+            "--controller-script",
+            script_file.to_str().unwrap(),
+        ],
+    );
+
+    let goose_attack = common::build_load_test(
+        configuration,
+        vec![scenario!("Index").register_transaction(transaction!(get_index))],
+        None,
+        None,
+    );
+    let goose_metrics = tokio::time::timeout(
+        Duration::from_secs(30),
+        common::run_load_test(goose_attack, None),
+    )
+    .await
+    .expect("script didn't shut down the load test");
+
+    assert!(mock_endpoints[INDEX_KEY].hits() > 0);
+    assert_eq!(goose_metrics.maximum_users, 4);
+
+    std::fs::remove_file(script_file).unwrap();
+}
+
+#[tokio::test]
+// Conditions are checked against recent metrics, so they can become true again once the
+// load test recovers from failures.
+async fn test_controller_script_window() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Requests fail until the server recovers.
+    let mut failing = server.mock(|when, then| {
+        when.method(GET).path(INDEX_PATH);
+        then.status(500);
+    });
+
+    let script_file = std::env::temp_dir().join(format!(
+        "goose-{}-controller-script-window.txt",
+        std::process::id()
+    ));
+    std::fs::write(&script_file, "when failures < 1% shutdown\n").unwrap();
+
+    // Disable all network Controllers, the script shuts down the load test.
+    let configuration = common::build_configuration(
+        &server,
+        vec![
+            "--no-telnet",
+            "--no-websocket",
+            "--no-http",
+            "--run-time",
+            "0",
+            "--controller-script",
+            script_file.to_str().unwrap(),
+        ],
+    );
+
+    let goose_attack = common::build_load_test(
+        configuration,
+        vec![scenario!("Index").register_transaction(transaction!(get_index))],
+        None,
+        None,
+    );
+    let load_test = tokio::time::timeout(
+        Duration::from_secs(60),
+        common::run_load_test(goose_attack, None),
+    );
+    // The server recovers after a few seconds. Counting all requests since the load test
+    // started, failures wouldn't drop below 1% until long after the timeout.
+    let recover = async {
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        let failed = failing.hits();
+        failing.delete();
+        let recovered = server.mock(|when, then| {
+            when.method(GET).path(INDEX_PATH);
+            then.status(200);
+        });
+        (failed, recovered)
+    };
+    let (goose_metrics, (failed, recovered)) = tokio::join!(load_test, recover);
+    goose_metrics.expect("script didn't shut down the load test");
+
+    assert!(failed > 0);
+    assert!(recovered.hits() > 0);
+
+    std::fs::remove_file(script_file).unwrap();
+}
+
+#[test]
+// Invalid script files are refused.
+fn test_invalid_controller_script_file() {
+    let server = MockServer::start();
+
+    let script_file = std::env::temp_dir().join(format!(
+        "goose-{}-invalid-controller-script.txt",
+        std::process::id()
+    ));
+    std::fs::write(&script_file, "at 1s users many\n").unwrap();
+    let script_path = script_file.to_str().unwrap();
+
+    // Refused as a run-time option.
+    assert!(GooseConfiguration::parse_args_default(&["--controller-script", script_path]).is_err());
+
+    // Refused as a default.
+    let configuration = common::build_configuration(&server, vec![]);
+    assert!(
+        GooseAttack::<GooseUser>::initialize_with_config(configuration)
+            .unwrap()
+            .set_default(GooseDefault::ControllerScript, script_path)
+            .is_err()
+    );
+
+    std::fs::remove_file(script_file).unwrap();
+}
+
+#[tokio::test]
+// Upload a script to the WebSocket Controller.
+async fn test_controller_script_upload() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let _mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Only enable the WebSocket Controller, and run until shut down.
+    let configuration = common::build_configuration(
+        &server,
+        vec![
+            "--no-telnet",
+            "--no-http",
+            "--websocket-host",
+            "127.0.0.1",
+            "--websocket-port",
+            WEBSOCKET_PORT,
+            "--users",
+            "2",
+            "--hatch-rate",
+            "10",
+            "--run-time",
+            "0",
+        ],
+    );
+
+    let goose_attack = common::build_load_test(
+        configuration,
+        vec![scenario!("Index").register_transaction(transaction!(get_index))],
+        None,
+        None,
+    );
+    let load_test = tokio::spawn(common::run_load_test(goose_attack, None));
+
+    // Wait for the WebSocket Controller to start listening.
+    let mut websocket = common::connect_websocket_controller(WEBSOCKET_PORT).await;
+
+    // Invalid scripts are refused.
+    let response = common::websocket_command(
+        &mut websocket,
+        "script",
+        Some(json!("at 1s users 10\nwhen latency > 5 stop")),
+    )
+    .await;
+    assert_eq!(response["error"]["code"], "invalid_value");
+
+    // Add two users, then remove one as soon as four users are running.
+    let response = common::websocket_command(
+        &mut websocket,
+        "script",
+        Some(json!("at 0 users +2\nwhen users >= 4 users -1")),
+    )
+    .await;
+    assert_eq!(response["success"], true);
+    assert_eq!(response["message"], "script started");
+
+    let mut retries = 0;
+    loop {
+        let status = common::websocket_status(&mut websocket).await;
+        if status.target_users == 3 && status.users == 3 {
+            break;
+        }
+        retries += 1;
+        assert!(retries < 100, "script never changed users: {:?}", status);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    let response = common::websocket_command(&mut websocket, "shutdown", None).await;
+    assert_eq!(response["success"], true);
+
+    let goose_metrics = load_test.await.unwrap();
+    assert_eq!(goose_metrics.maximum_users, 4);
+}
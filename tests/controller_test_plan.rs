@@ -0,0 +1,243 @@
+use httpmock::{Method::GET, Mock, MockServer};
+use serde_json::json;
+use std::time::Duration;
+
+mod common;
+
+use goose::prelude::*;
+use goose::AttackPhase;
+
+// Paths used in load tests performed during these tests.
+const INDEX_PATH: &str = "/";
+
+// Indexes to the above paths.
+const INDEX_KEY: usize = 0;
+
+// Each test's WebSocket Controller listens on a port not used by any other test.
+const CHANGE_WEBSOCKET_PORT: &str = "5430";
+const APPEND_WEBSOCKET_PORT: &str = "5431";
+const JUMP_DOWN_WEBSOCKET_PORT: &str = "5433";
+const APPEND_DOWN_WEBSOCKET_PORT: &str = "5434";
+
+// Test transaction.
+pub async fn get_index(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up INDEX_PATH, store in vector at INDEX_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(INDEX_PATH);
+            then.status(200);
+        }),
+    ]
+}
+
+// Wait until the load test is in the expected phase with the expected number of users.
+async fn wait_for(websocket: &mut common::ControllerWebSocket, phase: AttackPhase, users: usize) {
+    let mut retries = 0;
+    loop {
+        let status = common::websocket_status(websocket).await;
+        if status.phase == phase && status.users == users {
+            break;
+        }
+        retries += 1;
+        assert!(retries < 100, "load test never reached {:?}", phase);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+// Build a load test with the WebSocket Controller enabled, running the test plan.
+fn build_load_test(server: &MockServer, port: &str, test_plan: &str) -> GooseAttack<GooseUser> {
+    let mut configuration = common::build_configuration(
+        server,
+        vec![
+            "--no-telnet",
+            "--no-http",
+            "--websocket-host",
+            "127.0.0.1",
+            "--websocket-port",
+            port,
+            "--test-plan",
+            test_plan,
+        ],
+    );
+    // Do not set --run-time, --hatch-rate or --users with --test-plan.
+    configuration.run_time = "".to_string();
+    configuration.hatch_rate = None;
+    configuration.users = None;
+    common::build_load_test(
+        configuration,
+        vec![scenario!("Index").register_transaction(transaction!(get_index))],
+        None,
+        None,
+    )
+}
+
+#[tokio::test]
+// Extend, append to and jump between the steps of a running test plan.
+async fn test_change_running_test_plan() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    let goose_attack = build_load_test(&server, CHANGE_WEBSOCKET_PORT, "2,1s;2,3s;0,0s");
+    let load_test = tokio::spawn(common::run_load_test(goose_attack, None));
+
+    let mut websocket = common::connect_websocket_controller(CHANGE_WEBSOCKET_PORT).await;
+    wait_for(&mut websocket, AttackPhase::Maintain, 2).await;
+
+    // Keep running the current step instead of shutting down.
+    let response =
+        common::websocket_command(&mut websocket, "extend-step", Some(json!("1h"))).await;
+    assert_eq!(response["success"], true);
+    assert_eq!(response["message"], "test-plan step extended");
+    let response =
+        common::websocket_command(&mut websocket, "extend-step", Some(json!("soon"))).await;
+    assert_eq!(response["error"]["code"], "invalid_value");
+
+    // Append steps after the final step, which don't start yet.
+    let response =
+        common::websocket_command(&mut websocket, "append-plan", Some(json!("4,1s;4,1h"))).await;
+    assert_eq!(response["success"], true);
+    assert_eq!(response["message"], "test-plan steps appended");
+    let response = common::websocket_command(
+        &mut websocket,
+        "append-plan",
+        Some(json!("sine(30,10,1m,1m)")),
+    )
+    .await;
+    assert_eq!(response["error"]["code"], "command_failed");
+
+    // Jump to the first appended step, launching more users.
+    let response = common::websocket_command(&mut websocket, "jump-step", Some(json!(4))).await;
+    assert_eq!(response["success"], true);
+    assert_eq!(response["message"], "jumped to test-plan step");
+    wait_for(&mut websocket, AttackPhase::Maintain, 4).await;
+
+    // Jump back to the first step, stopping users.
+    let response = common::websocket_command(&mut websocket, "jump-step", Some(json!(1))).await;
+    assert_eq!(response["success"], true);
+    wait_for(&mut websocket, AttackPhase::Maintain, 2).await;
+
+    // Steps that don't exist can't be jumped to.
+    for step in [json!(0), json!(7), json!("cooldown")] {
+        let response = common::websocket_command(&mut websocket, "jump-step", Some(step)).await;
+        assert_eq!(response["error"]["code"], "command_failed");
+    }
+
+    let response = common::websocket_command(&mut websocket, "shutdown", None).await;
+    assert_eq!(response["success"], true);
+
+    let goose_metrics = load_test.await.unwrap();
+    assert!(mock_endpoints[INDEX_KEY].hits() > 0);
+    assert_eq!(goose_metrics.maximum_users, 4);
+
+    // Each jump is recorded as a test plan step, starting from the users running.
+    let history: Vec<(String, usize)> = goose_metrics
+        .history
+        .iter()
+        .map(|step| (format!("{:?}", step.action), step.users))
+        .collect();
+    assert!(history.contains(&("Increasing".to_string(), 2)));
+    assert!(history.contains(&("Decreasing".to_string(), 4)));
+}
+
+#[tokio::test]
+// Append steps to a test plan after its last step completed.
+async fn test_append_completed_test_plan() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let _mock_endpoints = setup_mock_server_endpoints(&server);
+
+    let goose_attack = build_load_test(&server, APPEND_WEBSOCKET_PORT, "2,1s");
+    let load_test = tokio::spawn(common::run_load_test(goose_attack, None));
+
+    let mut websocket = common::connect_websocket_controller(APPEND_WEBSOCKET_PORT).await;
+    wait_for(&mut websocket, AttackPhase::Maintain, 2).await;
+
+    // The users of the last step are maintained until shut down, so it can't be extended.
+    let response =
+        common::websocket_command(&mut websocket, "extend-step", Some(json!("1m"))).await;
+    assert_eq!(response["error"]["code"], "command_failed");
+
+    // Appended steps start immediately, and the load test shuts down once they complete.
+    let response =
+        common::websocket_command(&mut websocket, "append-plan", Some(json!("3,1s;0,0s"))).await;
+    assert_eq!(response["success"], true);
+
+    let goose_metrics = tokio::time::timeout(Duration::from_secs(30), load_test)
+        .await
+        .expect("appended steps didn't shut down the load test")
+        .unwrap();
+    assert_eq!(goose_metrics.maximum_users, 3);
+}
+
+#[tokio::test]
+// Jump to test plan steps that only stop users.
+async fn test_jump_to_lower_step() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let _mock_endpoints = setup_mock_server_endpoints(&server);
+
+    let goose_attack = build_load_test(
+        &server,
+        JUMP_DOWN_WEBSOCKET_PORT,
+        "4,1s;4,1h;2,1s;2,1h;0,0s",
+    );
+    let load_test = tokio::spawn(common::run_load_test(goose_attack, None));
+
+    let mut websocket = common::connect_websocket_controller(JUMP_DOWN_WEBSOCKET_PORT).await;
+    wait_for(&mut websocket, AttackPhase::Maintain, 4).await;
+
+    // Jump to a step with fewer users, which has no users left to launch.
+    let response = common::websocket_command(&mut websocket, "jump-step", Some(json!(3))).await;
+    assert_eq!(response["success"], true);
+    wait_for(&mut websocket, AttackPhase::Maintain, 2).await;
+
+    // Jump to the last step, stopping all users and shutting down the load test.
+    let response = common::websocket_command(&mut websocket, "jump-step", Some(json!(5))).await;
+    assert_eq!(response["success"], true);
+
+    let goose_metrics = tokio::time::timeout(Duration::from_secs(30), load_test)
+        .await
+        .expect("jumping to the last step didn't shut down the load test")
+        .unwrap();
+    assert_eq!(goose_metrics.maximum_users, 4);
+}
+
+#[tokio::test]
+// Append a step that only stops users after the test plan completed.
+async fn test_append_ramp_down() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let _mock_endpoints = setup_mock_server_endpoints(&server);
+
+    let goose_attack = build_load_test(&server, APPEND_DOWN_WEBSOCKET_PORT, "2,1s");
+    let load_test = tokio::spawn(common::run_load_test(goose_attack, None));
+
+    let mut websocket = common::connect_websocket_controller(APPEND_DOWN_WEBSOCKET_PORT).await;
+    wait_for(&mut websocket, AttackPhase::Maintain, 2).await;
+
+    // The appended step stops all users, then the load test shuts down.
+    let response =
+        common::websocket_command(&mut websocket, "append-plan", Some(json!("0,1s"))).await;
+    assert_eq!(response["success"], true);
+
+    let goose_metrics = tokio::time::timeout(Duration::from_secs(30), load_test)
+        .await
+        .expect("appended ramp-down didn't shut down the load test")
+        .unwrap();
+    assert_eq!(goose_metrics.maximum_users, 2);
+}
@@ -0,0 +1,222 @@
+use httpmock::{
+    Method::{GET, POST},
+    Mock, MockServer,
+};
+
+mod common;
+
+use goose::goose::GooseFailurePolicy;
+use goose::prelude::*;
+
+// Paths used in load tests performed during these tests.
+const LOGIN_PATH: &str = "/login";
+const STALE_PATH: &str = "/stale";
+const FAIL_PATH: &str = "/fail";
+const INDEX_PATH: &str = "/";
+const LOGOUT_PATH: &str = "/logout";
+
+// Indexes to the above paths.
+const LOGIN_KEY: usize = 0;
+const STALE_KEY: usize = 1;
+const FAIL_KEY: usize = 2;
+const INDEX_KEY: usize = 3;
+const LOGOUT_KEY: usize = 4;
+
+// Session data set when logging in.
+struct Session;
+
+// Test transaction, logging in and storing session data.
+pub async fn login(user: &mut GooseUser) -> TransactionResult {
+    // Session data should never be present when logging in.
+    if user.get_session_data::<Session>().is_some() {
+        let _goose = user.get(STALE_PATH).await?;
+    }
+    let _goose = user.post(LOGIN_PATH, "username=foo").await?;
+    user.set_session_data(Session);
+
+    Ok(())
+}
+
+// Test transaction, always fails.
+pub async fn get_fail(user: &mut GooseUser) -> TransactionResult {
+    let goose = user.get(FAIL_PATH).await?;
+    goose.response?.error_for_status()?;
+
+    Ok(())
+}
+
+// Test transaction.
+pub async fn get_index(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(INDEX_PATH).await?;
+
+    Ok(())
+}
+
+// Test transaction.
+pub async fn logout(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.post(LOGOUT_PATH, "").await?;
+
+    Ok(())
+}
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up LOGIN_PATH, store in vector at LOGIN_KEY.
+        server.mock(|when, then| {
+            when.method(POST).path(LOGIN_PATH);
+            then.status(200);
+        }),
+        // Next set up STALE_PATH, store in vector at STALE_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(STALE_PATH);
+            then.status(200);
+        }),
+        // Next set up FAIL_PATH, store in vector at FAIL_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(FAIL_PATH);
+            then.status(500);
+        }),
+        // Next set up INDEX_PATH, store in vector at INDEX_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(INDEX_PATH);
+            then.status(200);
+        }),
+        // Last set up LOGOUT_PATH, store in vector at LOGOUT_KEY.
+        server.mock(|when, then| {
+            when.method(POST).path(LOGOUT_PATH);
+            then.status(200);
+        }),
+    ]
+}
+
+// Run a load test with a single scenario, returning the metrics.
+async fn run_load_test(server: &MockServer, scenario: Scenario<GooseUser>) -> GooseMetrics {
+    let configuration = common::build_configuration(server, vec!["--no-reset-metrics"]);
+    common::run_load_test(
+        common::build_load_test(configuration, vec![scenario], None, None),
+        None,
+    )
+    .await
+}
+
+#[tokio::test]
+// By default users continue running transactions after a failure.
+async fn test_failure_policy_continue() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    run_load_test(
+        &server,
+        scenario!("LoadTest")
+            .register_transaction(transaction!(get_fail))
+            .register_transaction(transaction!(get_index)),
+    )
+    .await;
+
+    assert!(mock_endpoints[FAIL_KEY].hits() > 0);
+    assert!(mock_endpoints[INDEX_KEY].hits() > 0);
+}
+
+#[tokio::test]
+// Skip the rest of the iteration after a failure.
+async fn test_failure_policy_restart_iteration() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    let goose_metrics = run_load_test(
+        &server,
+        scenario!("LoadTest")
+            .set_failure_policy(GooseFailurePolicy::RestartIteration)
+            .register_transaction(transaction!(login).set_on_start())
+            .register_transaction(transaction!(get_fail))
+            .register_transaction(transaction!(get_index)),
+    )
+    .await;
+
+    // The user logged in once, then restarted the iteration after each failure.
+    assert_eq!(mock_endpoints[LOGIN_KEY].hits(), 1);
+    assert!(mock_endpoints[FAIL_KEY].hits() > 1);
+    assert_eq!(mock_endpoints[INDEX_KEY].hits(), 0);
+
+    // Aborted iterations aren't included in the scenario metrics.
+    assert_eq!(goose_metrics.scenarios[0].counter, 0);
+}
+
+#[tokio::test]
+// Start over with a fresh session after a failure.
+async fn test_failure_policy_restart_user() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    run_load_test(
+        &server,
+        scenario!("LoadTest")
+            .register_transaction(transaction!(login).set_on_start())
+            .register_transaction(
+                transaction!(get_fail).set_failure_policy(GooseFailurePolicy::RestartUser),
+            )
+            .register_transaction(transaction!(get_index))
+            .register_transaction(transaction!(logout).set_on_stop()),
+    )
+    .await;
+
+    // The user logged in again after each failure, unless the load test ended first,
+    // always with a fresh session.
+    let logins = mock_endpoints[LOGIN_KEY].hits();
+    let failures = mock_endpoints[FAIL_KEY].hits();
+    assert!(logins > 1);
+    assert!(logins == failures || logins == failures + 1);
+    assert_eq!(mock_endpoints[STALE_KEY].hits(), 0);
+    assert_eq!(mock_endpoints[INDEX_KEY].hits(), 0);
+
+    // The user only stopped once.
+    assert_eq!(mock_endpoints[LOGOUT_KEY].hits(), 1);
+}
+
+#[tokio::test]
+// Stop the user after a failure.
+async fn test_failure_policy_stop_user() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    let started = std::time::Instant::now();
+    let configuration = common::build_configuration(&server, vec!["--run-time", "30"]);
+    common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![scenario!("LoadTest")
+                .register_transaction(
+                    transaction!(get_fail)
+                        .set_on_start()
+                        .set_failure_policy(GooseFailurePolicy::StopUser),
+                )
+                .register_transaction(transaction!(get_index))
+                .register_transaction(transaction!(logout).set_on_stop())],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    // The user stopped without running any normal transactions.
+    assert_eq!(mock_endpoints[FAIL_KEY].hits(), 1);
+    assert_eq!(mock_endpoints[INDEX_KEY].hits(), 0);
+    assert_eq!(mock_endpoints[LOGOUT_KEY].hits(), 1);
+
+    // With all users stopped, the load test ended early.
+    assert!(started.elapsed().as_secs() < 30);
+}
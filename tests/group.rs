@@ -0,0 +1,151 @@
+use httpmock::{
+    Method::{GET, POST},
+    Mock, MockServer,
+};
+
+mod common;
+
+use goose::prelude::*;
+
+// Paths used in load tests performed during these tests.
+const CART_PATH: &str = "/cart";
+const SHIPPING_PATH: &str = "/shipping";
+const RATES_PATH: &str = "/rates";
+const PAYMENT_PATH: &str = "/payment";
+
+// Indexes to the above paths.
+const CART_KEY: usize = 0;
+const SHIPPING_KEY: usize = 1;
+const RATES_KEY: usize = 2;
+const PAYMENT_KEY: usize = 3;
+
+// Test transaction, checking out in separately timed groups.
+pub async fn checkout(user: &mut GooseUser) -> TransactionResult {
+    user.group("cart", |user| {
+        Box::pin(async move {
+            let _goose = user.post(CART_PATH, "item=1").await?;
+
+            Ok(())
+        })
+    })
+    .await?;
+
+    // Groups can be nested.
+    user.group("shipping", |user| {
+        Box::pin(async move {
+            user.group("rates", |user| {
+                Box::pin(async move {
+                    let _goose = user.get(RATES_PATH).await?;
+
+                    Ok(())
+                })
+            })
+            .await?;
+            let _goose = user.post(SHIPPING_PATH, "method=ground").await?;
+
+            Ok(())
+        })
+    })
+    .await?;
+
+    // The payment group always fails.
+    user.group("payment", |user| {
+        Box::pin(async move {
+            let goose = user.post(PAYMENT_PATH, "card=1").await?;
+            goose.response?.error_for_status()?;
+
+            Ok(())
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up CART_PATH, store in vector at CART_KEY.
+        server.mock(|when, then| {
+            when.method(POST).path(CART_PATH);
+            then.status(200);
+        }),
+        // Next set up SHIPPING_PATH, store in vector at SHIPPING_KEY.
+        server.mock(|when, then| {
+            when.method(POST).path(SHIPPING_PATH);
+            then.status(200);
+        }),
+        // Next set up RATES_PATH, store in vector at RATES_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(RATES_PATH);
+            then.status(200);
+        }),
+        // Last set up PAYMENT_PATH, store in vector at PAYMENT_KEY.
+        server.mock(|when, then| {
+            when.method(POST).path(PAYMENT_PATH);
+            then.status(500);
+        }),
+    ]
+}
+
+#[tokio::test]
+// Load test with nested transaction groups, confirming each group is timed separately.
+async fn test_group() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Build configuration.
+    let configuration = common::build_configuration(&server, vec!["--no-reset-metrics"]);
+
+    // Run the Goose Attack.
+    let goose_metrics = common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![scenario!("Checkout")
+                .register_transaction(transaction!(checkout).set_name("checkout"))],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    // Every checkout failed in the payment group.
+    let checkout = &goose_metrics.transactions[0][0];
+    assert!(checkout.counter > 0);
+    assert_eq!(checkout.fail_count, checkout.counter);
+    assert_eq!(checkout.counter, mock_endpoints[PAYMENT_KEY].hits());
+
+    // Groups are tracked in the order they first run.
+    let groups: Vec<&str> = checkout
+        .groups
+        .iter()
+        .map(|g| g.transaction_name.as_str())
+        .collect();
+    assert_eq!(groups, vec!["cart", "shipping", "payment"]);
+
+    let cart = &checkout.groups[0];
+    assert_eq!(cart.success_count, mock_endpoints[CART_KEY].hits());
+    assert_eq!(cart.fail_count, 0);
+
+    let shipping = &checkout.groups[1];
+    assert_eq!(shipping.success_count, mock_endpoints[SHIPPING_KEY].hits());
+    assert_eq!(shipping.groups.len(), 1);
+    let rates = &shipping.groups[0];
+    assert_eq!(rates.transaction_name, "rates");
+    assert_eq!(rates.success_count, mock_endpoints[RATES_KEY].hits());
+
+    let payment = &checkout.groups[2];
+    assert_eq!(payment.success_count, 0);
+    assert_eq!(payment.fail_count, mock_endpoints[PAYMENT_KEY].hits());
+
+    // Groups are displayed beneath their transaction.
+    let output = goose_metrics.to_string();
+    assert!(output.contains("  1: checkout"));
+    assert!(output.contains("    1.1: cart"));
+    assert!(output.contains("      1.2.1: rates"));
+    assert!(output.contains("    1.3: payment"));
+}
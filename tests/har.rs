@@ -0,0 +1,230 @@
+use httpmock::{
+    Method::{GET, POST},
+    Mock, MockServer,
+};
+use std::time::Duration;
+
+mod common;
+
+use goose::goose::GooseWaitTime;
+use goose::har::HarImporter;
+use goose::prelude::*;
+
+// Paths used in load tests performed during these tests.
+const INDEX_PATH: &str = "/";
+const LOGIN_PATH: &str = "/login";
+
+// Indexes to the above paths.
+const INDEX_KEY: usize = 0;
+const LOGIN_KEY: usize = 1;
+
+// Body and header recorded with the login request.
+const LOGIN_BODY: &str = "name=foo&pass=bar";
+const LOGIN_CONTENT_TYPE: &str = "application/x-www-form-urlencoded";
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up INDEX_PATH, store in vector at INDEX_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(INDEX_PATH);
+            then.status(200);
+        }),
+        // Next set up LOGIN_PATH, store in vector at LOGIN_KEY. Only matches if the
+        // recorded header and body are replayed.
+        server.mock(|when, then| {
+            when.method(POST)
+                .path(LOGIN_PATH)
+                .header("content-type", LOGIN_CONTENT_TYPE)
+                .body(LOGIN_BODY);
+            then.status(200);
+        }),
+    ]
+}
+
+// Build a HAR recording of two pages loaded 3 seconds apart, including a request to a
+// third-party host and an unsupported method.
+fn build_har(server: &MockServer) -> String {
+    let base_url = server.base_url();
+    serde_json::json!({
+        "log": {
+            "pages": [
+                { "id": "page_1", "title": "Front page" },
+                { "id": "page_2", "title": "Log in" }
+            ],
+            "entries": [
+                {
+                    "pageref": "page_1",
+                    "startedDateTime": "2022-08-01T10:00:00.000Z",
+                    "time": 100,
+                    "request": {
+                        "method": "GET",
+                        "url": format!("{}{}", base_url, INDEX_PATH),
+                        "headers": [
+                            { "name": "Host", "value": "recorded.example.com" },
+                            { "name": "Accept", "value": "text/html" }
+                        ]
+                    }
+                },
+                {
+                    "pageref": "page_1",
+                    "startedDateTime": "2022-08-01T10:00:00.500Z",
+                    "time": 500,
+                    "request": {
+                        "method": "GET",
+                        "url": "http://analytics.example.com/track.js",
+                        "headers": []
+                    }
+                },
+                {
+                    "pageref": "page_2",
+                    "startedDateTime": "2022-08-01T10:00:03.000Z",
+                    "time": 100,
+                    "request": {
+                        "method": "OPTIONS",
+                        "url": format!("{}{}", base_url, LOGIN_PATH),
+                        "headers": []
+                    }
+                },
+                {
+                    "pageref": "page_2",
+                    "startedDateTime": "2022-08-01T10:00:03.100Z",
+                    "time": 100,
+                    "request": {
+                        "method": "POST",
+                        "url": format!("{}{}", base_url, LOGIN_PATH),
+                        "headers": [
+                            { "name": "Content-Type", "value": LOGIN_CONTENT_TYPE },
+                            { "name": "Cookie", "value": "session=stale" }
+                        ],
+                        "postData": { "mimeType": LOGIN_CONTENT_TYPE, "text": LOGIN_BODY }
+                    }
+                }
+            ]
+        }
+    })
+    .to_string()
+}
+
+#[test]
+// Confirm requests are grouped per page, and third-party hosts and unsupported methods
+// are filtered out.
+fn test_har_pages() {
+    let server = MockServer::start();
+    let har = HarImporter::from_json(&build_har(&server)).unwrap();
+
+    let pages = har.pages().unwrap();
+    assert_eq!(pages.len(), 2);
+    assert_eq!(pages[0].title, "Front page");
+    assert_eq!(pages[0].requests.len(), 1);
+    assert_eq!(pages[0].requests[0].path, INDEX_PATH);
+    // The Host header is managed by the client and not replayed.
+    assert_eq!(
+        pages[0].requests[0].headers,
+        vec![("Accept".to_string(), "text/html".to_string())]
+    );
+    assert_eq!(pages[1].title, "Log in");
+    assert_eq!(pages[1].requests.len(), 1);
+    assert_eq!(pages[1].requests[0].method, GooseMethod::Post);
+    assert_eq!(pages[1].requests[0].body.as_deref(), Some(LOGIN_BODY));
+
+    // Explicitly allowing the third-party host keeps its request, with a full URL.
+    let har = HarImporter::from_json(&build_har(&server))
+        .unwrap()
+        .allow_host("analytics.example.com");
+    let pages = har.pages().unwrap();
+    assert_eq!(pages[0].requests.len(), 2);
+    assert_eq!(
+        pages[0].requests[1].path,
+        "http://analytics.example.com/track.js"
+    );
+
+    // Invalid json returns an error.
+    assert!(HarImporter::from_json("{\"foo\": \"bar\"}").is_err());
+}
+
+#[test]
+// Confirm recorded think times are only configured when requested.
+fn test_har_think_time() {
+    let server = MockServer::start();
+
+    let scenario: Scenario<GooseUser> = HarImporter::from_json(&build_har(&server))
+        .unwrap()
+        .scenario("LoadTest")
+        .unwrap();
+    assert!(scenario.transaction_wait.is_none());
+    assert_eq!(scenario.host, Some(server.base_url()));
+
+    // Ignoring filtered requests, the first page completed 100ms in and the second
+    // page started 3.1s in.
+    let scenario: Scenario<GooseUser> = HarImporter::from_json(&build_har(&server))
+        .unwrap()
+        .keep_think_time(true)
+        .scenario("LoadTest")
+        .unwrap();
+    assert_eq!(
+        scenario.transaction_wait,
+        Some(GooseWaitTime::Uniform(
+            Duration::from_secs(3),
+            Duration::from_secs(3)
+        ))
+    );
+}
+
+#[test]
+// Confirm the generated source code replays the recorded requests.
+fn test_har_to_rust() {
+    let server = MockServer::start();
+    let source = HarImporter::from_json(&build_har(&server))
+        .unwrap()
+        .to_rust("LoadTest")
+        .unwrap();
+
+    assert!(source.contains("scenario!(\"LoadTest\")"));
+    assert!(source.contains(&format!(".set_host({:?})", server.base_url())));
+    assert!(source.contains(
+        ".register_transaction(transaction!(page_1).set_name(\"Front page\").set_sequence(1))"
+    ));
+    assert!(source.contains("async fn page_2(user: &mut GooseUser) -> TransactionResult {"));
+    assert!(source.contains("user.get_request_builder(&GooseMethod::Post, \"/login\")?"));
+    assert!(source.contains(&format!(".body({:?})", LOGIN_BODY)));
+    assert!(!source.contains("analytics.example.com"));
+    assert!(!source.contains("session=stale"));
+}
+
+#[tokio::test]
+// Load test replaying an imported HAR recording.
+async fn test_har_scenario() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Build the scenario from the recording.
+    let scenario = HarImporter::from_json(&build_har(&server))
+        .unwrap()
+        .scenario("LoadTest")
+        .unwrap();
+
+    // Build configuration.
+    let configuration = common::build_configuration(&server, vec![]);
+
+    // Run the Goose Attack.
+    let goose_metrics = common::run_load_test(
+        common::build_load_test(configuration, vec![scenario], None, None),
+        None,
+    )
+    .await;
+
+    // Confirm that both pages were loaded, including the recorded header and body.
+    assert!(mock_endpoints[INDEX_KEY].hits() > 0);
+    assert!(mock_endpoints[LOGIN_KEY].hits() > 0);
+
+    // Confirm transactions were named after the recorded pages.
+    assert_eq!(
+        goose_metrics.transactions[0][0].transaction_name,
+        "Front page"
+    );
+    assert_eq!(goose_metrics.transactions[0][1].transaction_name, "Log in");
+}
@@ -0,0 +1,346 @@
+use httpmock::{Method::GET, Mock, MockServer};
+use reqwest::StatusCode;
+use serde_json::json;
+use std::time::Duration;
+
+mod common;
+
+use goose::controller::{ControllerHttpResponse, ControllerStatus};
+use goose::prelude::*;
+use goose::AttackPhase;
+
+// Paths used in load tests performed during these tests.
+const INDEX_PATH: &str = "/";
+
+// Indexes to the above paths.
+const INDEX_KEY: usize = 0;
+
+// Each test's HTTP Controller listens on a port not used by any other test.
+const HTTP_PORT: &str = "5418";
+const TOKEN_HTTP_PORT: &str = "5423";
+#[cfg(feature = "rustls-tls")]
+const TLS_HTTP_PORT: &str = "5425";
+
+// The tokens Controller clients authenticate with.
+const CONTROL_TOKEN: &str = "s3cr3t";
+const READ_TOKEN: &str = "viewer";
+
+// Test transaction.
+pub async fn get_index(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up INDEX_PATH, store in vector at INDEX_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(INDEX_PATH);
+            then.status(200);
+        }),
+    ]
+}
+
+// Build the full url of an HTTP Controller endpoint.
+fn controller_url(path: &str) -> String {
+    format!("http://127.0.0.1:{}{}", HTTP_PORT, path)
+}
+
+// Request the load test status from the HTTP Controller.
+async fn get_status(client: &reqwest::Client) -> ControllerStatus {
+    let response = client.get(controller_url("/status")).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    response.json().await.unwrap()
+}
+
+// Send a command to the HTTP Controller, returning the status code and response.
+async fn post(
+    client: &reqwest::Client,
+    path: &str,
+    body: Option<serde_json::Value>,
+) -> (StatusCode, ControllerHttpResponse) {
+    let mut request = client.post(controller_url(path));
+    if let Some(body) = body {
+        request = request.json(&body);
+    }
+    let response = request.send().await.unwrap();
+    (response.status(), response.json().await.unwrap())
+}
+
+// Start an idle load test with only the HTTP Controller enabled, configured by the options.
+fn start_load_test(
+    server: &MockServer,
+    options: Vec<&str>,
+) -> tokio::task::JoinHandle<GooseMetrics> {
+    let mut options = options;
+    options.extend(vec!["--no-telnet", "--no-websocket", "--no-autostart"]);
+    let mut configuration = common::build_configuration(server, options);
+    configuration.users = None;
+    configuration.run_time = "".to_string();
+
+    let goose_attack = common::build_load_test(
+        configuration,
+        vec![scenario!("Index").register_transaction(transaction!(get_index))],
+        None,
+        None,
+    );
+    tokio::spawn(common::run_load_test(goose_attack, None))
+}
+
+// Wait for an HTTP Controller to start listening.
+async fn wait_for_http_controller(client: &reqwest::Client, url: &str) {
+    let mut retries = 0;
+    while client.get(url).send().await.is_err() {
+        retries += 1;
+        assert!(retries < 50, "HTTP Controller never started");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "http-controller"), ignore)]
+// Configure, start and shut down a load test with the HTTP Controller.
+async fn test_http_controller() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Only enable the HTTP Controller, and wait for it to start the load test.
+    let mut configuration = common::build_configuration(
+        &server,
+        vec![
+            "--no-telnet",
+            "--no-websocket",
+            "--no-autostart",
+            "--http-host",
+            "127.0.0.1",
+            "--http-port",
+            HTTP_PORT,
+            "--hatch-rate",
+            "10",
+        ],
+    );
+    configuration.users = None;
+    configuration.run_time = "".to_string();
+
+    let goose_attack = common::build_load_test(
+        configuration,
+        vec![scenario!("Index").register_transaction(transaction!(get_index))],
+        None,
+        None,
+    );
+    let load_test = tokio::spawn(common::run_load_test(goose_attack, None));
+
+    let client = reqwest::Client::new();
+
+    // Wait for the HTTP Controller to start listening.
+    wait_for_http_controller(&client, &controller_url("/status")).await;
+
+    // The load test is idle until started.
+    let status = get_status(&client).await;
+    assert_eq!(status.phase, AttackPhase::Idle);
+    assert_eq!(status.users, 0);
+
+    // Invalid requests are rejected.
+    let response = client.get(controller_url("/foo")).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = client.get(controller_url("/users")).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    let (status_code, response) = post(&client, "/users", None).await;
+    assert_eq!(status_code, StatusCode::BAD_REQUEST);
+    assert!(!response.success);
+    let (status_code, _) = post(&client, "/users", Some(json!({"users": "many"}))).await;
+    assert_eq!(status_code, StatusCode::BAD_REQUEST);
+    let (status_code, _) = post(&client, "/host", Some(json!({"host": "foo"}))).await;
+    assert_eq!(status_code, StatusCode::BAD_REQUEST);
+
+    // An idle load test can't be stopped.
+    let (status_code, response) = post(&client, "/stop", None).await;
+    assert_eq!(status_code, StatusCode::CONFLICT);
+    assert!(!response.success);
+
+    // Configure and start the load test.
+    let (status_code, response) = post(&client, "/users", Some(json!({"users": 3}))).await;
+    assert_eq!(status_code, StatusCode::OK);
+    assert!(response.success);
+    assert_eq!(response.message, "users configured");
+    let (status_code, _) = post(&client, "/throttle", Some(json!({"throttle": 100}))).await;
+    assert_eq!(status_code, StatusCode::OK);
+    let (status_code, response) = post(&client, "/start", None).await;
+    assert_eq!(status_code, StatusCode::OK);
+    assert_eq!(response.message, "load test started");
+
+    // Wait for all users to start.
+    let mut retries = 0;
+    loop {
+        let status = get_status(&client).await;
+        if status.phase == AttackPhase::Maintain {
+            assert_eq!(status.users, 3);
+            assert_eq!(status.throttle_requests, 100);
+            assert_eq!(status.host, server.base_url());
+            break;
+        }
+        retries += 1;
+        assert!(retries < 50, "load test never started all users");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    // Configuration and metrics are returned as json.
+    let config: serde_json::Value = client
+        .get(controller_url("/config"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(config["users"], 3);
+    let metrics: serde_json::Value = client
+        .get(controller_url("/metrics"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(metrics["maximum_users"], 3);
+
+    // Shut down the load test.
+    let (status_code, response) = post(&client, "/shutdown", None).await;
+    assert_eq!(status_code, StatusCode::OK);
+    assert_eq!(response.message, "load test shut down");
+
+    let goose_metrics = load_test.await.unwrap();
+    assert!(mock_endpoints[INDEX_KEY].hits() > 0);
+    assert_eq!(goose_metrics.maximum_users, 3);
+}
+
+#[tokio::test]
+#[cfg_attr(not(feature = "http-controller"), ignore)]
+// Require HTTP Controller clients to authenticate with control or read-only tokens.
+async fn test_http_controller_tokens() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    let tokens = format!("control:{},read:{}", CONTROL_TOKEN, READ_TOKEN);
+    let load_test = start_load_test(
+        &server,
+        vec![
+            "--http-host",
+            "127.0.0.1",
+            "--http-port",
+            TOKEN_HTTP_PORT,
+            "--controller-token",
+            &tokens,
+        ],
+    );
+
+    // Requests without a valid token are refused.
+    let client = reqwest::Client::new();
+    let status_url = format!("http://127.0.0.1:{}/status", TOKEN_HTTP_PORT);
+    let users_url = format!("http://127.0.0.1:{}/users", TOKEN_HTTP_PORT);
+    wait_for_http_controller(&client, &status_url).await;
+    let response = client.get(&status_url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(response.headers()["www-authenticate"], "Bearer");
+    let response = client
+        .get(&status_url)
+        .bearer_auth("wrong")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // Read-only tokens can request the status, but can't change the load test.
+    let response = client
+        .get(&status_url)
+        .bearer_auth(READ_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = client
+        .post(&users_url)
+        .bearer_auth(READ_TOKEN)
+        .json(&json!({"users": 2}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        response["message"],
+        "permission denied, users requires a control token"
+    );
+
+    // Control tokens can also be provided as a query parameter.
+    let response = client
+        .post(format!("{}?token={}", users_url, CONTROL_TOKEN))
+        .json(&json!({"users": 2}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Shut down the load test with the control token.
+    let response = client
+        .post(format!("http://127.0.0.1:{}/shutdown", TOKEN_HTTP_PORT))
+        .bearer_auth(CONTROL_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    load_test.await.unwrap();
+}
+
+#[cfg(feature = "rustls-tls")]
+#[tokio::test]
+#[cfg_attr(not(feature = "http-controller"), ignore)]
+// Only accept HTTPS requests when the HTTP Controller is configured with a certificate.
+async fn test_http_controller_tls() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    let load_test = start_load_test(
+        &server,
+        vec![
+            "--http-host",
+            "127.0.0.1",
+            "--http-port",
+            TLS_HTTP_PORT,
+            "--controller-tls-cert",
+            "tests/tls/controller-cert.pem",
+            "--controller-tls-key",
+            "tests/tls/controller-key.pem",
+        ],
+    );
+
+    // Trust the self-signed certificate.
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .build()
+        .unwrap();
+    let status_url = format!("https://127.0.0.1:{}/status", TLS_HTTP_PORT);
+    wait_for_http_controller(&client, &status_url).await;
+    let response = client.get(&status_url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(client
+        .get(format!("http://127.0.0.1:{}/status", TLS_HTTP_PORT))
+        .send()
+        .await
+        .is_err());
+
+    // Shut down the load test.
+    let response = client
+        .post(format!("https://127.0.0.1:{}/shutdown", TLS_HTTP_PORT))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    load_test.await.unwrap();
+}
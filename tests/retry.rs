@@ -0,0 +1,186 @@
+use httpmock::{Method::GET, Mock, MockServer};
+use std::time::{Duration, Instant};
+
+mod common;
+
+use goose::goose::GooseRetryPolicy;
+use goose::prelude::*;
+
+// Paths used in load tests performed during these tests.
+const LIMITED_PATH: &str = "/limited";
+const ERROR_PATH: &str = "/error";
+const SLOW_PATH: &str = "/slow";
+
+// Indexes to the above paths.
+const LIMITED_KEY: usize = 0;
+const ERROR_KEY: usize = 1;
+const SLOW_KEY: usize = 2;
+
+// How many times each request is attempted.
+const MAX_ATTEMPTS: usize = 3;
+
+// Test transaction, retrying with the scenario retry policy.
+pub async fn get_limited(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(LIMITED_PATH).await?;
+    Ok(())
+}
+
+// Test transaction, overriding the scenario retry policy.
+pub async fn get_error(user: &mut GooseUser) -> TransactionResult {
+    let goose_request = GooseRequest::builder()
+        .path(ERROR_PATH)
+        .retry_policy(GooseRetryPolicy::new(MAX_ATTEMPTS).set_status_codes(&[500]))
+        .build();
+    let _goose = user.request(goose_request).await?;
+    Ok(())
+}
+
+// Test transaction, backing off for longer than the load test runs.
+pub async fn get_slow(user: &mut GooseUser) -> TransactionResult {
+    let goose_request = GooseRequest::builder()
+        .path(SLOW_PATH)
+        .retry_policy(
+            GooseRetryPolicy::new(MAX_ATTEMPTS)
+                .set_backoff(Duration::from_secs(60), Duration::from_secs(60)),
+        )
+        .build();
+    let _goose = user.request(goose_request).await?;
+    Ok(())
+}
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up LIMITED_PATH, store in vector at LIMITED_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(LIMITED_PATH);
+            then.status(429).header("Retry-After", "0");
+        }),
+        // Next set up ERROR_PATH, store in vector at ERROR_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(ERROR_PATH);
+            then.status(500);
+        }),
+        // Next set up SLOW_PATH, store in vector at SLOW_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(SLOW_PATH);
+            then.status(503).header("Retry-After", "60");
+        }),
+    ]
+}
+
+#[tokio::test]
+// Retry failed requests, recording each attempt.
+async fn test_retry() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Build configuration.
+    let configuration = common::build_configuration(&server, vec!["--no-reset-metrics"]);
+
+    // Run the Goose Attack.
+    let goose_metrics = common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![scenario!("LoadTest")
+                .set_retry_policy(
+                    GooseRetryPolicy::new(MAX_ATTEMPTS)
+                        .set_backoff(Duration::from_millis(10), Duration::from_millis(50)),
+                )
+                .register_transaction(transaction!(get_limited))
+                .register_transaction(transaction!(get_error))],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    // Every attempt was made and recorded as failed. The error request is only retried
+    // because its retry policy overrides the scenario retry policy.
+    for (key, index) in [("GET /limited", LIMITED_KEY), ("GET /error", ERROR_KEY)] {
+        let request = goose_metrics.requests.get(key).unwrap();
+        let hits = mock_endpoints[index].hits();
+        assert!(hits >= MAX_ATTEMPTS);
+        assert_eq!(request.fail_count, hits);
+        assert_eq!(request.success_count, 0);
+        // All but the final attempt at each request was retried.
+        assert_eq!(hits % MAX_ATTEMPTS, 0);
+        assert_eq!(
+            request.retry_count,
+            hits / MAX_ATTEMPTS * (MAX_ATTEMPTS - 1)
+        );
+    }
+    // Retries are displayed for the retried requests.
+    let output = goose_metrics.to_string();
+    assert!(output.contains("PER REQUEST RETRIES"));
+}
+
+#[tokio::test]
+// Requests aren't retried if their retry policy doesn't allow it.
+async fn test_no_retry() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Build configuration.
+    let configuration = common::build_configuration(&server, vec!["--no-reset-metrics"]);
+
+    // Run the Goose Attack, without a retry policy.
+    let goose_metrics = common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![scenario!("LoadTest").register_transaction(transaction!(get_limited))],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    let request = goose_metrics.requests.get("GET /limited").unwrap();
+    assert_eq!(request.fail_count, mock_endpoints[LIMITED_KEY].hits());
+    assert_eq!(request.retry_count, 0);
+    let output = goose_metrics.to_string();
+    assert!(!output.contains("PER REQUEST RETRIES"));
+}
+
+#[tokio::test]
+// A backoff doesn't keep the load test running after it should shut down.
+async fn test_retry_shutdown() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Build configuration, running for less time than the backoff.
+    let configuration =
+        common::build_configuration(&server, vec!["--no-reset-metrics", "--run-time", "1"]);
+
+    // Run the Goose Attack.
+    let started = Instant::now();
+    let goose_metrics = common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![scenario!("LoadTest").register_transaction(transaction!(get_slow))],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    // The load test shut down without waiting for the backoff to finish, and without
+    // retrying the failed attempt.
+    assert!(started.elapsed() < Duration::from_secs(10));
+    let request = goose_metrics.requests.get("GET /slow").unwrap();
+    assert_eq!(mock_endpoints[SLOW_KEY].hits(), 1);
+    assert_eq!(request.fail_count, 1);
+    assert_eq!(request.retry_count, 0);
+}
@@ -0,0 +1,185 @@
+use httpmock::{
+    Method::{GET, POST},
+    Mock, MockServer,
+};
+
+mod common;
+
+use goose::prelude::*;
+use goose::scenario_file::ScenarioFile;
+
+// Paths used in load tests performed during these tests.
+const INDEX_PATH: &str = "/";
+const LOGIN_PATH: &str = "/login";
+const ABOUT_PATH: &str = "/about";
+
+// Indexes to the above paths.
+const LOGIN_FORM_KEY: usize = 0;
+const LOGIN_KEY: usize = 1;
+const INDEX_KEY: usize = 2;
+const ABOUT_KEY: usize = 3;
+
+// A scenario file exercising requests, weights, sequences, on_start, wait times,
+// assertions and extracted variables.
+const SCENARIO_YAML: &str = r#"
+scenarios:
+  - name: LoadTest
+    wait_time: { min: 0, max: 10 }
+    transactions:
+      - name: log in
+        on_start: true
+        requests:
+          - path: /login
+            extract:
+              - name: token
+                regex: 'name="token" value="([^"]+)"'
+              - name: session
+                header: x-session
+          - method: post
+            path: /login
+            headers:
+              X-Session: "{{session}}"
+            body: "name=foo&token={{token}}"
+            assert:
+              - contains: Welcome
+      - name: index
+        weight: 3
+        sequence: 1
+        requests:
+          - path: /
+            name: front page
+            assert:
+              - not_contains: Error
+              - header: x-session
+      - name: about
+        sequence: 2
+        requests:
+          - path: /about
+            assert:
+              - contains: no such text
+"#;
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up LOGIN_PATH form, store in vector at LOGIN_FORM_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(LOGIN_PATH);
+            then.status(200)
+                .header("x-session", "abc")
+                .body(r#"<input name="token" value="12345">"#);
+        }),
+        // Next set up LOGIN_PATH, store in vector at LOGIN_KEY. Only matches if the
+        // extracted variables are sent.
+        server.mock(|when, then| {
+            when.method(POST)
+                .path(LOGIN_PATH)
+                .header("x-session", "abc")
+                .body("name=foo&token=12345");
+            then.status(200).body("Welcome foo");
+        }),
+        // Next set up INDEX_PATH, store in vector at INDEX_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(INDEX_PATH);
+            then.status(200)
+                .header("x-session", "abc")
+                .body("Front page");
+        }),
+        // Last set up ABOUT_PATH, store in vector at ABOUT_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(ABOUT_PATH);
+            then.status(200).body("About page");
+        }),
+    ]
+}
+
+#[test]
+// Confirm the scenario file is translated into the expected scenarios and transactions.
+fn test_scenario_file_build() {
+    let scenarios = ScenarioFile::from_yaml(SCENARIO_YAML)
+        .unwrap()
+        .scenarios()
+        .unwrap();
+    assert_eq!(scenarios.len(), 1);
+    let scenario = &scenarios[0];
+    assert_eq!(scenario.name, "LoadTest");
+    assert!(scenario.transaction_wait.is_some());
+    assert_eq!(scenario.transactions.len(), 3);
+    assert_eq!(scenario.transactions[0].name, "log in");
+    assert!(scenario.transactions[0].on_start);
+    assert_eq!(scenario.transactions[1].weight, 3);
+    assert_eq!(scenario.transactions[1].sequence, 1);
+    assert_eq!(scenario.transactions[2].sequence, 2);
+
+    // JSON scenario files are also supported.
+    let json = r#"{"scenarios": [{"name": "LoadTest", "weight": 2, "transactions": [
+        {"requests": [{"path": "/"}]}
+    ]}]}"#;
+    let scenarios = ScenarioFile::from_json(json).unwrap().scenarios().unwrap();
+    assert_eq!(scenarios[0].weight, 2);
+
+    // Invalid scenario files return an error.
+    let invalid = vec![
+        // Unknown field.
+        r#"{"scenarios": [{"name": "a", "foo": 1, "transactions": []}]}"#,
+        // No transactions.
+        r#"{"scenarios": [{"name": "a", "transactions": []}]}"#,
+        // Unsupported method.
+        r#"{"scenarios": [{"name": "a", "transactions": [{"requests": [{"method": "TRACE", "path": "/"}]}]}]}"#,
+        // Invalid regex.
+        r#"{"scenarios": [{"name": "a", "transactions": [{"requests": [{"path": "/", "extract": [{"name": "a", "regex": "("}]}]}]}]}"#,
+        // Both body and json.
+        r#"{"scenarios": [{"name": "a", "transactions": [{"requests": [{"path": "/", "body": "a", "json": {}}]}]}]}"#,
+        // Invalid wait time.
+        r#"{"scenarios": [{"name": "a", "wait_time": {"min": 2, "max": 1}, "transactions": [{"requests": [{"path": "/"}]}]}]}"#,
+    ];
+    for json in invalid {
+        let result = ScenarioFile::from_json(json).and_then(|f| f.scenarios());
+        assert!(result.is_err(), "expected error for {}", json);
+    }
+}
+
+#[tokio::test]
+// Load test built from a scenario file, confirming variables are extracted and
+// assertions are enforced.
+async fn test_scenario_file() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Write the scenario file to disk.
+    let file_name = "scenario-file-test.yaml";
+    std::fs::write(file_name, SCENARIO_YAML).unwrap();
+
+    // Build configuration.
+    let configuration = common::build_configuration(&server, vec!["--no-reset-metrics"]);
+
+    // Run the Goose Attack.
+    let goose_attack = GooseAttack::initialize_with_config(configuration)
+        .unwrap()
+        .register_scenarios_from_file(file_name)
+        .unwrap();
+    let goose_metrics = common::run_load_test(goose_attack, None).await;
+
+    // Confirm the user logged in with the extracted variables.
+    assert!(mock_endpoints[LOGIN_FORM_KEY].hits() == 1);
+    assert!(mock_endpoints[LOGIN_KEY].hits() == 1);
+    assert!(mock_endpoints[INDEX_KEY].hits() > 0);
+    assert!(mock_endpoints[ABOUT_KEY].hits() > 0);
+
+    // The named request passes its assertions.
+    let index_metrics = goose_metrics.requests.get("GET front page").unwrap();
+    assert!(index_metrics.success_count == mock_endpoints[INDEX_KEY].hits());
+    assert!(index_metrics.fail_count == 0);
+
+    // Every request to the about page fails its assertion. Unnamed requests are named
+    // after their transaction.
+    let about_metrics = goose_metrics.requests.get("GET about").unwrap();
+    assert!(about_metrics.success_count == 0);
+    assert!(about_metrics.fail_count == mock_endpoints[ABOUT_KEY].hits());
+
+    // Cleanup from test.
+    common::cleanup_files(vec![file_name]);
+}
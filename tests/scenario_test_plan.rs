@@ -0,0 +1,207 @@
+use httpmock::{Method::GET, Mock, MockServer};
+
+mod common;
+
+use goose::prelude::*;
+
+// Paths used in load tests performed during these tests.
+const INDEX_PATH: &str = "/";
+const ADMIN_PATH: &str = "/admin";
+
+// Indexes to the above paths.
+const INDEX_KEY: usize = 0;
+const ADMIN_KEY: usize = 1;
+
+// Load test configuration.
+const ADMIN_USERS: usize = 3;
+
+// Test transaction.
+pub async fn get_index(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+// Test transaction.
+pub async fn get_admin(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(ADMIN_PATH).await?;
+    Ok(())
+}
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up INDEX_PATH, store in vector at INDEX_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(INDEX_PATH);
+            then.status(200);
+        }),
+        // Next set up ADMIN_PATH, store in vector at ADMIN_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(ADMIN_PATH);
+            then.status(200);
+        }),
+    ]
+}
+
+#[tokio::test]
+// Run one scenario with its own test plan alongside the load test's test plan.
+async fn test_scenario_test_plan() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    let report_file = std::env::temp_dir()
+        .join(format!(
+            "goose-{}-scenario-test-plan.html",
+            std::process::id()
+        ))
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    // Build configuration.
+    let configuration = common::build_configuration(
+        &server,
+        vec![
+            "--users",
+            "2",
+            "--hatch-rate",
+            "4",
+            "--run-time",
+            "2",
+            "--no-reset-metrics",
+            "--report-file",
+            &report_file,
+        ],
+    );
+
+    // Run the Goose Attack.
+    let goose_metrics = common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![
+                scenario!("Index").register_transaction(transaction!(get_index)),
+                scenario!("Admin")
+                    // Admin users aren't allocated by weight.
+                    .set_weight(10)
+                    .unwrap()
+                    .set_test_plan(&format!("{},0s", ADMIN_USERS))
+                    .unwrap()
+                    .register_transaction(transaction!(get_admin)),
+            ],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    assert!(mock_endpoints[INDEX_KEY].hits() > 0);
+    assert!(mock_endpoints[ADMIN_KEY].hits() > 0);
+
+    // Each scenario ran the number of users configured by its test plan.
+    assert_eq!(goose_metrics.scenarios[INDEX_KEY].users.len(), 2);
+    assert_eq!(goose_metrics.scenarios[ADMIN_KEY].users.len(), ADMIN_USERS);
+    assert_eq!(goose_metrics.maximum_users, 2 + ADMIN_USERS);
+
+    // The active users graph is broken down by scenario.
+    let report = std::fs::read_to_string(&report_file).unwrap();
+    assert!(report.contains("name: 'Index'"));
+    assert!(report.contains("name: 'Admin'"));
+
+    std::fs::remove_file(report_file).unwrap();
+}
+
+#[tokio::test]
+// All scenarios can have their own test plans.
+async fn test_all_scenario_test_plans() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Build configuration.
+    let configuration =
+        common::build_configuration(&server, vec!["--run-time", "2", "--no-reset-metrics"]);
+
+    // Run the Goose Attack.
+    let goose_metrics = common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![
+                scenario!("Index")
+                    .set_test_plan("1,0s")
+                    .unwrap()
+                    .register_transaction(transaction!(get_index)),
+                scenario!("Admin")
+                    .set_test_plan(&format!("{},1s", ADMIN_USERS))
+                    .unwrap()
+                    .register_transaction(transaction!(get_admin)),
+            ],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    assert!(mock_endpoints[INDEX_KEY].hits() > 0);
+    assert!(mock_endpoints[ADMIN_KEY].hits() > 0);
+    assert_eq!(goose_metrics.scenarios[INDEX_KEY].users.len(), 1);
+    assert_eq!(goose_metrics.scenarios[ADMIN_KEY].users.len(), ADMIN_USERS);
+    assert_eq!(goose_metrics.maximum_users, 1 + ADMIN_USERS);
+}
+
+#[tokio::test]
+// Scenario test plans keep running from where they were when metrics are reset.
+async fn test_scenario_test_plan_metrics_reset() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Build configuration, resetting metrics once all users started after 2 seconds.
+    let configuration = common::build_configuration(
+        &server,
+        vec!["--users", "3", "--hatch-rate", "1", "--run-time", "2"],
+    );
+
+    // Run the Goose Attack.
+    let goose_metrics = common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![
+                scenario!("Index").register_transaction(transaction!(get_index)),
+                scenario!("Admin")
+                    // Admin users stop after 1 second, before metrics are reset.
+                    .set_test_plan(&format!("{},0s;{},1s;0,0s", ADMIN_USERS, ADMIN_USERS))
+                    .unwrap()
+                    .register_transaction(transaction!(get_admin)),
+            ],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    assert!(mock_endpoints[INDEX_KEY].hits() > 0);
+    assert!(mock_endpoints[ADMIN_KEY].hits() > 0);
+
+    // Admin users didn't start again when metrics were reset.
+    assert!(goose_metrics.requests.get("GET /").is_some());
+    assert!(goose_metrics.requests.get("GET /admin").is_none());
+}
+
+#[test]
+// Scenario test plans must be valid.
+fn test_invalid_scenario_test_plan() {
+    assert!(scenario!("Index")
+        .register_transaction(transaction!(get_index))
+        .set_test_plan("not a test plan")
+        .is_err());
+}
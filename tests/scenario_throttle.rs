@@ -0,0 +1,180 @@
+use httpmock::{Method::GET, Mock, MockServer};
+
+mod common;
+
+use goose::prelude::*;
+
+// Paths used in load tests performed during these tests.
+const INDEX_PATH: &str = "/";
+const ADMIN_PATH: &str = "/admin";
+
+// Indexes to the above paths.
+const INDEX_KEY: usize = 0;
+const ADMIN_KEY: usize = 1;
+
+// Load test configuration.
+const THROTTLE_REQUESTS: usize = 2;
+const RUN_TIME: usize = 2;
+
+// Test transaction.
+pub async fn get_index(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+// Test transaction.
+pub async fn get_admin(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(ADMIN_PATH).await?;
+    Ok(())
+}
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up INDEX_PATH, store in vector at INDEX_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(INDEX_PATH);
+            then.status(200);
+        }),
+        // Next set up ADMIN_PATH, store in vector at ADMIN_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(ADMIN_PATH);
+            then.status(200);
+        }),
+    ]
+}
+
+// The most requests a throttle allows during the load test.
+fn maximum_throttled_requests() -> usize {
+    // Requests are made while GooseUsers are hatched, and then for RUN_TIME seconds.
+    (RUN_TIME + 1) * THROTTLE_REQUESTS + 1
+}
+
+#[tokio::test]
+// Throttle requests made by one scenario without throttling other scenarios.
+async fn test_scenario_throttle() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Build configuration.
+    let configuration = common::build_configuration(
+        &server,
+        vec![
+            "--users",
+            "2",
+            "--hatch-rate",
+            "2",
+            "--run-time",
+            &RUN_TIME.to_string(),
+        ],
+    );
+
+    // Run the Goose Attack.
+    common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![
+                scenario!("Index").register_transaction(transaction!(get_index)),
+                scenario!("Admin")
+                    .set_throttle_requests(THROTTLE_REQUESTS)
+                    .unwrap()
+                    .register_transaction(transaction!(get_admin)),
+            ],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    // Only the admin scenario was throttled.
+    let admin_hits = mock_endpoints[ADMIN_KEY].hits();
+    assert!(admin_hits > 0);
+    assert!(admin_hits <= maximum_throttled_requests());
+    assert!(mock_endpoints[INDEX_KEY].hits() > maximum_throttled_requests() * 2);
+}
+
+#[tokio::test]
+// Throttle requests made to one host.
+async fn test_host_throttle() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Build configuration.
+    let configuration =
+        common::build_configuration(&server, vec!["--run-time", &RUN_TIME.to_string()]);
+
+    // Run the Goose Attack.
+    common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![scenario!("Index").register_transaction(transaction!(get_index))],
+            None,
+            None,
+        )
+        .set_host_throttle(&server.base_url(), THROTTLE_REQUESTS)
+        .unwrap(),
+        None,
+    )
+    .await;
+
+    let index_hits = mock_endpoints[INDEX_KEY].hits();
+    assert!(index_hits > 0);
+    assert!(index_hits <= maximum_throttled_requests());
+}
+
+#[tokio::test]
+// Requests to other hosts aren't throttled.
+async fn test_other_host_throttle() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Build configuration.
+    let configuration =
+        common::build_configuration(&server, vec!["--run-time", &RUN_TIME.to_string()]);
+
+    // Run the Goose Attack.
+    common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![scenario!("Index").register_transaction(transaction!(get_index))],
+            None,
+            None,
+        )
+        .set_host_throttle("http://other.example.com/", THROTTLE_REQUESTS)
+        .unwrap(),
+        None,
+    )
+    .await;
+
+    assert!(mock_endpoints[INDEX_KEY].hits() > maximum_throttled_requests() * 2);
+}
+
+#[test]
+// Throttles must be valid.
+fn test_invalid_throttle() {
+    assert!(scenario!("Index")
+        .register_transaction(transaction!(get_index))
+        .set_throttle_requests(0)
+        .is_err());
+
+    let server = MockServer::start();
+    let goose_attack = common::build_load_test(
+        common::build_configuration(&server, vec![]),
+        vec![scenario!("Index").register_transaction(transaction!(get_index))],
+        None,
+        None,
+    );
+    assert!(goose_attack
+        .set_host_throttle("not a host", THROTTLE_REQUESTS)
+        .is_err());
+}
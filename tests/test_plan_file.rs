@@ -0,0 +1,142 @@
+use gumdrop::Options;
+use httpmock::{Method::GET, Mock, MockServer};
+
+mod common;
+
+use goose::config::GooseConfiguration;
+use goose::prelude::*;
+
+// Paths used in load tests performed during these tests.
+const INDEX_PATH: &str = "/";
+
+// Indexes to the above paths.
+const INDEX_KEY: usize = 0;
+
+// A test plan with named stages.
+const TEST_PLAN: &str = r#"
+stages:
+  - name: warmup
+    users: 2
+    duration: 1s
+    hold: 1s
+  - name: shutdown
+    users: 0
+"#;
+
+// Test transaction.
+pub async fn get_index(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up INDEX_PATH, store in vector at INDEX_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(INDEX_PATH);
+            then.status(200);
+        }),
+    ]
+}
+
+// Write a file to the temporary directory, returning its path.
+fn write_file(name: &str, contents: &str) -> String {
+    let path = std::env::temp_dir().join(format!("goose-{}-{}", std::process::id(), name));
+    std::fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[tokio::test]
+// Load a test plan with named stages from a file, and display the stage names.
+async fn test_test_plan_file() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    let test_plan_file = write_file("test-plan.yaml", TEST_PLAN);
+    let report_file = write_file("test-plan-report.html", "");
+
+    // Build configuration, unsetting the options that can't be set with a test plan.
+    let mut configuration = common::build_configuration(
+        &server,
+        vec![
+            "--test-plan-file",
+            &test_plan_file,
+            "--report-file",
+            &report_file,
+        ],
+    );
+    configuration.run_time = "".to_string();
+    configuration.users = None;
+    configuration.hatch_rate = None;
+
+    // Run the Goose Attack.
+    let goose_metrics = common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![scenario!("LoadTest").register_transaction(transaction!(get_index))],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    assert!(mock_endpoints[INDEX_KEY].hits() > 0);
+
+    // The stage names are recorded in the test plan history.
+    let stages: Vec<Option<&str>> = goose_metrics
+        .history
+        .iter()
+        .map(|step| step.stage.as_deref())
+        .collect();
+    assert_eq!(
+        stages,
+        vec![Some("warmup"), Some("warmup"), Some("shutdown"), None]
+    );
+
+    // The stage names are displayed in the metrics and the report.
+    assert!(goose_metrics.to_string().contains("warmup (Increasing):"));
+    let report = std::fs::read_to_string(&report_file).unwrap();
+    assert!(report.contains("<td>warmup (Increasing)</td>"));
+    assert!(report.contains("name: \"warmup\","));
+
+    std::fs::remove_file(test_plan_file).unwrap();
+    std::fs::remove_file(report_file).unwrap();
+}
+
+#[tokio::test]
+// Invalid test plan files are rejected.
+async fn test_invalid_test_plan_file() {
+    let server = MockServer::start();
+
+    // The test plan file must exist.
+    let configuration = GooseConfiguration::parse_args_default(&[
+        "--host",
+        &server.base_url(),
+        "--test-plan-file",
+        "/does/not/exist.yaml",
+    ]);
+    assert!(configuration.is_err());
+
+    // The test plan file can't be combined with a test plan.
+    let test_plan_file = write_file("invalid-test-plan.yaml", TEST_PLAN);
+    let mut configuration = common::build_configuration(
+        &server,
+        vec!["--test-plan-file", &test_plan_file, "--test-plan", "1,1s"],
+    );
+    configuration.run_time = "".to_string();
+    configuration.users = None;
+    configuration.hatch_rate = None;
+    let goose_attack = common::build_load_test(
+        configuration,
+        vec![scenario!("LoadTest").register_transaction(transaction!(get_index))],
+        None,
+        None,
+    );
+    assert!(goose_attack.execute().await.is_err());
+    std::fs::remove_file(test_plan_file).unwrap();
+}
@@ -0,0 +1,239 @@
+use httpmock::{Method::GET, Mock, MockServer};
+use std::time::Duration;
+
+mod common;
+
+use goose::prelude::*;
+use goose::AttackPhase;
+
+// Paths used in load tests performed during these tests.
+const INDEX_PATH: &str = "/";
+
+// Indexes to the above paths.
+const INDEX_KEY: usize = 0;
+
+// Load test configuration.
+const THROTTLE_REQUESTS: usize = 5;
+const THROTTLE_BURST: usize = 5;
+const RUN_TIME: usize = 2;
+
+// The WebSocket Controller listens on a port not used by any other test.
+const WEBSOCKET_PORT: &str = "5432";
+
+// Test transaction.
+pub async fn get_index(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up INDEX_PATH, store in vector at INDEX_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(INDEX_PATH);
+            then.status(200);
+        }),
+    ]
+}
+
+#[tokio::test]
+// Throttle requests with a burst, and display the throttle rate in the metrics.
+async fn test_throttle_burst() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Build configuration.
+    let configuration = common::build_configuration(
+        &server,
+        vec![
+            "--no-reset-metrics",
+            "--run-time",
+            &RUN_TIME.to_string(),
+            "--throttle-requests",
+            &THROTTLE_REQUESTS.to_string(),
+            "--throttle-burst",
+            &THROTTLE_BURST.to_string(),
+        ],
+    );
+
+    // Run the Goose Attack.
+    let goose_metrics = common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![scenario!("LoadTest").register_transaction(transaction!(get_index))],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    // Requests are limited by the throttle, plus at most one burst.
+    let hits = mock_endpoints[INDEX_KEY].hits();
+    assert!(hits > 0);
+    assert!(hits <= (RUN_TIME + 1) * THROTTLE_REQUESTS + THROTTLE_BURST);
+
+    // The throttle rate is displayed in the metrics.
+    assert_eq!(goose_metrics.throttle_requests, THROTTLE_REQUESTS);
+    let output = goose_metrics.to_string();
+    assert!(output.contains("=== THROTTLE ==="));
+}
+
+#[tokio::test]
+// The throttle isn't displayed when it's not enabled.
+async fn test_no_throttle() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let _mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Build configuration.
+    let configuration = common::build_configuration(&server, vec!["--no-reset-metrics"]);
+
+    // Run the Goose Attack.
+    let goose_metrics = common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![scenario!("LoadTest").register_transaction(transaction!(get_index))],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    assert_eq!(goose_metrics.throttle_requests, 0);
+    let output = goose_metrics.to_string();
+    assert!(!output.contains("=== THROTTLE ==="));
+}
+
+#[tokio::test]
+// Enable the throttle from a controller script, without any network Controllers.
+async fn test_script_throttle() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    let script_file =
+        std::env::temp_dir().join(format!("goose-{}-throttle-script.txt", std::process::id()));
+    std::fs::write(
+        &script_file,
+        format!(
+            "at 0s throttle {}\nat {}s shutdown\n",
+            THROTTLE_REQUESTS,
+            RUN_TIME * 2
+        ),
+    )
+    .unwrap();
+
+    // Disable all network Controllers, the script enables the throttle.
+    let configuration = common::build_configuration(
+        &server,
+        vec![
+            "--no-telnet",
+            "--no-websocket",
+            "--no-http",
+            "--no-reset-metrics",
+            "--run-time",
+            "0",
+            "--controller-script",
+            script_file.to_str().unwrap(),
+        ],
+    );
+
+    let goose_attack = common::build_load_test(
+        configuration,
+        vec![scenario!("LoadTest").register_transaction(transaction!(get_index))],
+        None,
+        None,
+    );
+    let load_test = tokio::spawn(common::run_load_test(goose_attack, None));
+
+    // Requests aren't throttled until the script runs, so only count later requests.
+    tokio::time::sleep(Duration::from_secs(RUN_TIME as u64)).await;
+    let counted_from = mock_endpoints[INDEX_KEY].hits();
+    let goose_metrics = load_test.await.unwrap();
+
+    // Requests are limited by the throttle.
+    let hits = mock_endpoints[INDEX_KEY].hits() - counted_from;
+    assert!(hits > 0);
+    assert!(
+        hits <= (RUN_TIME + 1) * THROTTLE_REQUESTS + 1,
+        "{} hits",
+        hits
+    );
+    assert_eq!(goose_metrics.throttle_requests, THROTTLE_REQUESTS);
+
+    std::fs::remove_file(script_file).unwrap();
+}
+
+#[tokio::test]
+// Throttled users keep making requests after the load test is stopped and started again.
+async fn test_throttle_restart() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Only enable the WebSocket Controller, and wait for it to start the load test.
+    let mut configuration = common::build_configuration(
+        &server,
+        vec![
+            "--no-telnet",
+            "--no-http",
+            "--websocket-host",
+            "127.0.0.1",
+            "--websocket-port",
+            WEBSOCKET_PORT,
+            "--no-autostart",
+            "--hatch-rate",
+            "10",
+            "--throttle-requests",
+            &THROTTLE_REQUESTS.to_string(),
+        ],
+    );
+    configuration.run_time = "".to_string();
+
+    let goose_attack = common::build_load_test(
+        configuration,
+        vec![scenario!("LoadTest").register_transaction(transaction!(get_index))],
+        None,
+        None,
+    );
+    let load_test = tokio::spawn(common::run_load_test(goose_attack, None));
+
+    let mut websocket = common::connect_websocket_controller(WEBSOCKET_PORT).await;
+    common::websocket_wait_for_phase(&mut websocket, AttackPhase::Idle).await;
+
+    // Start and stop the load test, closing the throttle.
+    let response = common::websocket_command(&mut websocket, "start", None).await;
+    assert_eq!(response["success"], true);
+    common::websocket_wait_for_phase(&mut websocket, AttackPhase::Maintain).await;
+    let response = common::websocket_command(&mut websocket, "stop", None).await;
+    assert_eq!(response["success"], true);
+    common::websocket_wait_for_phase(&mut websocket, AttackPhase::Idle).await;
+    let stopped_hits = mock_endpoints[INDEX_KEY].hits();
+
+    // Once started again, requests are throttled rather than canceled.
+    let response = common::websocket_command(&mut websocket, "start", None).await;
+    assert_eq!(response["success"], true);
+    common::websocket_wait_for_phase(&mut websocket, AttackPhase::Maintain).await;
+    let mut retries = 0;
+    while mock_endpoints[INDEX_KEY].hits() <= stopped_hits {
+        retries += 1;
+        assert!(retries < 50, "no requests made after restarting");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    let response = common::websocket_command(&mut websocket, "shutdown", None).await;
+    assert_eq!(response["success"], true);
+    load_test.await.unwrap();
+}
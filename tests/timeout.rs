@@ -0,0 +1,133 @@
+use httpmock::{Method::GET, Mock, MockServer};
+use std::time::Duration;
+
+mod common;
+
+use goose::prelude::*;
+
+// Paths used in load tests performed during these tests.
+const SLOW_PATH: &str = "/slow";
+
+// How long the mock server takes to respond.
+const SLOW_DELAY: Duration = Duration::from_secs(3);
+
+// How long to wait before timing out.
+const TIMEOUT: Duration = Duration::from_millis(200);
+
+// Test transaction.
+pub async fn get_slow(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(SLOW_PATH).await?;
+    Ok(())
+}
+
+// Test transaction, timing out the request.
+pub async fn get_slow_request_timeout(user: &mut GooseUser) -> TransactionResult {
+    let goose_request = GooseRequest::builder()
+        .path(SLOW_PATH)
+        .timeout(TIMEOUT)
+        .build();
+    let _goose = user.request(goose_request).await?;
+    Ok(())
+}
+
+// All tests in this file run against a common endpoint.
+fn setup_mock_server_endpoint(server: &MockServer) -> Mock {
+    server.mock(|when, then| {
+        when.method(GET).path(SLOW_PATH);
+        then.status(200).delay(SLOW_DELAY);
+    })
+}
+
+#[tokio::test]
+// Cancel transactions that run longer than their timeout.
+async fn test_transaction_timeout() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoint needed for this test.
+    let slow = setup_mock_server_endpoint(&server);
+
+    // Build configuration.
+    let configuration = common::build_configuration(&server, vec!["--no-reset-metrics"]);
+
+    // Run the Goose Attack.
+    let goose_metrics = common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![scenario!("LoadTest")
+                .register_transaction(transaction!(get_slow).set_timeout(TIMEOUT))],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    // The user didn't block on the slow endpoint, so made multiple attempts.
+    assert!(slow.hits() > 1, "{} hits", slow.hits());
+
+    // Every transaction was canceled and recorded as failed.
+    let transaction = &goose_metrics.transactions[0][0];
+    assert!(transaction.counter > 1);
+    assert_eq!(transaction.success_count, 0);
+    assert_eq!(transaction.fail_count, transaction.counter);
+
+    // The canceled requests were recorded as failed with a timeout error.
+    let request = goose_metrics.requests.get("GET /slow").unwrap();
+    assert_eq!(request.success_count, 0);
+    assert!(request.fail_count > 0);
+    assert_eq!(goose_metrics.errors.len(), 1);
+    let error = goose_metrics.errors.values().next().unwrap();
+    assert!(error.error.contains("timeout"), "{}", error.error);
+    assert_eq!(error.occurrences, request.fail_count);
+}
+
+#[tokio::test]
+// Cancel requests that run longer than their timeout, recording a timeout error.
+async fn test_request_timeout() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoint needed for this test.
+    let slow = setup_mock_server_endpoint(&server);
+
+    // Build configuration.
+    let configuration = common::build_configuration(&server, vec!["--no-reset-metrics"]);
+
+    // Run the Goose Attack.
+    let goose_metrics =
+        common::run_load_test(
+            common::build_load_test(
+                configuration,
+                vec![scenario!("LoadTest")
+                    .register_transaction(transaction!(get_slow_request_timeout))],
+                None,
+                None,
+            ),
+            None,
+        )
+        .await;
+
+    // The user didn't block on the slow endpoint, so made multiple attempts.
+    assert!(slow.hits() > 1, "{} hits", slow.hits());
+
+    // Every request failed with a timeout error.
+    let request = goose_metrics.requests.get("GET /slow").unwrap();
+    assert_eq!(request.success_count, 0);
+    assert!(request.fail_count > 1);
+    assert_eq!(goose_metrics.errors.len(), 1);
+    let error = goose_metrics.errors.values().next().unwrap();
+    assert!(error.error.contains("timeout"), "{}", error.error);
+    assert_eq!(error.occurrences, request.fail_count);
+}
+
+#[test]
+// Transaction wait times must be valid.
+fn test_transaction_wait_time() {
+    assert!(transaction!(get_slow)
+        .set_wait_time(Duration::from_secs(1), Duration::from_secs(2))
+        .is_ok());
+    assert!(transaction!(get_slow)
+        .set_wait_time(Duration::from_secs(2), Duration::from_secs(1))
+        .is_err());
+}
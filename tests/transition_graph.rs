@@ -0,0 +1,190 @@
+use httpmock::{Method::GET, Mock, MockServer};
+
+mod common;
+
+use goose::goose::TransitionGraph;
+use goose::prelude::*;
+
+// Paths used in load tests performed during these tests.
+const SEARCH_PATH: &str = "/search";
+const PRODUCT_PATH: &str = "/product";
+const CART_PATH: &str = "/cart";
+
+// Indexes to the above paths.
+const SEARCH_KEY: usize = 0;
+const PRODUCT_KEY: usize = 1;
+const CART_KEY: usize = 2;
+
+// Load test configuration.
+const USERS: usize = 2;
+const ITERATIONS: usize = 10;
+
+// Test transaction.
+pub async fn search(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(SEARCH_PATH).await?;
+    Ok(())
+}
+
+// Test transaction.
+pub async fn product(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(PRODUCT_PATH).await?;
+    Ok(())
+}
+
+// Test transaction.
+pub async fn cart(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(CART_PATH).await?;
+    Ok(())
+}
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up SEARCH_PATH, store in vector at SEARCH_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(SEARCH_PATH);
+            then.status(200);
+        }),
+        // Next set up PRODUCT_PATH, store in vector at PRODUCT_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(PRODUCT_PATH);
+            then.status(200);
+        }),
+        // Last set up CART_PATH, store in vector at CART_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(CART_PATH);
+            then.status(200);
+        }),
+    ]
+}
+
+// Build a scenario with three named transactions.
+fn build_scenario() -> Scenario<GooseUser> {
+    scenario!("Shopper")
+        .register_transaction(transaction!(search).set_name("search"))
+        .register_transaction(transaction!(product).set_name("product"))
+        .register_transaction(transaction!(cart).set_name("cart"))
+}
+
+#[test]
+// Confirm invalid transition graphs are rejected.
+fn test_invalid_transition_graph() {
+    // The entry transaction must exist.
+    assert!(build_scenario()
+        .set_transition_graph(TransitionGraph::new("checkout"))
+        .is_err());
+
+    // All transactions in transitions must exist.
+    assert!(build_scenario()
+        .set_transition_graph(
+            TransitionGraph::new("search").add_transition("search", "checkout", 1.0)
+        )
+        .is_err());
+
+    // Weights can not be negative.
+    assert!(build_scenario()
+        .set_transition_graph(TransitionGraph::new("search").set_exit_weight("search", -1.0))
+        .is_err());
+
+    // Transaction names must be unique.
+    assert!(build_scenario()
+        .register_transaction(transaction!(search).set_name("search"))
+        .set_transition_graph(TransitionGraph::new("search"))
+        .is_err());
+}
+
+#[test]
+// Confirm transition graphs that never end the iteration are rejected.
+fn test_endless_transition_graph() {
+    // Transactions that only transition to each other never end the iteration.
+    assert!(build_scenario()
+        .set_transition_graph(
+            TransitionGraph::new("search")
+                .add_transition("search", "product", 1.0)
+                .add_transition("product", "search", 1.0)
+        )
+        .is_err());
+
+    // A zero weight exit never ends the iteration either.
+    assert!(build_scenario()
+        .set_transition_graph(
+            TransitionGraph::new("search")
+                .add_transition("search", "search", 1.0)
+                .set_exit_weight("search", 0.0)
+        )
+        .is_err());
+
+    // Loops are allowed as long as the iteration can eventually end.
+    assert!(build_scenario()
+        .set_transition_graph(
+            TransitionGraph::new("search")
+                .add_transition("search", "product", 1.0)
+                .add_transition("product", "search", 1.0)
+                .add_transition("product", "cart", 1.0)
+        )
+        .is_ok());
+}
+
+#[tokio::test]
+// Load test walking a transition graph, confirming per-transition metrics.
+async fn test_transition_graph() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    // Each iteration always searches then views a product, then either searches again
+    // or continues to the cart. The cart has no transitions so always ends the iteration.
+    let scenario = build_scenario()
+        .set_transition_graph(
+            TransitionGraph::new("search")
+                .add_transition("search", "product", 1.0)
+                .add_transition("product", "search", 1.0)
+                .add_transition("product", "cart", 1.0),
+        )
+        .unwrap();
+
+    // Build configuration, running a fixed number of iterations.
+    let mut configuration = common::build_configuration(
+        &server,
+        vec![
+            "--users",
+            &USERS.to_string(),
+            "--hatch-rate",
+            &USERS.to_string(),
+            "--iterations",
+            &ITERATIONS.to_string(),
+        ],
+    );
+    // Do not set --run-time with --iterations.
+    configuration.run_time = "".to_string();
+
+    // Run the Goose Attack.
+    let goose_metrics = common::run_load_test(
+        common::build_load_test(configuration, vec![scenario], None, None),
+        None,
+    )
+    .await;
+
+    let search_hits = mock_endpoints[SEARCH_KEY].hits();
+    let product_hits = mock_endpoints[PRODUCT_KEY].hits();
+    let cart_hits = mock_endpoints[CART_KEY].hits();
+
+    // Every iteration ends on the cart.
+    assert_eq!(cart_hits, USERS * ITERATIONS);
+    // Every search is followed by a product page.
+    assert_eq!(search_hits, product_hits);
+    // Every product page is followed by a search or the cart.
+    assert!(product_hits >= cart_hits);
+
+    // Confirm every transition was counted.
+    let transitions = &goose_metrics.scenarios[0].transitions;
+    assert_eq!(transitions["1: search"]["2: product"], search_hits);
+    assert_eq!(
+        transitions["2: product"].get("1: search").unwrap_or(&0),
+        &(product_hits - cart_hits)
+    );
+    assert_eq!(transitions["2: product"]["3: cart"], cart_hits);
+    assert_eq!(transitions["3: cart"]["exit"], cart_hits);
+}
@@ -0,0 +1,92 @@
+use httpmock::{Method::GET, Mock, MockServer};
+use std::time::Duration;
+
+mod common;
+
+use goose::goose::GooseWaitTime;
+use goose::prelude::*;
+
+// Paths used in load tests performed during these tests.
+const INDEX_PATH: &str = "/";
+
+// Test transaction.
+pub async fn get_index(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+// All tests in this file run against a common endpoint.
+fn setup_mock_server_endpoint(server: &MockServer) -> Mock {
+    server.mock(|when, then| {
+        when.method(GET).path(INDEX_PATH);
+        then.status(200);
+    })
+}
+
+#[tokio::test]
+// Pace iterations to start at a fixed interval.
+async fn test_pacing() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoint needed for this test.
+    let index = setup_mock_server_endpoint(&server);
+
+    // Build configuration.
+    let configuration =
+        common::build_configuration(&server, vec!["--run-time", "2", "--no-reset-metrics"]);
+
+    // Run the Goose Attack, starting an iteration every 500 milliseconds.
+    common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![scenario!("LoadTest")
+                .register_transaction(transaction!(get_index))
+                .set_pacing(Duration::from_millis(500))],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    // The user runs for at least the 2 second run time, plus time spent launching and
+    // stopping. Without pacing the index would be loaded many more times.
+    assert!(index.hits() >= 4, "{} hits", index.hits());
+    assert!(index.hits() <= 10, "{} hits", index.hits());
+}
+
+#[tokio::test]
+// Override the scenario wait time on a transaction.
+async fn test_transaction_wait_time() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoint needed for this test.
+    let index = setup_mock_server_endpoint(&server);
+
+    // Build configuration.
+    let configuration = common::build_configuration(&server, vec!["--no-reset-metrics"]);
+
+    // Run the Goose Attack, with a scenario wait time longer than the load test.
+    common::run_load_test(
+        common::build_load_test(
+            configuration,
+            vec![scenario!("LoadTest")
+                .set_wait_time_distribution(GooseWaitTime::Constant(Duration::from_secs(10)))
+                .unwrap()
+                .register_transaction(
+                    transaction!(get_index)
+                        .set_wait_time_distribution(GooseWaitTime::Constant(Duration::from_secs(0)))
+                        .unwrap(),
+                )],
+            None,
+            None,
+        ),
+        None,
+    )
+    .await;
+
+    // The transaction doesn't wait, so runs many times.
+    assert!(index.hits() > 10, "{} hits", index.hits());
+}
@@ -0,0 +1,258 @@
+use futures::{SinkExt, StreamExt};
+use httpmock::{Method::GET, Mock, MockServer};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+mod common;
+
+use goose::controller::WEBSOCKET_PROTOCOL_VERSION;
+use goose::prelude::*;
+
+// Paths used in load tests performed during these tests.
+const INDEX_PATH: &str = "/";
+
+// Indexes to the above paths.
+const INDEX_KEY: usize = 0;
+
+// The WebSocket Controller listens on ports not used by any other test.
+const WEBSOCKET_PORT: &str = "5419";
+const SUBSCRIBE_WEBSOCKET_PORT: &str = "5420";
+
+type WebSocket =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+// Test transaction.
+pub async fn get_index(user: &mut GooseUser) -> TransactionResult {
+    let _goose = user.get(INDEX_PATH).await?;
+    Ok(())
+}
+
+// All tests in this file run against common endpoints.
+fn setup_mock_server_endpoints(server: &MockServer) -> Vec<Mock> {
+    vec![
+        // First set up INDEX_PATH, store in vector at INDEX_KEY.
+        server.mock(|when, then| {
+            when.method(GET).path(INDEX_PATH);
+            then.status(200);
+        }),
+    ]
+}
+
+// Read the next json message from the WebSocket Controller.
+async fn next_message(websocket: &mut WebSocket) -> Value {
+    match websocket.next().await {
+        Some(Ok(Message::Text(message))) => serde_json::from_str(&message).unwrap(),
+        other => panic!("unexpected message: {:?}", other),
+    }
+}
+
+// Send a json request to the WebSocket Controller and return the json response, ignoring
+// any pushed events.
+async fn request(websocket: &mut WebSocket, request: Value) -> Value {
+    websocket
+        .send(Message::Text(request.to_string()))
+        .await
+        .unwrap();
+    loop {
+        let message = next_message(websocket).await;
+        if message.get("event").is_none() {
+            return message;
+        }
+    }
+}
+
+// Start a load test controlled by the WebSocket Controller listening on the given port,
+// and connect to it.
+async fn start_load_test(
+    server: &MockServer,
+    port: &str,
+) -> (tokio::task::JoinHandle<GooseMetrics>, WebSocket) {
+    // Only enable the WebSocket Controller, and wait for it to start the load test.
+    let mut configuration = common::build_configuration(
+        server,
+        vec![
+            "--no-telnet",
+            "--no-http",
+            "--no-autostart",
+            "--websocket-host",
+            "127.0.0.1",
+            "--websocket-port",
+            port,
+            "--hatch-rate",
+            "10",
+        ],
+    );
+    configuration.users = None;
+    configuration.run_time = "".to_string();
+
+    let goose_attack = common::build_load_test(
+        configuration,
+        vec![scenario!("Index").register_transaction(transaction!(get_index))],
+        None,
+        None,
+    );
+    let load_test = tokio::spawn(common::run_load_test(goose_attack, None));
+
+    // Wait for the WebSocket Controller to start listening.
+    let url = format!("ws://127.0.0.1:{}", port);
+    let mut retries = 0;
+    let websocket = loop {
+        if let Ok((websocket, _)) = tokio_tungstenite::connect_async(&url).await {
+            break websocket;
+        }
+        retries += 1;
+        assert!(retries < 50, "WebSocket Controller never started");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    };
+
+    (load_test, websocket)
+}
+
+#[tokio::test]
+// Control a load test with typed WebSocket Controller requests.
+async fn test_websocket_typed_protocol() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    let (load_test, mut websocket) = start_load_test(&server, WEBSOCKET_PORT).await;
+
+    // The original protocol is still supported.
+    let response = request(&mut websocket, json!({"request": "stop"})).await;
+    assert_eq!(response["success"], false);
+    assert_eq!(
+        response["response"],
+        "load test not running, failed to stop"
+    );
+
+    // Typed responses return the request id and the load test status as data.
+    let response = request(
+        &mut websocket,
+        json!({"version": WEBSOCKET_PROTOCOL_VERSION, "id": 1, "command": "status"}),
+    )
+    .await;
+    assert_eq!(response["version"], WEBSOCKET_PROTOCOL_VERSION);
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["command"], "status");
+    assert_eq!(response["success"], true);
+    assert_eq!(response["data"]["status"]["phase"], "Idle");
+
+    // Invalid requests return an error code.
+    let response = request(&mut websocket, json!({"version": 1, "command": "status"})).await;
+    assert_eq!(response["error"]["code"], "unsupported_version");
+    let response = request(&mut websocket, json!({"id": "a", "command": "foo"})).await;
+    assert_eq!(response["id"], "a");
+    assert_eq!(response["success"], false);
+    assert_eq!(response["error"]["code"], "unrecognized_command");
+    let response = request(&mut websocket, json!({"command": "users", "value": "many"})).await;
+    assert_eq!(response["error"]["code"], "invalid_value");
+    let response = request(&mut websocket, json!({"command": "users"})).await;
+    assert_eq!(response["error"]["code"], "invalid_value");
+    let response = request(&mut websocket, json!({"command": "stop"})).await;
+    assert_eq!(response["error"]["code"], "command_failed");
+    assert_eq!(
+        response["error"]["message"],
+        "load test not running, failed to stop"
+    );
+
+    // Configure and start the load test.
+    let response = request(&mut websocket, json!({"command": "users", "value": 2})).await;
+    assert_eq!(response["success"], true);
+    assert_eq!(response["message"], "users configured");
+    assert_eq!(response["error"], Value::Null);
+    let response = request(&mut websocket, json!({"command": "start"})).await;
+    assert_eq!(response["message"], "load test started");
+
+    // Wait for all users to start.
+    let mut retries = 0;
+    loop {
+        let response = request(&mut websocket, json!({"command": "status"})).await;
+        if response["data"]["status"]["phase"] == "Maintain" {
+            assert_eq!(response["data"]["status"]["users"], 2);
+            break;
+        }
+        retries += 1;
+        assert!(retries < 50, "load test never started all users");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    // Configuration and metrics are returned as objects, not as strings.
+    let response = request(&mut websocket, json!({"command": "config"})).await;
+    assert_eq!(response["data"]["config"]["users"], 2);
+    let response = request(&mut websocket, json!({"command": "metrics-json"})).await;
+    assert_eq!(response["data"]["metrics"]["maximum_users"], 2);
+
+    // Shut down the load test.
+    let response = request(&mut websocket, json!({"id": 9, "command": "shutdown"})).await;
+    assert_eq!(response["id"], 9);
+    assert_eq!(response["message"], "load test shut down");
+
+    let goose_metrics = load_test.await.unwrap();
+    assert!(mock_endpoints[INDEX_KEY].hits() > 0);
+    assert_eq!(goose_metrics.maximum_users, 2);
+}
+
+#[tokio::test]
+// Push live metrics and phase changes to a subscribed WebSocket Controller client.
+async fn test_websocket_subscribe() {
+    // Start the mock server.
+    let server = MockServer::start();
+
+    // Setup the mock endpoints needed for this test.
+    let mock_endpoints = setup_mock_server_endpoints(&server);
+
+    let (load_test, mut websocket) = start_load_test(&server, SUBSCRIBE_WEBSOCKET_PORT).await;
+
+    // Clients must subscribe before they can unsubscribe.
+    let response = request(&mut websocket, json!({"command": "unsubscribe"})).await;
+    assert_eq!(response["error"]["code"], "command_failed");
+
+    // Subscribe with the original protocol, and start the load test.
+    let response = request(&mut websocket, json!({"request": "subscribe"})).await;
+    assert_eq!(response["response"], "subscribed to live metrics");
+    let response = request(&mut websocket, json!({"command": "users", "value": 2})).await;
+    assert_eq!(response["success"], true);
+    websocket
+        .send(Message::Text(json!({"command": "start"}).to_string()))
+        .await
+        .unwrap();
+
+    // Phase changes and metrics are pushed without being requested.
+    let mut phases = Vec::new();
+    let mut metrics = Vec::new();
+    while metrics.len() < 2 {
+        let message = tokio::time::timeout(Duration::from_secs(10), next_message(&mut websocket))
+            .await
+            .expect("no live metrics pushed");
+        if let Some(event) = message.get("event") {
+            assert_eq!(message["version"], WEBSOCKET_PROTOCOL_VERSION);
+            if let Some(phase) = event.get("phase") {
+                phases.push(phase.as_str().unwrap().to_string());
+            } else {
+                metrics.push(event["metrics"].clone());
+            }
+        } else {
+            assert_eq!(message["message"], "load test started");
+        }
+    }
+    assert_eq!(phases, vec!["Increase", "Maintain"]);
+    assert_eq!(metrics[0]["second"], 0);
+    assert_eq!(metrics[1]["second"], 1);
+    assert_eq!(metrics[1]["users"], 2);
+    assert!(metrics.iter().any(|m| m["requests"].as_u64().unwrap() > 0));
+    assert!(metrics.iter().all(|m| m["errors"] == 0));
+
+    // Stop pushing live metrics.
+    let response = request(&mut websocket, json!({"command": "unsubscribe"})).await;
+    assert_eq!(response["message"], "unsubscribed from live metrics");
+
+    // Shut down the load test.
+    let response = request(&mut websocket, json!({"command": "shutdown"})).await;
+    assert_eq!(response["message"], "load test shut down");
+
+    load_test.await.unwrap();
+    assert!(mock_endpoints[INDEX_KEY].hits() > 0);
+}